@@ -0,0 +1,334 @@
+//! `/msg`, `/reply` and `/channel`, which players chat with, and `/mute`, `/tempmute`, `/unmute`
+//! and `/slowmode`, which moderators keep chat in order with.
+
+use std::time::Duration;
+
+use clap::{Parser, ValueHint};
+use flecs_ecs::core::{
+    Builder, Entity, EntityView, EntityViewGet, QueryAPI, QueryBuilderImpl, World, WorldGet,
+    WorldProvider,
+};
+use hyperion::{
+    net::agnostic,
+    simulation::{IgnMap, Name, Player, Uuid},
+    storage::{MuteStorage, Sanction, unix_now},
+    uuid,
+};
+use hyperion_clap::{CommandPermission, MinecraftCommand, arg::GreedyString};
+use hyperion_rank_tree::Team;
+use hyperion_text::Key;
+
+use crate::{Channel, ChatSettings, ReplyTo, filter::Audience, messages, mute::Mute};
+
+/// The reason of mutes issued without one.
+const DEFAULT_REASON: &str = "Muted by a moderator";
+
+fn reply(system: EntityView<'_>, caller: Entity, key: Key, args: &[&str]) {
+    let caller = caller.entity_view(system.world());
+    let chat = agnostic::translated(caller, key, args);
+    agnostic::unicast_chat(caller, &chat, system);
+}
+
+/// The name of who runs a command, for logs and the mutes they issue.
+fn issuer(world: &World, caller: Entity) -> String {
+    caller
+        .entity_view(world)
+        .try_get::<&Name>(ToString::to_string)
+        .unwrap_or_else(|| "Console".to_owned())
+}
+
+/// The online player named `name`.
+fn online(world: &World, name: &str) -> Option<Entity> {
+    world.get::<&IgnMap>(|ign_map| ign_map.get(name).copied())
+}
+
+/// A player a mute command names, who is online or given by UUID.
+struct Resolved {
+    uuid: uuid::Uuid,
+    player: Option<Entity>,
+}
+
+/// Resolves `target`, which is the name of an online player or a UUID.
+fn resolve(world: &World, target: &str) -> Option<Resolved> {
+    if let Some(player) = online(world, target) {
+        let uuid = player.entity_view(world).try_get::<&Uuid>(|uuid| **uuid)?;
+
+        return Some(Resolved {
+            uuid,
+            player: Some(player),
+        });
+    }
+
+    let uuid = target.parse::<uuid::Uuid>().ok()?;
+
+    let mut player = None;
+
+    world
+        .query::<&Uuid>()
+        .with::<Player>()
+        .build()
+        .each_entity(|entity, player_uuid| {
+            if **player_uuid == uuid {
+                player = Some(entity.id());
+            }
+        });
+
+    Some(Resolved { uuid, player })
+}
+
+/// Mutes `target`, telling them if they are online and the caller.
+fn mute(
+    system: EntityView<'_>,
+    caller: Entity,
+    target: &str,
+    reason: Option<GreedyString>,
+    duration: Option<Duration>,
+) {
+    let world = system.world();
+
+    let Some(resolved) = resolve(&world, target) else {
+        reply(
+            system,
+            caller,
+            hyperion_clap::messages::PLAYER_NOT_FOUND,
+            &[target],
+        );
+        return;
+    };
+
+    let issuer = issuer(&world, caller);
+    let reason = reason.map_or_else(|| DEFAULT_REASON.to_owned(), |reason| reason.0);
+
+    let name = resolved.player.and_then(|player| {
+        player
+            .entity_view(world)
+            .try_get::<&Name>(ToString::to_string)
+    });
+
+    let mute = Sanction::new(name, reason, issuer.clone(), duration);
+
+    if let Err(e) = world.get::<&MuteStorage>(|mutes| mutes.insert(resolved.uuid, &mute)) {
+        tracing::error!("failed to save mute of {}: {e}", resolved.uuid);
+        reply(system, caller, messages::SAVE_FAILED, &[]);
+        return;
+    }
+
+    let now = unix_now();
+    let remaining = mute.remaining_text(now);
+
+    tracing::info!(
+        target: "moderation",
+        "{issuer} muted {target} for {remaining}: {}",
+        mute.reason
+    );
+
+    if let Some(player) = resolved.player {
+        let player = player.entity_view(world);
+        reply(system, player.id(), messages::YOU_WERE_MUTED, &[
+            &mute.reason
+        ]);
+        player.set(Mute(mute));
+    }
+
+    match duration {
+        Some(_) => reply(system, caller, messages::PLAYER_TEMPMUTED, &[
+            target, &remaining,
+        ]),
+        None => reply(system, caller, messages::PLAYER_MUTED, &[target]),
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "mute")]
+#[command_permission(node = "hyperion.command.mute")]
+pub struct MuteCommand {
+    /// The online player or UUID to mute
+    #[arg(value_hint = ValueHint::Username)]
+    target: String,
+    /// Why the player is muted, which they are told
+    reason: Option<GreedyString>,
+}
+
+impl MinecraftCommand for MuteCommand {
+    const CONSOLE: bool = true;
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        mute(system, caller, &self.target, self.reason, None);
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "tempmute")]
+#[command_permission(node = "hyperion.command.tempmute")]
+pub struct TempMuteCommand {
+    /// The online player or UUID to mute
+    #[arg(value_hint = ValueHint::Username)]
+    target: String,
+    /// How long the mute lasts, such as `10m`, `1h` or `1d`
+    duration: humantime::Duration,
+    /// Why the player is muted, which they are told
+    reason: Option<GreedyString>,
+}
+
+impl MinecraftCommand for TempMuteCommand {
+    const CONSOLE: bool = true;
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let duration = Some(*self.duration);
+        mute(system, caller, &self.target, self.reason, duration);
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "unmute")]
+#[command_permission(node = "hyperion.command.unmute")]
+pub struct UnmuteCommand {
+    /// The online player or UUID to unmute
+    #[arg(value_hint = ValueHint::Username)]
+    target: String,
+}
+
+impl MinecraftCommand for UnmuteCommand {
+    const CONSOLE: bool = true;
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+
+        let Some(resolved) = resolve(&world, &self.target) else {
+            reply(
+                system,
+                caller,
+                hyperion_clap::messages::PLAYER_NOT_FOUND,
+                &[&self.target],
+            );
+            return;
+        };
+
+        match world.get::<&MuteStorage>(|mutes| mutes.remove(resolved.uuid)) {
+            Ok(Some(_)) => {
+                let issuer = issuer(&world, caller);
+                tracing::info!(target: "moderation", "{issuer} unmuted {}", self.target);
+
+                if let Some(player) = resolved.player {
+                    let player = player.entity_view(world);
+                    player.remove::<Mute>();
+                    reply(system, player.id(), messages::YOU_WERE_UNMUTED, &[]);
+                }
+
+                reply(system, caller, messages::PLAYER_UNMUTED, &[&self.target]);
+            }
+            Ok(None) => reply(system, caller, messages::NOT_MUTED, &[&self.target]),
+            Err(e) => {
+                tracing::error!("failed to unmute {}: {e}", self.target);
+                reply(system, caller, messages::SAVE_FAILED, &[]);
+            }
+        }
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "msg")]
+#[command_permission(node = "hyperion.command.msg")]
+pub struct MsgCommand {
+    /// The player to message
+    #[arg(value_hint = ValueHint::Username)]
+    player: String,
+    message: GreedyString,
+}
+
+impl MinecraftCommand for MsgCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+
+        let Some(receiver) = online(&world, &self.player) else {
+            reply(
+                system,
+                caller,
+                hyperion_clap::messages::PLAYER_NOT_FOUND,
+                &[&self.player],
+            );
+            return;
+        };
+
+        let sender = caller.entity_view(world);
+        crate::send(sender, Audience::Player(receiver), &self.message.0, system);
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "reply")]
+#[command_permission(node = "hyperion.command.reply")]
+pub struct ReplyCommand {
+    message: GreedyString,
+}
+
+impl MinecraftCommand for ReplyCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let sender = caller.entity_view(world);
+
+        let receiver = sender
+            .try_get::<&ReplyTo>(|reply_to| reply_to.0)
+            .filter(|receiver| receiver.entity_view(world).is_alive());
+
+        let Some(receiver) = receiver else {
+            reply(system, caller, messages::NO_REPLY, &[]);
+            return;
+        };
+
+        crate::send(sender, Audience::Player(receiver), &self.message.0, system);
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "channel")]
+#[command_permission(node = "hyperion.command.channel")]
+pub struct ChannelCommand {
+    /// Who your messages are sent to
+    channel: Channel,
+}
+
+impl MinecraftCommand for ChannelCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let player = caller.entity_view(system.world());
+
+        if self.channel == Channel::Team && !player.has::<Team>() {
+            reply(system, caller, messages::NO_TEAM, &[]);
+            return;
+        }
+
+        player.set(self.channel);
+        reply(system, caller, messages::CHANNEL_SET, &[self
+            .channel
+            .name()]);
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "slowmode")]
+#[command_permission(node = "hyperion.command.slowmode")]
+pub struct SlowModeCommand {
+    /// The seconds players wait between messages, or 0 to turn slow mode off
+    seconds: u32,
+}
+
+impl MinecraftCommand for SlowModeCommand {
+    const CONSOLE: bool = true;
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+
+        world.get::<&mut ChatSettings>(|settings| settings.slow_mode = self.seconds);
+
+        let issuer = issuer(&world, caller);
+        tracing::info!(target: "moderation", "{issuer} set slow mode to {}s", self.seconds);
+
+        if self.seconds == 0 {
+            reply(system, caller, messages::SLOW_MODE_OFF, &[]);
+        } else {
+            reply(system, caller, messages::SLOW_MODE_SET, &[&self
+                .seconds
+                .to_string()]);
+        }
+    }
+}
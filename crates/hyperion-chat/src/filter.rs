@@ -0,0 +1,395 @@
+//! Filters each chat message runs through before it is sent, which may change it or refuse it.
+//!
+//! The built-in filters mute, slow down, censor and calm down players according to
+//! [`ChatSettings`]. Plugins add their own to [`ChatFilters`]:
+//!
+//! ```ignore
+//! struct NoLinks;
+//!
+//! impl ChatFilter for NoLinks {
+//!     fn filter(&self, message: &mut Message<'_>, _: &ChatSettings) -> Result<(), Refusal> {
+//!         if message.text.contains("://") {
+//!             return Err(Refusal::new(messages::NO_LINKS, &[]));
+//!         }
+//!
+//!         Ok(())
+//!     }
+//! }
+//!
+//! world.get::<&mut ChatFilters>(|filters| filters.push(NoLinks));
+//! ```
+
+use flecs_ecs::{
+    core::{Entity, EntityView, EntityViewGet},
+    macros::Component,
+};
+use hyperion::storage::unix_now;
+use hyperion_text::Key;
+
+use crate::{Channel, ChatSettings, messages, mute::Mute};
+
+/// The ticks per second, which message times are counted in.
+const TICKS_PER_SECOND: i64 = 20;
+
+/// The ticks over which [`ChatSettings::max_burst`](hyperion::config::Chat::max_burst) messages
+/// may be sent.
+const BURST_TICKS: i64 = 10 * TICKS_PER_SECOND;
+
+/// The fewest letters a message has before it may be too loud.
+const MIN_CAPS_LETTERS: usize = 8;
+
+/// Who a message is sent to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Audience {
+    Channel(Channel),
+    /// A private message to a player.
+    Player(Entity),
+}
+
+/// A message a player is sending.
+pub struct Message<'a> {
+    pub sender: EntityView<'a>,
+    pub audience: Audience,
+    pub text: String,
+    /// The tick the message was sent on.
+    pub tick: i64,
+}
+
+/// Why a message was refused, which the sender is told.
+#[derive(Clone, Debug)]
+pub struct Refusal {
+    pub key: Key,
+    pub args: Vec<String>,
+}
+
+impl Refusal {
+    #[must_use]
+    pub fn new(key: Key, args: &[&str]) -> Self {
+        Self {
+            key,
+            args: args.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+/// Changes or refuses chat messages.
+pub trait ChatFilter: Send + Sync {
+    /// Changes `message`, or refuses it with what the sender is told.
+    fn filter(&self, message: &mut Message<'_>, settings: &ChatSettings) -> Result<(), Refusal>;
+
+    /// Whether players with the `hyperion.chat.bypass` permission skip the filter.
+    fn bypassable(&self) -> bool {
+        true
+    }
+}
+
+/// A singleton with the filters messages run through, in order.
+#[derive(Component)]
+pub struct ChatFilters {
+    filters: Vec<Box<dyn ChatFilter>>,
+}
+
+impl Default for ChatFilters {
+    fn default() -> Self {
+        let mut filters = Self {
+            filters: Vec::new(),
+        };
+
+        filters.push(MuteFilter);
+        filters.push(SlowModeFilter);
+        filters.push(SpamFilter);
+        filters.push(CapsFilter);
+        filters.push(WordFilter);
+
+        filters
+    }
+}
+
+impl ChatFilters {
+    /// Adds `filter` after the others.
+    pub fn push(&mut self, filter: impl ChatFilter + 'static) {
+        self.filters.push(Box::new(filter));
+    }
+
+    /// Runs `message` through every filter, skipping the bypassable ones if `bypass` is set,
+    /// until one refuses it.
+    pub fn run(
+        &self,
+        message: &mut Message<'_>,
+        settings: &ChatSettings,
+        bypass: bool,
+    ) -> Result<(), Refusal> {
+        self.filters
+            .iter()
+            .filter(|filter| !bypass || !filter.bypassable())
+            .try_for_each(|filter| filter.filter(message, settings))
+    }
+}
+
+/// The recent messages of a player, which the spam filters look at.
+#[derive(Component, Debug, Default)]
+pub struct ChatHistory {
+    last_tick: Option<i64>,
+    last_message: String,
+    /// How many times in a row [`Self::last_message`] was sent.
+    repeats: u32,
+    /// The tick the current burst of messages started on.
+    burst_start: i64,
+    /// How many messages were sent since [`Self::burst_start`].
+    burst: u32,
+}
+
+impl ChatHistory {
+    /// Records a message sent on `tick`.
+    pub fn record(&mut self, tick: i64, text: &str) {
+        if self.last_message.eq_ignore_ascii_case(text) {
+            self.repeats += 1;
+        } else {
+            self.last_message = text.to_owned();
+            self.repeats = 1;
+        }
+
+        if tick - self.burst_start >= BURST_TICKS {
+            self.burst_start = tick;
+            self.burst = 0;
+        }
+
+        self.burst += 1;
+        self.last_tick = Some(tick);
+    }
+
+    /// The ticks since the last message, or `None` if none was sent.
+    #[must_use]
+    pub fn since_last(&self, tick: i64) -> Option<i64> {
+        self.last_tick.map(|last| tick - last)
+    }
+
+    /// How many times in a row `text` was just sent.
+    #[must_use]
+    pub fn repeats_of(&self, text: &str) -> u32 {
+        if self.last_message.eq_ignore_ascii_case(text) {
+            self.repeats
+        } else {
+            0
+        }
+    }
+
+    /// How many messages were sent in the burst `tick` is part of.
+    #[must_use]
+    pub fn burst_at(&self, tick: i64) -> u32 {
+        if tick - self.burst_start < BURST_TICKS {
+            self.burst
+        } else {
+            0
+        }
+    }
+}
+
+/// Refuses messages from muted players, who may not bypass it.
+pub struct MuteFilter;
+
+impl ChatFilter for MuteFilter {
+    fn filter(&self, message: &mut Message<'_>, _: &ChatSettings) -> Result<(), Refusal> {
+        let now = unix_now();
+
+        let Some(mute) = message.sender.try_get::<&Mute>(Clone::clone) else {
+            return Ok(());
+        };
+
+        if mute.is_expired(now) {
+            message.sender.remove::<Mute>();
+            return Ok(());
+        }
+
+        Err(Refusal::new(messages::MUTED, &[&mute.remaining_text(now)]))
+    }
+
+    fn bypassable(&self) -> bool {
+        false
+    }
+}
+
+/// Makes players wait [`slow_mode`](hyperion::config::Chat::slow_mode) seconds between messages.
+pub struct SlowModeFilter;
+
+impl ChatFilter for SlowModeFilter {
+    fn filter(&self, message: &mut Message<'_>, settings: &ChatSettings) -> Result<(), Refusal> {
+        let cooldown = i64::from(settings.slow_mode) * TICKS_PER_SECOND;
+
+        let since_last = message
+            .sender
+            .try_get::<&ChatHistory>(|history| history.since_last(message.tick))
+            .flatten();
+
+        match since_last {
+            Some(since_last) if since_last < cooldown => {
+                #[expect(
+                    clippy::cast_precision_loss,
+                    reason = "the ticks left are at most a few hours"
+                )]
+                let remaining = (cooldown - since_last) as f32 / TICKS_PER_SECOND as f32;
+
+                Err(Refusal::new(messages::SLOW_MODE, &[&format!(
+                    "{remaining:.1}"
+                )]))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Refuses the same message sent too many times in a row, and bursts of too many messages.
+pub struct SpamFilter;
+
+impl ChatFilter for SpamFilter {
+    fn filter(&self, message: &mut Message<'_>, settings: &ChatSettings) -> Result<(), Refusal> {
+        let (repeats, burst) = message
+            .sender
+            .try_get::<&ChatHistory>(|history| {
+                (
+                    history.repeats_of(&message.text),
+                    history.burst_at(message.tick),
+                )
+            })
+            .unwrap_or_default();
+
+        if repeats >= settings.max_repeats {
+            return Err(Refusal::new(messages::REPEATED, &[]));
+        }
+
+        if burst >= settings.max_burst {
+            return Err(Refusal::new(messages::TOO_FAST, &[]));
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether more than `max` of the letters of `text` are capitals, once it has enough letters to
+/// tell.
+#[must_use]
+pub fn is_too_loud(text: &str, max: f32) -> bool {
+    let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+    let capitals = text.chars().filter(|c| c.is_uppercase()).count();
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "chat messages are at most 256 characters"
+    )]
+    let loud = capitals as f32 > max * letters as f32;
+
+    letters >= MIN_CAPS_LETTERS && loud
+}
+
+/// Lowercases messages with too many capitals.
+pub struct CapsFilter;
+
+impl ChatFilter for CapsFilter {
+    fn filter(&self, message: &mut Message<'_>, settings: &ChatSettings) -> Result<(), Refusal> {
+        if is_too_loud(&message.text, settings.max_caps) {
+            message.text = message.text.to_lowercase();
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces the words of `text` which are in `blocked` with asterisks, ignoring case, or returns
+/// `None` if none of them are. Only whole words are replaced, so blocking `ass` leaves `class`.
+#[must_use]
+pub fn censor(text: &str, blocked: &[String]) -> Option<String> {
+    let mut censored = String::with_capacity(text.len());
+    let mut word = String::new();
+    let mut changed = false;
+
+    let mut flush = |word: &mut String, censored: &mut String| {
+        if blocked
+            .iter()
+            .any(|blocked| blocked.eq_ignore_ascii_case(word))
+        {
+            censored.extend(word.chars().map(|_| '*'));
+            changed = true;
+        } else {
+            censored.push_str(word);
+        }
+
+        word.clear();
+    };
+
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut censored);
+            censored.push(c);
+        }
+    }
+
+    flush(&mut word, &mut censored);
+
+    changed.then_some(censored)
+}
+
+/// Replaces the [`blocked_words`](hyperion::config::Chat::blocked_words) in messages with
+/// asterisks.
+pub struct WordFilter;
+
+impl ChatFilter for WordFilter {
+    fn filter(&self, message: &mut Message<'_>, settings: &ChatSettings) -> Result<(), Refusal> {
+        if let Some(censored) = censor(&message.text, &settings.blocked_words) {
+            message.sender.try_get::<&hyperion::simulation::Name>(|name| {
+                tracing::info!(target: "moderation", "censored message from {name}: {}", message.text);
+            });
+
+            message.text = censored;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_censor() {
+        let blocked = vec!["ass".to_owned(), "heck".to_owned()];
+
+        assert_eq!(
+            censor("what the HECK!", &blocked).as_deref(),
+            Some("what the ****!")
+        );
+        assert_eq!(censor("first class", &blocked), None);
+        assert_eq!(censor("", &blocked), None);
+    }
+
+    #[test]
+    fn test_is_too_loud() {
+        assert!(is_too_loud("HELLO EVERYONE", 0.7));
+        assert!(!is_too_loud("Hello Everyone", 0.7));
+        // too short to tell
+        assert!(!is_too_loud("GG", 0.7));
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = ChatHistory::default();
+
+        assert_eq!(history.since_last(0), None);
+
+        history.record(0, "hi");
+        history.record(20, "HI");
+
+        assert_eq!(history.since_last(30), Some(10));
+        assert_eq!(history.repeats_of("hi"), 2);
+        assert_eq!(history.repeats_of("bye"), 0);
+        assert_eq!(history.burst_at(30), 2);
+
+        // the burst ends once its window has passed
+        assert_eq!(history.burst_at(BURST_TICKS), 0);
+        history.record(BURST_TICKS, "bye");
+        assert_eq!(history.burst_at(BURST_TICKS), 1);
+        assert_eq!(history.repeats_of("bye"), 1);
+    }
+}
@@ -0,0 +1,298 @@
+//! Chat with channels, private messages and moderation.
+//!
+//! Every message runs through the [`ChatFilters`], which mute, slow down and censor players
+//! according to [`ChatSettings`], before it is sent to the [`Channel`] of its sender: everyone,
+//! the players nearby or the players on the same [`Team`]. Mutes are kept in [`MuteStorage`], and
+//! moderation, such as refused messages and mutes, is logged with the `moderation` target.
+//!
+//! Messages are sent as system messages unless `player_chat` is set in the `chat` section of the
+//! [`Config`], in which case they are sent as [player chat](player_chat).
+
+use clap::ValueEnum;
+use derive_more::{Deref, DerefMut};
+use flecs_ecs::{
+    core::{
+        Builder, Entity, EntityView, EntityViewGet, QueryAPI, QueryBuilderImpl, SystemAPI,
+        TermBuilderImpl, World, WorldGet, WorldProvider, flecs,
+    },
+    macros::{Component, observer, system},
+    prelude::Module,
+};
+use hyperion::{
+    config::{self, Config},
+    net::{Compose, agnostic},
+    simulation::{Name, Player, Position, Uuid, event},
+    storage::{EventQueue, MuteStorage},
+    valence_protocol::{packets::play, text::IntoText},
+};
+use hyperion_clap::{MinecraftCommand, hyperion_command::CommandRegistry};
+use hyperion_rank_tree::Team;
+
+pub mod command;
+pub mod filter;
+pub mod messages;
+pub mod mute;
+pub mod player_chat;
+
+use filter::{Audience, ChatFilters, ChatHistory, Message};
+use mute::Mute;
+
+/// Players with this permission skip the filters which limit how they chat, apart from mutes.
+pub const BYPASS_PERMISSION: &str = "hyperion.chat.bypass";
+
+/// Who the messages of a player are sent to.
+#[derive(Component, Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Channel {
+    /// Every player
+    Global,
+    /// The players nearby
+    #[default]
+    Local,
+    /// The players on the same team
+    Team,
+}
+
+impl Channel {
+    /// The name players pick the channel by.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Global => "global",
+            Self::Local => "local",
+            Self::Team => "team",
+        }
+    }
+}
+
+/// A singleton with how chat is moderated, which starts as the `chat` section of the
+/// [`Config`] and may be changed while the server runs, such as by `/slowmode`.
+#[derive(Component, Debug, Deref, DerefMut)]
+pub struct ChatSettings(pub config::Chat);
+
+/// The player a player last exchanged private messages with, who `/reply` answers.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct ReplyTo(pub Entity);
+
+#[derive(Component)]
+pub struct ChatModule;
+
+/// The color of the names of players on `team`.
+const fn team_color(team: Option<Team>) -> &'static str {
+    match team {
+        Some(Team::Blue) => "§9",
+        Some(Team::Green) => "§a",
+        Some(Team::Red) => "§c",
+        Some(Team::Yellow) => "§e",
+        None => "§f",
+    }
+}
+
+fn tell(player: EntityView<'_>, key: hyperion_text::Key, args: &[&str], system: EntityView<'_>) {
+    let chat = agnostic::translated(player, key, args);
+    agnostic::unicast_chat(player, &chat, system);
+}
+
+/// Sends `text`, which the filters already let through, from `sender` to `audience` as system
+/// messages, which clients show without knowing who sent them.
+fn deliver_system(
+    sender: EntityView<'_>,
+    audience: Audience,
+    text: &str,
+    team: Option<Team>,
+    system: EntityView<'_>,
+) -> anyhow::Result<()> {
+    let world = sender.world();
+
+    let name = sender
+        .try_get::<&Name>(ToString::to_string)
+        .unwrap_or_default();
+    let color = team_color(team);
+
+    match audience {
+        Audience::Player(receiver) => {
+            let receiver = receiver.entity_view(world);
+            let receiver_name = receiver
+                .try_get::<&Name>(ToString::to_string)
+                .unwrap_or_default();
+
+            tell(receiver, messages::WHISPER_RECEIVED, &[&name, text], system);
+            tell(
+                sender,
+                messages::WHISPER_SENT,
+                &[&receiver_name, text],
+                system,
+            );
+        }
+        Audience::Channel(Channel::Team) => {
+            let Some(team) = team else {
+                return Ok(());
+            };
+
+            let chat = agnostic::chat(format!("§8[Team] <{color}{name}§8>§r {text}"));
+
+            world
+                .query::<&Team>()
+                .with::<Player>()
+                .build()
+                .each_entity(|player, player_team| {
+                    if *player_team == team {
+                        agnostic::unicast_chat(player, &chat, system);
+                    }
+                });
+        }
+        Audience::Channel(channel) => {
+            let chat = format!("§8<{color}{name}§8>§r {text}").into_cow_text();
+            let packet = play::GameMessageS2c {
+                chat,
+                overlay: false,
+            };
+
+            return world.get::<&Compose>(|compose| match channel {
+                Channel::Global => compose.broadcast(&packet, system).send(),
+                _ => {
+                    let center = sender
+                        .try_get::<&Position>(|position| position.to_chunk())
+                        .unwrap_or_default();
+
+                    compose.broadcast_local(&packet, center, system).send()
+                }
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends `text` from `sender` to `audience` once the [`ChatFilters`] let it through, or tells the
+/// sender why they did not.
+pub fn send(sender: EntityView<'_>, audience: Audience, text: &str, system: EntityView<'_>) {
+    let world = sender.world();
+
+    let team = sender.try_get::<&Team>(|team| *team);
+
+    if audience == Audience::Channel(Channel::Team) && team.is_none() {
+        tell(sender, messages::NO_TEAM, &[], system);
+        return;
+    }
+
+    let bypass = hyperion_permission::has_permission(sender, BYPASS_PERMISSION);
+
+    let tick = world.get::<&Compose>(|compose| compose.global().tick);
+
+    let mut message = Message {
+        sender,
+        audience,
+        text: text.to_owned(),
+        tick,
+    };
+
+    let result = world.get::<&ChatFilters>(|filters| {
+        world.get::<&ChatSettings>(|settings| filters.run(&mut message, settings, bypass))
+    });
+
+    if let Err(refusal) = result {
+        let name = sender
+            .try_get::<&Name>(ToString::to_string)
+            .unwrap_or_default();
+
+        tracing::info!(
+            target: "moderation",
+            "refused message from {name} ({}): {text}",
+            refusal.key.name
+        );
+
+        let args: Vec<&str> = refusal.args.iter().map(String::as_str).collect();
+        tell(sender, refusal.key, &args, system);
+        return;
+    }
+
+    sender.try_get::<&mut ChatHistory>(|history| history.record(tick, &message.text));
+
+    if let Audience::Player(receiver) = audience {
+        let receiver = receiver.entity_view(world);
+        receiver.set(ReplyTo(sender.id()));
+        sender.set(ReplyTo(receiver.id()));
+    }
+
+    let player_chat = world.get::<&ChatSettings>(|settings| settings.player_chat);
+
+    let result = if player_chat {
+        player_chat::deliver(sender, audience, &message.text, team, system)
+    } else {
+        deliver_system(sender, audience, &message.text, team, system)
+    };
+
+    if let Err(e) = result {
+        tracing::error!("failed to send chat message: {e}");
+    }
+}
+
+impl Module for ChatModule {
+    fn module(world: &World) {
+        world.import::<hyperion_rank_tree::RankTree>();
+        world.import::<hyperion_permission::PermissionModule>();
+        world.import::<hyperion_clap::ClapCommandModule>();
+
+        world.component::<Channel>();
+        world.component::<ChatSettings>();
+        world.component::<ChatFilters>();
+        world.component::<ChatHistory>();
+        world.component::<ReplyTo>();
+        world.component::<Mute>();
+        world.component::<player_chat::MessageIndex>();
+
+        world
+            .component::<Player>()
+            .add_trait::<(flecs::With, Channel)>()
+            .add_trait::<(flecs::With, ChatHistory)>()
+            .add_trait::<(flecs::With, player_chat::MessageIndex)>();
+
+        let settings = world.get::<&Config>(|config| config.chat.clone());
+        world.set(ChatSettings(settings));
+        world.set(ChatFilters::default());
+
+        observer!(world, flecs::OnSet, &Uuid, &MuteStorage($))
+            .with::<Player>()
+            .each_entity(|entity, (uuid, storage)| match storage.find(**uuid) {
+                Ok(Some(mute)) => {
+                    entity.set(Mute(mute));
+                }
+                Ok(None) => {}
+                Err(e) => tracing::error!("failed to load mute of {}: {e}", **uuid),
+            });
+
+        system!(
+            "handle_chat_messages",
+            world,
+            &mut EventQueue<event::ChatMessage<'static>>($),
+        )
+        .each_iter(|it, _, event_queue| {
+            let world = it.world();
+            let system = it.system();
+
+            for event::ChatMessage { msg, by } in event_queue.drain() {
+                let by = world.entity_from_id(by);
+
+                // todo: we should not need this; death should occur such that this is always valid
+                if !by.is_alive() {
+                    continue;
+                }
+
+                let channel = by
+                    .try_get::<&Channel>(|channel| *channel)
+                    .unwrap_or_default();
+                send(by, Audience::Channel(channel), msg, system);
+            }
+        });
+
+        world.get::<&mut CommandRegistry>(|registry| {
+            command::ChannelCommand::register(registry, world);
+            command::MsgCommand::register(registry, world);
+            command::ReplyCommand::register(registry, world);
+            command::MuteCommand::register(registry, world);
+            command::TempMuteCommand::register(registry, world);
+            command::UnmuteCommand::register(registry, world);
+            command::SlowModeCommand::register(registry, world);
+        });
+    }
+}
@@ -0,0 +1,33 @@
+//! The messages sent by chat and its commands, which can be translated in `run/lang`.
+
+hyperion_text::translation_keys! {
+    /// `{0}` is the number of seconds left.
+    pub SLOW_MODE = "chat.slow_mode" => "<red>Please wait {0} seconds before sending another message";
+    /// `{0}` is how long the mute has left, or `never`.
+    pub MUTED = "chat.muted" => "<red>You are muted and may not chat. Your mute ends in {0}";
+    pub REPEATED = "chat.repeated" => "<red>Please do not repeat the same message";
+    pub TOO_FAST = "chat.too_fast" => "<red>You are sending messages too quickly";
+    /// `{0}` is the name of the channel.
+    pub CHANNEL_SET = "chat.channel.set" => "You are now chatting in <yellow>{0}";
+    pub NO_TEAM = "chat.channel.no_team" => "<red>You are not on a team";
+    /// `{0}` is the name of the player.
+    pub PLAYER_MUTED = "chat.mute.muted" => "Muted <aqua>{0}";
+    /// `{0}` is the name of the player and `{1}` how long the mute lasts.
+    pub PLAYER_TEMPMUTED = "chat.mute.tempmuted" => "Muted <aqua>{0}<reset> for <yellow>{1}";
+    /// `{0}` is the name of the player.
+    pub PLAYER_UNMUTED = "chat.mute.unmuted" => "Unmuted <aqua>{0}";
+    /// `{0}` is the name of the player.
+    pub NOT_MUTED = "chat.mute.not_muted" => "<red>{0} is not muted";
+    /// `{0}` is the reason.
+    pub YOU_WERE_MUTED = "chat.mute.you_were_muted" => "<red>You have been muted: {0}";
+    pub YOU_WERE_UNMUTED = "chat.mute.you_were_unmuted" => "<green>You are no longer muted";
+    /// `{0}` is the number of seconds, or `0` when slow mode is turned off.
+    pub SLOW_MODE_SET = "chat.slow_mode.set" => "Players now wait <yellow>{0}<reset> seconds between messages";
+    pub SLOW_MODE_OFF = "chat.slow_mode.off" => "Slow mode is now off";
+    /// `{0}` is the name of the receiver and `{1}` the message.
+    pub WHISPER_SENT = "chat.msg.sent" => "<gray>To <aqua>{0}<gray>: {1}";
+    /// `{0}` is the name of the sender and `{1}` the message.
+    pub WHISPER_RECEIVED = "chat.msg.received" => "<gray>From <aqua>{0}<gray>: {1}";
+    pub NO_REPLY = "chat.msg.no_reply" => "<red>There is nobody to reply to";
+    pub SAVE_FAILED = "chat.save_failed" => "<red>Failed to save the mute, see the server logs";
+}
@@ -0,0 +1,10 @@
+//! Mutes, which keep players from chatting until they are lifted or expire. They are kept in the
+//! [`MuteStorage`](hyperion::storage::MuteStorage) so that they last across sessions.
+
+use derive_more::{Deref, DerefMut};
+use flecs_ecs::macros::Component;
+use hyperion::storage::Sanction;
+
+/// Why and until when a player is muted.
+#[derive(Component, Deref, DerefMut, Clone, Debug, PartialEq, Eq)]
+pub struct Mute(pub Sanction);
@@ -0,0 +1,183 @@
+//! Delivers messages as player chat rather than system messages, so that clients know who sent
+//! them and may hide the messages of players they blocked.
+//!
+//! The server runs in offline mode, so the signatures of messages cannot be verified and
+//! messages are relayed unsigned. Clients are told secure chat is not enforced when they join,
+//! which keeps them from warning that messages cannot be verified.
+
+use std::{
+    borrow::Cow,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use flecs_ecs::{
+    core::{
+        Builder, EntityView, EntityViewGet, QueryAPI, QueryBuilderImpl, WorldGet, WorldProvider,
+    },
+    macros::Component,
+};
+use hyperion::{
+    net::{Compose, ConnectionId},
+    simulation::{Name, Player, Position, Uuid},
+    valence_protocol::{
+        VarInt,
+        packets::play::{self, chat_message_s2c::MessageFilterType},
+        text::{IntoText, Text},
+    },
+};
+use hyperion_rank_tree::Team;
+
+use crate::{Channel, filter::Audience};
+
+/// The ids of the chat types in the registry sent to players as they join, which decorate
+/// messages the way the client translates them.
+mod chat_type {
+    /// `<sender> content`
+    pub const CHAT: i32 = 0;
+    /// A private message to the receiver, as `sender whispers to you: content`.
+    pub const MSG_INCOMING: i32 = 2;
+    /// A private message as its sender sees it, as `You whisper to target: content`.
+    pub const MSG_OUTGOING: i32 = 3;
+    /// `target <sender> content`, where the target is the team.
+    pub const TEAM_INCOMING: i32 = 6;
+}
+
+/// How many messages a player has sent this session, which each message they send is numbered
+/// with.
+#[derive(Component, Copy, Clone, Debug, Default)]
+pub struct MessageIndex(i32);
+
+impl MessageIndex {
+    /// The index of the next message, counting it as sent.
+    pub const fn next(&mut self) -> i32 {
+        let index = self.0;
+        self.0 = self.0.wrapping_add(1);
+        index
+    }
+}
+
+/// The milliseconds since the Unix epoch, which messages are timestamped in.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| {
+            u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+        })
+}
+
+fn unicast(
+    compose: &Compose,
+    receiver: EntityView<'_>,
+    packet: &play::ChatMessageS2c<'_>,
+    system: EntityView<'_>,
+) -> anyhow::Result<()> {
+    receiver
+        .try_get::<&ConnectionId>(|stream| compose.unicast(packet, *stream, system))
+        .unwrap_or(Ok(()))
+}
+
+/// Sends `text`, which the filters already let through, from `sender` to `audience` as player
+/// chat.
+pub fn deliver(
+    sender: EntityView<'_>,
+    audience: Audience,
+    text: &str,
+    team: Option<Team>,
+    system: EntityView<'_>,
+) -> anyhow::Result<()> {
+    let world = sender.world();
+
+    let Some(uuid) = sender.try_get::<&Uuid>(|uuid| **uuid) else {
+        return Ok(());
+    };
+
+    let name = sender
+        .try_get::<&Name>(ToString::to_string)
+        .unwrap_or_default();
+    let color = crate::team_color(team);
+
+    let index = sender
+        .try_get::<&mut MessageIndex>(MessageIndex::next)
+        .unwrap_or_default();
+
+    let time_stamp = now_millis();
+
+    let packet = |chat_type: i32, target: Option<Cow<'static, Text>>| play::ChatMessageS2c {
+        sender: uuid,
+        index: VarInt(index),
+        message_signature: None,
+        message: text,
+        time_stamp,
+        salt: 0,
+        previous_messages: Vec::new(),
+        unsigned_content: None,
+        filter_type: MessageFilterType::PassThrough,
+        filter_type_bits: None,
+        chat_type: VarInt(chat_type),
+        network_name: format!("{color}{name}").into_cow_text(),
+        network_target_name: target,
+    };
+
+    world.get::<&Compose>(|compose| match audience {
+        Audience::Player(receiver) => {
+            let receiver = receiver.entity_view(world);
+            let receiver_name = receiver
+                .try_get::<&Name>(ToString::to_string)
+                .unwrap_or_default();
+
+            let incoming = packet(chat_type::MSG_INCOMING, None);
+            unicast(compose, receiver, &incoming, system)?;
+
+            let outgoing = packet(chat_type::MSG_OUTGOING, Some(receiver_name.into_cow_text()));
+            unicast(compose, sender, &outgoing, system)
+        }
+        Audience::Channel(Channel::Team) => {
+            let Some(team) = team else {
+                return Ok(());
+            };
+
+            let target = format!("{color}[{team:?}]").into_cow_text();
+            let packet = packet(chat_type::TEAM_INCOMING, Some(target));
+
+            let mut result = Ok(());
+
+            world
+                .query::<&Team>()
+                .with::<Player>()
+                .build()
+                .each_entity(|player, player_team| {
+                    if *player_team == team && result.is_ok() {
+                        result = unicast(compose, player, &packet, system);
+                    }
+                });
+
+            result
+        }
+        Audience::Channel(Channel::Global) => {
+            let packet = packet(chat_type::CHAT, None);
+            compose.broadcast(&packet, system).send()
+        }
+        Audience::Channel(Channel::Local) => {
+            let packet = packet(chat_type::CHAT, None);
+            let center = sender
+                .try_get::<&Position>(|position| position.to_chunk())
+                .unwrap_or_default();
+
+            compose.broadcast_local(&packet, center, system).send()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_index() {
+        let mut index = MessageIndex::default();
+
+        assert_eq!(index.next(), 0);
+        assert_eq!(index.next(), 1);
+        assert_eq!(index.next(), 2);
+    }
+}
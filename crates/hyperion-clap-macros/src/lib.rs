@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{DeriveInput, Error, Ident, Lit, parse_macro_input};
+use syn::{DeriveInput, Error, Lit, parse_macro_input};
 
 #[proc_macro_derive(CommandPermission, attributes(command_permission))]
 pub fn derive_command_permission(input: TokenStream) -> TokenStream {
@@ -8,14 +8,14 @@ pub fn derive_command_permission(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident.clone(); // Clone the Ident to prevent moving
 
-    // Extract the group from the `#[command_permission(group = "Admin")]` attribute
-    let mut group = None;
+    // Extract the node from the `#[command_permission(node = "tag.command.fly")]` attribute
+    let mut node = None;
     for attr in &input.attrs {
         if attr.path().is_ident("command_permission") {
             if let Err(err) = attr.parse_nested_meta(|meta| {
-                if meta.path.is_ident("group") {
+                if meta.path.is_ident("node") {
                     if let Ok(Lit::Str(lit)) = meta.value()?.parse::<Lit>() {
-                        group = Some(lit);
+                        node = Some(lit);
                     }
                 }
                 Ok(())
@@ -27,31 +27,38 @@ pub fn derive_command_permission(input: TokenStream) -> TokenStream {
         }
     }
 
-    let group_ident = match group {
-        Some(g) => Ident::new(&g.value(), g.span()),
-        None => {
-            return Error::new_spanned(
-                input,
-                "Missing required `#[command_permission(group = \"<GroupName>\")]` attribute.",
-            )
-            .to_compile_error()
-            .into();
-        }
+    let Some(node) = node else {
+        return Error::new_spanned(
+            input,
+            "Missing required `#[command_permission(node = \"<permission.node>\")]` attribute.",
+        )
+        .to_compile_error()
+        .into();
     };
 
+    let value = node.value();
+    let valid = !value.is_empty()
+        && !value.contains('*')
+        && value.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"_-".contains(&b))
+        });
+
+    if !valid {
+        return Error::new_spanned(
+            node,
+            "Permission nodes are lowercase segments separated by dots, such as `tag.command.fly`.",
+        )
+        .to_compile_error()
+        .into();
+    }
+
     // Generate the trait implementation
     let expanded = quote! {
         impl CommandPermission for #name {
-            fn has_required_permission(user_group: ::hyperion_permission::Group) -> bool {
-                const REQUIRED_GROUP: ::hyperion_permission::Group = ::hyperion_permission::Group::#group_ident;
-
-                if REQUIRED_GROUP == ::hyperion_permission::Group::Banned {
-                    // When checking for the group "Banned" we don't want to check for higher groups.
-                    return REQUIRED_GROUP == user_group;
-                } else {
-                    return user_group as u32 >= REQUIRED_GROUP as u32
-                }
-            }
+            const PERMISSION: &'static str = #node;
         }
     };
 
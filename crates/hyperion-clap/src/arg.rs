@@ -0,0 +1,379 @@
+//! Argument types with a matching Brigadier parser, so that the client validates and highlights
+//! them as it does for vanilla commands.
+//!
+//! Use them as the types of fields in a [`MinecraftCommand`](crate::MinecraftCommand):
+//!
+//! ```ignore
+//! #[derive(Parser, CommandPermission, Debug)]
+//! #[command(name = "setblock")]
+//! #[command_permission(node = "example.command.setblock")]
+//! pub struct SetBlockCommand {
+//!     position: BlockCoordinates,
+//!     block: BlockId,
+//! }
+//! ```
+
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use derive_more::{Deref, Display};
+use hyperion::{
+    BlockKind, BlockState, ItemKind,
+    glam::{DVec3, IVec3, Vec3},
+    valence_protocol::block::{PropName, PropValue},
+};
+use thiserror::Error;
+
+/// An argument which could not be parsed.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ArgError {
+    #[error("expected a number, found `{0}`")]
+    Number(String),
+    #[error("expected {expected} coordinates, found {found}")]
+    CoordinateCount { expected: usize, found: usize },
+    #[error("cannot mix local coordinates (^) with world coordinates")]
+    MixedCoordinates,
+    #[error("block coordinates must be whole numbers, found `{0}`")]
+    FractionalBlockCoordinate(String),
+    #[error("unknown item `{0}`")]
+    UnknownItem(String),
+    #[error("unknown block `{0}`")]
+    UnknownBlock(String),
+    #[error("`{block}` has no property `{property}` with value `{value}`")]
+    UnknownProperty {
+        block: String,
+        property: String,
+        value: String,
+    },
+}
+
+/// A single coordinate of a position.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Coordinate {
+    /// A world coordinate, such as `12.5`.
+    Absolute(f64),
+    /// An offset from the caller along the world axis, such as `~2`.
+    Relative(f64),
+    /// An offset from the caller along the axis of where they look, such as `^2`.
+    Local(f64),
+}
+
+impl Coordinate {
+    const fn is_local(self) -> bool {
+        matches!(self, Self::Local(_))
+    }
+
+    const fn value(self) -> f64 {
+        match self {
+            Self::Absolute(value) | Self::Relative(value) | Self::Local(value) => value,
+        }
+    }
+
+    /// The world coordinate, given the coordinate of the caller on the same axis. Local
+    /// coordinates are resolved by [`Coordinates::resolve`] instead.
+    const fn resolve_axis(self, origin: f64) -> f64 {
+        match self {
+            Self::Absolute(value) => value,
+            Self::Relative(offset) | Self::Local(offset) => origin + offset,
+        }
+    }
+}
+
+impl FromStr for Coordinate {
+    type Err = ArgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |number: &str| {
+            number
+                .parse::<f64>()
+                .map_err(|_| ArgError::Number(s.to_owned()))
+        };
+
+        // `~` and `^` on their own are no offset
+        let offset = |offset: &str| {
+            if offset.is_empty() {
+                Ok(0.0)
+            } else {
+                number(offset)
+            }
+        };
+
+        if let Some(rest) = s.strip_prefix('~') {
+            offset(rest).map(Self::Relative)
+        } else if let Some(rest) = s.strip_prefix('^') {
+            offset(rest).map(Self::Local)
+        } else {
+            number(s).map(Self::Absolute)
+        }
+    }
+}
+
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Absolute(value) => write!(f, "{value}"),
+            Self::Relative(offset) => write!(f, "~{offset}"),
+            Self::Local(offset) => write!(f, "^{offset}"),
+        }
+    }
+}
+
+/// The three coordinates of a position, such as `~ ~1 ~` or `^ ^ ^5`, parsed by the client as a
+/// `vec3`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Coordinates {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub z: Coordinate,
+}
+
+impl Coordinates {
+    /// The position in the world, for a caller at `origin` looking at `yaw` and `pitch` in
+    /// degrees.
+    #[must_use]
+    pub fn resolve(&self, origin: Vec3, yaw: f32, pitch: f32) -> Vec3 {
+        let origin = origin.as_dvec3();
+
+        if !self.x.is_local() {
+            return DVec3::new(
+                self.x.resolve_axis(origin.x),
+                self.y.resolve_axis(origin.y),
+                self.z.resolve_axis(origin.z),
+            )
+            .as_vec3();
+        }
+
+        // the same axes as vanilla: left, up and forward from where the caller looks
+        let yaw = f64::from(yaw + 90.0).to_radians();
+        let pitch = f64::from(-pitch).to_radians();
+        let pitch_up = pitch + std::f64::consts::FRAC_PI_2;
+
+        let forward = DVec3::new(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        );
+        let up = DVec3::new(
+            yaw.cos() * pitch_up.cos(),
+            pitch_up.sin(),
+            yaw.sin() * pitch_up.cos(),
+        );
+        let left = -forward.cross(up);
+
+        (origin + left * self.x.value() + up * self.y.value() + forward * self.z.value()).as_vec3()
+    }
+}
+
+fn parse_coordinates<const N: usize>(s: &str) -> Result<[Coordinate; N], ArgError> {
+    let coordinates = s
+        .split_whitespace()
+        .map(Coordinate::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let found = coordinates.len();
+
+    let coordinates: [Coordinate; N] = coordinates
+        .try_into()
+        .map_err(|_| ArgError::CoordinateCount { expected: N, found })?;
+
+    // local coordinates only make sense together
+    if coordinates.iter().any(|c| c.is_local()) && !coordinates.iter().all(|c| c.is_local()) {
+        return Err(ArgError::MixedCoordinates);
+    }
+
+    Ok(coordinates)
+}
+
+impl FromStr for Coordinates {
+    type Err = ArgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x, y, z] = parse_coordinates(s)?;
+        Ok(Self { x, y, z })
+    }
+}
+
+/// The three coordinates of a block, such as `~ ~-1 ~`, parsed by the client as a `block_pos`.
+/// Unlike [`Coordinates`], world coordinates must be whole numbers.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlockCoordinates(pub Coordinates);
+
+impl BlockCoordinates {
+    /// The block in the world, for a caller at `origin` looking at `yaw` and `pitch` in degrees.
+    #[must_use]
+    pub fn resolve(&self, origin: Vec3, yaw: f32, pitch: f32) -> IVec3 {
+        // relative block coordinates start at the block the caller stands in
+        self.0
+            .resolve(origin.floor(), yaw, pitch)
+            .floor()
+            .as_ivec3()
+    }
+}
+
+impl FromStr for BlockCoordinates {
+    type Err = ArgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let coordinates = Coordinates::from_str(s)?;
+
+        for coordinate in [coordinates.x, coordinates.y, coordinates.z] {
+            if let Coordinate::Absolute(value) = coordinate
+                && value.fract().abs() > 0.0
+            {
+                return Err(ArgError::FractionalBlockCoordinate(coordinate.to_string()));
+            }
+        }
+
+        Ok(Self(coordinates))
+    }
+}
+
+/// The rest of the command, spaces included, such as the message of `/say`.
+#[derive(Clone, Debug, PartialEq, Eq, Deref, Display)]
+pub struct GreedyString(pub String);
+
+impl FromStr for GreedyString {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_owned()))
+    }
+}
+
+/// Removes the `minecraft:` namespace, which ids may or may not have.
+fn strip_namespace(id: &str) -> &str {
+    id.strip_prefix("minecraft:").unwrap_or(id)
+}
+
+/// An item, such as `diamond_sword` or `minecraft:stone`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deref)]
+pub struct ItemId(pub ItemKind);
+
+impl FromStr for ItemId {
+    type Err = ArgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ItemKind::from_str(strip_namespace(s))
+            .map(Self)
+            .ok_or_else(|| ArgError::UnknownItem(s.to_owned()))
+    }
+}
+
+/// A block with optional properties, such as `oak_stairs[facing=east,half=top]`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deref)]
+pub struct BlockId(pub BlockState);
+
+impl FromStr for BlockId {
+    type Err = ArgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, properties) = match s.split_once('[') {
+            Some((name, properties)) => (name, properties.strip_suffix(']').unwrap_or(properties)),
+            None => (s, ""),
+        };
+
+        let kind = BlockKind::from_str(strip_namespace(name))
+            .ok_or_else(|| ArgError::UnknownBlock(name.to_owned()))?;
+
+        let mut state = kind.to_state();
+
+        for property in properties.split(',').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = property.split_once('=').unwrap_or((property, ""));
+            let (key, value) = (key.trim(), value.trim());
+
+            let unknown = || ArgError::UnknownProperty {
+                block: name.to_owned(),
+                property: key.to_owned(),
+                value: value.to_owned(),
+            };
+
+            let (Some(prop_name), Some(prop_value)) =
+                (PropName::from_str(key), PropValue::from_str(value))
+            else {
+                return Err(unknown());
+            };
+
+            state = state.set(prop_name, prop_value);
+
+            if state.get(prop_name) != Some(prop_value) {
+                return Err(unknown());
+            }
+        }
+
+        Ok(Self(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coordinates() {
+        let coordinates: Coordinates = "~ ~1.5 -3".parse().unwrap();
+
+        assert_eq!(coordinates.x, Coordinate::Relative(0.0));
+        assert_eq!(coordinates.y, Coordinate::Relative(1.5));
+        assert_eq!(coordinates.z, Coordinate::Absolute(-3.0));
+
+        assert_eq!(
+            "^ ~ ^".parse::<Coordinates>(),
+            Err(ArgError::MixedCoordinates)
+        );
+        assert_eq!(
+            "1 2".parse::<Coordinates>(),
+            Err(ArgError::CoordinateCount {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            "1 2 x".parse::<Coordinates>(),
+            Err(ArgError::Number("x".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_coordinates() {
+        let origin = Vec3::new(10.0, 64.0, -5.0);
+
+        let relative: Coordinates = "~1 ~ 3".parse().unwrap();
+        assert_eq!(
+            relative.resolve(origin, 0.0, 0.0),
+            Vec3::new(11.0, 64.0, 3.0)
+        );
+
+        // a yaw of 0 looks towards +z
+        let forward: Coordinates = "^ ^ ^2".parse().unwrap();
+        let resolved = forward.resolve(origin, 0.0, 0.0);
+        assert!(resolved.abs_diff_eq(Vec3::new(10.0, 64.0, -3.0), 1e-4));
+
+        // and left is +x
+        let left: Coordinates = "^1 ^ ^".parse().unwrap();
+        let resolved = left.resolve(origin, 0.0, 0.0);
+        assert!(resolved.abs_diff_eq(Vec3::new(11.0, 64.0, -5.0), 1e-4));
+    }
+
+    #[test]
+    fn test_block_coordinates() {
+        let block: BlockCoordinates = "~ ~-1 ~".parse().unwrap();
+        let origin = Vec3::new(0.5, 64.0, -0.5);
+
+        assert_eq!(block.resolve(origin, 0.0, 0.0), IVec3::new(0, 63, -1));
+        assert!("1.5 2 3".parse::<BlockCoordinates>().is_err());
+    }
+
+    #[test]
+    fn test_parse_ids() {
+        assert_eq!(
+            "minecraft:diamond_sword".parse::<ItemId>(),
+            Ok(ItemId(ItemKind::DiamondSword))
+        );
+        assert!("not_an_item".parse::<ItemId>().is_err());
+
+        let BlockId(stairs) = "oak_stairs[facing=east]".parse().unwrap();
+        assert_eq!(stairs.to_kind(), BlockKind::OakStairs);
+        assert_eq!(stairs.get(PropName::Facing), Some(PropValue::East));
+
+        assert!("stone[facing=east]".parse::<BlockId>().is_err());
+    }
+}
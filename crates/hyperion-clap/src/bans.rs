@@ -0,0 +1,361 @@
+//! `/ban`, `/tempban`, `/unban` and `/banlist`, which manage the bans kept in
+//! [`BanStorage`] and checked when players log in.
+
+use std::{net::IpAddr, time::Duration};
+
+use clap::{Arg as ClapArg, Parser, ValueHint};
+use flecs_ecs::core::{
+    Builder, Entity, EntityView, EntityViewGet, QueryAPI, QueryBuilderImpl, World, WorldGet,
+    WorldProvider,
+};
+use hyperion::{
+    ingress::PendingRemove,
+    net::{ClientAddress, agnostic},
+    simulation::{IgnMap, Name, Player, Uuid},
+    storage::{Ban, BanStorage, BanTarget, ban_message, unix_now},
+    uuid,
+};
+use hyperion_text::Key;
+
+use crate::{
+    CommandPermission, MinecraftCommand,
+    arg::GreedyString,
+    complete::{self, CompletionContext, Suggestion},
+    messages,
+};
+
+/// The reason of bans issued without one.
+const DEFAULT_REASON: &str = "Banned by an operator";
+
+/// Who a ban command names, and the name of the player if they are known.
+struct Resolved {
+    target: BanTarget,
+    name: Option<String>,
+}
+
+/// Resolves `target`, which is the name of an online player, a UUID or an IP address. With `ip`,
+/// players are resolved to the address they are connected from.
+fn resolve(world: &World, target: &str, ip: bool) -> Result<Resolved, (Key, String)> {
+    let online = world.get::<&IgnMap>(|ign_map| ign_map.get(target).copied());
+
+    if let Some(player) = online {
+        let player = player.entity_view(world);
+        let name = player.try_get::<&Name>(ToString::to_string);
+
+        let target = if ip {
+            let address = player.try_get::<&ClientAddress>(|address| address.ip());
+            BanTarget::Ip(address.ok_or((messages::NO_ADDRESS, target.to_owned()))?)
+        } else {
+            let uuid = player.try_get::<&Uuid>(|uuid| **uuid);
+            BanTarget::Player(uuid.ok_or((messages::PLAYER_NOT_FOUND, target.to_owned()))?)
+        };
+
+        return Ok(Resolved { target, name });
+    }
+
+    if let Ok(address) = target.parse::<IpAddr>() {
+        return Ok(Resolved {
+            target: BanTarget::Ip(address),
+            name: None,
+        });
+    }
+
+    match target.parse::<uuid::Uuid>() {
+        // the address an offline player connects from is not known
+        Ok(_) if ip => Err((messages::NO_ADDRESS, target.to_owned())),
+        Ok(uuid) => Ok(Resolved {
+            target: BanTarget::Player(uuid),
+            name: None,
+        }),
+        Err(_) => Err((messages::PLAYER_NOT_FOUND, target.to_owned())),
+    }
+}
+
+/// What a ban is shown as in messages: the name of the player if it is known, and the UUID or
+/// address otherwise.
+fn display_name(target: BanTarget, ban: &Ban) -> String {
+    match (&ban.name, target) {
+        (Some(name), BanTarget::Player(_)) => name.clone(),
+        (Some(name), BanTarget::Ip(address)) => format!("{address} ({name})"),
+        (None, target) => target.to_string(),
+    }
+}
+
+/// Durations suggested for temporary bans.
+fn durations(_: &CompletionContext<'_>) -> Vec<Suggestion> {
+    [
+        ("1h", "One hour"),
+        ("1d", "One day"),
+        ("7d", "One week"),
+        ("30d", "One month"),
+    ]
+    .into_iter()
+    .map(|(duration, tooltip)| Suggestion::new(duration).tooltip(tooltip))
+    .collect()
+}
+
+/// The players and addresses which are banned.
+fn banned(context: &CompletionContext<'_>) -> Vec<Suggestion> {
+    let bans = context.world.get::<&BanStorage>(BanStorage::list);
+
+    bans.unwrap_or_default()
+        .into_iter()
+        .map(|(target, ban)| {
+            let text = match (ban.name, target) {
+                (Some(name), BanTarget::Player(_)) => name,
+                _ => target.to_string(),
+            };
+
+            Suggestion::new(text).tooltip(ban.reason)
+        })
+        .collect()
+}
+
+fn reply(system: EntityView<'_>, caller: Entity, key: Key, args: &[&str]) {
+    let caller = caller.entity_view(system.world());
+    let chat = agnostic::translated(caller, key, args);
+    agnostic::unicast_chat(caller, &chat, system);
+}
+
+/// Bans `target` and disconnects the online players it applies to, telling the caller.
+fn ban(
+    system: EntityView<'_>,
+    caller: Entity,
+    target: &str,
+    ip: bool,
+    reason: Option<GreedyString>,
+    duration: Option<Duration>,
+) {
+    let world = system.world();
+
+    let resolved = match resolve(&world, target, ip) {
+        Ok(resolved) => resolved,
+        Err((key, target)) => {
+            reply(system, caller, key, &[&target]);
+            return;
+        }
+    };
+
+    let issuer = caller
+        .entity_view(world)
+        .try_get::<&Name>(ToString::to_string)
+        .unwrap_or_else(|| "Console".to_owned());
+
+    let reason = reason.map_or_else(|| DEFAULT_REASON.to_owned(), |reason| reason.0);
+
+    let ban = Ban::new(resolved.name, reason, issuer, duration);
+
+    if let Err(e) = world.get::<&BanStorage>(|bans| bans.insert(resolved.target, &ban)) {
+        tracing::error!("failed to save ban of {}: {e}", resolved.target);
+        reply(system, caller, messages::SAVE_FAILED, &[]);
+        return;
+    }
+
+    let now = unix_now();
+
+    tracing::info!(
+        target: "moderation",
+        "{} banned {} for {}: {}",
+        ban.issuer,
+        display_name(resolved.target, &ban),
+        ban.remaining_text(now),
+        ban.reason
+    );
+    let message = ban_message(&ban, now);
+
+    world
+        .query::<&Uuid>()
+        .with::<Player>()
+        .build()
+        .each_entity(|player, uuid| {
+            let banned = match resolved.target {
+                BanTarget::Player(banned) => **uuid == banned,
+                BanTarget::Ip(banned) => player
+                    .try_get::<&ClientAddress>(|address| address.ip() == banned)
+                    .unwrap_or(false),
+            };
+
+            if banned {
+                player.set(PendingRemove::new(message.clone()));
+            }
+        });
+
+    let name = display_name(resolved.target, &ban);
+
+    match duration {
+        Some(_) => {
+            let remaining = ban.remaining_text(now);
+            reply(system, caller, messages::TEMPBANNED, &[
+                &name,
+                &remaining,
+                &ban.reason,
+            ]);
+        }
+        None => reply(system, caller, messages::BANNED, &[&name, &ban.reason]),
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "ban")]
+#[command_permission(node = "hyperion.command.ban")]
+pub struct BanCommand {
+    /// The online player, UUID or IP address to ban
+    #[arg(value_hint = ValueHint::Username)]
+    target: String,
+    /// Ban the address the player is connected from
+    #[arg(long)]
+    ip: bool,
+    /// Why the player is banned, which they are shown when they try to join
+    reason: Option<GreedyString>,
+}
+
+impl MinecraftCommand for BanCommand {
+    const CONSOLE: bool = true;
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        ban(system, caller, &self.target, self.ip, self.reason, None);
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "tempban")]
+#[command_permission(node = "hyperion.command.tempban")]
+pub struct TempBanCommand {
+    /// The online player, UUID or IP address to ban
+    #[arg(value_hint = ValueHint::Username)]
+    target: String,
+    /// How long the ban lasts, such as `30m`, `12h` or `7d`
+    duration: humantime::Duration,
+    /// Ban the address the player is connected from
+    #[arg(long)]
+    ip: bool,
+    /// Why the player is banned, which they are shown when they try to join
+    reason: Option<GreedyString>,
+}
+
+impl MinecraftCommand for TempBanCommand {
+    const CONSOLE: bool = true;
+
+    fn completer(arg: &ClapArg) -> Option<complete::Completer> {
+        match arg.get_id().as_str() {
+            "duration" => Some(durations),
+            _ => complete::completer(arg),
+        }
+    }
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let duration = Some(*self.duration);
+        ban(system, caller, &self.target, self.ip, self.reason, duration);
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "unban")]
+#[command_permission(node = "hyperion.command.unban")]
+pub struct UnbanCommand {
+    /// The name of the banned player, or the UUID or IP address to unban
+    target: String,
+}
+
+impl MinecraftCommand for UnbanCommand {
+    const CONSOLE: bool = true;
+
+    fn completer(arg: &ClapArg) -> Option<complete::Completer> {
+        match arg.get_id().as_str() {
+            "target" => Some(banned),
+            _ => complete::completer(arg),
+        }
+    }
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+
+        let result = world.get::<&BanStorage>(|bans| {
+            let target = if let Ok(address) = self.target.parse::<IpAddr>() {
+                Some(BanTarget::Ip(address))
+            } else if let Ok(uuid) = self.target.parse::<uuid::Uuid>() {
+                Some(BanTarget::Player(uuid))
+            } else {
+                // offline players are found by the name they were banned with
+                bans.list()?.into_iter().find_map(|(target, ban)| {
+                    let name = ban.name?;
+                    (matches!(target, BanTarget::Player(_))
+                        && name.eq_ignore_ascii_case(&self.target))
+                    .then_some(target)
+                })
+            };
+
+            match target {
+                Some(target) => bans.remove(target),
+                None => Ok(None),
+            }
+        });
+
+        match result {
+            Ok(Some(ban)) => {
+                let issuer = caller
+                    .entity_view(world)
+                    .try_get::<&Name>(ToString::to_string)
+                    .unwrap_or_else(|| "Console".to_owned());
+
+                let name = ban.name.unwrap_or_else(|| self.target.clone());
+                tracing::info!(target: "moderation", "{issuer} unbanned {name}");
+
+                reply(system, caller, messages::UNBANNED, &[&name]);
+            }
+            Ok(None) => reply(system, caller, messages::NOT_BANNED, &[&self.target]),
+            Err(e) => {
+                tracing::error!("failed to unban {}: {e}", self.target);
+                reply(system, caller, messages::SAVE_FAILED, &[]);
+            }
+        }
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "banlist")]
+#[command_permission(node = "hyperion.command.banlist")]
+pub struct BanListCommand;
+
+impl MinecraftCommand for BanListCommand {
+    const CONSOLE: bool = true;
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+
+        let bans = match world.get::<&BanStorage>(BanStorage::list) {
+            Ok(bans) => bans,
+            Err(e) => {
+                tracing::error!("failed to list bans: {e}");
+                reply(system, caller, messages::SAVE_FAILED, &[]);
+                return;
+            }
+        };
+
+        if bans.is_empty() {
+            reply(system, caller, messages::BAN_LIST_EMPTY, &[]);
+            return;
+        }
+
+        let now = unix_now();
+
+        reply(system, caller, messages::BAN_LIST, &[&bans
+            .len()
+            .to_string()]);
+
+        for (target, ban) in &bans {
+            let name = display_name(*target, ban);
+            let remaining = match ban.remaining(now) {
+                Some(_) => format!("{} left", ban.remaining_text(now)),
+                None => "permanent".to_owned(),
+            };
+
+            reply(system, caller, messages::BAN_LIST_ENTRY, &[
+                &name,
+                &ban.issuer,
+                &remaining,
+                &ban.reason,
+            ]);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Suggestions for the argument a player is typing, which the client lists when they press tab.
+//!
+//! Argument types implement [`Complete`] to suggest values, such as the names of online players
+//! for a [`Selector`] or the block at the crosshair for [`Coordinates`]. Commands with arguments
+//! of their own types, such as a dynamic list of arenas, return the completer of those types from
+//! [`MinecraftCommand::completer`](crate::MinecraftCommand::completer):
+//!
+//! ```ignore
+//! impl Complete for Arena {
+//!     fn complete(context: &CompletionContext<'_>) -> Vec<Suggestion> {
+//!         context.world.get::<&Arenas>(|arenas| {
+//!             arenas.iter().map(|arena| Suggestion::new(&arena.name)).collect()
+//!         })
+//!     }
+//! }
+//! ```
+
+use std::any::TypeId;
+
+use clap::{Arg as ClapArg, ValueHint};
+use flecs_ecs::core::{Entity, World, WorldGet};
+use geometry::ray::Ray;
+use hyperion::{
+    BlockKind, ItemKind,
+    glam::{IVec3, Vec3},
+    simulation::{
+        IgnMap, blocks::Blocks, get_direction_from_rotation, handlers::PacketSwitchQuery,
+    },
+};
+
+use crate::{
+    arg::{BlockCoordinates, BlockId, Coordinates, ItemId},
+    selector::Selector,
+};
+
+/// How far away the block at the crosshair may be to be suggested as coordinates.
+const REACH: f32 = 10.0;
+
+/// The height of the eyes of a standing player, where the crosshair is.
+const EYE_HEIGHT: f32 = 1.62;
+
+/// A value the player may be typing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The text which replaces the argument being typed.
+    pub text: String,
+    /// Shown when the player hovers over the suggestion.
+    pub tooltip: Option<String>,
+}
+
+impl Suggestion {
+    #[must_use]
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            tooltip: None,
+        }
+    }
+
+    #[must_use]
+    pub fn tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Whether the suggestion continues `prefix`, ignoring case and the `minecraft:` namespace.
+    #[must_use]
+    pub fn matches(&self, prefix: &str) -> bool {
+        let strip = |s: &str| s.strip_prefix("minecraft:").unwrap_or(s).to_lowercase();
+        strip(&self.text).starts_with(&strip(prefix))
+    }
+}
+
+/// The player asking for suggestions.
+pub struct CompletionContext<'a> {
+    pub world: &'a World,
+    pub caller: Entity,
+    pub blocks: &'a Blocks,
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl<'a> CompletionContext<'a> {
+    #[must_use]
+    pub fn new(query: &'a PacketSwitchQuery<'_>) -> Self {
+        Self {
+            world: query.world,
+            caller: query.id,
+            blocks: query.blocks,
+            position: **query.position,
+            yaw: **query.yaw,
+            pitch: **query.pitch,
+        }
+    }
+
+    /// The block at the caller's crosshair, if one is within reach.
+    #[must_use]
+    pub fn targeted_block(&self) -> Option<IVec3> {
+        let eye = self.position + Vec3::new(0.0, EYE_HEIGHT, 0.0);
+        let direction = get_direction_from_rotation(self.yaw, self.pitch);
+
+        self.blocks
+            .first_collision(Ray::new(eye, direction) * REACH)
+            .map(|collision| collision.location)
+    }
+}
+
+/// Suggests values for arguments of a type.
+pub trait Complete {
+    fn complete(context: &CompletionContext<'_>) -> Vec<Suggestion>;
+}
+
+/// Suggests values for an argument, such as [`Complete::complete`] of its type.
+pub type Completer = fn(&CompletionContext<'_>) -> Vec<Suggestion>;
+
+/// The names of the players online.
+#[must_use]
+pub fn players(context: &CompletionContext<'_>) -> Vec<Suggestion> {
+    context.world.get::<&IgnMap>(|ign_map| {
+        ign_map
+            .iter()
+            .map(|(name, _)| Suggestion::new(&**name))
+            .collect()
+    })
+}
+
+impl Complete for Selector {
+    fn complete(context: &CompletionContext<'_>) -> Vec<Suggestion> {
+        let selectors = [
+            ("@p", "Nearest player"),
+            ("@a", "All players"),
+            ("@r", "Random player"),
+            ("@e", "All entities"),
+            ("@s", "Yourself"),
+        ];
+
+        selectors
+            .into_iter()
+            .map(|(selector, tooltip)| Suggestion::new(selector).tooltip(tooltip))
+            .chain(players(context))
+            .collect()
+    }
+}
+
+impl Complete for Coordinates {
+    fn complete(context: &CompletionContext<'_>) -> Vec<Suggestion> {
+        let mut suggestions = Vec::new();
+
+        if let Some(block) = context.targeted_block() {
+            suggestions.push(
+                Suggestion::new(format!("{} {} {}", block.x, block.y, block.z))
+                    .tooltip("Targeted block"),
+            );
+        }
+
+        suggestions.push(Suggestion::new("~ ~ ~").tooltip("Your position"));
+        suggestions
+    }
+}
+
+impl Complete for BlockCoordinates {
+    fn complete(context: &CompletionContext<'_>) -> Vec<Suggestion> {
+        Coordinates::complete(context)
+    }
+}
+
+impl Complete for ItemId {
+    fn complete(_: &CompletionContext<'_>) -> Vec<Suggestion> {
+        ItemKind::ALL
+            .iter()
+            .map(|item| Suggestion::new(item.to_str()))
+            .collect()
+    }
+}
+
+impl Complete for BlockId {
+    fn complete(_: &CompletionContext<'_>) -> Vec<Suggestion> {
+        BlockKind::ALL
+            .iter()
+            .map(|block| Suggestion::new(block.to_str()))
+            .collect()
+    }
+}
+
+/// The completer for `arg`, from the type it is parsed into. Arguments hinted as
+/// [`ValueHint::Username`] complete the names of online players.
+#[must_use]
+pub fn completer(arg: &ClapArg) -> Option<Completer> {
+    if arg.get_value_hint() == ValueHint::Username {
+        return Some(players);
+    }
+
+    let ty = arg.get_value_parser().type_id();
+
+    let completers: [(TypeId, Completer); 5] = [
+        (TypeId::of::<Selector>(), Selector::complete),
+        (TypeId::of::<Coordinates>(), Coordinates::complete),
+        (TypeId::of::<BlockCoordinates>(), BlockCoordinates::complete),
+        (TypeId::of::<ItemId>(), ItemId::complete),
+        (TypeId::of::<BlockId>(), BlockId::complete),
+    ];
+
+    completers
+        .into_iter()
+        .find(|(id, _)| ty == *id)
+        .map(|(_, completer)| completer)
+}
+
+/// The values clap accepts for `arg`, such as the variants of a [`clap::ValueEnum`], with their
+/// help as the tooltip.
+#[must_use]
+pub fn possible_values(arg: &ClapArg) -> Vec<Suggestion> {
+    arg.get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| Suggestion {
+            text: value.get_name().to_owned(),
+            tooltip: value.get_help().map(ToString::to_string),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestion_matches() {
+        let stone = Suggestion::new("stone");
+
+        assert!(stone.matches(""));
+        assert!(stone.matches("St"));
+        assert!(stone.matches("minecraft:sto"));
+        assert!(!stone.matches("dirt"));
+    }
+
+    #[test]
+    fn test_completer_from_type() {
+        let arg = |arg: ClapArg| completer(&arg).is_some();
+
+        assert!(arg(
+            ClapArg::new("targets").value_parser(clap::value_parser!(Selector))
+        ));
+        assert!(arg(ClapArg::new("player").value_hint(ValueHint::Username)));
+        assert!(!arg(ClapArg::new("name")));
+    }
+}
@@ -0,0 +1,58 @@
+//! `/egress`, which dumps the [`EgressStats`] recorded while `egress_stats` is enabled in the
+//! config.
+
+use clap::Parser;
+use flecs_ecs::core::{Entity, EntityView, WorldGet, WorldProvider};
+use hyperion::{
+    net::{
+        agnostic,
+        stats::{EgressStats, STATS_DIR},
+    },
+    runtime::AsyncRuntime,
+};
+
+use crate::{CommandPermission, MinecraftCommand, messages};
+
+/// The file the statistics are written to when no name is given.
+const DEFAULT_FILE: &str = "egress.csv";
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "egress")]
+#[command_permission(node = "hyperion.command.egress")]
+pub struct EgressCommand {
+    /// The name of the CSV file in the stats directory of the server to write the statistics to
+    file: Option<String>,
+}
+
+impl MinecraftCommand for EgressCommand {
+    const CONSOLE: bool = true;
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let caller = caller.entity_view(world);
+        let file = self.file.as_deref().unwrap_or(DEFAULT_FILE);
+
+        let chat = world.get::<&EgressStats>(|stats| {
+            if stats.ticks == 0 {
+                return agnostic::translated(caller, messages::EGRESS_DISABLED, &[]);
+            }
+
+            match world.get::<&AsyncRuntime>(|runtime| stats.dump(file, runtime)) {
+                Ok(path) => {
+                    let total = stats.total.total();
+                    agnostic::translated(caller, messages::EGRESS_DUMPED, &[
+                        &path.display().to_string(),
+                        &stats.ticks.to_string(),
+                        &total.count.to_string(),
+                        &total.bytes.to_string(),
+                    ])
+                }
+                Err(_) => {
+                    agnostic::translated(caller, messages::EGRESS_INVALID_FILE, &[file, STATS_DIR])
+                }
+            }
+        });
+
+        agnostic::unicast_chat(caller, &chat, system);
+    }
+}
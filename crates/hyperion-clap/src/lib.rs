@@ -1,32 +1,68 @@
-use std::iter::zip;
+#![feature(let_chains)]
 
 use clap::{Arg as ClapArg, Parser, ValueEnum, ValueHint, error::ErrorKind};
 use flecs_ecs::{
-    core::{Entity, EntityView, EntityViewGet, World, WorldGet, WorldProvider},
+    core::{Entity, EntityView, World, WorldGet, WorldProvider, flecs},
     prelude::{Component, Module},
 };
 use hyperion::{
-    net::{Compose, ConnectionId, DataBundle, agnostic},
-    simulation::{IgnMap, command::get_root_command_entity, handlers::PacketSwitchQuery},
+    net::agnostic,
+    simulation::{
+        EntitySize, Position, command::get_root_command_entity, console::Console,
+        handlers::PacketSwitchQuery,
+    },
     storage::{CommandCompletionRequest, EventFn},
 };
 pub use hyperion_clap_macros::CommandPermission;
 pub use hyperion_command;
 use hyperion_command::{CommandHandler, CommandRegistry};
-use hyperion_permission::Group;
 use valence_protocol::{
     VarInt,
     packets::{
         play,
-        play::{command_suggestions_s2c::CommandSuggestionsMatch, command_tree_s2c::StringArg},
+        play::{
+            command_suggestions_s2c::CommandSuggestionsMatch,
+            command_tree_s2c::Parser as BrigadierParser,
+        },
     },
+    text::IntoText,
 };
 
+pub mod arg;
+pub mod bans;
+pub mod complete;
+pub mod egress;
+pub mod messages;
+pub mod parser;
+pub mod perms;
+pub mod selector;
+mod tree;
+
 pub trait MinecraftCommand: Parser + CommandPermission {
     fn execute(self, system: EntityView<'_>, caller: Entity);
 
+    /// Whether the console may run the command. Commands which act on the player running them
+    /// leave this off, so that the console is told to run them as a player instead.
+    const CONSOLE: bool = false;
+
     fn pre_register(_world: &World) {}
 
+    /// The parser the client validates `arg` with, which by default is picked from the type of
+    /// the argument by [`parser::parser`]. Override it to give numbers bounds, which clap does
+    /// not expose.
+    #[must_use]
+    fn parser(arg: &ClapArg) -> BrigadierParser {
+        parser::parser(arg)
+    }
+
+    /// What to suggest while `arg` is typed, which by default is picked from the type of the
+    /// argument by [`complete::completer`]. Arguments without a completer suggest the values clap
+    /// accepts for them. Override it to complete arguments of other types.
+    #[must_use]
+    fn completer(arg: &ClapArg) -> Option<complete::Completer> {
+        complete::completer(arg)
+    }
+
     fn register(registry: &mut CommandRegistry, world: &World) {
         Self::pre_register(world);
 
@@ -34,57 +70,30 @@ pub trait MinecraftCommand: Parser + CommandPermission {
         let name = cmd.get_name();
 
         let has_permissions = |world: &World, caller: Entity| {
-            caller
-                .entity_view(world)
-                .get::<&Group>(|group| Self::has_required_permission(*group))
+            hyperion_permission::has_permission(caller.entity_view(world), Self::PERMISSION)
         };
 
-        let node_to_register =
-            hyperion::simulation::command::Command::literal(name, has_permissions);
-
-        let mut on = world
-            .entity()
-            .set(node_to_register)
-            .child_of_id(get_root_command_entity());
-
-        for arg in cmd.get_arguments() {
-            use valence_protocol::packets::play::command_tree_s2c::Parser as ValenceParser;
-            let name = arg.get_value_names().unwrap().first().unwrap();
-            let name = name.to_ascii_lowercase();
-            let node_to_register = hyperion::simulation::command::Command::argument(
-                name,
-                ValenceParser::String(StringArg::SingleWord),
-            );
-
-            on = world.entity().set(node_to_register).child_of_id(on);
-        }
+        let literal = hyperion::simulation::command::Command::literal(name, has_permissions);
+        tree::add_command_nodes::<Self>(world, &cmd, get_root_command_entity(), literal);
 
         let on_execute = |input: &str, system: EntityView<'_>, caller: Entity| {
-            let input = input.split_whitespace();
+            let input = tree::split_arguments::<Self>(input);
             let world = system.world();
+            let caller = caller.entity_view(world);
+
+            if !Self::CONSOLE && caller.has::<Console>() {
+                let chat = agnostic::translated(caller, messages::PLAYER_REQUIRED, &[]);
+                agnostic::unicast_chat(caller, &chat, system);
+                return;
+            }
 
             match Self::try_parse_from(input) {
                 Ok(elem) => {
-                    if world.get::<&Compose>(|compose| {
-                        caller.entity_view(world).get::<(&ConnectionId, &Group)>(
-                            |(stream, group)| {
-                                if Self::has_required_permission(*group) {
-                                    true
-                                } else {
-                                    let chat = agnostic::chat(
-                                        "§cYou do not have permission to use this command!",
-                                    );
-
-                                    let mut bundle = DataBundle::new(compose, system);
-                                    bundle.add_packet(&chat).unwrap();
-                                    bundle.unicast(*stream).unwrap();
-
-                                    false
-                                }
-                            },
-                        )
-                    }) {
-                        elem.execute(system, caller);
+                    if hyperion_permission::has_permission(caller, Self::PERMISSION) {
+                        elem.execute(system, caller.id());
+                    } else {
+                        let chat = agnostic::translated(caller, messages::NO_PERMISSION, &[]);
+                        agnostic::unicast_chat(caller, &chat, system);
                     }
                 }
                 Err(e) => {
@@ -95,16 +104,8 @@ pub trait MinecraftCommand: Parser + CommandPermission {
                     };
 
                     // minecraft red
-                    let msg = format!("{prefix}{e}");
-
-                    world.get::<&Compose>(|compose| {
-                        caller
-                            .entity_view(world)
-                            .get::<&hyperion::net::ConnectionId>(|stream| {
-                                let msg = agnostic::chat(msg);
-                                compose.unicast(&msg, *stream, system).unwrap();
-                            });
-                    });
+                    let msg = agnostic::chat(format!("{prefix}{e}"));
+                    agnostic::unicast_chat(caller, &msg, system);
 
                     tracing::warn!("could not parse command {e}");
                 }
@@ -115,106 +116,53 @@ pub trait MinecraftCommand: Parser + CommandPermission {
             |packet_switch_query: &mut PacketSwitchQuery<'_>,
              completion: &CommandCompletionRequest<'_>| {
                 let full_query = completion.query;
-                let id = completion.id;
 
+                // the client only asks to complete commands, which start with a slash
                 let Some(query) = full_query.strip_prefix('/') else {
-                    // todo: send error message to player
                     tracing::warn!("could not parse command {full_query}");
                     return;
                 };
 
-                let mut query = query.split_whitespace();
-                let _command_name = query.next().unwrap();
-
                 let command = Self::command();
-                let mut positionals = command.get_positionals();
-
-                'positionals: for (input_arg, cmd_arg) in zip(query, positionals.by_ref()) {
-                    // see if anything matches
-                    let possible_values = cmd_arg.get_possible_values();
-                    for possible in &possible_values {
-                        if possible.matches(input_arg, true) {
-                            continue 'positionals;
-                        }
-                    }
 
-                    // nothing matches! let's see if a substring matches
-                    let mut substring_matches = possible_values
-                        .iter()
-                        .filter(|possible| {
-                            // todo: this is inefficient
-                            possible
-                                .get_name()
-                                .to_lowercase()
-                                .starts_with(&input_arg.to_lowercase())
-                        })
-                        .peekable();
-
-                    if substring_matches.peek().is_none() {
-                        // no matches
-                        return;
-                    }
+                let Some(completing) = tree::completing::<Self>(&command, query) else {
+                    return;
+                };
 
-                    let matches = substring_matches
-                        .map(clap::builder::PossibleValue::get_name)
-                        .map(|name| CommandSuggestionsMatch {
-                            suggested_match: name,
-                            tooltip: None,
-                        })
-                        .collect();
-
-                    let start = input_arg.as_ptr() as usize - full_query.as_ptr() as usize;
-                    let len = input_arg.len();
-
-                    let start = i32::try_from(start).unwrap();
-                    let len = i32::try_from(len).unwrap();
-
-                    let packet = play::CommandSuggestionsS2c {
-                        id: VarInt(id),
-                        start: VarInt(start),
-                        length: VarInt(len),
-                        matches,
-                    };
+                let context = complete::CompletionContext::new(packet_switch_query);
 
-                    packet_switch_query
-                        .compose
-                        .unicast(
-                            &packet,
-                            packet_switch_query.io_ref,
-                            packet_switch_query.system,
-                        )
-                        .unwrap();
+                let mut suggestions: Vec<_> = completing
+                    .literals
+                    .into_iter()
+                    .map(complete::Suggestion::new)
+                    .collect();
 
-                    // todo: send possible matches to player
-                    return;
+                if let Some(arg) = completing.arg {
+                    match Self::completer(arg) {
+                        Some(completer) => suggestions.extend(completer(&context)),
+                        None => suggestions.extend(complete::possible_values(arg)),
+                    }
                 }
 
-                let Some(remaining_positional) = positionals.next() else {
-                    // we are all done completing
-                    return;
-                };
-
-                let possible_values = remaining_positional.get_possible_values();
+                let typed = &query[completing.start..];
+                suggestions.retain(|suggestion| suggestion.matches(typed));
 
-                let names = possible_values
+                let matches = suggestions
                     .iter()
-                    .map(clap::builder::PossibleValue::get_name);
-
-                let matches = names
-                    .into_iter()
-                    .map(|name| CommandSuggestionsMatch {
-                        suggested_match: name,
-                        tooltip: None,
+                    .map(|suggestion| CommandSuggestionsMatch {
+                        suggested_match: &suggestion.text,
+                        tooltip: suggestion.tooltip.as_deref().map(IntoText::into_cow_text),
                     })
                     .collect();
 
-                let start = full_query.len();
-                let start = i32::try_from(start).unwrap();
+                // the slash is part of what the client sent
+                let start = i32::try_from(completing.start + 1).unwrap();
+                let length = i32::try_from(typed.len()).unwrap();
 
                 let packet = play::CommandSuggestionsS2c {
-                    id: VarInt(id),
+                    id: VarInt(completion.id),
                     start: VarInt(start),
-                    length: VarInt(0),
+                    length: VarInt(length),
                     matches,
                 };
 
@@ -242,6 +190,7 @@ pub trait MinecraftCommand: Parser + CommandPermission {
 }
 
 pub enum Arg {
+    /// An online player, which the client parses as a player name or selector.
     Player,
 }
 
@@ -261,7 +210,8 @@ impl MinecraftArg for ClapArg {
 }
 
 pub trait CommandPermission {
-    fn has_required_permission(user_group: hyperion_permission::Group) -> bool;
+    /// The permission node needed to run the command, such as `tag.command.fly`.
+    const PERMISSION: &'static str;
 }
 
 #[derive(Clone, Debug, ValueEnum, PartialEq, Eq)]
@@ -275,94 +225,25 @@ pub enum GameMode {
 #[derive(Component)]
 pub struct ClapCommandModule;
 
-#[derive(clap::Parser, Debug)]
-pub struct SetCommand {
-    player: String,
-    group: Group,
-}
-
-#[derive(clap::Parser, Debug)]
-pub struct GetCommand {
-    player: String,
-}
-
-#[derive(Parser, CommandPermission, Debug)]
-#[command(name = "perms")]
-#[command_permission(group = "Normal")]
-pub enum PermissionCommand {
-    Set(SetCommand),
-    Get(GetCommand),
-}
-
-impl MinecraftCommand for PermissionCommand {
-    fn execute(self, system: EntityView<'_>, caller: Entity) {
-        let world = system.world();
-        world.get::<&IgnMap>(|ign_map| {
-            match self {
-                Self::Set(cmd) => {
-                    // Handle setting permissions
-                    let Some(entity) = ign_map.get(cmd.player.as_str()) else {
-                        caller.entity_view(world).get::<&ConnectionId>(|stream| {
-                            let msg = format!("§c{} not found", cmd.player);
-                            let chat = hyperion::net::agnostic::chat(msg);
-                            world.get::<&Compose>(|compose| {
-                                compose.unicast(&chat, *stream, system).unwrap();
-                            });
-                        });
-                        return;
-                    };
-
-                    entity.entity_view(world).get::<&mut Group>(|group| {
-                        if *group != cmd.group {
-                            *group = cmd.group;
-                            entity.entity_view(world).modified::<Group>();
-                        }
-
-                        caller.entity_view(world).get::<&ConnectionId>(|stream| {
-                            let msg = format!(
-                                "§b{}§r's group has been set to §e{:?}",
-                                cmd.player, cmd.group
-                            );
-                            let chat = hyperion::net::agnostic::chat(msg);
-                            world.get::<&Compose>(|compose| {
-                                compose.unicast(&chat, *stream, system).unwrap();
-                            });
-                        });
-                    });
-                }
-                Self::Get(cmd) => {
-                    let Some(entity) = ign_map.get(cmd.player.as_str()) else {
-                        caller.entity_view(world).get::<&ConnectionId>(|stream| {
-                            let msg = format!("§c{} not found", cmd.player);
-                            let chat = hyperion::net::agnostic::chat(msg);
-                            world.get::<&Compose>(|compose| {
-                                compose.unicast(&chat, *stream, system).unwrap();
-                            });
-                        });
-                        return;
-                    };
-
-                    entity.entity_view(world).get::<&Group>(|group| {
-                        caller.entity_view(world).get::<&ConnectionId>(|stream| {
-                            let msg = format!("§b{}§r's group is §e{:?}", cmd.player, group);
-                            let chat = hyperion::net::agnostic::chat(msg);
-                            world.get::<&Compose>(|compose| {
-                                compose.unicast(&chat, *stream, system).unwrap();
-                            });
-                        });
-                    });
-                }
-            }
-        });
-    }
-}
-
 impl Module for ClapCommandModule {
     fn module(world: &World) {
         world.import::<hyperion_command::CommandModule>();
+        world.import::<spatial::SpatialModule>();
+
+        // selectors find the entities in an area through the spatial index, so every entity with
+        // a position is indexed
+        world
+            .component::<Position>()
+            .add_trait::<(flecs::With, EntitySize)>()
+            .add_trait::<(flecs::With, spatial::Spatial)>();
 
         world.get::<&mut CommandRegistry>(|registry| {
-            PermissionCommand::register(registry, world);
+            perms::PermissionCommand::register(registry, world);
+            egress::EgressCommand::register(registry, world);
+            bans::BanCommand::register(registry, world);
+            bans::TempBanCommand::register(registry, world);
+            bans::UnbanCommand::register(registry, world);
+            bans::BanListCommand::register(registry, world);
         });
     }
 }
@@ -0,0 +1,65 @@
+//! The messages sent by commands, which can be translated in `run/lang`.
+
+hyperion_text::translation_keys! {
+    pub NO_PERMISSION = "command.no_permission" => "<red>You do not have permission to use this command!";
+    pub PLAYER_REQUIRED = "command.player_required" => "<red>A player is required to run this command here";
+    /// `{0}` is the name of the player.
+    pub PLAYER_NOT_FOUND = "command.player_not_found" => "<red>{0} not found";
+    /// `{0}` is the name of the player, `{1}` their groups and `{2}` their own nodes.
+    pub PLAYER_PERMISSIONS = "command.perms.player_info" => "<aqua>{0}<reset> is in <yellow>{1}<reset> with <yellow>{2}";
+    /// `{0}` is the name of the player and `{1}` the group.
+    pub PLAYER_GROUP_ADDED = "command.perms.player_group_added" => "<aqua>{0}<reset> was added to <yellow>{1}";
+    /// `{0}` is the name of the player and `{1}` the group.
+    pub PLAYER_GROUP_REMOVED = "command.perms.player_group_removed" => "<aqua>{0}<reset> was removed from <yellow>{1}";
+    /// `{0}` is the name of the player or group, `{1}` the node and `{2}` whether it is granted.
+    pub NODE_SET = "command.perms.node_set" => "<yellow>{1}<reset> is now <yellow>{2}<reset> for <aqua>{0}";
+    /// `{0}` is the name of the player or group and `{1}` the node.
+    pub NODE_UNSET = "command.perms.node_unset" => "<yellow>{1}<reset> is no longer set for <aqua>{0}";
+    /// `{0}` is the name of the player and `{1}` the node.
+    pub PERMISSION_GRANTED = "command.perms.granted" => "<aqua>{0}<reset> has <green>{1}";
+    /// `{0}` is the name of the player and `{1}` the node.
+    pub PERMISSION_DENIED = "command.perms.denied" => "<aqua>{0}<reset> does not have <red>{1}";
+    /// `{0}` is the name of the group, `{1}` its parents and `{2}` its nodes.
+    pub GROUP_INFO = "command.perms.group_info" => "<aqua>{0}<reset> inherits from <yellow>{1}<reset> with <yellow>{2}";
+    /// `{0}` is the names of the groups.
+    pub GROUP_LIST = "command.perms.group_list" => "Groups: <yellow>{0}";
+    /// `{0}` is the name of the group.
+    pub GROUP_CREATED = "command.perms.group_created" => "Created group <aqua>{0}";
+    /// `{0}` is the name of the group.
+    pub GROUP_DELETED = "command.perms.group_deleted" => "Deleted group <aqua>{0}";
+    /// `{0}` is the name of the group.
+    pub GROUP_EXISTS = "command.perms.group_exists" => "<red>Group {0} already exists";
+    /// `{0}` is the name of the group.
+    pub UNKNOWN_GROUP = "command.perms.unknown_group" => "<red>Unknown group {0}";
+    /// `{0}` is the name of the group and `{1}` the parent.
+    pub PARENT_ADDED = "command.perms.parent_added" => "<aqua>{0}<reset> now inherits from <yellow>{1}";
+    /// `{0}` is the name of the group and `{1}` the parent.
+    pub PARENT_REMOVED = "command.perms.parent_removed" => "<aqua>{0}<reset> no longer inherits from <yellow>{1}";
+    /// `{0}` is the name of the group and `{1}` the parent, which already inherits from it.
+    pub PARENT_CYCLE = "command.perms.parent_cycle" => "<red>{1} already inherits from {0}";
+    /// `{0}` is the node.
+    pub INVALID_NODE = "command.perms.invalid_node" => "<red>{0} is not a permission node, such as tag.command.fly or tag.*";
+    pub SAVE_FAILED = "command.perms.save_failed" => "<red>Failed to save permissions, see the server logs";
+    /// `{0}` is the file, `{1}` the number of ticks recorded, `{2}` the number of packets and `{3}`
+    /// their size in bytes.
+    pub EGRESS_DUMPED = "command.egress.dumped" => "Writing egress stats of <yellow>{1}<reset> ticks (<yellow>{2}<reset> packets, <yellow>{3}<reset> bytes) to <aqua>{0}";
+    pub EGRESS_DISABLED = "command.egress.disabled" => "<red>No egress stats were recorded; set egress_stats = true in the config";
+    /// `{0}` is the file name and `{1}` the directory stats are written to.
+    pub EGRESS_INVALID_FILE = "command.egress.invalid_file" => "<red>{0} is not a file name; stats are written to a file in {1}";
+    /// `{0}` is the name of the player.
+    pub NO_ADDRESS = "command.ban.no_address" => "<red>The address of {0} is not known";
+    /// `{0}` is the banned player or address and `{1}` the reason.
+    pub BANNED = "command.ban.banned" => "Banned <aqua>{0}<reset>: {1}";
+    /// `{0}` is the banned player or address, `{1}` how long the ban lasts and `{2}` the reason.
+    pub TEMPBANNED = "command.ban.tempbanned" => "Banned <aqua>{0}<reset> for <yellow>{1}<reset>: {2}";
+    /// `{0}` is the unbanned player or address.
+    pub UNBANNED = "command.ban.unbanned" => "Unbanned <aqua>{0}";
+    /// `{0}` is the player or address.
+    pub NOT_BANNED = "command.ban.not_banned" => "<red>{0} is not banned";
+    pub BAN_LIST_EMPTY = "command.banlist.empty" => "There are no bans";
+    /// `{0}` is the number of bans.
+    pub BAN_LIST = "command.banlist.header" => "There are <yellow>{0}<reset> bans:";
+    /// `{0}` is the banned player or address, `{1}` who banned them, `{2}` how long the ban has
+    /// left and `{3}` the reason.
+    pub BAN_LIST_ENTRY = "command.banlist.entry" => "<aqua>{0}<reset> by <yellow>{1}<reset> (<yellow>{2}<reset>): {3}";
+}
@@ -0,0 +1,138 @@
+//! Picks the Brigadier parser the client uses for each clap argument.
+
+use std::any::TypeId;
+
+use clap::{Arg as ClapArg, ValueHint};
+use hyperion::simulation::command::Parser;
+use valence_protocol::packets::play::command_tree_s2c::StringArg;
+
+use crate::{
+    arg::{BlockCoordinates, BlockId, Coordinates, GreedyString, ItemId},
+    selector::Selector,
+};
+
+/// The parser for integers from `min` to `max`, which are longs if they do not fit an `i32`.
+fn integer(min: i64, max: i64) -> Parser {
+    match (i32::try_from(min), i32::try_from(max)) {
+        (Ok(min), Ok(max)) => Parser::Integer {
+            min: Some(min),
+            max: Some(max),
+        },
+        _ => Parser::Long {
+            min: Some(min),
+            max: Some(max),
+        },
+    }
+}
+
+/// The parser for `arg`, from the type it is parsed into. Arguments hinted as
+/// [`ValueHint::Username`] are players, and types without a parser of their own are single words.
+#[must_use]
+pub fn parser(arg: &ClapArg) -> Parser {
+    if arg.get_value_hint() == ValueHint::Username {
+        return Parser::Entity {
+            single: true,
+            only_players: true,
+        };
+    }
+
+    let ty = arg.get_value_parser().type_id();
+
+    let parsers = [
+        (TypeId::of::<bool>(), Parser::Bool),
+        (TypeId::of::<i8>(), integer(i8::MIN.into(), i8::MAX.into())),
+        (
+            TypeId::of::<i16>(),
+            integer(i16::MIN.into(), i16::MAX.into()),
+        ),
+        (
+            TypeId::of::<i32>(),
+            integer(i32::MIN.into(), i32::MAX.into()),
+        ),
+        (TypeId::of::<i64>(), integer(i64::MIN, i64::MAX)),
+        (TypeId::of::<u8>(), integer(0, u8::MAX.into())),
+        (TypeId::of::<u16>(), integer(0, u16::MAX.into())),
+        (TypeId::of::<u32>(), integer(0, u32::MAX.into())),
+        (TypeId::of::<u64>(), Parser::Long {
+            min: Some(0),
+            max: None,
+        }),
+        (TypeId::of::<f32>(), Parser::Float {
+            min: None,
+            max: None,
+        }),
+        (TypeId::of::<f64>(), Parser::Double {
+            min: None,
+            max: None,
+        }),
+        (
+            TypeId::of::<GreedyString>(),
+            Parser::String(StringArg::GreedyPhrase),
+        ),
+        (TypeId::of::<Coordinates>(), Parser::Vec3),
+        (TypeId::of::<BlockCoordinates>(), Parser::BlockPos),
+        (TypeId::of::<ItemId>(), Parser::ItemStack),
+        (TypeId::of::<BlockId>(), Parser::BlockState),
+        (TypeId::of::<Selector>(), Parser::Entity {
+            single: false,
+            only_players: false,
+        }),
+    ];
+
+    parsers
+        .into_iter()
+        .find(|(id, _)| ty == *id)
+        .map_or(Parser::String(StringArg::SingleWord), |(_, parser)| parser)
+}
+
+/// How many words the client sends for an argument parsed by `parser`, or `None` if it takes
+/// the rest of the command.
+#[must_use]
+pub const fn word_count(parser: &Parser) -> Option<usize> {
+    match parser {
+        Parser::BlockPos | Parser::Vec3 => Some(3),
+        Parser::ColumnPos | Parser::Vec2 | Parser::Rotation => Some(2),
+        Parser::String(StringArg::GreedyPhrase) | Parser::Message => None,
+        _ => Some(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser_from_type() {
+        let arg = |arg: ClapArg| parser(&arg);
+
+        assert!(matches!(
+            arg(ClapArg::new("amount").value_parser(clap::value_parser!(u16))),
+            Parser::Integer {
+                min: Some(0),
+                max: Some(65535)
+            }
+        ));
+        assert!(matches!(
+            arg(ClapArg::new("position").value_parser(clap::value_parser!(BlockCoordinates))),
+            Parser::BlockPos
+        ));
+        assert!(matches!(
+            arg(ClapArg::new("player").value_hint(ValueHint::Username)),
+            Parser::Entity {
+                single: true,
+                only_players: true
+            }
+        ));
+        assert!(matches!(
+            arg(ClapArg::new("targets").value_parser(clap::value_parser!(Selector))),
+            Parser::Entity {
+                single: false,
+                only_players: false
+            }
+        ));
+        assert!(matches!(
+            arg(ClapArg::new("name")),
+            Parser::String(StringArg::SingleWord)
+        ));
+    }
+}
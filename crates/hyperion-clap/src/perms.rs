@@ -0,0 +1,384 @@
+//! `/perms`, which manages the groups of players and the permission nodes granted to players and
+//! groups.
+
+use clap::{Arg as ClapArg, Parser, Subcommand};
+use flecs_ecs::core::{Entity, EntityView, EntityViewGet, World, WorldGet, WorldProvider};
+use hyperion::{
+    net::agnostic,
+    simulation::{Name, Uuid},
+};
+use hyperion_permission::{DEFAULT_GROUP, Group, PermissionStorage, Permissions, node};
+use hyperion_text::Key;
+
+use crate::{
+    CommandPermission, MinecraftCommand,
+    complete::{self, CompletionContext, Suggestion},
+    messages,
+    selector::Selector,
+};
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "perms")]
+#[command_permission(node = "hyperion.command.perms")]
+pub enum PermissionCommand {
+    /// Manages the groups and nodes of players
+    Player(PlayerCommand),
+    /// Manages a group
+    Group(GroupCommand),
+    /// Lists the groups
+    Groups,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct PlayerCommand {
+    player: Selector,
+    #[command(subcommand)]
+    action: PlayerAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PlayerAction {
+    /// Shows the groups and nodes of the players
+    Info,
+    /// Adds the players to a group
+    Add { group: String },
+    /// Removes the players from a group
+    Remove { group: String },
+    /// Grants or denies a node to the players, over what their groups grant
+    Set { node: String, value: Option<bool> },
+    /// Removes a node granted or denied to the players
+    Unset { node: String },
+    /// Shows whether the players have a node
+    Check { node: String },
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct GroupCommand {
+    group: String,
+    #[command(subcommand)]
+    action: GroupAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GroupAction {
+    /// Shows the parents and nodes of the group
+    Info,
+    /// Creates the group
+    Create,
+    /// Deletes the group
+    Delete,
+    /// Grants or denies a node to the group
+    Set { node: String, value: Option<bool> },
+    /// Removes a node granted or denied to the group
+    Unset { node: String },
+    /// Makes the group inherit the nodes of another
+    AddParent { parent: String },
+    /// Stops the group from inheriting the nodes of another
+    RemoveParent { parent: String },
+}
+
+/// Lists `items`, or `none` if there are none.
+fn list<'a>(items: impl IntoIterator<Item = &'a str>) -> String {
+    let items: Vec<&str> = items.into_iter().collect();
+
+    if items.is_empty() {
+        "none".to_owned()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// Lists `nodes`, with the denied ones prefixed by `-`.
+fn list_nodes<'a>(nodes: impl IntoIterator<Item = (&'a String, &'a bool)>) -> String {
+    let nodes: Vec<String> = nodes
+        .into_iter()
+        .map(|(node, granted)| {
+            if *granted {
+                node.clone()
+            } else {
+                format!("-{node}")
+            }
+        })
+        .collect();
+
+    list(nodes.iter().map(String::as_str))
+}
+
+/// The names of the groups.
+fn groups(context: &CompletionContext<'_>) -> Vec<Suggestion> {
+    context.world.get::<&PermissionStorage>(|storage| {
+        storage
+            .groups()
+            .iter()
+            .map(|(name, group)| {
+                Suggestion::new(name).tooltip(format!("{} nodes", group.nodes.len()))
+            })
+            .collect()
+    })
+}
+
+/// Replies to the caller of a command.
+struct Reply<'a> {
+    caller: EntityView<'a>,
+    system: EntityView<'a>,
+}
+
+impl Reply<'_> {
+    fn send(&self, key: Key, args: &[&str]) {
+        let chat = agnostic::translated(self.caller, key, args);
+        agnostic::unicast_chat(self.caller, &chat, self.system);
+    }
+
+    /// Tells the caller whether `result` saved the permissions, returning whether it did.
+    fn saved<E: std::fmt::Display>(&self, result: Result<(), E>) -> bool {
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::error!("failed to save permissions: {e}");
+                self.send(messages::SAVE_FAILED, &[]);
+                false
+            }
+        }
+    }
+}
+
+impl PlayerCommand {
+    fn execute(self, world: &World, reply: &Reply<'_>) {
+        let node = match &self.action {
+            PlayerAction::Set { node, .. }
+            | PlayerAction::Unset { node }
+            | PlayerAction::Check { node } => Some(node),
+            _ => None,
+        };
+
+        if let Some(node) = node
+            && !node::is_valid(node)
+        {
+            reply.send(messages::INVALID_NODE, &[node]);
+            return;
+        }
+
+        if let PlayerAction::Add { group } = &self.action
+            && world.get::<&PermissionStorage>(|storage| storage.group(group).is_none())
+        {
+            reply.send(messages::UNKNOWN_GROUP, &[group]);
+            return;
+        }
+
+        let targets = self.player.resolve(world, reply.caller.id());
+
+        if targets.is_empty() {
+            reply.send(messages::PLAYER_NOT_FOUND, &[&self.player.to_string()]);
+            return;
+        }
+
+        world.get::<&PermissionStorage>(|storage| {
+            for target in targets {
+                let changed = target.try_get::<(&mut Permissions, &Name)>(|(permissions, name)| {
+                    self.apply(permissions, name, storage, reply)
+                        .then(|| permissions.clone())
+                });
+
+                let Some(changed) = changed else {
+                    reply.send(messages::PLAYER_NOT_FOUND, &[&self.player.to_string()]);
+                    continue;
+                };
+
+                let Some(permissions) = changed else {
+                    continue;
+                };
+
+                target.modified::<Permissions>();
+
+                // the console is not saved, since it always has every node
+                if let Some(uuid) = target.try_get::<&Uuid>(|uuid| **uuid) {
+                    reply.saved(storage.set(uuid, &permissions));
+                }
+            }
+        });
+    }
+
+    /// Applies the action to the `permissions` of the player `name`, returning whether they
+    /// changed.
+    fn apply(
+        &self,
+        permissions: &mut Permissions,
+        name: &str,
+        storage: &PermissionStorage,
+        reply: &Reply<'_>,
+    ) -> bool {
+        match &self.action {
+            PlayerAction::Info => {
+                let groups = list(permissions.groups.iter().map(String::as_str));
+                let nodes = list_nodes(&permissions.nodes);
+                reply.send(messages::PLAYER_PERMISSIONS, &[name, &groups, &nodes]);
+                false
+            }
+            PlayerAction::Add { group } => {
+                reply.send(messages::PLAYER_GROUP_ADDED, &[name, group]);
+
+                if permissions.groups.contains(group) {
+                    return false;
+                }
+
+                permissions.groups.push(group.clone());
+                true
+            }
+            PlayerAction::Remove { group } => {
+                reply.send(messages::PLAYER_GROUP_REMOVED, &[name, group]);
+
+                let len = permissions.groups.len();
+                permissions.groups.retain(|g| g != group);
+                permissions.groups.len() != len
+            }
+            PlayerAction::Set { node, value } => {
+                let value = value.unwrap_or(true);
+                reply.send(messages::NODE_SET, &[name, node, &value.to_string()]);
+                permissions.nodes.insert(node.clone(), value) != Some(value)
+            }
+            PlayerAction::Unset { node } => {
+                reply.send(messages::NODE_UNSET, &[name, node]);
+                permissions.nodes.remove(node).is_some()
+            }
+            PlayerAction::Check { node } => {
+                let key = if storage.has(permissions, node) {
+                    messages::PERMISSION_GRANTED
+                } else {
+                    messages::PERMISSION_DENIED
+                };
+
+                reply.send(key, &[name, node]);
+                false
+            }
+        }
+    }
+}
+
+impl GroupCommand {
+    fn execute(self, world: &World, reply: &Reply<'_>) {
+        let name = self.group.as_str();
+
+        let changed = world.get::<&mut PermissionStorage>(|storage| {
+            let group = storage.group(name).cloned();
+
+            let mut group = match (group, &self.action) {
+                (Some(_), GroupAction::Create) => {
+                    reply.send(messages::GROUP_EXISTS, &[name]);
+                    return false;
+                }
+                (None, GroupAction::Create) => Group::default(),
+                (Some(group), _) => group,
+                (None, _) => {
+                    reply.send(messages::UNKNOWN_GROUP, &[name]);
+                    return false;
+                }
+            };
+
+            match &self.action {
+                GroupAction::Info => {
+                    let parents = list(group.parents.iter().map(String::as_str));
+                    let nodes = list_nodes(&group.nodes);
+                    reply.send(messages::GROUP_INFO, &[name, &parents, &nodes]);
+                    return false;
+                }
+                GroupAction::Create => {
+                    if !reply.saved(storage.set_group(name, group)) {
+                        return false;
+                    }
+
+                    reply.send(messages::GROUP_CREATED, &[name]);
+                    return false;
+                }
+                GroupAction::Delete => {
+                    if !reply.saved(storage.remove_group(name).map(drop)) {
+                        return false;
+                    }
+
+                    reply.send(messages::GROUP_DELETED, &[name]);
+                    return true;
+                }
+                GroupAction::Set { node, value } => {
+                    if !node::is_valid(node) {
+                        reply.send(messages::INVALID_NODE, &[node]);
+                        return false;
+                    }
+
+                    let value = value.unwrap_or(true);
+                    group.nodes.insert(node.clone(), value);
+                    reply.send(messages::NODE_SET, &[name, node, &value.to_string()]);
+                }
+                GroupAction::Unset { node } => {
+                    group.nodes.remove(node);
+                    reply.send(messages::NODE_UNSET, &[name, node]);
+                }
+                GroupAction::AddParent { parent } => {
+                    if storage.group(parent).is_none() {
+                        reply.send(messages::UNKNOWN_GROUP, &[parent]);
+                        return false;
+                    }
+
+                    if parent == name || storage.inherits(parent, name) {
+                        reply.send(messages::PARENT_CYCLE, &[name, parent]);
+                        return false;
+                    }
+
+                    if !group.parents.contains(parent) {
+                        group.parents.push(parent.clone());
+                    }
+
+                    reply.send(messages::PARENT_ADDED, &[name, parent]);
+                }
+                GroupAction::RemoveParent { parent } => {
+                    group.parents.retain(|p| p != parent);
+                    reply.send(messages::PARENT_REMOVED, &[name, parent]);
+                }
+            }
+
+            reply.saved(storage.set_group(name, group))
+        });
+
+        // the commands players may run change with the nodes of their groups
+        if changed {
+            hyperion_permission::refresh_commands(world);
+        }
+    }
+}
+
+impl MinecraftCommand for PermissionCommand {
+    const CONSOLE: bool = true;
+
+    fn completer(arg: &ClapArg) -> Option<complete::Completer> {
+        match arg.get_id().as_str() {
+            "group" | "parent" => Some(groups),
+            _ => complete::completer(arg),
+        }
+    }
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+
+        let reply = Reply {
+            caller: caller.entity_view(world),
+            system,
+        };
+
+        match self {
+            Self::Player(command) => command.execute(&world, &reply),
+            Self::Group(command) => command.execute(&world, &reply),
+            Self::Groups => {
+                world.get::<&PermissionStorage>(|storage| {
+                    let mut names: Vec<&str> =
+                        storage.groups().keys().map(String::as_str).collect();
+                    names.sort_unstable();
+
+                    // every player is in the default group, even when it has not been created
+                    if !names.contains(&DEFAULT_GROUP) {
+                        names.insert(0, DEFAULT_GROUP);
+                    }
+
+                    reply.send(messages::GROUP_LIST, &[&list(names)]);
+                });
+            }
+        }
+    }
+}
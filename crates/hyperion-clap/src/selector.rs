@@ -0,0 +1,568 @@
+//! Entity selectors such as `@a[team=red,distance=..10]`, which target entities by where they are,
+//! their team and their tags, as in vanilla.
+//!
+//! | Selector | Targets                              | Default sort | Default limit |
+//! |----------|--------------------------------------|--------------|---------------|
+//! | `@p`     | the nearest player                   | nearest      | 1             |
+//! | `@a`     | every player                         | arbitrary    | none          |
+//! | `@r`     | a random player                      | random       | 1             |
+//! | `@e`     | every entity                         | arbitrary    | none          |
+//! | `@s`     | the caller                           |              |               |
+//!
+//! Filters are `x`, `y` and `z` for the origin, `dx`, `dy` and `dz` for a box starting at the
+//! origin, `distance` as a range such as `..10` or `2..5`, `limit`, `sort` (`nearest`,
+//! `furthest`, `random` or `arbitrary`), and `team` and `tag`, which may be negated with `!`.
+
+use std::{fmt, str::FromStr};
+
+use flecs_ecs::core::{Builder, Entity, EntityView, EntityViewGet, QueryAPI, World, WorldGet};
+use geometry::aabb::Aabb;
+use hyperion::{
+    glam::Vec3,
+    simulation::{EntityTags, IgnMap, Player, Position, TeamName},
+};
+use spatial::SpatialIndex;
+use thiserror::Error;
+
+/// A selector which could not be parsed.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SelectorError {
+    #[error("expected a player name or a selector")]
+    Empty,
+    #[error("unknown selector `@{0}`, expected `@p`, `@a`, `@r`, `@e` or `@s`")]
+    UnknownKind(String),
+    #[error("the filters of a selector must be in `[...]`")]
+    Unclosed,
+    #[error("unknown selector filter `{0}`")]
+    UnknownFilter(String),
+    #[error("invalid value `{value}` for selector filter `{filter}`")]
+    InvalidValue { filter: String, value: String },
+}
+
+/// Which entities a selector starts from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SelectorKind {
+    /// `@p`
+    NearestPlayer,
+    /// `@a`
+    AllPlayers,
+    /// `@r`
+    RandomPlayer,
+    /// `@e`
+    AllEntities,
+    /// `@s`
+    Caller,
+}
+
+impl SelectorKind {
+    const fn players_only(self) -> bool {
+        matches!(
+            self,
+            Self::NearestPlayer | Self::AllPlayers | Self::RandomPlayer
+        )
+    }
+
+    const fn default_sort(self) -> Sort {
+        match self {
+            Self::NearestPlayer => Sort::Nearest,
+            Self::RandomPlayer => Sort::Random,
+            Self::AllPlayers | Self::AllEntities | Self::Caller => Sort::Arbitrary,
+        }
+    }
+
+    const fn default_limit(self) -> Option<usize> {
+        match self {
+            Self::NearestPlayer | Self::RandomPlayer | Self::Caller => Some(1),
+            Self::AllPlayers | Self::AllEntities => None,
+        }
+    }
+}
+
+/// The order of the selected entities, which matters with a `limit`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sort {
+    Nearest,
+    Furthest,
+    Random,
+    Arbitrary,
+}
+
+/// A range of numbers such as `..5`, `2..` or `2..5`, where both ends are included.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Range {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Range {
+    #[must_use]
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+impl FromStr for Range {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bound = |bound: &str| -> Result<Option<f64>, ()> {
+            if bound.is_empty() {
+                Ok(None)
+            } else {
+                bound.parse().map(Some).map_err(|_| ())
+            }
+        };
+
+        let Some((min, max)) = s.split_once("..") else {
+            let exact = bound(s)?.ok_or(())?;
+            return Ok(Self {
+                min: Some(exact),
+                max: Some(exact),
+            });
+        };
+
+        Ok(Self {
+            min: bound(min)?,
+            max: bound(max)?,
+        })
+    }
+}
+
+/// A `team=` or `tag=` filter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matcher {
+    /// The team or tag, where an empty value stands for having none.
+    pub value: String,
+    /// Whether the filter was written with `!`, selecting entities which do not match.
+    pub negated: bool,
+}
+
+impl Matcher {
+    fn parse(value: &str) -> Self {
+        match value.strip_prefix('!') {
+            Some(value) => Self {
+                value: value.to_owned(),
+                negated: true,
+            },
+            None => Self {
+                value: value.to_owned(),
+                negated: false,
+            },
+        }
+    }
+
+    /// Whether an entity with `values` matches, such as its team or its tags.
+    fn matches<'a>(&self, mut values: impl Iterator<Item = &'a str>) -> bool {
+        let matched = if self.value.is_empty() {
+            values.next().is_none()
+        } else {
+            values.any(|value| value == self.value)
+        };
+
+        matched != self.negated
+    }
+}
+
+/// The filters in the brackets of a selector.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filters {
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+    pub dx: Option<f64>,
+    pub dy: Option<f64>,
+    pub dz: Option<f64>,
+    pub distance: Option<Range>,
+    pub limit: Option<usize>,
+    pub sort: Option<Sort>,
+    pub teams: Vec<Matcher>,
+    pub tags: Vec<Matcher>,
+}
+
+impl Filters {
+    fn apply(&mut self, filter: &str, value: &str) -> Result<(), SelectorError> {
+        let invalid = || SelectorError::InvalidValue {
+            filter: filter.to_owned(),
+            value: value.to_owned(),
+        };
+
+        let number = || value.parse::<f64>().map_err(|_| invalid());
+
+        match filter {
+            "x" => self.x = Some(number()?),
+            "y" => self.y = Some(number()?),
+            "z" => self.z = Some(number()?),
+            "dx" => self.dx = Some(number()?),
+            "dy" => self.dy = Some(number()?),
+            "dz" => self.dz = Some(number()?),
+            "distance" => {
+                let range = value.parse::<Range>().map_err(|()| invalid())?;
+
+                if range.min.is_some_and(|min| min < 0.0) || range.max.is_some_and(|max| max < 0.0)
+                {
+                    return Err(invalid());
+                }
+
+                self.distance = Some(range);
+            }
+            "limit" => {
+                let limit = value.parse::<usize>().map_err(|_| invalid())?;
+
+                if limit == 0 {
+                    return Err(invalid());
+                }
+
+                self.limit = Some(limit);
+            }
+            "sort" => {
+                self.sort = Some(match value {
+                    "nearest" => Sort::Nearest,
+                    "furthest" => Sort::Furthest,
+                    "random" => Sort::Random,
+                    "arbitrary" => Sort::Arbitrary,
+                    _ => return Err(invalid()),
+                });
+            }
+            "team" => self.teams.push(Matcher::parse(value)),
+            "tag" => self.tags.push(Matcher::parse(value)),
+            _ => return Err(SelectorError::UnknownFilter(filter.to_owned())),
+        }
+
+        Ok(())
+    }
+
+    /// The box given by `dx`, `dy` and `dz`, which includes the blocks at both of its corners as
+    /// in vanilla.
+    fn bounding_box(&self, origin: Vec3) -> Option<Aabb> {
+        if self.dx.is_none() && self.dy.is_none() && self.dz.is_none() {
+            return None;
+        }
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "positions are stored as f32"
+        )]
+        let delta = Vec3::new(
+            self.dx.unwrap_or_default() as f32,
+            self.dy.unwrap_or_default() as f32,
+            self.dz.unwrap_or_default() as f32,
+        );
+
+        let corner = origin + delta;
+
+        Some(Aabb::new(
+            origin.min(corner),
+            origin.max(corner) + Vec3::ONE,
+        ))
+    }
+}
+
+/// What a selector targets.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    /// An online player by name.
+    Name(String),
+    /// Entities picked by a selector such as `@a[team=red]`.
+    Entities {
+        kind: SelectorKind,
+        filters: Filters,
+    },
+}
+
+/// A player name or an entity selector, which the client parses as an `entity` argument.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selector {
+    input: String,
+    target: Target,
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.to_owned();
+
+        let Some(rest) = s.strip_prefix('@') else {
+            if s.is_empty() {
+                return Err(SelectorError::Empty);
+            }
+
+            return Ok(Self {
+                input,
+                target: Target::Name(s.to_owned()),
+            });
+        };
+
+        let split = rest.find('[').unwrap_or(rest.len());
+        let (kind, filters) = rest.split_at(split);
+
+        let kind = match kind {
+            "p" => SelectorKind::NearestPlayer,
+            "a" => SelectorKind::AllPlayers,
+            "r" => SelectorKind::RandomPlayer,
+            "e" => SelectorKind::AllEntities,
+            "s" => SelectorKind::Caller,
+            _ => return Err(SelectorError::UnknownKind(kind.to_owned())),
+        };
+
+        let mut parsed = Filters::default();
+
+        if !filters.is_empty() {
+            let filters = filters
+                .strip_prefix('[')
+                .and_then(|filters| filters.strip_suffix(']'))
+                .ok_or(SelectorError::Unclosed)?;
+
+            for filter in filters
+                .split(',')
+                .filter(|filter| !filter.trim().is_empty())
+            {
+                let (filter, value) = filter
+                    .split_once('=')
+                    .ok_or_else(|| SelectorError::UnknownFilter(filter.to_owned()))?;
+
+                parsed.apply(filter.trim(), value.trim())?;
+            }
+        }
+
+        Ok(Self {
+            input,
+            target: Target::Entities {
+                kind,
+                filters: parsed,
+            },
+        })
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.input)
+    }
+}
+
+impl Selector {
+    #[must_use]
+    pub const fn target(&self) -> &Target {
+        &self.target
+    }
+
+    /// The entities the selector targets when `caller` runs the command. Distances are measured
+    /// from the caller unless `x`, `y` or `z` are given.
+    #[must_use]
+    pub fn resolve<'a>(&self, world: &'a World, caller: Entity) -> Vec<EntityView<'a>> {
+        let (kind, filters) = match &self.target {
+            Target::Name(name) => {
+                return world
+                    .get::<&IgnMap>(|ign_map| ign_map.get(name.as_str()).copied())
+                    .map(|entity| world.entity_from_id(entity))
+                    .into_iter()
+                    .collect();
+            }
+            Target::Entities { kind, filters } => (*kind, filters),
+        };
+
+        let caller = world.entity_from_id(caller);
+
+        let caller_position = caller
+            .try_get::<&Position>(|position| **position)
+            .unwrap_or_default();
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "positions are stored as f32"
+        )]
+        let origin = Vec3::new(
+            filters.x.map_or(caller_position.x, |x| x as f32),
+            filters.y.map_or(caller_position.y, |y| y as f32),
+            filters.z.map_or(caller_position.z, |z| z as f32),
+        );
+
+        let mut candidates = if kind == SelectorKind::Caller {
+            vec![caller]
+        } else {
+            candidates(world, kind, filters, origin)
+        };
+
+        candidates.retain(|entity| {
+            let distance = entity
+                .try_get::<&Position>(|position| position.distance(origin))
+                .unwrap_or_default();
+
+            filters
+                .distance
+                .is_none_or(|range| range.contains(f64::from(distance)))
+                && filters.teams.iter().all(|matcher| {
+                    entity
+                        .try_get::<&TeamName>(|team| matcher.matches(std::iter::once(&***team)))
+                        .unwrap_or_else(|| matcher.matches(std::iter::empty()))
+                })
+                && filters.tags.iter().all(|matcher| {
+                    entity
+                        .try_get::<&EntityTags>(|tags| matcher.matches(tags.iter().map(|t| &**t)))
+                        .unwrap_or_else(|| matcher.matches(std::iter::empty()))
+                })
+        });
+
+        let distance = |entity: &EntityView<'_>| {
+            entity
+                .try_get::<&Position>(|position| position.distance_squared(origin))
+                .unwrap_or_default()
+        };
+
+        match filters.sort.unwrap_or_else(|| kind.default_sort()) {
+            Sort::Nearest => candidates.sort_by(|a, b| distance(a).total_cmp(&distance(b))),
+            Sort::Furthest => candidates.sort_by(|a, b| distance(b).total_cmp(&distance(a))),
+            Sort::Random => fastrand::shuffle(&mut candidates),
+            Sort::Arbitrary => {}
+        }
+
+        if let Some(limit) = filters.limit.or_else(|| kind.default_limit()) {
+            candidates.truncate(limit);
+        }
+
+        candidates
+    }
+}
+
+/// The entities of `kind` which may match the filters. Selectors limited to an area use the
+/// [`SpatialIndex`], which holds every entity with a position, rather than going through every
+/// entity.
+fn candidates<'a>(
+    world: &'a World,
+    kind: SelectorKind,
+    filters: &Filters,
+    origin: Vec3,
+) -> Vec<EntityView<'a>> {
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "positions are stored as f32"
+    )]
+    let within_distance = filters
+        .distance
+        .and_then(|range| range.max)
+        .map(|max| Aabb::new(origin - max as f32, origin + max as f32));
+
+    let area = match (filters.bounding_box(origin), within_distance) {
+        (Some(a), Some(b)) => match Aabb::overlap(&a, &b) {
+            Some(overlap) => Some(overlap),
+            // the box is further away than the maximum distance
+            None => return Vec::new(),
+        },
+        (a, b) => a.or(b),
+    };
+
+    let mut entities: Vec<EntityView<'a>> = match area {
+        Some(area) => world.get::<&SpatialIndex>(|index| {
+            index
+                .get_collisions(area, world)
+                .map(|entity| world.entity_from_id(entity))
+                .collect()
+        }),
+        None => {
+            let mut entities = Vec::new();
+
+            world
+                .query::<&Position>()
+                .build()
+                .each_entity(|entity, _| entities.push(entity));
+
+            entities
+        }
+    };
+
+    if kind.players_only() {
+        entities.retain(|entity| entity.has::<Player>());
+    }
+
+    entities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_selector() {
+        let selector: Selector = "@e[distance=..5,limit=3,sort=furthest,team=!red,tag=boss]"
+            .parse()
+            .unwrap();
+
+        let Target::Entities { kind, filters } = selector.target() else {
+            panic!("expected a selector");
+        };
+
+        assert_eq!(*kind, SelectorKind::AllEntities);
+        assert_eq!(
+            filters.distance,
+            Some(Range {
+                min: None,
+                max: Some(5.0)
+            })
+        );
+        assert_eq!(filters.limit, Some(3));
+        assert_eq!(filters.sort, Some(Sort::Furthest));
+        assert_eq!(filters.teams, [Matcher {
+            value: "red".to_owned(),
+            negated: true
+        }]);
+        assert_eq!(filters.tags, [Matcher {
+            value: "boss".to_owned(),
+            negated: false
+        }]);
+    }
+
+    #[test]
+    fn test_parse_names_and_errors() {
+        let selector: Selector = "Notch".parse().unwrap();
+        assert_eq!(selector.target(), &Target::Name("Notch".to_owned()));
+        assert_eq!(selector.to_string(), "Notch");
+
+        assert_eq!(
+            "@x".parse::<Selector>(),
+            Err(SelectorError::UnknownKind("x".to_owned()))
+        );
+        assert_eq!(
+            "@a[limit=1".parse::<Selector>(),
+            Err(SelectorError::Unclosed)
+        );
+        assert_eq!(
+            "@a[color=red]".parse::<Selector>(),
+            Err(SelectorError::UnknownFilter("color".to_owned()))
+        );
+        assert!("@a[limit=0]".parse::<Selector>().is_err());
+        assert!("@a[distance=-1..]".parse::<Selector>().is_err());
+    }
+
+    #[test]
+    fn test_range() {
+        let range: Range = "2..5".parse().unwrap();
+        assert!(range.contains(2.0));
+        assert!(range.contains(5.0));
+        assert!(!range.contains(5.5));
+
+        let exact: Range = "3".parse().unwrap();
+        assert!(exact.contains(3.0));
+        assert!(!exact.contains(2.0));
+
+        assert!("..x".parse::<Range>().is_err());
+    }
+
+    #[test]
+    fn test_matcher() {
+        let red = Matcher::parse("red");
+        assert!(red.matches(["red"].into_iter()));
+        assert!(!red.matches(std::iter::empty()));
+
+        let not_red = Matcher::parse("!red");
+        assert!(not_red.matches(["blue"].into_iter()));
+
+        // an empty value matches entities with no team
+        let none = Matcher::parse("");
+        assert!(none.matches(std::iter::empty()));
+        assert!(!none.matches(["red"].into_iter()));
+
+        let any = Matcher::parse("!");
+        assert!(any.matches(["red"].into_iter()));
+    }
+}
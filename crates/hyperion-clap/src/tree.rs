@@ -0,0 +1,430 @@
+//! Translates the structure of a clap command into the Brigadier graph the client completes and
+//! validates commands with, splits typed commands back into the values clap parses, and finds the
+//! argument a player is completing.
+
+use clap::{Arg as ClapArg, Command as ClapCommand};
+use flecs_ecs::core::{Entity, World};
+use hyperion::simulation::command::Command as CommandNode;
+
+use crate::{MinecraftCommand, parser};
+
+/// Whether the command can be run once the positionals before `consumed` are given.
+fn runnable_after(command: &ClapCommand, positionals: &[&ClapArg], consumed: usize) -> bool {
+    !command.is_subcommand_required_set()
+        && positionals[consumed..]
+            .iter()
+            .all(|arg| !arg.is_required_set())
+}
+
+/// The name of a positional or flag value in the graph.
+fn value_name(arg: &ClapArg) -> String {
+    arg.get_value_names()
+        .and_then(|names| names.first())
+        .map_or_else(|| arg.get_id().to_string(), ToString::to_string)
+        .to_ascii_lowercase()
+}
+
+/// The literal the player types for `arg`, if it is a flag or an option.
+fn flag_literal(arg: &ClapArg) -> Option<String> {
+    if arg.is_positional() || arg.is_hide_set() {
+        return None;
+    }
+
+    arg.get_long()
+        .map(|long| format!("--{long}"))
+        .or_else(|| arg.get_short().map(|short| format!("-{short}")))
+}
+
+/// Adds the nodes of `command` under `parent`, starting with `literal`. Positionals form a chain
+/// which can be run at every node after the required ones, flags may follow any such node and
+/// redirect back to it so that several can be given, and subcommands branch off the end of the
+/// chain.
+pub fn add_command_nodes<C: MinecraftCommand>(
+    world: &World,
+    command: &ClapCommand,
+    parent: Entity,
+    literal: CommandNode,
+) {
+    let positionals: Vec<&ClapArg> = command
+        .get_positionals()
+        .filter(|arg| !arg.is_hide_set())
+        .collect();
+
+    let runnable = runnable_after(command, &positionals, 0);
+
+    let literal = world
+        .entity()
+        .set(literal.executable(runnable))
+        .child_of_id(parent)
+        .id();
+
+    let mut executable = Vec::new();
+    if runnable {
+        executable.push(literal);
+    }
+
+    let mut last = literal;
+
+    for (i, arg) in positionals.iter().enumerate() {
+        let runnable = runnable_after(command, &positionals, i + 1);
+
+        let node = CommandNode::argument(value_name(arg), C::parser(arg)).executable(runnable);
+
+        last = world.entity().set(node).child_of_id(last).id();
+
+        if runnable {
+            executable.push(last);
+        }
+    }
+
+    for node in executable {
+        for arg in command.get_arguments() {
+            let Some(flag) = flag_literal(arg) else {
+                continue;
+            };
+
+            let flag = CommandNode::literal(flag, |_: _, _: _| true);
+
+            if arg.get_action().takes_values() {
+                let flag = world.entity().set(flag.executable(false)).child_of_id(node);
+
+                let value = CommandNode::argument(value_name(arg), C::parser(arg)).redirect(node);
+
+                world.entity().set(value).child_of_id(flag);
+            } else {
+                world.entity().set(flag.redirect(node)).child_of_id(node);
+            }
+        }
+    }
+
+    for subcommand in command.get_subcommands() {
+        if subcommand.is_hide_set() {
+            continue;
+        }
+
+        let literal = CommandNode::literal(subcommand.get_name(), |_: _, _: _| true);
+        add_command_nodes::<C>(world, subcommand, last, literal);
+    }
+}
+
+/// Takes the words of one value parsed by `parser`, which may span several words or, for greedy
+/// strings, the rest of the command.
+fn take_value<'a>(
+    first: &'a str,
+    parser: &hyperion::simulation::command::Parser,
+    words: &mut impl Iterator<Item = &'a str>,
+) -> String {
+    let rest = match parser::word_count(parser) {
+        Some(count) => words.take(count - 1).collect::<Vec<_>>(),
+        None => words.collect(),
+    };
+
+    std::iter::once(first)
+        .chain(rest)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn split_command<'a, C: MinecraftCommand>(
+    command: &ClapCommand,
+    words: &mut impl Iterator<Item = &'a str>,
+    values: &mut Vec<String>,
+) {
+    let mut positionals = command.get_positionals();
+
+    while let Some(word) = words.next() {
+        let flag = command
+            .get_arguments()
+            .find(|arg| flag_literal(arg).is_some_and(|flag| flag == word));
+
+        if let Some(flag) = flag {
+            values.push(word.to_owned());
+
+            if flag.get_action().takes_values()
+                && let Some(first) = words.next()
+            {
+                values.push(take_value(first, &C::parser(flag), words));
+            }
+
+            continue;
+        }
+
+        if let Some(subcommand) = command.find_subcommand(word) {
+            values.push(word.to_owned());
+            split_command::<C>(subcommand, words, values);
+            return;
+        }
+
+        match positionals.next() {
+            Some(arg) => values.push(take_value(word, &C::parser(arg), words)),
+            None => values.push(word.to_owned()),
+        }
+    }
+}
+
+/// Splits `input` into the values clap parses, where arguments such as coordinates are made of
+/// several words and greedy strings take the rest of the command.
+pub fn split_arguments<C: MinecraftCommand>(input: &str) -> Vec<String> {
+    let mut words = input.split_whitespace();
+
+    // the name of the command
+    let mut values: Vec<String> = words.next().map(str::to_owned).into_iter().collect();
+
+    split_command::<C>(&C::command(), &mut words, &mut values);
+
+    values
+}
+
+/// The part of a partly typed command which the player is completing.
+#[derive(Debug)]
+pub struct Completing<'a> {
+    /// The byte of the input the value being typed starts at.
+    pub start: usize,
+    /// The argument being typed, if there is one left.
+    pub arg: Option<&'a ClapArg>,
+    /// The flags and subcommands which may be typed instead of the argument.
+    pub literals: Vec<String>,
+}
+
+/// The words of `input` and the byte each starts at. Input ending in a space ends with an empty
+/// word, which is the one being typed.
+fn words_with_offsets(input: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut offset = 0;
+
+    for word in input.split(' ') {
+        if !word.is_empty() {
+            words.push((offset, word));
+        }
+        offset += word.len() + 1;
+    }
+
+    if input.is_empty() || input.ends_with(' ') {
+        words.push((input.len(), ""));
+    }
+
+    words
+}
+
+fn complete_command<'a, C: MinecraftCommand>(
+    command: &'a ClapCommand,
+    words: &[(usize, &str)],
+) -> Option<Completing<'a>> {
+    let mut positionals = command.get_positionals().filter(|arg| !arg.is_hide_set());
+    let mut i = 0;
+
+    while let Some(&(start, word)) = words.get(i) {
+        if i + 1 == words.len() {
+            let subcommands = command
+                .get_subcommands()
+                .filter(|subcommand| !subcommand.is_hide_set())
+                .map(|subcommand| subcommand.get_name().to_owned());
+
+            let literals = command
+                .get_arguments()
+                .filter_map(flag_literal)
+                .chain(subcommands)
+                .collect();
+
+            return Some(Completing {
+                start,
+                arg: positionals.next(),
+                literals,
+            });
+        }
+
+        let flag = command
+            .get_arguments()
+            .find(|arg| flag_literal(arg).is_some_and(|flag| flag == word));
+
+        if let Some(flag) = flag {
+            i += 1;
+
+            if !flag.get_action().takes_values() {
+                continue;
+            }
+
+            let Some(count) =
+                parser::word_count(&C::parser(flag)).filter(|count| i + count < words.len())
+            else {
+                return Some(Completing {
+                    start: words[i].0,
+                    arg: Some(flag),
+                    literals: Vec::new(),
+                });
+            };
+
+            i += count;
+            continue;
+        }
+
+        if let Some(subcommand) = command.find_subcommand(word) {
+            return complete_command::<C>(subcommand, &words[i + 1..]);
+        }
+
+        let arg = positionals.next();
+        let count = arg.map_or(Some(1), |arg| parser::word_count(&C::parser(arg)));
+
+        // the value spans the word being typed
+        let Some(count) = count.filter(|count| i + count < words.len()) else {
+            return Some(Completing {
+                start,
+                arg,
+                literals: Vec::new(),
+            });
+        };
+
+        i += count;
+    }
+
+    None
+}
+
+/// What the last word of `input`, a partly typed command starting with its name, is part of.
+pub fn completing<'a, C: MinecraftCommand>(
+    command: &'a ClapCommand,
+    input: &str,
+) -> Option<Completing<'a>> {
+    let words = words_with_offsets(input);
+
+    // the name of the command is completed by the client
+    complete_command::<C>(command, words.get(1..)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use hyperion::simulation::command::get_command_packet;
+    use valence_protocol::packets::play::command_tree_s2c::NodeData;
+
+    use super::*;
+    use crate::{
+        CommandPermission,
+        arg::{Coordinates, GreedyString},
+    };
+
+    #[derive(Parser, Debug)]
+    #[command(name = "team")]
+    enum TeamCommand {
+        Add {
+            name: String,
+            #[arg(long)]
+            silent: bool,
+        },
+        Teleport {
+            position: Coordinates,
+            message: Option<GreedyString>,
+        },
+    }
+
+    impl CommandPermission for TeamCommand {
+        const PERMISSION: &'static str = "test.command.team";
+    }
+
+    impl MinecraftCommand for TeamCommand {
+        fn execute(self, _: flecs_ecs::core::EntityView<'_>, _: Entity) {}
+    }
+
+    #[test]
+    fn test_split_arguments() {
+        assert_eq!(split_arguments::<TeamCommand>("team add blue --silent"), [
+            "team", "add", "blue", "--silent"
+        ]);
+        assert_eq!(
+            split_arguments::<TeamCommand>("team teleport ~ ~1 ~ hello there"),
+            ["team", "teleport", "~ ~1 ~", "hello there"]
+        );
+
+        let parsed =
+            TeamCommand::try_parse_from(split_arguments::<TeamCommand>("team teleport ^ ^ ^2"))
+                .unwrap();
+
+        assert!(matches!(parsed, TeamCommand::Teleport {
+            message: None,
+            ..
+        }));
+    }
+
+    #[test]
+    fn test_completing() {
+        let command = TeamCommand::command();
+
+        let name = |completing: Option<Completing<'_>>| {
+            let completing = completing.unwrap();
+            (
+                completing.start,
+                completing.arg.map(|arg| arg.get_id().to_string()),
+            )
+        };
+
+        let subcommand = completing::<TeamCommand>(&command, "team ").unwrap();
+        assert_eq!(subcommand.start, 5);
+        assert!(subcommand.arg.is_none());
+        assert!(subcommand.literals.contains(&"teleport".to_owned()));
+
+        assert_eq!(
+            name(completing::<TeamCommand>(&command, "team add bl")),
+            (9, Some("name".to_owned()))
+        );
+        assert_eq!(
+            name(completing::<TeamCommand>(&command, "team teleport 1 2")),
+            (14, Some("position".to_owned()))
+        );
+        assert_eq!(
+            name(completing::<TeamCommand>(
+                &command,
+                "team teleport 1 2 3 hi the"
+            )),
+            (20, Some("message".to_owned()))
+        );
+
+        let flag = completing::<TeamCommand>(&command, "team add blue --s").unwrap();
+        assert_eq!(flag.start, 14);
+        assert!(flag.literals.contains(&"--silent".to_owned()));
+    }
+
+    #[test]
+    fn test_command_graph() {
+        let world = World::new();
+        world.component::<CommandNode>();
+
+        let root = world.entity();
+
+        add_command_nodes::<TeamCommand>(
+            &world,
+            &TeamCommand::command(),
+            root.id(),
+            CommandNode::literal("team", |_: _, _: _| true),
+        );
+
+        let packet = get_command_packet(&world, root.id(), None);
+        let nodes = &packet.commands;
+
+        let find = |name: &str| {
+            nodes
+                .iter()
+                .position(|node| match &node.data {
+                    NodeData::Literal { name: literal }
+                    | NodeData::Argument { name: literal, .. } => literal == name,
+                    NodeData::Root => false,
+                })
+                .unwrap()
+        };
+
+        // a subcommand is required
+        assert!(!nodes[find("team")].executable);
+        assert!(!nodes[find("add")].executable);
+        assert!(nodes[find("name")].executable);
+
+        let silent = &nodes[find("--silent")];
+        assert!(silent.executable);
+        assert_eq!(
+            silent.redirect_node.map(|node| node.0),
+            Some(i32::try_from(find("name")).unwrap())
+        );
+
+        // the message is optional
+        assert!(nodes[find("position")].executable);
+        assert!(nodes[find("message")].executable);
+    }
+}
@@ -6,6 +6,8 @@ use flecs_ecs::{
 use hyperion::storage::{CommandCompletionRequest, EventFn};
 use indexmap::IndexMap;
 
+use crate::console::ConsoleCommands;
+
 pub struct CommandHandler {
     pub on_execute: fn(input: &str, system: EntityView<'_>, caller: Entity),
     pub on_tab_complete: EventFn<CommandCompletionRequest<'static>>,
@@ -52,5 +54,8 @@ impl Module for CommandComponentModule {
         world.set(CommandRegistry {
             commands: IndexMap::default(),
         });
+
+        world.component::<ConsoleCommands>();
+        world.set(ConsoleCommands::default());
     }
 }
@@ -0,0 +1,73 @@
+//! Commands typed into the server's terminal or sent by RCON clients, which run as the
+//! [`Console`](hyperion::simulation::console::Console).
+
+use flecs_ecs::macros::Component;
+use kanal::{Receiver, Sender};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    sync::oneshot,
+};
+
+/// A command for the console to run.
+#[derive(Debug)]
+pub struct ConsoleCommand {
+    /// The command without its leading slash.
+    pub command: String,
+    /// Receives the messages sent to the console while the command ran, for RCON clients waiting
+    /// on its response.
+    pub output: Option<oneshot::Sender<String>>,
+}
+
+/// A singleton with the commands sent to the console which have not run yet.
+#[derive(Component)]
+pub struct ConsoleCommands {
+    sender: Sender<ConsoleCommand>,
+    pub(crate) receiver: Receiver<ConsoleCommand>,
+}
+
+impl Default for ConsoleCommands {
+    fn default() -> Self {
+        let (sender, receiver) = kanal::unbounded();
+        Self { sender, receiver }
+    }
+}
+
+impl ConsoleCommands {
+    /// A sender of commands to the console, which may be used from other threads.
+    #[must_use]
+    pub fn sender(&self) -> Sender<ConsoleCommand> {
+        self.sender.clone()
+    }
+}
+
+/// Sends each line of stdin to the console as a command until stdin is closed.
+pub async fn read_stdin(commands: Sender<ConsoleCommand>) {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("stopped reading console commands from stdin: {e}");
+                return;
+            }
+        };
+
+        let command = line.trim();
+        let command = command.strip_prefix('/').unwrap_or(command);
+
+        if command.is_empty() {
+            continue;
+        }
+
+        let command = ConsoleCommand {
+            command: command.to_owned(),
+            output: None,
+        };
+
+        if commands.send(command).is_err() {
+            return;
+        }
+    }
+}
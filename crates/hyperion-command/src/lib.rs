@@ -1,11 +1,21 @@
 #![feature(iter_intersperse)]
 
-use flecs_ecs::{core::World, macros::Component, prelude::Module};
+use std::net::SocketAddr;
+
+use flecs_ecs::{
+    core::{World, WorldGet},
+    macros::Component,
+    prelude::Module,
+};
+use hyperion::{config::Config, runtime::AsyncRuntime};
 
 mod component;
+pub mod console;
+mod rcon;
 mod system;
 
 pub use component::{CommandHandler, CommandRegistry};
+pub use console::{ConsoleCommand, ConsoleCommands};
 
 #[derive(Component)]
 pub struct CommandModule;
@@ -14,5 +24,17 @@ impl Module for CommandModule {
     fn module(world: &World) {
         world.import::<component::CommandComponentModule>();
         world.import::<system::CommandSystemModule>();
+
+        let commands = world.get::<&ConsoleCommands>(ConsoleCommands::sender);
+        let rcon = world.get::<&Config>(|config| config.rcon.clone());
+
+        world.get::<&AsyncRuntime>(|runtime| {
+            runtime.spawn(console::read_stdin(commands.clone()));
+
+            if let Some(rcon) = rcon {
+                let address = SocketAddr::new(rcon.address, rcon.port);
+                runtime.spawn(rcon::listen(address, rcon.password, commands));
+            }
+        });
     }
 }
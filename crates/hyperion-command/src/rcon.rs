@@ -0,0 +1,294 @@
+//! A listener for clients of the [RCON protocol](https://wiki.vg/RCON), which log in with a
+//! password and run commands as the console.
+//!
+//! Clients which log in with the wrong password are disconnected, as in vanilla.
+//!
+//! Each packet is its length as a little-endian `i32`, followed by the id of the request, its
+//! type, and a null-terminated body with one more null byte after it.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::{Context, bail};
+use kanal::Sender;
+use subtle::ConstantTimeEq;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+
+use crate::console::ConsoleCommand;
+
+/// A client logging in with a password.
+const AUTH: i32 = 3;
+/// A client running a command, or the server telling a client whether it logged in.
+const EXEC_COMMAND: i32 = 2;
+const AUTH_RESPONSE: i32 = 2;
+/// The output of a command.
+const RESPONSE_VALUE: i32 = 0;
+
+/// The id of the response to a login with the wrong password.
+const AUTH_FAILED: i32 = -1;
+
+/// The largest packet clients may send, as in vanilla.
+const MAX_REQUEST_LEN: usize = 1460;
+/// The largest body of a response. Longer output is split over several responses.
+const MAX_RESPONSE_BODY: usize = 4096;
+
+/// The id, type and two null bytes of a packet.
+const HEADER_LEN: usize = 10;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Packet {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+impl Packet {
+    /// Decodes a packet without its length.
+    fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.len() < HEADER_LEN {
+            bail!("RCON packet of {} bytes is too short", bytes.len());
+        }
+
+        let (header, body) = bytes.split_at(8);
+
+        let id = i32::from_le_bytes(header[..4].try_into()?);
+        let kind = i32::from_le_bytes(header[4..].try_into()?);
+
+        let body = body
+            .strip_suffix(&[0, 0])
+            .context("RCON packet body is not null-terminated")?;
+
+        let body = String::from_utf8(body.to_vec()).context("RCON packet body is not UTF-8")?;
+
+        Ok(Self { id, kind, body })
+    }
+
+    /// Encodes the packet with its length.
+    fn encode(&self) -> Vec<u8> {
+        let len = i32::try_from(HEADER_LEN + self.body.len()).unwrap();
+
+        let mut bytes = Vec::with_capacity(4 + HEADER_LEN + self.body.len());
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(&self.id.to_le_bytes());
+        bytes.extend_from_slice(&self.kind.to_le_bytes());
+        bytes.extend_from_slice(self.body.as_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    /// Reads a packet, or `None` if the client disconnected.
+    async fn read(reader: &mut (impl AsyncRead + Unpin)) -> anyhow::Result<Option<Self>> {
+        let mut len = [0; 4];
+
+        match reader.read_exact(&mut len).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        let len = usize::try_from(i32::from_le_bytes(len)).unwrap_or_default();
+
+        if !(HEADER_LEN..=MAX_REQUEST_LEN).contains(&len) {
+            bail!("RCON packet of {len} bytes is not between {HEADER_LEN} and {MAX_REQUEST_LEN}");
+        }
+
+        let mut bytes = vec![0; len];
+        reader.read_exact(&mut bytes).await?;
+
+        Self::decode(&bytes).map(Some)
+    }
+
+    async fn write(&self, writer: &mut (impl AsyncWrite + Unpin)) -> anyhow::Result<()> {
+        writer.write_all(&self.encode()).await?;
+        Ok(())
+    }
+}
+
+/// Whether `attempt` is `password`, taking as long wherever they differ so that the password
+/// cannot be guessed a byte at a time.
+fn is_password(attempt: &str, password: &str) -> bool {
+    attempt.as_bytes().ct_eq(password.as_bytes()).into()
+}
+
+/// Splits `s` into parts of at most `max` bytes, without splitting characters. Empty output is a
+/// single empty part, so that a response is still sent.
+fn split_body(s: &str, max: usize) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = s;
+
+    while rest.len() > max {
+        let mut end = max;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let (part, after) = rest.split_at(end);
+        parts.push(part);
+        rest = after;
+    }
+
+    parts.push(rest);
+    parts
+}
+
+async fn handle_client(
+    mut stream: TcpStream,
+    password: Arc<str>,
+    commands: Sender<ConsoleCommand>,
+) -> anyhow::Result<()> {
+    let mut authenticated = false;
+
+    while let Some(packet) = Packet::read(&mut stream).await? {
+        match packet.kind {
+            AUTH => {
+                authenticated = is_password(&packet.body, &password);
+
+                let response = Packet {
+                    id: if authenticated {
+                        packet.id
+                    } else {
+                        AUTH_FAILED
+                    },
+                    kind: AUTH_RESPONSE,
+                    body: String::new(),
+                };
+
+                response.write(&mut stream).await?;
+
+                if !authenticated {
+                    bail!("logged in with the wrong password");
+                }
+            }
+            EXEC_COMMAND if authenticated => {
+                let (output, response) = oneshot::channel();
+
+                let command = packet.body.trim();
+                let command = ConsoleCommand {
+                    command: command.strip_prefix('/').unwrap_or(command).to_owned(),
+                    output: Some(output),
+                };
+
+                commands
+                    .send(command)
+                    .context("the server is no longer running commands")?;
+
+                let response = response.await.unwrap_or_default();
+
+                for body in split_body(&response, MAX_RESPONSE_BODY) {
+                    let response = Packet {
+                        id: packet.id,
+                        kind: RESPONSE_VALUE,
+                        body: body.to_owned(),
+                    };
+
+                    response.write(&mut stream).await?;
+                }
+            }
+            EXEC_COMMAND => {
+                let response = Packet {
+                    id: AUTH_FAILED,
+                    kind: AUTH_RESPONSE,
+                    body: String::new(),
+                };
+
+                response.write(&mut stream).await?;
+            }
+            kind => {
+                let response = Packet {
+                    id: packet.id,
+                    kind: RESPONSE_VALUE,
+                    body: format!("Unknown request {kind:x}"),
+                };
+
+                response.write(&mut stream).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Accepts RCON clients on `address`, which run commands as the console once they log in with
+/// `password`.
+pub async fn listen(address: SocketAddr, password: String, commands: Sender<ConsoleCommand>) {
+    if password.is_empty() {
+        tracing::warn!("not starting RCON because its password is empty");
+        return;
+    }
+
+    let listener = match TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!("failed to start RCON on {address}: {e}");
+            return;
+        }
+    };
+
+    tracing::info!("RCON listening on {address}");
+
+    let password: Arc<str> = password.into();
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!("failed to accept RCON client: {e}");
+                continue;
+            }
+        };
+
+        let password = password.clone();
+        let commands = commands.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(stream, password, commands).await {
+                tracing::warn!("RCON client {peer} disconnected: {e}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packet_roundtrip() {
+        let packet = Packet {
+            id: 7,
+            kind: EXEC_COMMAND,
+            body: "perms get Notch".to_owned(),
+        };
+
+        let bytes = packet.encode();
+        let len = i32::from_le_bytes(bytes[..4].try_into().unwrap());
+
+        assert_eq!(usize::try_from(len).unwrap(), bytes.len() - 4);
+        assert_eq!(Packet::decode(&bytes[4..]).unwrap(), packet);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(Packet::decode(&[0; 4]).is_err());
+        assert!(Packet::decode(&[0, 0, 0, 0, 3, 0, 0, 0, b'a', b'b']).is_err());
+    }
+
+    #[test]
+    fn test_is_password() {
+        assert!(is_password("hunter2", "hunter2"));
+        assert!(!is_password("hunter3", "hunter2"));
+        assert!(!is_password("hunter", "hunter2"));
+        assert!(!is_password("", "hunter2"));
+    }
+
+    #[test]
+    fn test_split_body() {
+        assert_eq!(split_body("", 4), [""]);
+        assert_eq!(split_body("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        // `é` is two bytes and is not split
+        assert_eq!(split_body("aéb", 2), ["a", "é", "b"]);
+    }
+}
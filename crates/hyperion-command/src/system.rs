@@ -1,21 +1,63 @@
 use std::fmt::Write;
 
 use flecs_ecs::{
-    core::{EntityViewGet, QueryBuilderImpl, SystemAPI, TermBuilderImpl, World, WorldGet},
+    core::{
+        EntityView, EntityViewGet, QueryBuilderImpl, SystemAPI, TermBuilderImpl, World, WorldGet,
+        WorldProvider,
+    },
     macros::{Component, system},
     prelude::Module,
 };
 use hyperion::{
     net::agnostic,
-    simulation::event,
+    simulation::{
+        console::{ConsoleOutput, get_console_entity},
+        event,
+    },
     storage::{EventQueue, GlobalEventHandlers},
 };
 
-use crate::component::CommandRegistry;
+use crate::{
+    component::CommandRegistry,
+    console::{ConsoleCommand, ConsoleCommands},
+};
 
 #[derive(Component)]
 pub struct CommandSystemModule;
 
+/// Runs `raw`, a command without its leading slash, as `by`.
+fn dispatch(registry: &CommandRegistry, raw: &str, system: EntityView<'_>, by: EntityView<'_>) {
+    let world = system.world();
+
+    let Some(first_word) = raw.split_whitespace().next() else {
+        tracing::warn!("command is empty");
+        return;
+    };
+
+    let Some(command) = registry.commands.get(first_word) else {
+        tracing::debug!("command {first_word} not found");
+
+        let mut msg = String::new();
+        write!(&mut msg, "§cAvailable commands: §r[").unwrap();
+
+        for w in registry.get_permitted(&world, by.id()).intersperse(", ") {
+            write!(&mut msg, "{w}").unwrap();
+        }
+
+        write!(&mut msg, "]").unwrap();
+
+        let chat = agnostic::chat(msg);
+        agnostic::unicast_chat(by, &chat, system);
+
+        return;
+    };
+
+    tracing::debug!("executing command {first_word}");
+
+    let command = command.on_execute;
+    command(raw, system, by.id());
+}
+
 impl Module for CommandSystemModule {
     fn module(world: &World) {
         system!(
@@ -26,42 +68,33 @@ impl Module for CommandSystemModule {
         )
         .each_iter(|it, _, (event_queue, registry)| {
             let system = it.system();
-
             let world = it.world();
-            for event::Command { raw, by } in event_queue.drain() {
-                let Some(first_word) = raw.split_whitespace().next() else {
-                    tracing::warn!("command is empty");
-                    continue;
-                };
 
-                let Some(command) = registry.commands.get(first_word) else {
-                    tracing::debug!("command {first_word} not found");
-
-                    let mut msg = String::new();
-                    write!(&mut msg, "§cAvailable commands: §r[").unwrap();
-
-                    for w in registry.get_permitted(&world, by).intersperse(", ") {
-                        write!(&mut msg, "{w}").unwrap();
-                    }
-
-                    write!(&mut msg, "]").unwrap();
-
-                    let chat = agnostic::chat(msg);
+            for event::Command { raw, by } in event_queue.drain() {
+                dispatch(registry, raw, system, by.entity_view(world));
+            }
+        });
 
-                    world.get::<&hyperion::net::Compose>(|compose| {
-                        by.entity_view(world)
-                            .get::<&hyperion::net::ConnectionId>(|stream| {
-                                compose.unicast(&chat, *stream, system).unwrap();
-                            });
-                    });
+        system!(
+            "execute_console_commands",
+            world,
+            &ConsoleCommands($),
+            &CommandRegistry($)
+        )
+        .each_iter(|it, _, (commands, registry)| {
+            let system = it.system();
+            let world = it.world();
+            let console = get_console_entity().entity_view(world);
 
-                    continue;
-                };
+            while let Ok(Some(ConsoleCommand { command, output })) = commands.receiver.try_recv() {
+                dispatch(registry, &command, system, console);
 
-                tracing::debug!("executing command {first_word}");
+                let response = console.get::<&mut ConsoleOutput>(ConsoleOutput::take);
 
-                let command = command.on_execute;
-                command(raw, system, by);
+                if let Some(output) = output {
+                    // the RCON client may have disconnected
+                    let _ = output.send(response);
+                }
             }
         });
 
@@ -0,0 +1,444 @@
+//! Loading crafting and cooking recipes from the JSON files of vanilla data packs.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, bail, ensure};
+use serde::Deserialize;
+use tracing::{debug, warn};
+use valence_protocol::{ItemKind, ItemStack};
+
+use crate::{
+    CraftingCategory, CraftingRegistry, CraftingShapedData, CraftingShapelessData, FurnaceKind,
+    Ingredient, SmeltingCategory, SmeltingData,
+};
+
+/// Item tags such as `minecraft:planks` and the items they contain.
+pub type ItemTags = HashMap<String, Vec<ItemKind>>;
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum RecipeJson {
+    #[serde(rename = "minecraft:crafting_shaped")]
+    Shaped {
+        #[serde(default)]
+        group: String,
+        #[serde(default)]
+        category: CraftingCategory,
+        pattern: Vec<String>,
+        key: HashMap<char, IngredientJson>,
+        result: ResultJson,
+        #[serde(default = "default_show_notification")]
+        show_notification: bool,
+    },
+    #[serde(rename = "minecraft:crafting_shapeless")]
+    Shapeless {
+        #[serde(default)]
+        group: String,
+        #[serde(default)]
+        category: CraftingCategory,
+        ingredients: Vec<IngredientJson>,
+        result: ResultJson,
+    },
+    #[serde(rename = "minecraft:smelting")]
+    Smelting(CookingJson),
+    #[serde(rename = "minecraft:blasting")]
+    Blasting(CookingJson),
+    #[serde(rename = "minecraft:smoking")]
+    Smoking(CookingJson),
+    /// Campfire, smithing and special recipes.
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Deserialize)]
+struct CookingJson {
+    #[serde(default)]
+    group: String,
+    #[serde(default)]
+    category: SmeltingCategory,
+    ingredient: IngredientJson,
+    /// Cooking recipes name their result without a count.
+    result: String,
+    #[serde(default)]
+    experience: f32,
+    #[serde(rename = "cookingtime")]
+    cooking_time: Option<u16>,
+}
+
+const fn default_show_notification() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IngredientJson {
+    Choice(ChoiceJson),
+    Choices(Vec<ChoiceJson>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChoiceJson {
+    Item { item: String },
+    Tag { tag: String },
+}
+
+#[derive(Deserialize)]
+struct ResultJson {
+    item: String,
+    #[serde(default = "default_count")]
+    count: i8,
+}
+
+const fn default_count() -> i8 {
+    1
+}
+
+fn item(name: &str) -> anyhow::Result<ItemKind> {
+    let path = name.strip_prefix("minecraft:").unwrap_or(name);
+    ItemKind::from_str(path).with_context(|| format!("unknown item {name}"))
+}
+
+impl ChoiceJson {
+    fn items(&self, tags: &ItemTags) -> anyhow::Result<Vec<ItemKind>> {
+        match self {
+            Self::Item { item: name } => Ok(vec![item(name)?]),
+            Self::Tag { tag } => tags
+                .get(tag)
+                .cloned()
+                .with_context(|| format!("unknown item tag {tag}")),
+        }
+    }
+}
+
+impl IngredientJson {
+    fn resolve(&self, tags: &ItemTags) -> anyhow::Result<Ingredient> {
+        let choices = match self {
+            Self::Choice(choice) => std::slice::from_ref(choice),
+            Self::Choices(choices) => choices,
+        };
+
+        let mut items = Vec::new();
+        for choice in choices {
+            items.extend(choice.items(tags)?);
+        }
+
+        ensure!(!items.is_empty(), "ingredient matches no items");
+
+        Ok(items.into_iter().collect())
+    }
+}
+
+impl ResultJson {
+    fn resolve(&self) -> anyhow::Result<ItemStack> {
+        Ok(ItemStack::new(item(&self.item)?, self.count, None))
+    }
+}
+
+impl CookingJson {
+    fn resolve(self, kind: FurnaceKind, tags: &ItemTags) -> anyhow::Result<SmeltingData> {
+        let cooking_time = self
+            .cooking_time
+            .unwrap_or_else(|| kind.default_cooking_time());
+
+        let data = SmeltingData::new(
+            self.ingredient.resolve(tags)?,
+            ItemStack::new(item(&self.result)?, 1, None),
+            cooking_time,
+        )
+        .group(self.group)
+        .category(self.category)
+        .experience(self.experience);
+
+        Ok(data)
+    }
+}
+
+impl CraftingRegistry {
+    /// Registers the recipe in `json`, which has the format of a recipe file in a data pack.
+    ///
+    /// Returns `false` if it is not a recipe this registry can hold, such as a smithing recipe.
+    pub fn register_json(
+        &mut self,
+        recipe_id: impl Into<String>,
+        json: &str,
+        tags: &ItemTags,
+    ) -> anyhow::Result<bool> {
+        let recipe: RecipeJson = serde_json::from_str(json)?;
+
+        match recipe {
+            RecipeJson::Shaped {
+                group,
+                category,
+                pattern,
+                key,
+                result,
+                show_notification,
+            } => {
+                let key = key
+                    .into_iter()
+                    .map(|(symbol, ingredient)| Ok((symbol, ingredient.resolve(tags)?)))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                let data = CraftingShapedData::new(
+                    pattern.iter().map(String::as_str),
+                    key,
+                    result.resolve()?,
+                )?
+                .group(group)
+                .category(category)
+                .show_notification(show_notification);
+
+                self.register_shaped(recipe_id, data)?;
+            }
+            RecipeJson::Shapeless {
+                group,
+                category,
+                ingredients,
+                result,
+            } => {
+                let mut data = CraftingShapelessData::new(result.resolve()?)
+                    .group(group)
+                    .category(category);
+
+                for ingredient in &ingredients {
+                    data = data.ingredient(ingredient.resolve(tags)?);
+                }
+
+                self.register_shapeless(recipe_id, data)?;
+            }
+            RecipeJson::Smelting(cooking) => {
+                let data = cooking.resolve(FurnaceKind::Furnace, tags)?;
+                self.register_smelting(recipe_id, FurnaceKind::Furnace, data)?;
+            }
+            RecipeJson::Blasting(cooking) => {
+                let data = cooking.resolve(FurnaceKind::BlastFurnace, tags)?;
+                self.register_smelting(recipe_id, FurnaceKind::BlastFurnace, data)?;
+            }
+            RecipeJson::Smoking(cooking) => {
+                let data = cooking.resolve(FurnaceKind::Smoker, tags)?;
+                self.register_smelting(recipe_id, FurnaceKind::Smoker, data)?;
+            }
+            RecipeJson::Unsupported => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Registers the crafting and cooking recipes of the data pack at `path`, which are the JSON files in
+    /// `data/<namespace>/recipes`. Recipes which cannot be loaded are skipped with a warning.
+    ///
+    /// Returns the number of recipes registered.
+    pub fn load_data_pack(&mut self, path: &Path, tags: &ItemTags) -> anyhow::Result<usize> {
+        let data = path.join("data");
+
+        if !data.is_dir() {
+            bail!("{} is not a data pack", path.display());
+        }
+
+        let mut count = 0;
+
+        for namespace in fs::read_dir(&data)? {
+            let namespace = namespace?;
+            let recipes = namespace.path().join("recipes");
+
+            if !recipes.is_dir() {
+                continue;
+            }
+
+            let namespace = namespace.file_name();
+            let namespace = namespace.to_string_lossy();
+
+            let mut files = Vec::new();
+            find_json_files(&recipes, &mut files)?;
+
+            for file in files {
+                let name = file
+                    .strip_prefix(&recipes)?
+                    .with_extension("")
+                    .to_string_lossy()
+                    .replace(std::path::MAIN_SEPARATOR, "/");
+
+                let recipe_id = format!("{namespace}:{name}");
+
+                let result = fs::read_to_string(&file)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| self.register_json(recipe_id.clone(), &json, tags));
+
+                match result {
+                    Ok(true) => count += 1,
+                    Ok(false) => debug!("skipping unsupported recipe {recipe_id}"),
+                    Err(e) => warn!("failed to load recipe {recipe_id}: {e}"),
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Loads every data pack in `dir` with [`Self::load_data_pack`].
+    pub fn load_data_packs(&mut self, dir: &Path, tags: &ItemTags) -> anyhow::Result<usize> {
+        let mut count = 0;
+
+        for pack in fs::read_dir(dir)? {
+            let pack = pack?.path();
+
+            if pack.is_dir() {
+                count += self.load_data_pack(&pack, tags)?;
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+fn find_json_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            find_json_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags() -> ItemTags {
+        HashMap::from([("minecraft:planks".to_string(), vec![
+            ItemKind::OakPlanks,
+            ItemKind::SprucePlanks,
+        ])])
+    }
+
+    #[test]
+    fn test_shaped_json() {
+        let mut registry = CraftingRegistry::default();
+
+        let json = r##"{
+            "type": "minecraft:crafting_shaped",
+            "category": "misc",
+            "key": { "#": { "tag": "minecraft:planks" } },
+            "pattern": ["#", "#"],
+            "result": { "count": 4, "item": "minecraft:stick" },
+            "show_notification": true
+        }"##;
+
+        assert!(
+            registry
+                .register_json("minecraft:stick", json, &tags())
+                .unwrap()
+        );
+
+        let mut grid = [ItemKind::Air; 9];
+        grid[2] = ItemKind::SprucePlanks;
+        grid[5] = ItemKind::OakPlanks;
+
+        let result = registry.get_result_3x3(grid).unwrap();
+        assert_eq!(result.item, ItemKind::Stick);
+        assert_eq!(result.count, 4);
+    }
+
+    #[test]
+    fn test_shapeless_json() {
+        let mut registry = CraftingRegistry::default();
+
+        let json = r#"{
+            "type": "minecraft:crafting_shapeless",
+            "category": "misc",
+            "ingredients": [
+                { "item": "minecraft:bowl" },
+                [{ "item": "minecraft:brown_mushroom" }, { "item": "minecraft:red_mushroom" }],
+                { "item": "minecraft:red_mushroom" }
+            ],
+            "result": { "item": "minecraft:mushroom_stew" }
+        }"#;
+
+        assert!(
+            registry
+                .register_json("minecraft:mushroom_stew", json, &tags())
+                .unwrap()
+        );
+
+        let grid = [
+            ItemKind::RedMushroom,
+            ItemKind::Air,
+            ItemKind::Bowl,
+            ItemKind::RedMushroom,
+        ];
+
+        let result = registry.get_result_2x2(grid).unwrap();
+        assert_eq!(result.item, ItemKind::MushroomStew);
+    }
+
+    #[test]
+    fn test_smelting_json() {
+        let mut registry = CraftingRegistry::default();
+
+        let json = r#"{
+            "type": "minecraft:smoking",
+            "category": "food",
+            "ingredient": { "item": "minecraft:beef" },
+            "result": "minecraft:cooked_beef",
+            "experience": 0.35
+        }"#;
+
+        assert!(
+            registry
+                .register_json("minecraft:cooked_beef_from_smoking", json, &tags())
+                .unwrap()
+        );
+
+        let recipe = registry
+            .get_smelting(FurnaceKind::Smoker, ItemKind::Beef)
+            .unwrap();
+        assert_eq!(recipe.result().item, ItemKind::CookedBeef);
+        assert_eq!(recipe.cooking_time(), 100);
+
+        assert!(
+            registry
+                .get_smelting(FurnaceKind::Furnace, ItemKind::Beef)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_unsupported_json() {
+        let mut registry = CraftingRegistry::default();
+
+        let json = r#"{
+            "type": "minecraft:stonecutting",
+            "ingredient": { "item": "minecraft:stone" },
+            "result": "minecraft:stone_slab",
+            "count": 2
+        }"#;
+
+        assert!(
+            !registry
+                .register_json("minecraft:stone_slab_from_stonecutting", json, &tags())
+                .unwrap()
+        );
+
+        let json = r##"{
+            "type": "minecraft:crafting_shaped",
+            "key": {},
+            "pattern": ["#"],
+            "result": { "item": "minecraft:stone" }
+        }"##;
+
+        assert!(
+            registry
+                .register_json("hyperion:invalid", json, &tags())
+                .is_err()
+        );
+    }
+}
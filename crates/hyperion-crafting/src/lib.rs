@@ -1,9 +1,20 @@
-use std::{collections::HashMap, io::Write};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+};
 
+use anyhow::{bail, ensure};
 use derive_build::Build;
 use flecs_ecs::macros::Component;
+use serde::Deserialize;
 use slotmap::{SecondaryMap, SlotMap, new_key_type};
-use valence_protocol::{Encode, ItemKind, ItemStack, Packet};
+use valence_protocol::{Encode, ItemKind, ItemStack, Packet, VarInt};
+
+mod data_pack;
+mod smelting;
+
+pub use data_pack::ItemTags;
+pub use smelting::{FurnaceKind, SmeltingData};
 
 /// Represents a packet sent from the server to the client to synchronize recipes.
 #[derive(Clone, Debug, Encode, Packet)]
@@ -27,7 +38,7 @@ pub struct Recipe {
 #[derive(Clone, Debug)]
 pub enum RecipeData {
     CraftingShapeless(CraftingShapelessData),
-    // CraftingShaped(CraftingShapedData<'a>),
+    CraftingShaped(CraftingShapedData),
     // CraftingSpecialArmordye(CraftingSpecialData),
     // CraftingSpecialBookcloning(CraftingSpecialData),
     // CraftingSpecialMapcloning(CraftingSpecialData),
@@ -42,9 +53,9 @@ pub enum RecipeData {
     // CraftingSpecialShulkerboxcoloring(CraftingSpecialData),
     // CraftingSpecialSuspiciousstew(CraftingSpecialData),
     // CraftingDecoratedPot(CraftingSpecialData),
-    // Smelting(SmeltingData<'a>),
-    // Blasting(SmeltingData<'a>),
-    // Smoking(SmeltingData<'a>),
+    Smelting(SmeltingData),
+    Blasting(SmeltingData),
+    Smoking(SmeltingData),
     // CampfireCooking(SmeltingData<'a>),
     // Stonecutting(StonecuttingData<'a>),
     // SmithingTransform(SmithingTransformData<'a>),
@@ -55,7 +66,7 @@ impl Encode for RecipeData {
     fn encode(&self, w: impl Write) -> anyhow::Result<()> {
         match self {
             Self::CraftingShapeless(data) => data.encode(w),
-            // RecipeData::CraftingShaped(data) => data.encode(w),
+            Self::CraftingShaped(data) => data.encode(w),
             // RecipeData::CraftingSpecialArmordye(data) => data.encode(w),
             // RecipeData::CraftingSpecialBookcloning(data) => data.encode(w),
             // RecipeData::CraftingSpecialMapcloning(data) => data.encode(w),
@@ -70,9 +81,7 @@ impl Encode for RecipeData {
             // RecipeData::CraftingSpecialShulkerboxcoloring(data) => data.encode(w),
             // RecipeData::CraftingSpecialSuspiciousstew(data) => data.encode(w),
             // RecipeData::CraftingDecoratedPot(data) => data.encode(w),
-            // RecipeData::Smelting(data) => data.encode(w),
-            // RecipeData::Blasting(data) => data.encode(w),
-            // RecipeData::Smoking(data) => data.encode(w),
+            Self::Smelting(data) | Self::Blasting(data) | Self::Smoking(data) => data.encode(w),
             // RecipeData::CampfireCooking(data) => data.encode(w),
             // RecipeData::Stonecutting(data) => data.encode(w),
             // RecipeData::SmithingTransform(data) => data.encode(w),
@@ -95,6 +104,179 @@ pub struct CraftingShapelessData {
     result: ItemStack,
 }
 
+/// Represents data for a shaped crafting recipe.
+///
+/// The pattern may be placed anywhere in the grid and also matches when mirrored horizontally.
+#[derive(Clone, Debug)]
+pub struct CraftingShapedData {
+    width: usize,
+    height: usize,
+    /// Used to group similar recipes together in the recipe book.
+    group: String,
+    /// The category of the recipe.
+    category: CraftingCategory,
+    /// The ingredients row by row; an empty ingredient is an empty slot.
+    ingredients: Vec<Ingredient>,
+    /// The result of the crafting recipe.
+    result: ItemStack,
+    /// Whether a toast is shown when the recipe is unlocked.
+    show_notification: bool,
+}
+
+impl CraftingShapedData {
+    /// Creates a recipe from a vanilla-style pattern, where each character of a row is looked up
+    /// in `key` and a space is an empty slot.
+    ///
+    /// ```
+    /// # use hyperion_crafting::CraftingShapedData;
+    /// # use valence_protocol::{ItemKind, ItemStack};
+    /// let table = CraftingShapedData::new(
+    ///     ["##", "##"],
+    ///     [('#', ItemKind::OakPlanks.into())],
+    ///     ItemStack::new(ItemKind::CraftingTable, 1, None),
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn new<'a>(
+        pattern: impl IntoIterator<Item = &'a str>,
+        key: impl IntoIterator<Item = (char, Ingredient)>,
+        result: ItemStack,
+    ) -> anyhow::Result<Self> {
+        let pattern: Vec<&str> = pattern.into_iter().collect();
+        let key: HashMap<char, Ingredient> = key.into_iter().collect();
+
+        let height = pattern.len();
+        let width = pattern.first().map_or(0, |row| row.chars().count());
+
+        ensure!(
+            (1..=3).contains(&width) && (1..=3).contains(&height),
+            "a pattern must be between 1x1 and 3x3 but is {width}x{height}"
+        );
+
+        let mut ingredients = Vec::with_capacity(width * height);
+
+        for row in &pattern {
+            ensure!(
+                row.chars().count() == width,
+                "every row of the pattern must be {width} wide"
+            );
+
+            for symbol in row.chars() {
+                if symbol == ' ' {
+                    ingredients.push(Ingredient::EMPTY);
+                    continue;
+                }
+
+                let Some(ingredient) = key.get(&symbol) else {
+                    bail!("the key has no ingredient for {symbol:?}");
+                };
+
+                ingredients.push(ingredient.clone());
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            group: String::new(),
+            category: CraftingCategory::default(),
+            ingredients,
+            result,
+            show_notification: true,
+        })
+    }
+
+    #[must_use]
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = group.into();
+        self
+    }
+
+    #[must_use]
+    pub const fn category(mut self, category: CraftingCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    #[must_use]
+    pub const fn show_notification(mut self, show_notification: bool) -> Self {
+        self.show_notification = show_notification;
+        self
+    }
+
+    #[must_use]
+    pub const fn result(&self) -> &ItemStack {
+        &self.result
+    }
+
+    /// Whether the items of `grid`, trimmed to the smallest rectangle holding all of them, match
+    /// the pattern or its mirror image.
+    fn matches(&self, grid: &Grid<'_>) -> bool {
+        if grid.width != self.width || grid.height != self.height {
+            return false;
+        }
+
+        let matches_with = |mirrored: bool| {
+            (0..self.height).all(|row| {
+                (0..self.width).all(|column| {
+                    let pattern_column = if mirrored {
+                        self.width - 1 - column
+                    } else {
+                        column
+                    };
+
+                    self.ingredients[row * self.width + pattern_column]
+                        .matches(grid.get(row, column))
+                })
+            })
+        };
+
+        matches_with(false) || matches_with(true)
+    }
+}
+
+/// The part of a crafting grid which holds items.
+struct Grid<'a> {
+    items: &'a [ItemKind],
+    grid_width: usize,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a> Grid<'a> {
+    /// Trims `items`, a square grid with rows of `grid_width` items, to the items in it. Returns
+    /// [`None`] if the grid is empty.
+    fn trim(items: &'a [ItemKind], grid_width: usize) -> Option<Self> {
+        let filled = || {
+            items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| **item != ItemKind::Air)
+                .map(|(idx, _)| (idx / grid_width, idx % grid_width))
+        };
+
+        let top = filled().map(|(row, _)| row).min()?;
+        let bottom = filled().map(|(row, _)| row).max()?;
+        let left = filled().map(|(_, column)| column).min()?;
+        let right = filled().map(|(_, column)| column).max()?;
+
+        Some(Self {
+            items,
+            grid_width,
+            left,
+            top,
+            width: right - left + 1,
+            height: bottom - top + 1,
+        })
+    }
+
+    fn get(&self, row: usize, column: usize) -> ItemKind {
+        self.items[(self.top + row) * self.grid_width + self.left + column]
+    }
+}
+
 /// Represents data for special crafting recipes.
 #[derive(Clone, Debug)]
 pub struct CraftingSpecialData {
@@ -103,7 +285,8 @@ pub struct CraftingSpecialData {
 }
 
 /// Represents the categories for crafting recipes.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CraftingCategory {
     Building,
     Redstone,
@@ -113,16 +296,52 @@ pub enum CraftingCategory {
 }
 
 /// Represents the categories for smelting recipes.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SmeltingCategory {
     Food,
     Blocks,
+    #[default]
     Misc,
 }
 
 /// Represents an ingredient in a recipe, which can be multiple possible items.
-#[derive(Encode, Clone, Debug)]
-struct Ingredient(Vec<ItemStack>);
+#[derive(Encode, Clone, Debug, PartialEq)]
+pub struct Ingredient(Vec<ItemStack>);
+
+impl Ingredient {
+    /// An empty slot of a shaped recipe.
+    pub const EMPTY: Self = Self(Vec::new());
+
+    /// Whether `item` may be used for this ingredient.
+    #[must_use]
+    pub fn matches(&self, item: ItemKind) -> bool {
+        if self.0.is_empty() {
+            return item == ItemKind::Air;
+        }
+
+        self.0.iter().any(|stack| stack.item == item)
+    }
+
+    /// The only item which may be used for this ingredient, if there are no alternatives.
+    fn single(&self) -> Option<ItemKind> {
+        if let [stack] = &self.0[..] {
+            return Some(stack.item);
+        }
+
+        None
+    }
+}
+
+impl FromIterator<ItemKind> for Ingredient {
+    fn from_iter<T: IntoIterator<Item = ItemKind>>(iter: T) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|item| ItemStack::new(item, 1, None))
+                .collect(),
+        )
+    }
+}
 
 impl From<Vec<ItemStack>> for Ingredient {
     fn from(value: Vec<ItemStack>) -> Self {
@@ -152,6 +371,21 @@ impl Encode for CraftingShapelessData {
     }
 }
 
+impl Encode for CraftingShapedData {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        VarInt(i32::try_from(self.width)?).encode(&mut w)?;
+        VarInt(i32::try_from(self.height)?).encode(&mut w)?;
+        self.group.encode(&mut w)?;
+        self.category.encode(&mut w)?;
+        // the number of ingredients follows from the size, so it is not prefixed
+        for ingredient in &self.ingredients {
+            ingredient.encode(&mut w)?;
+        }
+        self.result.encode(&mut w)?;
+        self.show_notification.encode(w)
+    }
+}
+
 impl Encode for CraftingSpecialData {
     fn encode(&self, w: impl Write) -> anyhow::Result<()> {
         self.category.encode(w)
@@ -218,30 +452,66 @@ impl FromIterator<ItemKind> for SortedItemList {
 
 // Define a custom key type
 new_key_type! { struct SortedItemId; }
+new_key_type! { struct ShapedId; }
+new_key_type! { struct SmeltingId; }
 
 #[derive(Component)]
 pub struct CraftingRegistry {
     // changes when the registry is updated
     epoch: u64,
 
+    recipe_ids: HashSet<String>,
+
+    // shapeless recipes where every ingredient is a single item
     shapeless_lookup: HashMap<SortedItemList, SortedItemId>,
+    // shapeless recipes with alternatives for an ingredient, which have to be checked one by one
+    shapeless_alternatives: Vec<SortedItemId>,
     shapeless: SlotMap<SortedItemId, CraftingShapelessData>,
     shapeless_ids: SecondaryMap<SortedItemId, String>,
+
+    shaped: SlotMap<ShapedId, CraftingShapedData>,
+    shaped_ids: SecondaryMap<ShapedId, String>,
+
+    smelting: SlotMap<SmeltingId, SmeltingData>,
+    smelting_ids: SecondaryMap<SmeltingId, (FurnaceKind, String)>,
+    smelting_lookup: HashMap<(FurnaceKind, ItemKind), SmeltingId>,
 }
 
 impl Default for CraftingRegistry {
     fn default() -> Self {
         let mut result = Self {
             epoch: 0,
+            recipe_ids: HashSet::default(),
             shapeless_lookup: HashMap::default(),
+            shapeless_alternatives: Vec::default(),
             shapeless: SlotMap::default(),
             shapeless_ids: SecondaryMap::default(),
+            shaped: SlotMap::default(),
+            shaped_ids: SecondaryMap::default(),
+            smelting: SlotMap::default(),
+            smelting_ids: SecondaryMap::default(),
+            smelting_lookup: HashMap::default(),
         };
 
         let shapeless = CraftingShapelessData::new(ItemStack::new(ItemKind::OakPlanks, 4, None))
             .ingredient(ItemKind::OakLog);
 
-        result.register_shapeless("hyperion:plank".to_string(), shapeless);
+        result
+            .register_shapeless("hyperion:plank", shapeless)
+            .unwrap();
+
+        let shaped = CraftingShapedData::new(
+            ["##", "##"],
+            [('#', ItemKind::OakPlanks.into())],
+            ItemStack::new(ItemKind::CraftingTable, 1, None),
+        )
+        .unwrap();
+
+        result
+            .register_shaped("hyperion:crafting_table", shaped)
+            .unwrap();
+
+        result.register_default_smelting();
 
         result
     }
@@ -252,6 +522,42 @@ pub struct ShapelessRecipe<'a> {
     pub data: &'a CraftingShapelessData,
 }
 
+/// Whether every item can be used for a different ingredient.
+fn matches_shapeless(ingredients: &[Ingredient], items: &[ItemKind]) -> bool {
+    fn assign(ingredients: &[Ingredient], items: &[ItemKind], used: &mut [bool]) -> bool {
+        let Some((&item, rest)) = items.split_first() else {
+            return true;
+        };
+
+        // there are at most 9 items, so trying every assignment is cheap
+        for (idx, ingredient) in ingredients.iter().enumerate() {
+            if used[idx] || !ingredient.matches(item) {
+                continue;
+            }
+
+            used[idx] = true;
+            if assign(ingredients, rest, used) {
+                return true;
+            }
+            used[idx] = false;
+        }
+
+        false
+    }
+
+    let items: Vec<ItemKind> = items
+        .iter()
+        .copied()
+        .filter(|item| *item != ItemKind::Air)
+        .collect();
+
+    if items.len() != ingredients.len() {
+        return false;
+    }
+
+    assign(ingredients, &items, &mut vec![false; ingredients.len()])
+}
+
 impl CraftingRegistry {
     fn mark_changed(&mut self) {
         self.epoch = self.epoch.wrapping_add(1);
@@ -264,19 +570,43 @@ impl CraftingRegistry {
             return None;
         }
 
-        let recipes: Vec<_> = self
-            .shapeless
-            .iter()
-            .map(|(id, data)| {
-                let recipe_id = self.shapeless_ids.get(id).unwrap();
+        let shapeless = self.shapeless.iter().map(|(id, data)| {
+            let recipe_id = self.shapeless_ids.get(id).unwrap();
 
-                Recipe {
-                    kind: "minecraft:crafting_shapeless",
-                    recipe_id: recipe_id.to_string(),
-                    data: RecipeData::CraftingShapeless(data.clone()),
-                }
-            })
-            .collect();
+            Recipe {
+                kind: "minecraft:crafting_shapeless",
+                recipe_id: recipe_id.to_string(),
+                data: RecipeData::CraftingShapeless(data.clone()),
+            }
+        });
+
+        let shaped = self.shaped.iter().map(|(id, data)| {
+            let recipe_id = self.shaped_ids.get(id).unwrap();
+
+            Recipe {
+                kind: "minecraft:crafting_shaped",
+                recipe_id: recipe_id.to_string(),
+                data: RecipeData::CraftingShaped(data.clone()),
+            }
+        });
+
+        let smelting = self.smelting.iter().map(|(id, data)| {
+            let (kind, recipe_id) = self.smelting_ids.get(id).unwrap();
+
+            let data = match kind {
+                FurnaceKind::Furnace => RecipeData::Smelting(data.clone()),
+                FurnaceKind::BlastFurnace => RecipeData::Blasting(data.clone()),
+                FurnaceKind::Smoker => RecipeData::Smoking(data.clone()),
+            };
+
+            Recipe {
+                kind: kind.recipe_kind(),
+                recipe_id: recipe_id.to_string(),
+                data,
+            }
+        });
+
+        let recipes = shapeless.chain(shaped).chain(smelting).collect();
 
         Some(SynchronizeRecipesS2c { recipes })
     }
@@ -285,8 +615,20 @@ impl CraftingRegistry {
         &self,
         input: impl IntoIterator<Item = ItemKind>,
     ) -> Option<ShapelessRecipe<'_>> {
-        let list: SortedItemList = input.into_iter().collect();
-        let id = self.shapeless_lookup.get(&list).copied()?;
+        let input: Vec<ItemKind> = input.into_iter().collect();
+
+        if input.len() > 9 {
+            return None;
+        }
+
+        let list: SortedItemList = input.iter().copied().collect();
+
+        let id = self.shapeless_lookup.get(&list).copied().or_else(|| {
+            self.shapeless_alternatives
+                .iter()
+                .copied()
+                .find(|id| matches_shapeless(&self.shapeless[*id].ingredients, &input))
+        })?;
 
         // let recipe_id = self.shapeless_ids.get(id).unwrap();
         let data = self.shapeless.get(id).unwrap();
@@ -294,28 +636,186 @@ impl CraftingRegistry {
         Some(ShapelessRecipe { data })
     }
 
-    fn register_shapeless(&mut self, recipe_id: String, data: CraftingShapelessData) {
-        let list: SortedItemList = data
-            .ingredients
-            .iter()
-            .flat_map(|x| &x.0)
-            .map(|x| x.item)
-            .collect();
+    /// Finds the shaped recipe matching a square grid with rows of `width` items.
+    #[must_use]
+    pub fn get_shaped(&self, grid: &[ItemKind], width: usize) -> Option<&CraftingShapedData> {
+        let grid = Grid::trim(grid, width)?;
+
+        self.shaped.values().find(|data| data.matches(&grid))
+    }
+
+    fn add_recipe_id(&mut self, recipe_id: String) -> anyhow::Result<String> {
+        ensure!(
+            !self.recipe_ids.contains(&recipe_id),
+            "a recipe with the id {recipe_id} is already registered"
+        );
+
+        self.recipe_ids.insert(recipe_id.clone());
+
+        Ok(recipe_id)
+    }
+
+    pub fn register_shapeless(
+        &mut self,
+        recipe_id: impl Into<String>,
+        data: CraftingShapelessData,
+    ) -> anyhow::Result<()> {
+        let count = data.ingredients.len();
+
+        ensure!(
+            (1..=9).contains(&count),
+            "a shapeless recipe must have between 1 and 9 ingredients but has {count}"
+        );
+
+        let recipe_id = self.add_recipe_id(recipe_id.into())?;
+
+        let single: Option<SortedItemList> =
+            data.ingredients.iter().map(Ingredient::single).collect();
 
         let entity_id = self.shapeless.insert(data);
         self.shapeless_ids.insert(entity_id, recipe_id);
 
-        self.shapeless_lookup.insert(list, entity_id);
+        match single {
+            Some(list) => {
+                self.shapeless_lookup.insert(list, entity_id);
+            }
+            None => self.shapeless_alternatives.push(entity_id),
+        }
+
+        self.mark_changed();
+
+        Ok(())
+    }
+
+    pub fn register_shaped(
+        &mut self,
+        recipe_id: impl Into<String>,
+        data: CraftingShapedData,
+    ) -> anyhow::Result<()> {
+        let recipe_id = self.add_recipe_id(recipe_id.into())?;
+
+        let id = self.shaped.insert(data);
+        self.shaped_ids.insert(id, recipe_id);
 
         self.mark_changed();
+
+        Ok(())
     }
 
+    /// The result of crafting with a square grid with rows of `width` items. Shaped recipes are
+    /// checked before shapeless ones.
     #[must_use]
-    pub fn get_result_2x2(&self, grid: Crafting2x2) -> Option<&ItemStack> {
-        if let Some(shapeless) = self.get_shapeless(grid) {
+    pub fn get_result(&self, grid: &[ItemKind], width: usize) -> Option<&ItemStack> {
+        if let Some(shaped) = self.get_shaped(grid, width) {
+            return Some(&shaped.result);
+        }
+
+        if let Some(shapeless) = self.get_shapeless(grid.iter().copied()) {
             return Some(&shapeless.data.result);
         }
 
         None
     }
+
+    #[must_use]
+    pub fn get_result_2x2(&self, grid: Crafting2x2) -> Option<&ItemStack> {
+        self.get_result(&grid, 2)
+    }
+
+    #[must_use]
+    pub fn get_result_3x3(&self, grid: Crafting3x3) -> Option<&ItemStack> {
+        self.get_result(&grid, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> CraftingRegistry {
+        let mut registry = CraftingRegistry::default();
+
+        let axe = CraftingShapedData::new(
+            ["##", "#|", " |"],
+            [
+                ('#', ItemKind::Cobblestone.into()),
+                ('|', ItemKind::Stick.into()),
+            ],
+            ItemStack::new(ItemKind::StoneAxe, 1, None),
+        )
+        .unwrap();
+
+        registry.register_shaped("hyperion:stone_axe", axe).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_shaped_anywhere_and_mirrored() {
+        use ItemKind::{Air, Cobblestone as C, Stick as S};
+
+        let registry = registry();
+
+        let grid = [C, C, Air, C, S, Air, Air, S, Air];
+        assert_eq!(
+            registry.get_result_3x3(grid).unwrap().item,
+            ItemKind::StoneAxe
+        );
+
+        let shifted = [Air, C, C, Air, C, S, Air, Air, S];
+        assert_eq!(
+            registry.get_result_3x3(shifted).unwrap().item,
+            ItemKind::StoneAxe
+        );
+
+        let mirrored = [C, C, Air, S, C, Air, S, Air, Air];
+        assert_eq!(
+            registry.get_result_3x3(mirrored).unwrap().item,
+            ItemKind::StoneAxe
+        );
+
+        let upside_down = [Air, S, Air, C, S, Air, C, C, Air];
+        assert!(registry.get_result_3x3(upside_down).is_none());
+    }
+
+    #[test]
+    fn test_shaped_2x2_in_3x3() {
+        use ItemKind::{Air, OakPlanks as P};
+
+        let registry = registry();
+
+        let small = [P, P, P, P];
+        assert_eq!(
+            registry.get_result_2x2(small).unwrap().item,
+            ItemKind::CraftingTable
+        );
+
+        let large = [Air, Air, Air, Air, P, P, Air, P, P];
+        assert_eq!(
+            registry.get_result_3x3(large).unwrap().item,
+            ItemKind::CraftingTable
+        );
+
+        let extra = [P, Air, Air, Air, P, P, Air, P, P];
+        assert!(registry.get_result_3x3(extra).is_none());
+    }
+
+    #[test]
+    fn test_duplicate_id() {
+        let mut registry = registry();
+
+        let data = CraftingShapelessData::new(ItemStack::new(ItemKind::Stick, 1, None))
+            .ingredient(ItemKind::Bamboo);
+
+        assert!(registry.register_shapeless("hyperion:plank", data).is_err());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let ingredient = || [('#', Ingredient::from(ItemKind::Stone))];
+        let result = || ItemStack::new(ItemKind::Stone, 1, None);
+
+        assert!(CraftingShapedData::new(["####"], ingredient(), result()).is_err());
+        assert!(CraftingShapedData::new(["##", "#"], ingredient(), result()).is_err());
+        assert!(CraftingShapedData::new(["#x"], ingredient(), result()).is_err());
+    }
 }
@@ -0,0 +1,258 @@
+//! Cooking recipes of furnaces, blast furnaces and smokers.
+
+use std::io::Write;
+
+use anyhow::ensure;
+use valence_protocol::{Encode, ItemKind, ItemStack, VarInt};
+
+use crate::{CraftingRegistry, Ingredient, SmeltingCategory};
+
+/// The blocks which cook items, each with recipes of their own.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum FurnaceKind {
+    Furnace,
+    BlastFurnace,
+    Smoker,
+}
+
+impl FurnaceKind {
+    /// The type of the recipes this block cooks with.
+    #[must_use]
+    pub const fn recipe_kind(self) -> &'static str {
+        match self {
+            Self::Furnace => "minecraft:smelting",
+            Self::BlastFurnace => "minecraft:blasting",
+            Self::Smoker => "minecraft:smoking",
+        }
+    }
+
+    /// The ticks a recipe of this kind takes if it does not say otherwise.
+    #[must_use]
+    pub const fn default_cooking_time(self) -> u16 {
+        match self {
+            Self::Furnace => 200,
+            Self::BlastFurnace | Self::Smoker => 100,
+        }
+    }
+}
+
+/// Represents data for a smelting, blasting or smoking recipe.
+#[derive(Clone, Debug)]
+pub struct SmeltingData {
+    /// Used to group similar recipes together in the recipe book.
+    group: String,
+    /// The category of the recipe.
+    category: SmeltingCategory,
+    /// The item which is cooked.
+    ingredient: Ingredient,
+    /// The result of cooking one ingredient.
+    result: ItemStack,
+    /// The experience awarded for taking the result.
+    experience: f32,
+    /// The ticks it takes to cook one ingredient.
+    cooking_time: u16,
+}
+
+impl SmeltingData {
+    #[must_use]
+    pub fn new(ingredient: impl Into<Ingredient>, result: ItemStack, cooking_time: u16) -> Self {
+        Self {
+            group: String::new(),
+            category: SmeltingCategory::default(),
+            ingredient: ingredient.into(),
+            result,
+            experience: 0.0,
+            cooking_time,
+        }
+    }
+
+    #[must_use]
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = group.into();
+        self
+    }
+
+    #[must_use]
+    pub const fn category(mut self, category: SmeltingCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    #[must_use]
+    pub const fn experience(mut self, experience: f32) -> Self {
+        self.experience = experience;
+        self
+    }
+
+    #[must_use]
+    pub const fn result(&self) -> &ItemStack {
+        &self.result
+    }
+
+    #[must_use]
+    pub const fn cooking_time(&self) -> u16 {
+        self.cooking_time
+    }
+}
+
+impl Encode for SmeltingData {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        self.group.encode(&mut w)?;
+        self.category.encode(&mut w)?;
+        self.ingredient.encode(&mut w)?;
+        self.result.encode(&mut w)?;
+        self.experience.encode(&mut w)?;
+        VarInt(i32::from(self.cooking_time)).encode(w)
+    }
+}
+
+/// The ores a miner brings back, which every furnace and blast furnace can smelt.
+const ORES: [(&str, [ItemKind; 3], ItemKind, f32); 3] = [
+    (
+        "iron_ingot",
+        [
+            ItemKind::RawIron,
+            ItemKind::IronOre,
+            ItemKind::DeepslateIronOre,
+        ],
+        ItemKind::IronIngot,
+        0.7,
+    ),
+    (
+        "gold_ingot",
+        [
+            ItemKind::RawGold,
+            ItemKind::GoldOre,
+            ItemKind::DeepslateGoldOre,
+        ],
+        ItemKind::GoldIngot,
+        1.0,
+    ),
+    (
+        "copper_ingot",
+        [
+            ItemKind::RawCopper,
+            ItemKind::CopperOre,
+            ItemKind::DeepslateCopperOre,
+        ],
+        ItemKind::CopperIngot,
+        0.7,
+    ),
+];
+
+impl CraftingRegistry {
+    pub(crate) fn register_default_smelting(&mut self) {
+        for (name, ingredients, result, experience) in ORES {
+            for kind in [FurnaceKind::Furnace, FurnaceKind::BlastFurnace] {
+                let data = SmeltingData::new(
+                    ingredients.into_iter().collect::<Ingredient>(),
+                    ItemStack::new(result, 1, None),
+                    kind.default_cooking_time(),
+                )
+                .category(SmeltingCategory::Misc)
+                .experience(experience);
+
+                let method = kind.recipe_kind().trim_start_matches("minecraft:");
+                let recipe_id = format!("hyperion:{name}_from_{method}");
+
+                self.register_smelting(recipe_id, kind, data).unwrap();
+            }
+        }
+    }
+
+    /// Registers a recipe which `kind` cooks with. If several recipes cook the same item, the one
+    /// registered first is used.
+    pub fn register_smelting(
+        &mut self,
+        recipe_id: impl Into<String>,
+        kind: FurnaceKind,
+        data: SmeltingData,
+    ) -> anyhow::Result<()> {
+        ensure!(
+            !data.ingredient.0.is_empty(),
+            "a smelting recipe must have an ingredient"
+        );
+        ensure!(
+            data.cooking_time > 0,
+            "a smelting recipe must take at least one tick"
+        );
+
+        let recipe_id = self.add_recipe_id(recipe_id.into())?;
+
+        let items: Vec<ItemKind> = data.ingredient.0.iter().map(|stack| stack.item).collect();
+
+        let id = self.smelting.insert(data);
+        self.smelting_ids.insert(id, (kind, recipe_id));
+
+        for item in items {
+            self.smelting_lookup.entry((kind, item)).or_insert(id);
+        }
+
+        self.mark_changed();
+
+        Ok(())
+    }
+
+    /// Finds the recipe `kind` cooks `item` with.
+    #[must_use]
+    pub fn get_smelting(&self, kind: FurnaceKind, item: ItemKind) -> Option<&SmeltingData> {
+        let id = self.smelting_lookup.get(&(kind, item))?;
+        self.smelting.get(*id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_ores() {
+        let registry = CraftingRegistry::default();
+
+        let furnace = registry
+            .get_smelting(FurnaceKind::Furnace, ItemKind::RawIron)
+            .unwrap();
+        assert_eq!(furnace.result().item, ItemKind::IronIngot);
+        assert_eq!(furnace.cooking_time(), 200);
+
+        let blast_furnace = registry
+            .get_smelting(FurnaceKind::BlastFurnace, ItemKind::DeepslateGoldOre)
+            .unwrap();
+        assert_eq!(blast_furnace.result().item, ItemKind::GoldIngot);
+        assert_eq!(blast_furnace.cooking_time(), 100);
+
+        assert!(
+            registry
+                .get_smelting(FurnaceKind::Smoker, ItemKind::RawIron)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_first_recipe_wins() {
+        let mut registry = CraftingRegistry::default();
+
+        let stone = SmeltingData::new(
+            ItemKind::Cobblestone,
+            ItemStack::new(ItemKind::Stone, 1, None),
+            200,
+        );
+        let gravel = SmeltingData::new(
+            ItemKind::Cobblestone,
+            ItemStack::new(ItemKind::Gravel, 1, None),
+            200,
+        );
+
+        registry
+            .register_smelting("hyperion:stone", FurnaceKind::Furnace, stone)
+            .unwrap();
+        registry
+            .register_smelting("hyperion:gravel", FurnaceKind::Furnace, gravel)
+            .unwrap();
+
+        let recipe = registry
+            .get_smelting(FurnaceKind::Furnace, ItemKind::Cobblestone)
+            .unwrap();
+        assert_eq!(recipe.result().item, ItemKind::Stone);
+    }
+}
@@ -1,24 +1,33 @@
-#![feature(thread_local)]
+#![feature(let_chains)]
 
-use std::{borrow::Cow, cell::Cell, collections::HashMap};
+use std::{borrow::Cow, sync::Arc};
 
-use flecs_ecs::core::{Entity, EntityView, EntityViewGet, WorldGet, WorldProvider};
+use flecs_ecs::{
+    core::{
+        Entity, EntityView, EntityViewGet, QueryBuilderImpl, SystemAPI, TermBuilderImpl, World,
+        WorldGet, WorldProvider,
+    },
+    macros::{Component, observer},
+    prelude::{Module, flecs},
+};
 use hyperion::{
     net::{Compose, ConnectionId},
     storage::GlobalEventHandlers,
     valence_protocol::{
         ItemStack, VarInt,
         packets::play::{
+            self,
             click_slot_c2s::ClickMode,
-            close_screen_s2c::CloseScreenS2c,
-            inventory_s2c::InventoryS2c,
             open_screen_s2c::{OpenScreenS2c, WindowType},
         },
         text::IntoText,
     },
 };
+use hyperion_inventory::{PlayerInventory, container::next_window_id};
 use serde::{Deserialize, Serialize};
 
+pub mod widget;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InventoryItem {
     pub id: String,
@@ -27,179 +36,516 @@ pub struct InventoryItem {
     pub quantity: u32,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// The kind of screen a [`Gui`] is shown in. This determines how many slots it has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContainerType {
-    Chest,
-    ShulkerBox,
+    /// A chest-like container with one row of nine slots.
+    Generic9x1,
+    Generic9x2,
+    /// A single chest.
+    Generic9x3,
+    Generic9x4,
+    Generic9x5,
+    /// A double chest.
+    Generic9x6,
+    /// A dispenser or dropper.
+    Generic3x3,
+    Anvil,
+    Beacon,
+    BlastFurnace,
+    BrewingStand,
+    Cartography,
+    Crafting,
+    Enchantment,
     Furnace,
-    Dispenser,
+    Grindstone,
     Hopper,
+    Lectern,
+    Loom,
+    Merchant,
+    ShulkerBox,
+    Smithing,
+    Smoker,
+    Stonecutter,
+}
+
+impl ContainerType {
+    /// The number of slots of the container, not including the player's inventory.
+    #[must_use]
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Generic9x1 => 9,
+            Self::Generic9x2 => 18,
+            Self::Generic9x3 | Self::ShulkerBox => 27,
+            Self::Generic9x4 => 36,
+            Self::Generic9x5 => 45,
+            Self::Generic9x6 => 54,
+            Self::Generic3x3 => 9,
+            Self::Beacon | Self::Lectern => 1,
+            Self::Enchantment | Self::Stonecutter => 2,
+            Self::Anvil
+            | Self::BlastFurnace
+            | Self::Cartography
+            | Self::Furnace
+            | Self::Grindstone
+            | Self::Merchant
+            | Self::Smoker => 3,
+            Self::Loom | Self::Smithing => 4,
+            Self::BrewingStand | Self::Hopper => 5,
+            Self::Crafting => 10,
+        }
+    }
+
+    #[must_use]
+    pub const fn window_type(self) -> WindowType {
+        match self {
+            Self::Generic9x1 => WindowType::Generic9x1,
+            Self::Generic9x2 => WindowType::Generic9x2,
+            Self::Generic9x3 => WindowType::Generic9x3,
+            Self::Generic9x4 => WindowType::Generic9x4,
+            Self::Generic9x5 => WindowType::Generic9x5,
+            Self::Generic9x6 => WindowType::Generic9x6,
+            Self::Generic3x3 => WindowType::Generic3x3,
+            Self::Anvil => WindowType::Anvil,
+            Self::Beacon => WindowType::Beacon,
+            Self::BlastFurnace => WindowType::BlastFurnace,
+            Self::BrewingStand => WindowType::BrewingStand,
+            Self::Cartography => WindowType::Cartography,
+            Self::Crafting => WindowType::Crafting,
+            Self::Enchantment => WindowType::Enchantment,
+            Self::Furnace => WindowType::Furnace,
+            Self::Grindstone => WindowType::Grindstone,
+            Self::Hopper => WindowType::Hopper,
+            Self::Lectern => WindowType::Lectern,
+            Self::Loom => WindowType::Loom,
+            Self::Merchant => WindowType::Merchant,
+            Self::ShulkerBox => WindowType::ShulkerBox,
+            Self::Smithing => WindowType::Smithing,
+            Self::Smoker => WindowType::Smoker,
+            Self::Stonecutter => WindowType::Stonecutter,
+        }
+    }
 }
 
+pub type ClickFn = Arc<dyn Fn(&mut GuiClick<'_>) + Send + Sync>;
+pub type CloseFn = Arc<dyn Fn(EntityView<'_>) + Send + Sync>;
+
 #[derive(Clone)]
 pub struct Gui {
-    items: HashMap<usize, GuiItem>,
-    size: usize,
+    items: Vec<Option<GuiItem>>,
     title: String,
-    window_id: u8,
     container_type: ContainerType,
+    on_close: Option<CloseFn>,
 }
 
 #[derive(Clone)]
 pub struct GuiItem {
     item: ItemStack,
-    on_click: fn(Entity, ClickMode),
+    on_click: Option<ClickFn>,
 }
 
-/// Thread-local non-zero id means that it will be very unlikely that one player will have two
-/// of the same IDs at the same time when opening GUIs in succession.
+/// A player's currently open [`Gui`].
 ///
-/// We are skipping 0 because it is reserved for the player's inventory.
-fn non_zero_window_id() -> u8 {
-    #[thread_local]
-    static ID: Cell<u8> = Cell::new(0);
-
-    ID.set(ID.get().wrapping_add(1));
-
-    if ID.get() == 0 {
-        ID.set(1);
-    }
+/// Removing this component closes the GUI on the server side. It is removed when the player
+/// closes the screen or disconnects, and replaced when another GUI is opened; the
+/// [`Gui::on_close`] callback runs in all three cases.
+#[derive(Component)]
+pub struct GuiSession {
+    window_id: u8,
+    state_id: i32,
+    gui: Gui,
+}
 
-    ID.get()
+/// The context of a click on a [`GuiItem`].
+pub struct GuiClick<'a> {
+    pub player: Entity,
+    pub slot: usize,
+    pub mode: ClickMode,
+    pub button: i8,
+    pub system: EntityView<'a>,
+    session: &'a mut GuiSession,
+    compose: &'a Compose,
+    stream: ConnectionId,
+    close: bool,
+    redraw: bool,
 }
 
 impl Gui {
     #[must_use]
-    pub fn new(size: usize, title: String, container_type: ContainerType) -> Self {
+    pub fn new(title: impl Into<String>, container_type: ContainerType) -> Self {
         Self {
-            window_id: non_zero_window_id(),
-            title,
-            size,
+            items: vec![None; container_type.size()],
+            title: title.into(),
             container_type,
-            items: HashMap::new(),
+            on_close: None,
         }
     }
 
     #[must_use]
-    pub const fn get_window_type(&self) -> WindowType {
-        match self.container_type {
-            ContainerType::Chest => WindowType::Generic9x3,
-            ContainerType::ShulkerBox => WindowType::ShulkerBox,
-            ContainerType::Furnace => WindowType::Furnace,
-            ContainerType::Dispenser => WindowType::Generic3x3,
-            ContainerType::Hopper => WindowType::Hopper,
-        }
+    pub const fn size(&self) -> usize {
+        self.container_type.size()
+    }
+
+    #[must_use]
+    pub const fn container_type(&self) -> ContainerType {
+        self.container_type
     }
 
     pub fn add_item(&mut self, slot: usize, item: GuiItem) -> Result<(), String> {
-        if slot >= self.size {
+        let size = self.size();
+        let Some(entry) = self.items.get_mut(slot) else {
             return Err(format!(
-                "Slot {} is out of bounds for GUI of size {}",
-                slot, self.size
+                "Slot {slot} is out of bounds for GUI of size {size}"
             ));
-        }
+        };
 
-        self.items.insert(slot, item);
+        *entry = Some(item);
 
         Ok(())
     }
 
-    pub fn draw<'a>(&'a self, system: EntityView<'_>, player: Entity) {
-        let container_items: Cow<'a, [ItemStack]> = (0..self.size)
-            .map(|slot| {
-                self.items
-                    .get(&slot)
-                    .map(|gui_item| gui_item.item.clone())
-                    .unwrap_or_default()
-            })
-            .collect();
+    /// Sets a callback which runs once the GUI is no longer open for a player, whether they
+    /// closed it, another GUI replaced it or they disconnected.
+    pub fn on_close(&mut self, on_close: impl Fn(EntityView<'_>) + Send + Sync + 'static) {
+        self.on_close = Some(Arc::new(on_close));
+    }
 
-        let binding = ItemStack::default();
-        let set_content_packet = InventoryS2c {
-            window_id: self.window_id,
-            state_id: VarInt(0),
-            slots: container_items,
-            carried_item: Cow::Borrowed(&binding),
-        };
+    fn stack(&self, slot: usize) -> ItemStack {
+        self.items
+            .get(slot)
+            .and_then(Option::as_ref)
+            .map(|gui_item| gui_item.item.clone())
+            .unwrap_or_default()
+    }
 
+    /// Opens the GUI for `player`, replacing any GUI they already have open.
+    pub fn open(self, system: EntityView<'_>, player: Entity) {
         let world = system.world();
+        let player = player.entity_view(world);
+
+        // the cursor of an open container has to be given back before its window is replaced
+        hyperion::simulation::container::close(player, system);
+
+        let mut session = GuiSession {
+            window_id: next_window_id(),
+            state_id: 0,
+            gui: self,
+        };
 
         world.get::<&Compose>(|compose| {
-            player.entity_view(world).get::<&ConnectionId>(|stream| {
-                compose
-                    .unicast(&set_content_packet, *stream, system)
-                    .unwrap();
+            player.get::<(&ConnectionId, &PlayerInventory)>(|(stream, inventory)| {
+                session.send_open(compose, *stream, system);
+                session.draw(inventory, compose, *stream, system);
             });
         });
+
+        // the GUI being replaced is closed by the client when it opens the new screen
+        player.try_get::<&GuiSession>(|replaced| {
+            if let Some(on_close) = &replaced.gui.on_close {
+                on_close(player);
+            }
+        });
+
+        player.set(session);
+    }
+}
+
+impl GuiItem {
+    pub fn new(
+        item: ItemStack,
+        on_click: impl Fn(&mut GuiClick<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            item,
+            on_click: Some(Arc::new(on_click)),
+        }
     }
 
-    pub fn open(&mut self, system: EntityView<'_>, player: Entity) {
-        let open_screen_packet = OpenScreenS2c {
+    /// An item which does nothing when clicked.
+    #[must_use]
+    pub const fn display(item: ItemStack) -> Self {
+        Self {
+            item,
+            on_click: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn item(&self) -> &ItemStack {
+        &self.item
+    }
+}
+
+impl GuiSession {
+    #[must_use]
+    pub const fn window_id(&self) -> u8 {
+        self.window_id
+    }
+
+    #[must_use]
+    pub const fn gui(&self) -> &Gui {
+        &self.gui
+    }
+
+    const fn next_state_id(&mut self) -> VarInt {
+        self.state_id = self.state_id.wrapping_add(1) & i32::MAX;
+        VarInt(self.state_id)
+    }
+
+    /// Sends the whole window, including the player's inventory, and clears the item on their
+    /// cursor.
+    pub fn draw(
+        &mut self,
+        inventory: &PlayerInventory,
+        compose: &Compose,
+        stream: ConnectionId,
+        system: EntityView<'_>,
+    ) {
+        // the main inventory and hotbar follow the slots of the container
+        const PLAYER_SLOTS: std::ops::Range<usize> = 9..45;
+
+        let player_slots = &inventory.slots()[PLAYER_SLOTS];
+
+        let slots: Vec<ItemStack> = (0..self.gui.size())
+            .map(|slot| self.gui.stack(slot))
+            .chain(player_slots.iter().cloned())
+            .collect();
+
+        let pkt = play::InventoryS2c {
+            window_id: self.window_id,
+            state_id: self.next_state_id(),
+            slots: Cow::Owned(slots),
+            carried_item: Cow::Borrowed(&ItemStack::EMPTY),
+        };
+
+        compose.unicast(&pkt, stream, system).unwrap();
+    }
+
+    /// Replaces the item in `slot` and sends only that slot to the player.
+    pub fn set_item(
+        &mut self,
+        slot: usize,
+        item: GuiItem,
+        compose: &Compose,
+        stream: ConnectionId,
+        system: EntityView<'_>,
+    ) -> Result<(), String> {
+        self.gui.add_item(slot, item)?;
+        self.send_slot(slot, compose, stream, system);
+        Ok(())
+    }
+
+    fn send_slot(
+        &mut self,
+        slot: usize,
+        compose: &Compose,
+        stream: ConnectionId,
+        system: EntityView<'_>,
+    ) {
+        let stack = self.gui.stack(slot);
+
+        let pkt = play::ScreenHandlerSlotUpdateS2c {
+            window_id: i8::try_from(self.window_id).unwrap(),
+            state_id: self.next_state_id(),
+            slot_idx: i16::try_from(slot).unwrap(),
+            slot_data: Cow::Owned(stack),
+        };
+
+        compose.unicast(&pkt, stream, system).unwrap();
+    }
+
+    fn send_open(&self, compose: &Compose, stream: ConnectionId, system: EntityView<'_>) {
+        let pkt = OpenScreenS2c {
             window_id: VarInt(i32::from(self.window_id)),
-            window_type: self.get_window_type(),
-            window_title: self.title.clone().into_cow_text(),
+            window_type: self.gui.container_type.window_type(),
+            window_title: self.gui.title.clone().into_cow_text(),
         };
 
-        let world = system.world();
+        compose.unicast(&pkt, stream, system).unwrap();
+    }
 
-        world.get::<&Compose>(|compose| {
-            player.entity_view(world).get::<&ConnectionId>(|stream| {
-                compose
-                    .unicast(&open_screen_packet, *stream, system)
-                    .unwrap();
+    /// Closes the screen on the client. The session must be removed separately.
+    fn send_close(&self, compose: &Compose, stream: ConnectionId, system: EntityView<'_>) {
+        let pkt = play::CloseScreenS2c {
+            window_id: self.window_id,
+        };
+
+        compose.unicast(&pkt, stream, system).unwrap();
+    }
+}
+
+impl GuiClick<'_> {
+    #[must_use]
+    pub const fn gui(&self) -> &Gui {
+        &self.session.gui
+    }
+
+    /// Replaces the item in `slot`, updating only that slot for the player.
+    pub fn set_item(&mut self, slot: usize, item: GuiItem) -> Result<(), String> {
+        self.session
+            .set_item(slot, item, self.compose, self.stream, self.system)
+    }
+
+    /// Closes the GUI once the click has been handled.
+    pub const fn close(&mut self) {
+        self.close = true;
+        self.redraw = false;
+    }
+
+    /// Shows `gui` in place of the current GUI once the click has been handled.
+    ///
+    /// The session stays open, so the `on_close` callback of the replaced GUI does not run. The
+    /// screen is only reopened if the title or container type changed. Use this rather than
+    /// [`Gui::open`] to navigate from a click.
+    pub fn set_gui(&mut self, gui: Gui) {
+        let reopen = gui.container_type != self.session.gui.container_type
+            || gui.title != self.session.gui.title;
+
+        self.session.gui = gui;
+
+        if reopen {
+            self.session.window_id = next_window_id();
+            self.session
+                .send_open(self.compose, self.stream, self.system);
+        }
+
+        self.close = false;
+        self.redraw = true;
+    }
+}
+
+/// Closes the GUI `player` has open, if any.
+pub fn close(player: EntityView<'_>, system: EntityView<'_>) {
+    let world = system.world();
+
+    let open = player
+        .try_get::<(&ConnectionId, &GuiSession)>(|(stream, session)| {
+            world.get::<&Compose>(|compose| {
+                session.send_close(compose, *stream, system);
             });
-        });
+        })
+        .is_some();
+
+    if open {
+        player.remove::<GuiSession>();
+    }
+}
+
+#[derive(Component)]
+pub struct GuiModule;
 
-        self.draw(system, player);
+impl Module for GuiModule {
+    fn module(world: &World) {
+        world.component::<GuiSession>();
+
+        observer!(world, flecs::OnRemove, &GuiSession).each_entity(|entity, session| {
+            if let Some(on_close) = &session.gui.on_close {
+                on_close(*entity);
+            }
+        });
 
-        world.get::<&mut GlobalEventHandlers>(|event_handlers| {
-            let window_id = self.window_id;
-            let items = self.items.clone();
-            let gui = self.clone();
-            event_handlers.click.register(move |query, event| {
+        world.get::<&mut GlobalEventHandlers>(|handlers| {
+            handlers.click.register(|query, event| {
+                let player = query.view;
                 let system = query.system;
-                let button = event.mode;
+                let compose = query.compose;
+                let stream = query.io_ref;
 
-                if event.window_id != window_id {
-                    return;
+                let inventory = &*query.inventory;
+
+                let closed = player.try_get::<&mut GuiSession>(|session| {
+                    if event.window_id != session.window_id {
+                        return false;
+                    }
+
+                    let slot = usize::from(event.slot_idx);
+
+                    let on_click = session
+                        .gui
+                        .items
+                        .get(slot)
+                        .and_then(Option::as_ref)
+                        .and_then(|item| item.on_click.clone());
+
+                    let (close, redraw) = {
+                        let mut click = GuiClick {
+                            player: player.id(),
+                            slot,
+                            mode: event.mode,
+                            button: event.button,
+                            system,
+                            session,
+                            compose,
+                            stream,
+                            close: false,
+                            redraw: false,
+                        };
+
+                        if let Some(on_click) = on_click {
+                            on_click(&mut click);
+                        }
+
+                        (click.close, click.redraw)
+                    };
+
+                    if close {
+                        session.send_close(compose, stream, system);
+                        return true;
+                    }
+
+                    // undo whatever the client predicted the click would do
+                    if !redraw
+                        && matches!(event.mode, ClickMode::Click)
+                        && slot < session.gui.size()
+                    {
+                        session.send_slot(slot, compose, stream, system);
+
+                        let clear_cursor = play::ScreenHandlerSlotUpdateS2c {
+                            window_id: -1,
+                            state_id: VarInt::default(),
+                            slot_idx: -1,
+                            slot_data: Cow::Borrowed(&ItemStack::EMPTY),
+                        };
+
+                        compose.unicast(&clear_cursor, stream, system).unwrap();
+                    } else {
+                        session.draw(inventory, compose, stream, system);
+                    }
+
+                    false
+                });
+
+                if closed == Some(true) {
+                    player.remove::<GuiSession>();
                 }
+            });
 
-                let slot = usize::from(event.slot_idx);
-                let Some(item) = items.get(&slot) else {
-                    return;
-                };
+            handlers.close_screen.register(|query, event| {
+                let player = query.view;
 
-                (item.on_click)(player, button);
-                gui.draw(query.system, player);
+                let closed = player
+                    .try_get::<&GuiSession>(|session| session.window_id == event.window_id)
+                    .unwrap_or(false);
 
-                let inventory = &*query.inventory;
-                let compose = query.compose;
-                let stream = query.io_ref;
+                if !closed {
+                    return;
+                }
 
-                // re-draw the inventory
-                let player_inv = inventory.slots();
+                player.remove::<GuiSession>();
 
-                let set_content_packet = InventoryS2c {
+                // items the client moved into or out of the GUI are not actually moved
+                let pkt = play::InventoryS2c {
                     window_id: 0,
                     state_id: VarInt(0),
-                    slots: Cow::Borrowed(player_inv),
+                    slots: Cow::Borrowed(query.inventory.slots()),
                     carried_item: Cow::Borrowed(&ItemStack::EMPTY),
                 };
 
-                compose
-                    .unicast(&set_content_packet, stream, system)
+                query
+                    .compose
+                    .unicast(&pkt, query.io_ref, query.system)
                     .unwrap();
             });
         });
     }
-
-    pub fn handle_close(&mut self, _player: Entity, _close_packet: CloseScreenS2c) {
-        todo!()
-    }
-}
-
-impl GuiItem {
-    pub fn new(item: ItemStack, on_click: fn(Entity, ClickMode)) -> Self {
-        Self { item, on_click }
-    }
 }
@@ -0,0 +1,419 @@
+//! Reusable menus built on [`Gui`].
+//!
+//! Every widget lays itself out for the slots of its [`ContainerType`], so any window size can
+//! be used.
+
+use std::sync::Arc;
+
+use flecs_ecs::core::{Entity, EntityView, World, WorldGet, WorldProvider};
+use hyperion::{ItemKind, simulation::IgnMap, valence_protocol::ItemStack};
+use hyperion_item::builder::ItemBuilder;
+
+use crate::{ClickFn, CloseFn, ContainerType, Gui, GuiClick, GuiItem};
+
+pub type SelectFn = Arc<dyn Fn(&mut GuiClick<'_>, Entity) + Send + Sync>;
+
+/// Whether the container is a chest-like grid with at least two rows of nine slots.
+const fn has_button_row(container_type: ContainerType) -> bool {
+    matches!(
+        container_type,
+        ContainerType::Generic9x2
+            | ContainerType::Generic9x3
+            | ContainerType::Generic9x4
+            | ContainerType::Generic9x5
+            | ContainerType::Generic9x6
+    )
+}
+
+fn filler() -> GuiItem {
+    GuiItem::display(
+        ItemBuilder::new(ItemKind::GrayStainedGlassPane)
+            .name(" ")
+            .build(),
+    )
+}
+
+/// Where the items and navigation buttons of a [`Paginated`] go.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct PageLayout {
+    per_page: usize,
+    previous: Option<usize>,
+    next: Option<usize>,
+    /// Slots between the buttons which are filled with glass panes.
+    filler: Option<(usize, usize)>,
+}
+
+impl PageLayout {
+    const fn new(container_type: ContainerType, len: usize) -> Self {
+        let size = container_type.size();
+
+        // containers this small have no room for buttons and only show the first page
+        if len <= size || size < 3 {
+            return Self {
+                per_page: size,
+                previous: None,
+                next: None,
+                filler: None,
+            };
+        }
+
+        if has_button_row(container_type) {
+            let row = size - 9;
+            return Self {
+                per_page: row,
+                previous: Some(row),
+                next: Some(size - 1),
+                filler: Some((row + 1, size - 1)),
+            };
+        }
+
+        Self {
+            per_page: size - 2,
+            previous: Some(size - 2),
+            next: Some(size - 1),
+            filler: None,
+        }
+    }
+
+    fn page_count(self, len: usize) -> usize {
+        if self.next.is_none() {
+            return 1;
+        }
+
+        len.div_ceil(self.per_page)
+    }
+}
+
+/// A list of items spread over as many pages as needed, with previous and next buttons.
+#[derive(Clone)]
+pub struct Paginated {
+    title: String,
+    container_type: ContainerType,
+    items: Vec<GuiItem>,
+    on_close: Option<CloseFn>,
+}
+
+impl Paginated {
+    #[must_use]
+    pub fn new(title: impl Into<String>, container_type: ContainerType) -> Self {
+        Self {
+            title: title.into(),
+            container_type,
+            items: Vec::new(),
+            on_close: None,
+        }
+    }
+
+    pub fn add_item(&mut self, item: GuiItem) {
+        self.items.push(item);
+    }
+
+    /// See [`Gui::on_close`]. Turning the page does not close the menu.
+    pub fn on_close(&mut self, on_close: impl Fn(EntityView<'_>) + Send + Sync + 'static) {
+        self.on_close = Some(Arc::new(on_close));
+    }
+
+    fn layout(&self) -> PageLayout {
+        PageLayout::new(self.container_type, self.items.len())
+    }
+
+    #[must_use]
+    pub fn page_count(&self) -> usize {
+        self.layout().page_count(self.items.len())
+    }
+
+    /// Builds the [`Gui`] showing `page`, counting from zero.
+    fn page(this: &Arc<Self>, page: usize) -> Gui {
+        let layout = this.layout();
+        let page_count = layout.page_count(this.items.len());
+
+        let mut gui = Gui::new(this.title.clone(), this.container_type);
+        gui.on_close = this.on_close.clone();
+
+        let start = page * layout.per_page;
+        let items = this.items.iter().skip(start).take(layout.per_page);
+
+        for (slot, item) in items.enumerate() {
+            gui.add_item(slot, item.clone()).unwrap();
+        }
+
+        if let Some((start, end)) = layout.filler {
+            for slot in start..end {
+                gui.add_item(slot, filler()).unwrap();
+            }
+        }
+
+        if let Some(slot) = layout.previous
+            && page > 0
+        {
+            let paginated = this.clone();
+            let button = ItemBuilder::new(ItemKind::Arrow)
+                .name(format!("Previous page ({page}/{page_count})"))
+                .build();
+
+            let button = GuiItem::new(button, move |click| {
+                click.set_gui(Self::page(&paginated, page - 1));
+            });
+
+            gui.add_item(slot, button).unwrap();
+        }
+
+        if let Some(slot) = layout.next
+            && page + 1 < page_count
+        {
+            let paginated = this.clone();
+            let button = ItemBuilder::new(ItemKind::Arrow)
+                .name(format!("Next page ({}/{page_count})", page + 2))
+                .build();
+
+            let button = GuiItem::new(button, move |click| {
+                click.set_gui(Self::page(&paginated, page + 1));
+            });
+
+            gui.add_item(slot, button).unwrap();
+        }
+
+        gui
+    }
+
+    /// Builds the [`Gui`] for the first page.
+    #[must_use]
+    pub fn build(self) -> Gui {
+        Self::page(&Arc::new(self), 0)
+    }
+
+    /// Opens the first page for `player`. See [`Gui::open`].
+    pub fn open(self, system: EntityView<'_>, player: Entity) {
+        self.build().open(system, player);
+    }
+}
+
+/// A dialog asking the player to confirm or cancel an action.
+///
+/// Both buttons close the dialog before running their callback, which may call
+/// [`GuiClick::set_gui`] to show another menu instead.
+#[must_use]
+pub struct Confirmation {
+    title: String,
+    container_type: ContainerType,
+    prompt: Option<ItemStack>,
+    on_confirm: ClickFn,
+    on_cancel: Option<ClickFn>,
+}
+
+impl Confirmation {
+    pub fn new(
+        title: impl Into<String>,
+        on_confirm: impl Fn(&mut GuiClick<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            container_type: ContainerType::Generic9x3,
+            prompt: None,
+            on_confirm: Arc::new(on_confirm),
+            on_cancel: None,
+        }
+    }
+
+    /// Shows `prompt` between the buttons, e.g. the item being bought.
+    pub fn prompt(mut self, prompt: ItemStack) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+
+    pub fn on_cancel(
+        mut self,
+        on_cancel: impl Fn(&mut GuiClick<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_cancel = Some(Arc::new(on_cancel));
+        self
+    }
+
+    /// Defaults to a single chest.
+    pub const fn container_type(mut self, container_type: ContainerType) -> Self {
+        self.container_type = container_type;
+        self
+    }
+
+    /// The slots of the confirm button, the prompt and the cancel button.
+    const fn layout(container_type: ContainerType) -> (usize, Option<usize>, Option<usize>) {
+        let size = container_type.size();
+
+        if has_button_row(container_type) {
+            let middle = size / 9 / 2 * 9;
+            return (middle + 2, Some(middle + 4), Some(middle + 6));
+        }
+
+        match size {
+            1 => (0, None, None),
+            2 => (0, None, Some(1)),
+            _ => (0, Some(size / 2), Some(size - 1)),
+        }
+    }
+
+    #[must_use]
+    pub fn build(self) -> Gui {
+        let (confirm_slot, prompt_slot, cancel_slot) = Self::layout(self.container_type);
+
+        let mut gui = Gui::new(self.title, self.container_type);
+
+        let on_confirm = self.on_confirm;
+        let confirm = ItemBuilder::new(ItemKind::LimeStainedGlassPane)
+            .name("§aConfirm")
+            .build();
+        let confirm = GuiItem::new(confirm, move |click| {
+            click.close();
+            on_confirm(click);
+        });
+        gui.add_item(confirm_slot, confirm).unwrap();
+
+        if let Some(slot) = prompt_slot
+            && let Some(prompt) = self.prompt
+        {
+            gui.add_item(slot, GuiItem::display(prompt)).unwrap();
+        }
+
+        if let Some(slot) = cancel_slot {
+            let on_cancel = self.on_cancel;
+            let cancel = ItemBuilder::new(ItemKind::RedStainedGlassPane)
+                .name("§cCancel")
+                .build();
+            let cancel = GuiItem::new(cancel, move |click| {
+                click.close();
+                if let Some(on_cancel) = &on_cancel {
+                    on_cancel(click);
+                }
+            });
+            gui.add_item(slot, cancel).unwrap();
+        }
+
+        gui
+    }
+
+    /// See [`Gui::open`].
+    pub fn open(self, system: EntityView<'_>, player: Entity) {
+        self.build().open(system, player);
+    }
+}
+
+/// A [`Paginated`] list of the online players, shown as their heads.
+#[must_use]
+pub struct PlayerSelector {
+    title: String,
+    container_type: ContainerType,
+    exclude: Option<Entity>,
+    on_select: SelectFn,
+}
+
+impl PlayerSelector {
+    /// `on_select` is called with the selected player. It is not called if they have left since
+    /// the menu was opened.
+    pub fn new(
+        title: impl Into<String>,
+        on_select: impl Fn(&mut GuiClick<'_>, Entity) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            container_type: ContainerType::Generic9x6,
+            exclude: None,
+            on_select: Arc::new(on_select),
+        }
+    }
+
+    /// Defaults to a double chest.
+    pub const fn container_type(mut self, container_type: ContainerType) -> Self {
+        self.container_type = container_type;
+        self
+    }
+
+    /// Leaves `player` out of the list, usually the player the menu is for.
+    pub const fn exclude(mut self, player: Entity) -> Self {
+        self.exclude = Some(player);
+        self
+    }
+
+    /// Builds the menu from the players who are online now, sorted by name.
+    #[must_use]
+    pub fn build(self, world: &World) -> Paginated {
+        let mut players: Vec<(String, Entity)> = Vec::new();
+
+        world.get::<&IgnMap>(|ign_map| {
+            for (name, &entity) in ign_map.iter() {
+                if Some(entity) == self.exclude || !world.is_alive(entity) {
+                    continue;
+                }
+                players.push((name.to_string(), entity));
+            }
+        });
+
+        players.sort_unstable();
+
+        let mut menu = Paginated::new(self.title, self.container_type);
+
+        for (name, entity) in players {
+            let head = ItemBuilder::new(ItemKind::PlayerHead).name(name).build();
+
+            let on_select = self.on_select.clone();
+            menu.add_item(GuiItem::new(head, move |click| {
+                if click.system.world().is_alive(entity) {
+                    on_select(click, entity);
+                }
+            }));
+        }
+
+        menu
+    }
+
+    /// See [`Gui::open`].
+    pub fn open(self, system: EntityView<'_>, player: Entity) {
+        let menu = self.build(&system.world());
+        menu.open(system, player);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_layout() {
+        let chest = PageLayout::new(ContainerType::Generic9x3, 100);
+        assert_eq!(chest.per_page, 18);
+        assert_eq!(chest.previous, Some(18));
+        assert_eq!(chest.next, Some(26));
+        assert_eq!(chest.page_count(100), 6);
+
+        let hopper = PageLayout::new(ContainerType::Hopper, 7);
+        assert_eq!(hopper.per_page, 3);
+        assert_eq!((hopper.previous, hopper.next), (Some(3), Some(4)));
+        assert_eq!(hopper.page_count(7), 3);
+
+        let fits = PageLayout::new(ContainerType::Generic9x3, 27);
+        assert_eq!(fits.per_page, 27);
+        assert_eq!(fits.page_count(27), 1);
+
+        let tiny = PageLayout::new(ContainerType::Beacon, 5);
+        assert_eq!(tiny.next, None);
+        assert_eq!(tiny.page_count(5), 1);
+    }
+
+    #[test]
+    fn test_confirmation_layout() {
+        assert_eq!(
+            Confirmation::layout(ContainerType::Generic9x3),
+            (11, Some(13), Some(15))
+        );
+        assert_eq!(
+            Confirmation::layout(ContainerType::Generic9x1),
+            (0, Some(4), Some(8))
+        );
+        assert_eq!(
+            Confirmation::layout(ContainerType::Hopper),
+            (0, Some(2), Some(4))
+        );
+        assert_eq!(
+            Confirmation::layout(ContainerType::Stonecutter),
+            (0, None, Some(1))
+        );
+    }
+}
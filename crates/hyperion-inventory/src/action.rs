@@ -42,8 +42,14 @@ pub enum InventoryAction {
     },
 
     /// 'Q' key
-    Drop,
-    CtrlDrop,
+    Drop {
+        slot: u16,
+    },
+    CtrlDrop {
+        slot: u16,
+    },
+    /// 'Q' key with no slot under the mouse, which does nothing
+    DropOutside,
 
     DragStart {
         button: FullMouseButton,
@@ -147,10 +153,11 @@ impl InventoryAndCursor {
             InventoryAction::MiddleClick { .. } => {
                 unimplemented!("Middle click");
             }
-            InventoryAction::Drop | InventoryAction::CtrlDrop => {
+            InventoryAction::Drop { .. } | InventoryAction::CtrlDrop { .. } => {
                 // Implement drop logic here
                 self.drop_cursor(Amount::All);
             }
+            InventoryAction::DropOutside => {}
             InventoryAction::DragStart { .. } => {
                 unimplemented!("Drag start");
             }
@@ -0,0 +1,1638 @@
+//! Server-side slots for windows other than the player's own inventory.
+//!
+//! A [`Container`] holds the slots of a chest, furnace or any other window and may be viewed by
+//! several players at once. Each viewer has an [`OpenContainer`] which tracks their window ID, the
+//! item on their cursor and what their client was last sent.
+//!
+//! Clicks are parsed with [`parser::create_inventory_action`] and applied to the server's slots.
+//! The slot changes the client predicted are only compared against the result to decide whether
+//! the client has to be resynced, so a desynced client can never create items.
+//!
+//! A container may also be a crafting grid, such as [`Container::crafting_table`]. Its first slot
+//! then holds the result, which the server computes from the grid and which can only be taken.
+//! The slots of a [`Container::furnace`] only accept fuel and the items cooked in them; see
+//! [`crate::furnace`].
+
+use std::{borrow::Cow, cell::Cell, cmp::min};
+
+use flecs_ecs::{core::Entity, macros::Component};
+use hyperion_crafting::{CraftingRegistry, FurnaceKind};
+use snafu::{ResultExt, Snafu};
+use valence_protocol::{
+    ItemKind, ItemStack, VarInt,
+    packets::play::{self, click_slot_c2s::SlotChange},
+};
+
+use crate::{
+    AddItemResult, InventoryAccessError, OFFHAND_SLOT, PlayerInventory,
+    action::{FullMouseButton, InventoryAction, MouseButton},
+    furnace::{self, FUEL_SLOT, INPUT_SLOT, OUTPUT_SLOT},
+    parser,
+};
+
+/// The player's main inventory and hotbar, which follow the container slots in every window.
+const PLAYER_SLOTS: std::ops::Range<u16> = 9..45;
+
+/// The slot of a crafting grid's result, which is followed by the grid row by row.
+const RESULT_SLOT: u16 = 0;
+
+/// Crafting with a full stack of ingredients yields at most this many crafts at once.
+const MAX_CRAFTS: usize = 64;
+
+/// Window IDs cycle through `1..=100` like vanilla.
+///
+/// A thread-local counter means that it will be very unlikely that one player will have two of
+/// the same IDs at the same time when opening windows in succession. We are skipping 0 because it
+/// is reserved for the player's inventory.
+#[must_use]
+pub fn next_window_id() -> u8 {
+    #[thread_local]
+    static ID: Cell<u8> = Cell::new(0);
+
+    ID.set(ID.get() % 100 + 1);
+
+    ID.get()
+}
+
+#[derive(Debug, Snafu)]
+pub enum ClickError {
+    #[snafu(display("click is for window {actual} but window {expected} is open"))]
+    WrongWindow { expected: u8, actual: u8 },
+
+    #[snafu(display("invalid button: {button}"))]
+    NegativeButton { button: i8 },
+
+    #[snafu(display("invalid click: {source}"))]
+    InvalidAction { source: parser::Error },
+
+    #[snafu(display("slot {slot} is outside of a window with {len} slots"))]
+    SlotOutOfRange { slot: u16, len: usize },
+}
+
+/// The slots of a window which is not the player's inventory, such as a chest.
+///
+/// Usually a component of its own entity so that every viewer's [`OpenContainer`] refers to the
+/// same slots.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Container {
+    slots: Box<[ItemStack]>,
+    layout: Layout,
+}
+
+/// What the slots of a [`Container`] are used for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Layout {
+    /// Slots which hold any item, like those of a chest.
+    Storage,
+    /// A crafting result followed by a grid with rows of `width` slots.
+    CraftingGrid { width: usize },
+    /// The input, fuel and output of a furnace.
+    Furnace(FurnaceKind),
+}
+
+impl Container {
+    #[must_use]
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: vec![ItemStack::EMPTY; size].into_boxed_slice(),
+            layout: Layout::Storage,
+        }
+    }
+
+    /// The result slot and 3x3 grid of a crafting table.
+    #[must_use]
+    pub fn crafting_table() -> Self {
+        Self {
+            layout: Layout::CraftingGrid { width: 3 },
+            ..Self::new(10)
+        }
+    }
+
+    /// The input, fuel and output slots of a furnace, blast furnace or smoker. The items are
+    /// cooked by a [`Furnace`](crate::furnace::Furnace).
+    #[must_use]
+    pub fn furnace(kind: FurnaceKind) -> Self {
+        Self {
+            layout: Layout::Furnace(kind),
+            ..Self::new(3)
+        }
+    }
+
+    #[must_use]
+    pub const fn is_crafting_grid(&self) -> bool {
+        matches!(self.layout, Layout::CraftingGrid { .. })
+    }
+
+    /// The kind of furnace, if the container is one.
+    #[must_use]
+    pub const fn furnace_kind(&self) -> Option<FurnaceKind> {
+        match self.layout {
+            Layout::Furnace(kind) => Some(kind),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.slots.len()
+    }
+
+    #[must_use]
+    pub fn slots(&self) -> &[ItemStack] {
+        &self.slots
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = (u16, &ItemStack)> + '_ {
+        self.slots.iter().enumerate().filter_map(|(idx, item)| {
+            if item.is_empty() {
+                None
+            } else {
+                Some((u16::try_from(idx).unwrap(), item))
+            }
+        })
+    }
+
+    pub fn get(&self, index: u16) -> Result<&ItemStack, InventoryAccessError> {
+        self.slots
+            .get(usize::from(index))
+            .ok_or(InventoryAccessError::InvalidSlot { index })
+    }
+
+    pub fn get_mut(&mut self, index: u16) -> Result<&mut ItemStack, InventoryAccessError> {
+        self.slots
+            .get_mut(usize::from(index))
+            .ok_or(InventoryAccessError::InvalidSlot { index })
+    }
+
+    pub fn set(&mut self, index: u16, stack: ItemStack) -> Result<(), InventoryAccessError> {
+        *self.get_mut(index)? = stack;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.fill(ItemStack::EMPTY);
+    }
+
+    /// Takes every item out of the container. The result of a crafting grid is only a preview,
+    /// so it is cleared instead.
+    pub fn drain(&mut self) -> Vec<ItemStack> {
+        let start = usize::from(self.is_crafting_grid());
+
+        self.slots[..start].fill(ItemStack::EMPTY);
+
+        self.slots[start..]
+            .iter_mut()
+            .map(|slot| std::mem::replace(slot, ItemStack::EMPTY))
+            .filter(|stack| !stack.is_empty())
+            .collect()
+    }
+
+    /// Recomputes the result of a crafting grid from the items in it. This is done after every
+    /// click, but has to be called when the grid is changed in another way.
+    pub fn update_crafting_result(&mut self, registry: &CraftingRegistry) {
+        let Layout::CraftingGrid { width } = self.layout else {
+            return;
+        };
+
+        let grid: Vec<_> = self.slots[1..]
+            .iter()
+            .map(|stack| {
+                if stack.is_empty() {
+                    ItemKind::Air
+                } else {
+                    stack.item
+                }
+            })
+            .collect();
+
+        self.slots[0] = registry
+            .get_result(&grid, width)
+            .cloned()
+            .unwrap_or(ItemStack::EMPTY);
+    }
+
+    /// Takes the result of a crafting grid and uses up one of each ingredient.
+    fn craft(&mut self, registry: &CraftingRegistry) -> ItemStack {
+        let result = std::mem::replace(&mut self.slots[0], ItemStack::EMPTY);
+
+        if result.is_empty() {
+            return result;
+        }
+
+        for ingredient in &mut self.slots[1..] {
+            if !ingredient.is_empty() {
+                ingredient.count -= 1;
+                normalize(ingredient);
+            }
+        }
+
+        self.update_crafting_result(registry);
+
+        result
+    }
+
+    /// Adds `item` to the first slots it fits in, stacking it onto existing items first.
+    pub fn try_add_item(&mut self, mut item: ItemStack) -> AddItemResult {
+        move_into(&mut item, &mut self.slots);
+
+        AddItemResult {
+            remaining: (!item.is_empty()).then_some(item),
+        }
+    }
+}
+
+/// The result of a click which was applied to the server's slots.
+#[derive(Debug, Default)]
+#[must_use]
+pub struct ClickResult {
+    /// The client's view of the window differs from the server's, so
+    /// [`OpenContainer::contents`] must be sent to it.
+    pub resync: bool,
+
+    /// Items thrown out of the window. They are no longer in any slot, so the caller must spawn
+    /// or return them.
+    pub dropped: Vec<ItemStack>,
+}
+
+/// A drag which has been started but not ended yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Drag {
+    button: FullMouseButton,
+    slots: Vec<u16>,
+}
+
+/// A player's view of a [`Container`].
+///
+/// The window has the slots of the container followed by the player's main inventory and hotbar.
+#[derive(Component, Debug)]
+pub struct OpenContainer {
+    window_id: u8,
+    container: Entity,
+    state_id: i32,
+    cursor: ItemStack,
+    drag: Option<Drag>,
+    /// The container slots as the client last saw them.
+    remote: Vec<ItemStack>,
+    /// The window properties as the client last saw them.
+    remote_properties: Vec<i16>,
+}
+
+/// The slots of a window, which are split between the container and the player's inventory.
+struct Window<'a> {
+    container: &'a mut Container,
+    inventory: &'a mut PlayerInventory,
+    registry: &'a CraftingRegistry,
+}
+
+impl Window<'_> {
+    fn is_result(&self, slot: u16) -> bool {
+        self.container.is_crafting_grid() && slot == RESULT_SLOT
+    }
+
+    /// Whether `slot` is the output of a furnace, which items can be taken out of but not put
+    /// into.
+    fn is_output(&self, slot: u16) -> bool {
+        self.container.furnace_kind().is_some() && slot == OUTPUT_SLOT
+    }
+
+    /// Whether `stack` may be put into `slot`.
+    fn accepts(&self, slot: u16, stack: &ItemStack) -> bool {
+        if slot >= self.container_size() {
+            return true;
+        }
+
+        match self.container.layout {
+            Layout::Storage => true,
+            Layout::CraftingGrid { .. } => slot != RESULT_SLOT,
+            Layout::Furnace(_) => match slot {
+                FUEL_SLOT => furnace::is_fuel_slot_item(stack.item),
+                OUTPUT_SLOT => false,
+                _ => true,
+            },
+        }
+    }
+
+    /// The container slots a stack shift-clicked in the player's inventory is moved to.
+    fn shift_targets(&self, stack: &ItemStack) -> Vec<u16> {
+        match self.container.layout {
+            Layout::Storage => (0..self.container_size()).collect(),
+            Layout::CraftingGrid { .. } => (RESULT_SLOT + 1..self.container_size()).collect(),
+            Layout::Furnace(kind) => {
+                if self.registry.get_smelting(kind, stack.item).is_some() {
+                    vec![INPUT_SLOT]
+                } else if furnace::fuel_value(stack.item).is_some() {
+                    vec![FUEL_SLOT]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    fn craft(&mut self) -> ItemStack {
+        self.container.craft(self.registry)
+    }
+
+    fn len(&self) -> usize {
+        self.container.size() + PLAYER_SLOTS.len()
+    }
+
+    fn container_size(&self) -> u16 {
+        u16::try_from(self.container.size()).unwrap()
+    }
+
+    /// The window slot showing `index` of the player's inventory.
+    fn player_slot(&self, index: u16) -> u16 {
+        self.container_size() + index - PLAYER_SLOTS.start
+    }
+
+    fn check(&self, slot: u16) -> Result<(), ClickError> {
+        let len = self.len();
+
+        if usize::from(slot) >= len {
+            return SlotOutOfRangeSnafu { slot, len }.fail();
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, slot: u16) -> &ItemStack {
+        let size = self.container_size();
+
+        if slot < size {
+            return self.container.get(slot).unwrap();
+        }
+
+        self.inventory
+            .get(slot - size + PLAYER_SLOTS.start)
+            .unwrap()
+    }
+
+    fn get_mut(&mut self, slot: u16) -> &mut ItemStack {
+        let size = self.container_size();
+
+        if slot < size {
+            return self.container.get_mut(slot).unwrap();
+        }
+
+        self.inventory
+            .get_mut(slot - size + PLAYER_SLOTS.start)
+            .unwrap()
+    }
+
+    fn take(&mut self, slot: u16) -> ItemStack {
+        std::mem::replace(self.get_mut(slot), ItemStack::EMPTY)
+    }
+
+    fn swap(&mut self, a: u16, b: u16) {
+        let stack_a = self.take(a);
+        let stack_b = std::mem::replace(self.get_mut(b), stack_a);
+        *self.get_mut(a) = stack_b;
+    }
+
+    fn snapshot(&self) -> Vec<ItemStack> {
+        let player_slots =
+            &self.inventory.slots()[usize::from(PLAYER_SLOTS.start)..usize::from(PLAYER_SLOTS.end)];
+
+        self.container
+            .slots()
+            .iter()
+            .chain(player_slots)
+            .cloned()
+            .collect()
+    }
+}
+
+fn stackable(a: &ItemStack, b: &ItemStack) -> bool {
+    a.item == b.item && a.nbt == b.nbt
+}
+
+/// Empty stacks may have any count or NBT, so they are compared separately.
+fn same(a: &ItemStack, b: &ItemStack) -> bool {
+    (a.is_empty() && b.is_empty()) || a == b
+}
+
+fn normalize(stack: &mut ItemStack) {
+    if stack.is_empty() {
+        *stack = ItemStack::EMPTY;
+    }
+}
+
+/// Moves as much of `from` as possible into `to`, but at most `max` items.
+fn merge(from: &mut ItemStack, to: &mut ItemStack, max: i8) {
+    if to.is_empty() {
+        let count = min(from.count, from.item.max_stack()).min(max);
+        *to = from.clone().with_count(count);
+        from.count -= count;
+    } else if stackable(from, to) {
+        let count = min(from.count, to.item.max_stack() - to.count).min(max);
+        if count <= 0 {
+            return;
+        }
+        to.count += count;
+        from.count -= count;
+    } else {
+        return;
+    }
+
+    normalize(from);
+}
+
+/// Moves `item` into `slots`, topping up matching stacks before filling empty slots.
+fn move_into(item: &mut ItemStack, slots: &mut [ItemStack]) {
+    for empty in [false, true] {
+        for slot in slots.iter_mut() {
+            if item.is_empty() {
+                return;
+            }
+            if slot.is_empty() == empty {
+                merge(item, slot, i8::MAX);
+            }
+        }
+    }
+}
+
+/// Moves `stack` into the window slots `targets`, topping up matching stacks before filling empty
+/// slots. Returns whether anything was moved.
+fn move_to(window: &mut Window<'_>, stack: &mut ItemStack, targets: &[u16]) -> bool {
+    let count = stack.count;
+
+    for empty in [false, true] {
+        for &target in targets {
+            if stack.is_empty() {
+                return true;
+            }
+
+            let target = window.get_mut(target);
+            if target.is_empty() == empty {
+                merge(stack, target, i8::MAX);
+            }
+        }
+    }
+
+    stack.count != count
+}
+
+/// How many items of `stack` fit in the window slots `targets`.
+fn space_for(window: &Window<'_>, stack: &ItemStack, targets: &[u16]) -> i8 {
+    let max = stack.item.max_stack();
+
+    let space: i32 = targets
+        .iter()
+        .map(|&target| window.get(target))
+        .map(|target| {
+            if target.is_empty() {
+                i32::from(max)
+            } else if stackable(target, stack) {
+                i32::from(max - target.count)
+            } else {
+                0
+            }
+        })
+        .sum();
+
+    i8::try_from(space).unwrap_or(i8::MAX)
+}
+
+fn take_count(stack: &mut ItemStack, count: i8) -> ItemStack {
+    let count = min(count, stack.count);
+    let taken = stack.clone().with_count(count);
+    stack.count -= count;
+    normalize(stack);
+    taken
+}
+
+impl OpenContainer {
+    #[must_use]
+    pub const fn new(window_id: u8, container: Entity) -> Self {
+        Self {
+            window_id,
+            container,
+            state_id: 0,
+            cursor: ItemStack::EMPTY,
+            drag: None,
+            remote: Vec::new(),
+            remote_properties: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn window_id(&self) -> u8 {
+        self.window_id
+    }
+
+    /// The entity with the [`Container`] this window shows.
+    #[must_use]
+    pub const fn container(&self) -> Entity {
+        self.container
+    }
+
+    #[must_use]
+    pub const fn state_id(&self) -> i32 {
+        self.state_id
+    }
+
+    #[must_use]
+    pub const fn cursor(&self) -> &ItemStack {
+        &self.cursor
+    }
+
+    /// Removes the item on the cursor, which must be given back to the player when the window is
+    /// closed.
+    pub fn take_cursor(&mut self) -> ItemStack {
+        std::mem::replace(&mut self.cursor, ItemStack::EMPTY)
+    }
+
+    const fn next_state_id(&mut self) -> VarInt {
+        // the client only keeps 15 bits of the state ID
+        self.state_id = (self.state_id + 1) & 0x7FFF;
+        VarInt(self.state_id)
+    }
+
+    /// The whole window and the cursor, which brings the client back in sync with the server.
+    pub fn contents(
+        &mut self,
+        container: &Container,
+        inventory: &PlayerInventory,
+    ) -> play::InventoryS2c<'_> {
+        let player_slots =
+            &inventory.slots()[usize::from(PLAYER_SLOTS.start)..usize::from(PLAYER_SLOTS.end)];
+
+        let slots: Vec<ItemStack> = container
+            .slots()
+            .iter()
+            .chain(player_slots)
+            .cloned()
+            .collect();
+
+        self.remote = container.slots().to_vec();
+
+        play::InventoryS2c {
+            window_id: self.window_id,
+            state_id: self.next_state_id(),
+            slots: Cow::Owned(slots),
+            carried_item: Cow::Borrowed(&self.cursor),
+        }
+    }
+
+    /// Updates for the container slots which changed since the client last saw them, e.g. because
+    /// another player clicked them.
+    ///
+    /// Changes to the player's own slots are sent with the player's inventory.
+    pub fn changes(
+        &mut self,
+        container: &Container,
+    ) -> Vec<play::ScreenHandlerSlotUpdateS2c<'static>> {
+        if self.remote.len() != container.size() {
+            self.remote = vec![ItemStack::EMPTY; container.size()];
+        }
+
+        let changed: Vec<usize> = (0..container.size())
+            .filter(|&slot| !same(&self.remote[slot], &container.slots()[slot]))
+            .collect();
+
+        if changed.is_empty() {
+            return Vec::new();
+        }
+
+        let window_id = i8::try_from(self.window_id).unwrap();
+        let state_id = self.next_state_id();
+
+        changed
+            .into_iter()
+            .map(|slot| {
+                let stack = container.slots()[slot].clone();
+                self.remote[slot] = stack.clone();
+
+                play::ScreenHandlerSlotUpdateS2c {
+                    window_id,
+                    state_id,
+                    slot_idx: i16::try_from(slot).unwrap(),
+                    slot_data: Cow::Owned(stack),
+                }
+            })
+            .collect()
+    }
+
+    /// Updates for the window properties which changed since the client last saw them, such as
+    /// the progress arrow of a furnace.
+    pub fn property_changes(
+        &mut self,
+        properties: &[i16],
+    ) -> Vec<play::ScreenHandlerPropertyUpdateS2c> {
+        if self.remote_properties.len() != properties.len() {
+            // the client starts without any property values, so every one is sent
+            self.remote_properties = vec![-1; properties.len()];
+        }
+
+        let window_id = self.window_id;
+
+        self.remote_properties
+            .iter_mut()
+            .zip(properties)
+            .enumerate()
+            .filter(|(_, (remote, value))| *remote != *value)
+            .map(|(property, (remote, value))| {
+                *remote = *value;
+
+                play::ScreenHandlerPropertyUpdateS2c {
+                    window_id,
+                    property: i16::try_from(property).unwrap(),
+                    value: *value,
+                }
+            })
+            .collect()
+    }
+
+    /// Applies a click on this window to `container` and `inventory`. Crafting grids use
+    /// `registry` to compute their result.
+    ///
+    /// Nothing is changed if the click is invalid. The client should then be resynced with
+    /// [`Self::contents`].
+    pub fn click(
+        &mut self,
+        container: &mut Container,
+        inventory: &mut PlayerInventory,
+        registry: &CraftingRegistry,
+        pkt: &play::ClickSlotC2s<'_>,
+    ) -> Result<ClickResult, ClickError> {
+        if pkt.window_id != self.window_id {
+            return WrongWindowSnafu {
+                expected: self.window_id,
+                actual: pkt.window_id,
+            }
+            .fail();
+        }
+
+        let button = u8::try_from(pkt.button)
+            .map_err(|_| ClickError::NegativeButton { button: pkt.button })?;
+
+        let action = parser::create_inventory_action(pkt.mode as u8, button, pkt.slot_idx)
+            .context(InvalidActionSnafu)?;
+
+        let mut window = Window {
+            container,
+            inventory,
+            registry,
+        };
+
+        let before = window.snapshot();
+
+        let dropped = self.apply(&mut window, action)?;
+
+        window.container.update_crafting_result(registry);
+
+        let after = window.snapshot();
+
+        // the client does not compute crafting results, it waits for the server to send them
+        let result_slot = window
+            .container
+            .is_crafting_grid()
+            .then_some(usize::from(RESULT_SLOT));
+
+        let predicted = self.matches_prediction(
+            &before,
+            &after,
+            result_slot,
+            &pkt.slot_changes,
+            &pkt.carried_item,
+        );
+        let resync = pkt.state_id.0 != self.state_id || !predicted;
+
+        if !resync {
+            // the client already shows the result of its click
+            let remote_result = self.remote.first().cloned();
+            self.remote = window.container.slots().to_vec();
+
+            if let Some(slot) = result_slot {
+                self.remote[slot] = pkt
+                    .slot_changes
+                    .iter()
+                    .find(|change| usize::try_from(change.idx) == Ok(slot))
+                    .map(|change| change.stack.clone())
+                    .or(remote_result)
+                    .unwrap_or(ItemStack::EMPTY);
+            }
+        }
+
+        Ok(ClickResult { resync, dropped })
+    }
+
+    /// Whether the client predicted exactly the changes the server made, apart from `ignored`.
+    fn matches_prediction(
+        &self,
+        before: &[ItemStack],
+        after: &[ItemStack],
+        ignored: Option<usize>,
+        slot_changes: &[SlotChange],
+        carried_item: &ItemStack,
+    ) -> bool {
+        if !same(&self.cursor, carried_item) {
+            return false;
+        }
+
+        for change in slot_changes {
+            let Ok(slot) = usize::try_from(change.idx) else {
+                return false;
+            };
+            if Some(slot) == ignored {
+                continue;
+            }
+            let Some(stack) = after.get(slot) else {
+                return false;
+            };
+            if !same(stack, &change.stack) {
+                return false;
+            }
+        }
+
+        before
+            .iter()
+            .zip(after)
+            .enumerate()
+            .filter(|(slot, (before, after))| Some(*slot) != ignored && !same(before, after))
+            .all(|(slot, _)| {
+                slot_changes
+                    .iter()
+                    .any(|change| usize::try_from(change.idx) == Ok(slot))
+            })
+    }
+
+    fn apply(
+        &mut self,
+        window: &mut Window<'_>,
+        action: InventoryAction,
+    ) -> Result<Vec<ItemStack>, ClickError> {
+        let mut dropped = Vec::new();
+
+        if !matches!(
+            action,
+            InventoryAction::DragAdd { .. } | InventoryAction::DragEnd { .. }
+        ) {
+            self.drag = None;
+        }
+
+        match action {
+            InventoryAction::NormalClick { button, slot } => {
+                window.check(slot)?;
+                if window.is_result(slot) {
+                    self.take_result(window);
+                } else {
+                    self.normal_click(window, button, slot);
+                }
+            }
+            InventoryAction::OutsideClick { button } => {
+                let count = match button {
+                    MouseButton::Left => self.cursor.count,
+                    MouseButton::Right => 1,
+                };
+
+                if !self.cursor.is_empty() {
+                    dropped.push(take_count(&mut self.cursor, count));
+                }
+            }
+            InventoryAction::ShiftClick { slot, .. } => {
+                window.check(slot)?;
+                if window.is_result(slot) {
+                    Self::craft_all(window);
+                } else {
+                    Self::shift_click(window, slot);
+                }
+            }
+            InventoryAction::NumberKey { key, slot } => {
+                window.check(slot)?;
+                let hotbar =
+                    window.player_slot(PlayerInventory::HOTBAR_START_SLOT + u16::from(key) - 1);
+                if window.is_result(slot) {
+                    // the result can only be moved to an empty slot
+                    if window.get(hotbar).is_empty() {
+                        let crafted = window.craft();
+                        *window.get_mut(hotbar) = crafted;
+                    }
+                } else if window.get(hotbar).is_empty() || window.accepts(slot, window.get(hotbar))
+                {
+                    window.swap(slot, hotbar);
+                }
+            }
+            InventoryAction::OffhandSwap { slot } => {
+                window.check(slot)?;
+                if window.is_result(slot) {
+                    if window.inventory.get_offhand().is_empty() {
+                        let crafted = window.craft();
+                        window.inventory.set_offhand(crafted);
+                    }
+                } else {
+                    let offhand = window.inventory.get_offhand();
+
+                    if offhand.is_empty() || window.accepts(slot, offhand) {
+                        let stack = window.take(slot);
+                        let offhand = std::mem::replace(
+                            window.inventory.get_mut(OFFHAND_SLOT).unwrap(),
+                            stack,
+                        );
+                        *window.get_mut(slot) = offhand;
+                    }
+                }
+            }
+            InventoryAction::MiddleClick { slot } => {
+                // cloning stacks needs creative mode, which containers do not know about
+                window.check(slot)?;
+            }
+            InventoryAction::Drop { slot } => {
+                window.check(slot)?;
+                if window.is_result(slot) {
+                    dropped.extend(self.throw_crafted(window, 1));
+                } else {
+                    dropped.extend(self.throw(window, slot, 1));
+                }
+            }
+            InventoryAction::CtrlDrop { slot } => {
+                window.check(slot)?;
+                if window.is_result(slot) {
+                    dropped.extend(self.throw_crafted(window, MAX_CRAFTS));
+                } else {
+                    dropped.extend(self.throw(window, slot, i8::MAX));
+                }
+            }
+            InventoryAction::DropOutside => {}
+            InventoryAction::DragStart { button } => {
+                if !self.cursor.is_empty() {
+                    self.drag = Some(Drag {
+                        button,
+                        slots: Vec::new(),
+                    });
+                }
+            }
+            InventoryAction::DragAdd { button, slot } => {
+                window.check(slot)?;
+                self.add_to_drag(window, button, slot);
+            }
+            InventoryAction::DragEnd { button } => {
+                if let Some(drag) = self.drag.take()
+                    && drag.button == button
+                {
+                    self.end_drag(window, &drag);
+                }
+            }
+            InventoryAction::DoubleClick { slot } => {
+                window.check(slot)?;
+                self.pick_up_all(window, false);
+            }
+            InventoryAction::PickupAllReverse { slot } => {
+                window.check(slot)?;
+                self.pick_up_all(window, true);
+            }
+        }
+
+        Ok(dropped)
+    }
+
+    /// Throws up to `count` items out of `slot`, which is only possible with an empty cursor.
+    fn throw(&self, window: &mut Window<'_>, slot: u16, count: i8) -> Option<ItemStack> {
+        let stack = window.get_mut(slot);
+
+        if !self.cursor.is_empty() || stack.is_empty() {
+            return None;
+        }
+
+        Some(take_count(stack, count))
+    }
+
+    /// Crafts up to `crafts` times and throws the results, which is only possible with an empty
+    /// cursor.
+    fn throw_crafted(&self, window: &mut Window<'_>, crafts: usize) -> Vec<ItemStack> {
+        if !self.cursor.is_empty() {
+            return Vec::new();
+        }
+
+        (0..crafts)
+            .map(|_| window.craft())
+            .take_while(|crafted| !crafted.is_empty())
+            .collect()
+    }
+
+    fn add_to_drag(&mut self, window: &Window<'_>, button: FullMouseButton, slot: u16) {
+        let stack = window.get(slot);
+        let fits = window.accepts(slot, &self.cursor)
+            && (stack.is_empty() || stackable(stack, &self.cursor));
+
+        // every dragged slot gets at least one item
+        let enough = usize::try_from(self.cursor.count).unwrap_or(0);
+
+        if let Some(drag) = &mut self.drag
+            && drag.button == button
+            && fits
+            && enough > drag.slots.len()
+            && !drag.slots.contains(&slot)
+        {
+            drag.slots.push(slot);
+        }
+    }
+
+    fn normal_click(&mut self, window: &mut Window<'_>, button: MouseButton, slot: u16) {
+        if !self.cursor.is_empty() && !window.accepts(slot, &self.cursor) {
+            // nothing can be put here, but matching items can still be picked up
+            let stack = window.get_mut(slot);
+
+            if stackable(stack, &self.cursor) {
+                merge(stack, &mut self.cursor, i8::MAX);
+            }
+
+            return;
+        }
+
+        let stack = window.get_mut(slot);
+        let cursor = &mut self.cursor;
+
+        match button {
+            MouseButton::Left => {
+                if cursor.is_empty() || stack.is_empty() || !stackable(cursor, stack) {
+                    std::mem::swap(cursor, stack);
+                } else {
+                    merge(cursor, stack, i8::MAX);
+                }
+            }
+            MouseButton::Right => {
+                if cursor.is_empty() {
+                    // pick up half, rounding up
+                    let half = stack.count - stack.count / 2;
+                    *cursor = take_count(stack, half);
+                } else if stack.is_empty() || stackable(cursor, stack) {
+                    merge(cursor, stack, 1);
+                } else {
+                    std::mem::swap(cursor, stack);
+                }
+            }
+        }
+
+        normalize(cursor);
+        normalize(stack);
+    }
+
+    /// Takes the result of a crafting grid onto the cursor.
+    fn take_result(&mut self, window: &mut Window<'_>) {
+        let result = window.get(RESULT_SLOT);
+
+        if result.is_empty() {
+            return;
+        }
+
+        if self.cursor.is_empty() {
+            self.cursor = window.craft();
+        } else if stackable(&self.cursor, result)
+            && i16::from(self.cursor.count) + i16::from(result.count)
+                <= i16::from(result.item.max_stack())
+        {
+            self.cursor.count += window.craft().count;
+        }
+    }
+
+    /// Crafts as often as the ingredients allow and the results fit in the player's inventory.
+    fn craft_all(window: &mut Window<'_>) {
+        let size = window.container_size();
+        let len = u16::try_from(window.len()).unwrap();
+
+        // like vanilla, the results go to the end of the player's inventory first
+        let targets: Vec<u16> = (size..len).rev().collect();
+
+        for _ in 0..MAX_CRAFTS {
+            let result = window.get(RESULT_SLOT);
+
+            if result.is_empty() || space_for(window, result, &targets) < result.count {
+                return;
+            }
+
+            let mut crafted = window.craft();
+            move_to(window, &mut crafted, &targets);
+        }
+    }
+
+    /// Moves a stack between the container and the player's inventory.
+    fn shift_click(window: &mut Window<'_>, slot: u16) {
+        let mut stack = window.take(slot);
+
+        if stack.is_empty() {
+            return;
+        }
+
+        let size = window.container_size();
+        let len = u16::try_from(window.len()).unwrap();
+        let layout = window.container.layout;
+
+        if slot < size {
+            // like vanilla, items from a chest or a furnace's output go to the end of the
+            // player's inventory first
+            let targets: Vec<u16> = if layout == Layout::Storage || window.is_output(slot) {
+                (size..len).rev().collect()
+            } else {
+                (size..len).collect()
+            };
+
+            move_to(window, &mut stack, &targets);
+        } else {
+            let targets = window.shift_targets(&stack);
+
+            let moved = move_to(window, &mut stack, &targets);
+
+            // crafting windows move items between the hotbar and the rest of the inventory if
+            // they do not fit in the grid, and furnaces do so for items they cannot use
+            let between_inventory = match layout {
+                Layout::Storage => false,
+                Layout::CraftingGrid { .. } => !moved,
+                Layout::Furnace(_) => targets.is_empty(),
+            };
+
+            if between_inventory {
+                let hotbar = window.player_slot(PlayerInventory::HOTBAR_START_SLOT);
+
+                let targets: Vec<u16> = if slot < hotbar {
+                    (hotbar..len).collect()
+                } else {
+                    (size..hotbar).collect()
+                };
+
+                move_to(window, &mut stack, &targets);
+            }
+        }
+
+        *window.get_mut(slot) = stack;
+    }
+
+    /// Spreads the cursor over the dragged slots: evenly for the left button and one item each
+    /// for the right button. Dragging with the middle button needs creative mode.
+    fn end_drag(&mut self, window: &mut Window<'_>, drag: &Drag) {
+        if let [slot] = drag.slots[..] {
+            match drag.button {
+                FullMouseButton::Left => self.normal_click(window, MouseButton::Left, slot),
+                FullMouseButton::Right => self.normal_click(window, MouseButton::Right, slot),
+                FullMouseButton::Middle => {}
+            }
+            return;
+        }
+
+        let Ok(slots) = i8::try_from(drag.slots.len()) else {
+            return;
+        };
+
+        if slots == 0 || self.cursor.count < slots {
+            return;
+        }
+
+        let per_slot = match drag.button {
+            FullMouseButton::Left => self.cursor.count / slots,
+            FullMouseButton::Right => 1,
+            FullMouseButton::Middle => return,
+        };
+
+        for &slot in &drag.slots {
+            let stack = window.get_mut(slot);
+
+            if !stack.is_empty() && !stackable(stack, &self.cursor) {
+                continue;
+            }
+
+            merge(&mut self.cursor, stack, per_slot);
+        }
+    }
+
+    /// Gathers items matching the cursor from the whole window, taking from partial stacks
+    /// before full ones.
+    fn pick_up_all(&mut self, window: &mut Window<'_>, reverse: bool) {
+        if self.cursor.is_empty() {
+            return;
+        }
+
+        let len = u16::try_from(window.len()).unwrap();
+        let mut slots: Vec<u16> = (0..len).collect();
+        if reverse {
+            slots.reverse();
+        }
+
+        for take_full in [false, true] {
+            for &slot in &slots {
+                let max = self.cursor.item.max_stack();
+
+                if self.cursor.count >= max {
+                    return;
+                }
+
+                if window.is_result(slot) {
+                    continue;
+                }
+
+                let stack = window.get_mut(slot);
+
+                if stack.is_empty() || !stackable(stack, &self.cursor) {
+                    continue;
+                }
+
+                if !take_full && stack.count >= max {
+                    continue;
+                }
+
+                let taken = take_count(stack, max - self.cursor.count);
+                self.cursor.count += taken.count;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_protocol::{ItemKind, packets::play::click_slot_c2s::ClickMode};
+
+    use super::*;
+
+    fn click(
+        open: &OpenContainer,
+        mode: ClickMode,
+        button: i8,
+        slot_idx: i16,
+        slot_changes: Vec<SlotChange>,
+        carried_item: ItemStack,
+    ) -> play::ClickSlotC2s<'static> {
+        play::ClickSlotC2s {
+            window_id: open.window_id(),
+            state_id: VarInt(open.state_id()),
+            slot_idx,
+            button,
+            mode,
+            slot_changes: Cow::Owned(slot_changes),
+            carried_item,
+        }
+    }
+
+    fn setup() -> (OpenContainer, Container, PlayerInventory) {
+        let open = OpenContainer::new(1, Entity::null());
+        let container = Container::new(27);
+        let inventory = PlayerInventory::default();
+        (open, container, inventory)
+    }
+
+    #[test]
+    fn test_pick_up_and_place() {
+        let (mut open, mut container, mut inventory) = setup();
+        container
+            .set(0, ItemStack::new(ItemKind::Diamond, 10, None))
+            .unwrap();
+
+        let pkt = click(
+            &open,
+            ClickMode::Click,
+            1,
+            0,
+            vec![SlotChange {
+                idx: 0,
+                stack: ItemStack::new(ItemKind::Diamond, 5, None),
+            }],
+            ItemStack::new(ItemKind::Diamond, 5, None),
+        );
+
+        let result = open
+            .click(
+                &mut container,
+                &mut inventory,
+                &CraftingRegistry::default(),
+                &pkt,
+            )
+            .unwrap();
+        assert!(!result.resync);
+        assert_eq!(open.cursor().count, 5);
+        assert_eq!(container.get(0).unwrap().count, 5);
+
+        let pkt = click(
+            &open,
+            ClickMode::Click,
+            0,
+            1,
+            vec![SlotChange {
+                idx: 1,
+                stack: ItemStack::new(ItemKind::Diamond, 5, None),
+            }],
+            ItemStack::EMPTY,
+        );
+
+        let result = open
+            .click(
+                &mut container,
+                &mut inventory,
+                &CraftingRegistry::default(),
+                &pkt,
+            )
+            .unwrap();
+        assert!(!result.resync);
+        assert!(open.cursor().is_empty());
+        assert_eq!(container.get(1).unwrap().count, 5);
+    }
+
+    #[test]
+    fn test_shift_click_routing() {
+        let (mut open, mut container, mut inventory) = setup();
+        container
+            .set(3, ItemStack::new(ItemKind::Stone, 64, None))
+            .unwrap();
+        inventory
+            .set(44, ItemStack::new(ItemKind::Stone, 60, None))
+            .unwrap();
+
+        // tops up the last hotbar slot, then fills the last empty hotbar slot
+        let pkt = click(
+            &open,
+            ClickMode::ShiftClick,
+            0,
+            3,
+            Vec::new(),
+            ItemStack::EMPTY,
+        );
+        let result = open
+            .click(
+                &mut container,
+                &mut inventory,
+                &CraftingRegistry::default(),
+                &pkt,
+            )
+            .unwrap();
+
+        assert!(result.resync, "the client predicted nothing");
+        assert!(container.get(3).unwrap().is_empty());
+        assert_eq!(inventory.get(44).unwrap().count, 64);
+        assert_eq!(inventory.get(43).unwrap().count, 60);
+
+        // and back into the first container slot
+        let pkt = click(
+            &open,
+            ClickMode::ShiftClick,
+            0,
+            27 + 34,
+            Vec::new(),
+            ItemStack::EMPTY,
+        );
+        let _ = open
+            .click(
+                &mut container,
+                &mut inventory,
+                &CraftingRegistry::default(),
+                &pkt,
+            )
+            .unwrap();
+
+        assert!(inventory.get(43).unwrap().is_empty());
+        assert_eq!(container.get(0).unwrap().count, 60);
+    }
+
+    #[test]
+    fn test_desync_cannot_duplicate() {
+        let (mut open, mut container, mut inventory) = setup();
+        container
+            .set(0, ItemStack::new(ItemKind::Diamond, 1, None))
+            .unwrap();
+
+        // the client claims it picked up a full stack and left the slot untouched
+        let pkt = click(
+            &open,
+            ClickMode::Click,
+            0,
+            0,
+            vec![SlotChange {
+                idx: 0,
+                stack: ItemStack::new(ItemKind::Diamond, 1, None),
+            }],
+            ItemStack::new(ItemKind::Diamond, 64, None),
+        );
+
+        let result = open
+            .click(
+                &mut container,
+                &mut inventory,
+                &CraftingRegistry::default(),
+                &pkt,
+            )
+            .unwrap();
+        assert!(result.resync);
+        assert_eq!(open.cursor().count, 1);
+        assert!(container.get(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stale_state_id() {
+        let (mut open, mut container, mut inventory) = setup();
+        let _ = open.contents(&container, &inventory);
+
+        let mut pkt = click(&open, ClickMode::Click, 0, 0, Vec::new(), ItemStack::EMPTY);
+        pkt.state_id = VarInt(0);
+
+        let result = open
+            .click(
+                &mut container,
+                &mut inventory,
+                &CraftingRegistry::default(),
+                &pkt,
+            )
+            .unwrap();
+        assert!(result.resync);
+    }
+
+    #[test]
+    fn test_invalid_click() {
+        let (mut open, mut container, mut inventory) = setup();
+
+        let pkt = click(&open, ClickMode::Click, 0, 63, Vec::new(), ItemStack::EMPTY);
+        assert!(matches!(
+            open.click(
+                &mut container,
+                &mut inventory,
+                &CraftingRegistry::default(),
+                &pkt
+            ),
+            Err(ClickError::SlotOutOfRange { slot: 63, len: 63 })
+        ));
+
+        let mut pkt = click(&open, ClickMode::Click, 0, 0, Vec::new(), ItemStack::EMPTY);
+        pkt.window_id = 2;
+        assert!(matches!(
+            open.click(
+                &mut container,
+                &mut inventory,
+                &CraftingRegistry::default(),
+                &pkt
+            ),
+            Err(ClickError::WrongWindow {
+                expected: 1,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_drag() {
+        let (mut open, mut container, mut inventory) = setup();
+        container
+            .set(0, ItemStack::new(ItemKind::Stone, 10, None))
+            .unwrap();
+
+        let pkt = click(&open, ClickMode::Click, 0, 0, Vec::new(), ItemStack::EMPTY);
+        let _ = open
+            .click(
+                &mut container,
+                &mut inventory,
+                &CraftingRegistry::default(),
+                &pkt,
+            )
+            .unwrap();
+
+        for (button, slot) in [(0, -999), (1, 1), (1, 2), (1, 3), (2, -999)] {
+            let pkt = click(
+                &open,
+                ClickMode::Drag,
+                button,
+                slot,
+                Vec::new(),
+                ItemStack::EMPTY,
+            );
+            let _ = open
+                .click(
+                    &mut container,
+                    &mut inventory,
+                    &CraftingRegistry::default(),
+                    &pkt,
+                )
+                .unwrap();
+        }
+
+        assert_eq!(container.get(1).unwrap().count, 3);
+        assert_eq!(container.get(2).unwrap().count, 3);
+        assert_eq!(container.get(3).unwrap().count, 3);
+        assert_eq!(open.cursor().count, 1);
+    }
+
+    #[test]
+    fn test_changes() {
+        let (mut open, mut container, inventory) = setup();
+        let _ = open.contents(&container, &inventory);
+        assert!(open.changes(&container).is_empty());
+
+        container
+            .set(5, ItemStack::new(ItemKind::Stone, 1, None))
+            .unwrap();
+
+        let changes = open.changes(&container);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].slot_idx, 5);
+        assert!(open.changes(&container).is_empty());
+    }
+
+    #[test]
+    fn test_crafting_table() {
+        let registry = CraftingRegistry::default();
+        let mut open = OpenContainer::new(1, Entity::null());
+        let mut container = Container::crafting_table();
+        let mut inventory = PlayerInventory::default();
+
+        for slot in [5, 6, 8, 9] {
+            container
+                .set(slot, ItemStack::new(ItemKind::OakPlanks, 3, None))
+                .unwrap();
+        }
+        container.update_crafting_result(&registry);
+        assert_eq!(container.get(0).unwrap().item, ItemKind::CraftingTable);
+
+        // the client does not predict the result, so it is not resynced for it
+        let pkt = click(
+            &open,
+            ClickMode::Click,
+            0,
+            0,
+            vec![
+                SlotChange {
+                    idx: 0,
+                    stack: ItemStack::EMPTY,
+                },
+                SlotChange {
+                    idx: 5,
+                    stack: ItemStack::new(ItemKind::OakPlanks, 2, None),
+                },
+                SlotChange {
+                    idx: 6,
+                    stack: ItemStack::new(ItemKind::OakPlanks, 2, None),
+                },
+                SlotChange {
+                    idx: 8,
+                    stack: ItemStack::new(ItemKind::OakPlanks, 2, None),
+                },
+                SlotChange {
+                    idx: 9,
+                    stack: ItemStack::new(ItemKind::OakPlanks, 2, None),
+                },
+            ],
+            ItemStack::new(ItemKind::CraftingTable, 1, None),
+        );
+
+        let result = open
+            .click(&mut container, &mut inventory, &registry, &pkt)
+            .unwrap();
+        assert!(!result.resync);
+        assert_eq!(open.cursor().item, ItemKind::CraftingTable);
+        assert_eq!(container.get(5).unwrap().count, 2);
+        assert_eq!(container.get(0).unwrap().item, ItemKind::CraftingTable);
+
+        // the client was told the result slot is empty, so the new result is sent to it
+        assert_eq!(open.changes(&container).len(), 1);
+
+        // clicking the result again stacks another one onto the cursor
+        let pkt = click(&open, ClickMode::Click, 0, 0, Vec::new(), ItemStack::EMPTY);
+        let _ = open
+            .click(&mut container, &mut inventory, &registry, &pkt)
+            .unwrap();
+        assert_eq!(open.cursor().count, 2);
+
+        let _ = open.take_cursor();
+
+        // crafts with everything that is left
+        let pkt = click(
+            &open,
+            ClickMode::ShiftClick,
+            0,
+            0,
+            Vec::new(),
+            ItemStack::EMPTY,
+        );
+        let _ = open
+            .click(&mut container, &mut inventory, &registry, &pkt)
+            .unwrap();
+
+        assert_eq!(inventory.get(44).unwrap().count, 1);
+        assert!(container.get(0).unwrap().is_empty());
+        assert!(container.drain().is_empty());
+    }
+
+    #[test]
+    fn test_crafting_table_shift_click() {
+        let registry = CraftingRegistry::default();
+        let mut open = OpenContainer::new(1, Entity::null());
+        let mut container = Container::crafting_table();
+        let mut inventory = PlayerInventory::default();
+
+        inventory
+            .set(9, ItemStack::new(ItemKind::OakLog, 3, None))
+            .unwrap();
+
+        // into the grid, past the result slot
+        let pkt = click(
+            &open,
+            ClickMode::ShiftClick,
+            0,
+            10,
+            Vec::new(),
+            ItemStack::EMPTY,
+        );
+        let _ = open
+            .click(&mut container, &mut inventory, &registry, &pkt)
+            .unwrap();
+
+        assert_eq!(container.get(1).unwrap().count, 3);
+        assert_eq!(container.get(0).unwrap().item, ItemKind::OakPlanks);
+
+        // and back into the inventory, in order
+        let pkt = click(
+            &open,
+            ClickMode::ShiftClick,
+            0,
+            1,
+            Vec::new(),
+            ItemStack::EMPTY,
+        );
+        let _ = open
+            .click(&mut container, &mut inventory, &registry, &pkt)
+            .unwrap();
+
+        assert_eq!(inventory.get(9).unwrap().count, 3);
+        assert!(container.get(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_property_changes() {
+        let mut open = OpenContainer::new(1, Entity::null());
+
+        assert_eq!(open.property_changes(&[0, 0, 0, 0]).len(), 4);
+        assert!(open.property_changes(&[0, 0, 0, 0]).is_empty());
+
+        let changes = open.property_changes(&[1600, 1600, 1, 200]);
+        assert_eq!(changes.len(), 4);
+        assert_eq!(changes[2].property, 2);
+        assert_eq!(changes[2].value, 1);
+
+        let changes = open.property_changes(&[1599, 1600, 2, 200]);
+        assert_eq!(changes.len(), 2);
+    }
+
+    #[test]
+    fn test_furnace_slots() {
+        let registry = CraftingRegistry::default();
+        let mut open = OpenContainer::new(1, Entity::null());
+        let mut container = Container::furnace(FurnaceKind::Furnace);
+        let mut inventory = PlayerInventory::default();
+
+        inventory
+            .set(9, ItemStack::new(ItemKind::RawIron, 5, None))
+            .unwrap();
+        inventory
+            .set(10, ItemStack::new(ItemKind::Coal, 2, None))
+            .unwrap();
+        inventory
+            .set(11, ItemStack::new(ItemKind::Dirt, 1, None))
+            .unwrap();
+
+        // smeltable items go to the input, fuel to the fuel slot and anything else to the hotbar
+        for slot in 3..=5 {
+            let pkt = click(
+                &open,
+                ClickMode::ShiftClick,
+                0,
+                slot,
+                Vec::new(),
+                ItemStack::EMPTY,
+            );
+            let _ = open
+                .click(&mut container, &mut inventory, &registry, &pkt)
+                .unwrap();
+        }
+
+        assert_eq!(container.get(INPUT_SLOT).unwrap().item, ItemKind::RawIron);
+        assert_eq!(container.get(FUEL_SLOT).unwrap().item, ItemKind::Coal);
+        assert_eq!(inventory.get(36).unwrap().item, ItemKind::Dirt);
+
+        // pick up the dirt and try to put it in the fuel and output slots
+        let pkt = click(
+            &open,
+            ClickMode::Click,
+            0,
+            30,
+            vec![SlotChange {
+                idx: 30,
+                stack: ItemStack::EMPTY,
+            }],
+            ItemStack::new(ItemKind::Dirt, 1, None),
+        );
+        let _ = open
+            .click(&mut container, &mut inventory, &registry, &pkt)
+            .unwrap();
+
+        for slot in [FUEL_SLOT, OUTPUT_SLOT] {
+            let pkt = click(
+                &open,
+                ClickMode::Click,
+                0,
+                i16::try_from(slot).unwrap(),
+                Vec::new(),
+                ItemStack::new(ItemKind::Dirt, 1, None),
+            );
+            let _ = open
+                .click(&mut container, &mut inventory, &registry, &pkt)
+                .unwrap();
+        }
+
+        assert_eq!(container.get(FUEL_SLOT).unwrap().item, ItemKind::Coal);
+        assert!(container.get(OUTPUT_SLOT).unwrap().is_empty());
+        assert_eq!(open.cursor().item, ItemKind::Dirt);
+    }
+}
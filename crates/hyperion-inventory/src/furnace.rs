@@ -0,0 +1,350 @@
+//! Furnaces, blast furnaces and smokers, which cook the items in their [`Container`] while they
+//! have fuel.
+//!
+//! The cooking state is kept in a [`Furnace`] next to the [`Container`] created with
+//! [`Container::furnace`] and advanced by [`Furnace::tick`] once per tick, whether or not anyone
+//! has the window open.
+
+use flecs_ecs::macros::Component;
+use hyperion_crafting::{CraftingRegistry, FurnaceKind};
+use valence_protocol::{ItemKind, ItemStack};
+
+use crate::container::Container;
+
+/// The slot with the item being cooked.
+pub const INPUT_SLOT: u16 = 0;
+
+/// The slot with the fuel which is burned next.
+pub const FUEL_SLOT: u16 = 1;
+
+/// The slot cooked items are put in, which items can only be taken out of.
+pub const OUTPUT_SLOT: u16 = 2;
+
+/// The window properties of a furnace, in the order of [`Furnace::properties`].
+pub const PROPERTIES: usize = 4;
+
+/// The ticks `item` burns for in a furnace, if it is a fuel.
+#[must_use]
+pub fn fuel_value(item: ItemKind) -> Option<u16> {
+    let ticks = match item {
+        ItemKind::LavaBucket => 20000,
+        ItemKind::CoalBlock => 16000,
+        ItemKind::DriedKelpBlock => 4001,
+        ItemKind::BlazeRod => 2400,
+        ItemKind::Coal | ItemKind::Charcoal => 1600,
+        ItemKind::CraftingTable
+        | ItemKind::Chest
+        | ItemKind::TrappedChest
+        | ItemKind::Barrel
+        | ItemKind::Bookshelf
+        | ItemKind::Ladder
+        | ItemKind::Bow
+        | ItemKind::FishingRod => 300,
+        ItemKind::WoodenSword
+        | ItemKind::WoodenPickaxe
+        | ItemKind::WoodenAxe
+        | ItemKind::WoodenShovel
+        | ItemKind::WoodenHoe => 200,
+        ItemKind::Stick | ItemKind::Bowl => 100,
+        ItemKind::Bamboo | ItemKind::Scaffolding => 50,
+        _ => {
+            let name = item.to_str();
+
+            // nether wood does not burn
+            if name.contains("crimson") || name.contains("warped") {
+                return None;
+            }
+
+            if name.ends_with("_boat") || name.ends_with("_raft") {
+                1200
+            } else if name.ends_with("_planks") || name.ends_with("_log") || name.ends_with("_wood")
+            {
+                300
+            } else if name.ends_with("_sapling") || name.ends_with("_wool") {
+                100
+            } else if name.ends_with("_carpet") {
+                67
+            } else {
+                return None;
+            }
+        }
+    };
+
+    Some(ticks)
+}
+
+/// Whether `item` may be put in the fuel slot. Empty buckets are allowed as they are what is left
+/// of a lava bucket.
+#[must_use]
+pub fn is_fuel_slot_item(item: ItemKind) -> bool {
+    item == ItemKind::Bucket || fuel_value(item).is_some()
+}
+
+/// The cooking state of a furnace, blast furnace or smoker.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct Furnace {
+    kind: FurnaceKind,
+    /// The ticks until the burning fuel is used up.
+    burn_time: u16,
+    /// The ticks the burning fuel lasted in total.
+    fuel_time: u16,
+    /// The ticks the input has been cooking for.
+    cook_time: u16,
+    /// The ticks it takes to cook the input.
+    cook_time_total: u16,
+    /// The item being cooked, so progress is lost when it is swapped for another.
+    cooking: Option<ItemKind>,
+}
+
+impl Furnace {
+    #[must_use]
+    pub const fn new(kind: FurnaceKind) -> Self {
+        Self {
+            kind,
+            burn_time: 0,
+            fuel_time: 0,
+            cook_time: 0,
+            cook_time_total: 0,
+            cooking: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn kind(&self) -> FurnaceKind {
+        self.kind
+    }
+
+    /// Whether fuel is burning, which lights up the block.
+    #[must_use]
+    pub const fn is_lit(&self) -> bool {
+        self.burn_time > 0
+    }
+
+    /// The ticks `item` burns for in this furnace. Blast furnaces and smokers cook twice as fast,
+    /// so they also burn through their fuel twice as fast.
+    #[must_use]
+    pub fn burn_time(&self, item: ItemKind) -> Option<u16> {
+        let ticks = fuel_value(item)?;
+
+        match self.kind {
+            FurnaceKind::Furnace => Some(ticks),
+            FurnaceKind::BlastFurnace | FurnaceKind::Smoker => Some(ticks / 2),
+        }
+    }
+
+    /// The values of the window properties which animate the flame and the progress arrow: the
+    /// remaining fuel, the total fuel, the cooking progress and the total cooking time.
+    #[must_use]
+    pub fn properties(&self) -> [i16; PROPERTIES] {
+        [
+            self.burn_time,
+            self.fuel_time,
+            self.cook_time,
+            self.cook_time_total,
+        ]
+        .map(|value| i16::try_from(value).unwrap_or(i16::MAX))
+    }
+
+    /// Advances the furnace by one tick: burns fuel, cooks the input and moves finished items to
+    /// the output. `container` must have been created with [`Container::furnace`].
+    pub fn tick(&mut self, container: &mut Container, registry: &CraftingRegistry) {
+        self.burn_time = self.burn_time.saturating_sub(1);
+
+        let input = container.get(INPUT_SLOT).unwrap();
+        let input = (!input.is_empty()).then_some(input.item);
+
+        if input != self.cooking {
+            self.cooking = input;
+            self.cook_time = 0;
+        }
+
+        let recipe = input.and_then(|item| registry.get_smelting(self.kind, item));
+
+        let can_cook = recipe.is_some_and(|recipe| {
+            let output = container.get(OUTPUT_SLOT).unwrap();
+            let result = recipe.result();
+
+            output.is_empty()
+                || (output.item == result.item
+                    && output.nbt == result.nbt
+                    && i16::from(output.count) + i16::from(result.count)
+                        <= i16::from(output.item.max_stack()))
+        });
+
+        let fuel = container.get(FUEL_SLOT).unwrap();
+
+        if !self.is_lit() && (fuel.is_empty() || input.is_none()) {
+            // the progress cools down while there is nothing to burn
+            self.cook_time = self.cook_time.saturating_sub(2);
+            return;
+        }
+
+        if !self.is_lit()
+            && can_cook
+            && let Some(burn_time) = self.burn_time(fuel.item)
+        {
+            self.burn_time = burn_time;
+            self.fuel_time = burn_time;
+
+            let fuel = container.get_mut(FUEL_SLOT).unwrap();
+
+            if fuel.item == ItemKind::LavaBucket {
+                *fuel = ItemStack::new(ItemKind::Bucket, 1, None);
+            } else {
+                fuel.count -= 1;
+                if fuel.is_empty() {
+                    *fuel = ItemStack::EMPTY;
+                }
+            }
+        }
+
+        let Some(recipe) = recipe.filter(|_| self.is_lit() && can_cook) else {
+            self.cook_time = 0;
+            return;
+        };
+
+        self.cook_time_total = recipe.cooking_time();
+        self.cook_time += 1;
+
+        if self.cook_time < self.cook_time_total {
+            return;
+        }
+
+        self.cook_time = 0;
+
+        let result = recipe.result().clone();
+
+        let input = container.get_mut(INPUT_SLOT).unwrap();
+        input.count -= 1;
+        if input.is_empty() {
+            *input = ItemStack::EMPTY;
+        }
+
+        let output = container.get_mut(OUTPUT_SLOT).unwrap();
+        if output.is_empty() {
+            *output = result;
+        } else {
+            output.count += result.count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn furnace(kind: FurnaceKind) -> (Furnace, Container) {
+        (Furnace::new(kind), Container::furnace(kind))
+    }
+
+    #[test]
+    fn test_smelt_with_coal() {
+        let registry = CraftingRegistry::default();
+        let (mut furnace, mut container) = furnace(FurnaceKind::Furnace);
+
+        container
+            .set(INPUT_SLOT, ItemStack::new(ItemKind::RawIron, 2, None))
+            .unwrap();
+        container
+            .set(FUEL_SLOT, ItemStack::new(ItemKind::Coal, 1, None))
+            .unwrap();
+
+        furnace.tick(&mut container, &registry);
+
+        assert!(furnace.is_lit());
+        assert!(container.get(FUEL_SLOT).unwrap().is_empty());
+        assert_eq!(furnace.properties(), [1600, 1600, 1, 200]);
+
+        for _ in 1..200 {
+            furnace.tick(&mut container, &registry);
+        }
+
+        assert_eq!(container.get(INPUT_SLOT).unwrap().count, 1);
+        assert_eq!(
+            container.get(OUTPUT_SLOT).unwrap().item,
+            ItemKind::IronIngot
+        );
+        assert_eq!(furnace.properties()[2], 0);
+    }
+
+    #[test]
+    fn test_blast_furnace_is_faster() {
+        let registry = CraftingRegistry::default();
+        let (mut furnace, mut container) = furnace(FurnaceKind::BlastFurnace);
+
+        container
+            .set(INPUT_SLOT, ItemStack::new(ItemKind::RawGold, 1, None))
+            .unwrap();
+        container
+            .set(FUEL_SLOT, ItemStack::new(ItemKind::Coal, 1, None))
+            .unwrap();
+
+        for _ in 0..100 {
+            furnace.tick(&mut container, &registry);
+        }
+
+        assert_eq!(
+            container.get(OUTPUT_SLOT).unwrap().item,
+            ItemKind::GoldIngot
+        );
+        assert_eq!(furnace.properties()[1], 800);
+    }
+
+    #[test]
+    fn test_no_fuel_without_recipe() {
+        let registry = CraftingRegistry::default();
+        let (mut furnace, mut container) = furnace(FurnaceKind::Furnace);
+
+        container
+            .set(INPUT_SLOT, ItemStack::new(ItemKind::Dirt, 1, None))
+            .unwrap();
+        container
+            .set(FUEL_SLOT, ItemStack::new(ItemKind::LavaBucket, 1, None))
+            .unwrap();
+
+        furnace.tick(&mut container, &registry);
+
+        assert!(!furnace.is_lit());
+        assert_eq!(container.get(FUEL_SLOT).unwrap().item, ItemKind::LavaBucket);
+
+        container
+            .set(INPUT_SLOT, ItemStack::new(ItemKind::RawCopper, 1, None))
+            .unwrap();
+
+        furnace.tick(&mut container, &registry);
+
+        assert!(furnace.is_lit());
+        assert_eq!(container.get(FUEL_SLOT).unwrap().item, ItemKind::Bucket);
+    }
+
+    #[test]
+    fn test_full_output_stops_cooking() {
+        let registry = CraftingRegistry::default();
+        let (mut furnace, mut container) = furnace(FurnaceKind::Furnace);
+
+        container
+            .set(INPUT_SLOT, ItemStack::new(ItemKind::RawIron, 1, None))
+            .unwrap();
+        container
+            .set(FUEL_SLOT, ItemStack::new(ItemKind::Coal, 1, None))
+            .unwrap();
+        container
+            .set(OUTPUT_SLOT, ItemStack::new(ItemKind::IronIngot, 64, None))
+            .unwrap();
+
+        furnace.tick(&mut container, &registry);
+
+        assert!(!furnace.is_lit());
+        assert_eq!(container.get(FUEL_SLOT).unwrap().count, 1);
+    }
+
+    #[test]
+    fn test_fuel_values() {
+        assert_eq!(fuel_value(ItemKind::OakPlanks), Some(300));
+        assert_eq!(fuel_value(ItemKind::BirchBoat), Some(1200));
+        assert_eq!(fuel_value(ItemKind::WhiteCarpet), Some(67));
+        assert_eq!(fuel_value(ItemKind::CrimsonPlanks), None);
+        assert_eq!(fuel_value(ItemKind::Cobblestone), None);
+        assert!(is_fuel_slot_item(ItemKind::Bucket));
+    }
+}
@@ -1,3 +1,6 @@
+#![feature(let_chains)]
+#![feature(thread_local)]
+
 use std::cmp::min;
 
 use flecs_ecs::{core::World, macros::Component, prelude::Module};
@@ -5,6 +8,8 @@ use roaring::RoaringBitmap;
 use valence_protocol::{ItemKind, ItemStack};
 
 pub mod action;
+pub mod container;
+pub mod furnace;
 pub mod parser;
 
 pub type PlayerInventory = Inventory<46>;
@@ -52,6 +57,16 @@ enum TryAddSlot {
 const HAND_START_SLOT: u16 = 36;
 
 impl<const N: usize> Inventory<N> {
+    /// Creates an inventory holding `slots` without marking any of them as updated, so nothing is
+    /// sent to the client until the inventory is changed.
+    #[must_use]
+    pub fn from_slots(slots: [ItemStack; N]) -> Self {
+        Self {
+            slots,
+            ..Self::default()
+        }
+    }
+
     pub fn set(&mut self, index: u16, stack: ItemStack) -> Result<(), InventoryAccessError> {
         let item = self.get_mut(index)?;
         *item = stack;
@@ -375,5 +390,8 @@ pub struct InventoryModule;
 impl Module for InventoryModule {
     fn module(world: &World) {
         world.component::<PlayerInventory>();
+        world.component::<container::Container>();
+        world.component::<container::OpenContainer>();
+        world.component::<furnace::Furnace>();
     }
 }
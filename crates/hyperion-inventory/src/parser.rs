@@ -132,9 +132,17 @@ pub fn create_inventory_action(mode: u8, button: u8, slot: i16) -> InventoryActi
             }),
             _ => InvalidButtonSnafu { mode, button }.fail(),
         },
+        4 if slot == -999 => match button {
+            0 | 1 => Ok(InventoryAction::DropOutside),
+            _ => InvalidButtonSnafu { mode, button }.fail(),
+        },
         4 => match button {
-            0 => Ok(InventoryAction::Drop),
-            1 => Ok(InventoryAction::CtrlDrop),
+            0 => Ok(InventoryAction::Drop {
+                slot: slot.try_into().context(NegativeSlotSnafu)?,
+            }),
+            1 => Ok(InventoryAction::CtrlDrop {
+                slot: slot.try_into().context(NegativeSlotSnafu)?,
+            }),
             _ => InvalidButtonSnafu { mode, button }.fail(),
         },
         5 => handle_drag(button, slot),
@@ -205,6 +213,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_drop() {
+        assert_eq!(
+            create_inventory_action(4, 1, 12).unwrap(),
+            InventoryAction::CtrlDrop { slot: 12 }
+        );
+
+        assert_eq!(
+            create_inventory_action(4, 0, -999).unwrap(),
+            InventoryAction::DropOutside
+        );
+    }
+
     #[test]
     fn test_pickup_all_reverse() {
         assert_eq!(
@@ -1,15 +1,45 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::BitOr,
+};
+
 use flecs_ecs::core::Entity;
+use hyperion::simulation::skin::PlayerSkin;
+use hyperion_text::Text;
 use valence_protocol::{ItemKind, ItemStack, nbt, nbt::Value};
 
 mod book;
+mod parse;
+mod potion;
+
 pub use book::BookBuilder;
+pub use potion::{PotionEffect, StatusEffect};
 
 /// A builder for creating Minecraft items with NBT data
+///
+/// An existing item can be turned back into a builder with [`ItemBuilder::from_stack`].
 #[derive(Clone, Debug)]
 #[must_use]
 pub struct ItemBuilder {
     kind: ItemKind,
     count: i8,
+    /// The name as a JSON text component.
+    name: Option<String>,
+    color: Option<Color>,
+    /// The lines of the lore as JSON text components.
+    lore: Vec<String>,
+    /// Enchantment IDs and their levels.
+    enchantments: Vec<(String, i16)>,
+    glowing: bool,
+    unbreakable: bool,
+    damage: Option<i32>,
+    custom_model_data: Option<i32>,
+    hide_flags: Option<HideFlags>,
+    skull: Option<Skull>,
+    potion_effects: Vec<PotionEffect>,
+    attributes: Vec<nbt::Compound<String>>,
+    handler: Option<Entity>,
+    /// Tags the builder has no method for, which are kept as they are.
     nbt: Option<nbt::Compound<String>>,
 }
 
@@ -68,14 +98,109 @@ impl Attribute for MaxHealth {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Color(pub u8, pub u8, pub u8);
 
+impl Color {
+    const fn to_rgb(self) -> u32 {
+        (u32::from(self.0) << 16) | (u32::from(self.1) << 8) | u32::from(self.2)
+    }
+
+    const fn from_rgb(rgb: u32) -> Self {
+        let [_, r, g, b] = rgb.to_be_bytes();
+        Self(r, g, b)
+    }
+}
+
+/// The parts of an item's tooltip which are hidden, set with [`ItemBuilder::hide_flags`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct HideFlags(pub i32);
+
+impl HideFlags {
+    pub const ADDITIONAL: Self = Self(1 << 5);
+    pub const ALL: Self = Self(0xFF);
+    pub const ARMOR_TRIM: Self = Self(1 << 7);
+    pub const ATTRIBUTE_MODIFIERS: Self = Self(1 << 1);
+    pub const CAN_DESTROY: Self = Self(1 << 3);
+    pub const CAN_PLACE_ON: Self = Self(1 << 4);
+    pub const DYE: Self = Self(1 << 6);
+    pub const ENCHANTMENTS: Self = Self(1 << 0);
+    pub const UNBREAKABLE: Self = Self(1 << 2);
+}
+
+impl BitOr for HideFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// The texture of a player head.
+#[derive(Clone, Debug)]
+struct Skull {
+    /// The client caches textures by this ID, so it must differ between textures.
+    id: Vec<i32>,
+    skin: PlayerSkin,
+}
+
+impl Skull {
+    fn new(skin: &PlayerSkin) -> Self {
+        let hash = |salt: u8| {
+            let mut hasher = DefaultHasher::new();
+            salt.hash(&mut hasher);
+            skin.textures.hash(&mut hasher);
+            hasher.finish().to_be_bytes()
+        };
+
+        let bytes = [hash(0), hash(1)].concat();
+
+        let id = bytes
+            .chunks_exact(4)
+            .map(|chunk| i32::from_be_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        Self {
+            id,
+            skin: skin.clone(),
+        }
+    }
+
+    fn to_nbt(&self) -> nbt::Compound<String> {
+        let mut texture = nbt::Compound::new();
+        texture.insert("Value", Value::String(self.skin.textures.clone()));
+        if !self.skin.signature.is_empty() {
+            texture.insert("Signature", Value::String(self.skin.signature.clone()));
+        }
+
+        let mut properties = nbt::Compound::new();
+        properties.insert("textures", Value::List(nbt::List::Compound(vec![texture])));
+
+        let mut owner = nbt::Compound::new();
+        owner.insert("Id", Value::IntArray(self.id.clone()));
+        owner.insert("Properties", Value::Compound(properties));
+        owner
+    }
+}
+
 impl ItemBuilder {
     pub const fn new(kind: ItemKind) -> Self {
         Self {
             kind,
             count: 1,
+            name: None,
+            color: None,
+            lore: Vec::new(),
+            enchantments: Vec::new(),
+            glowing: false,
+            unbreakable: false,
+            damage: None,
+            custom_model_data: None,
+            hide_flags: None,
+            skull: None,
+            potion_effects: Vec::new(),
+            attributes: Vec::new(),
+            handler: None,
             nbt: None,
         }
     }
@@ -91,27 +216,8 @@ impl ItemBuilder {
     ///     .color(Color(255, 0, 0))
     ///     .build();
     /// ```
-    pub fn color(mut self, color: Color) -> Self {
-        let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
-
-        // Create or get existing display compound
-        let display = match nbt.remove("display") {
-            Some(Value::Compound(display)) => display,
-            _ => nbt::Compound::new(),
-        };
-
-        let r = color.0;
-        let g = color.1;
-        let b = color.2;
-
-        // Create a new display compound with the color
-        let mut new_display = display;
-        let color = (u32::from(r) << 16) | (u32::from(g) << 8) | u32::from(b);
-        let color = bytemuck::cast(color);
-        new_display.insert("color", Value::Int(color));
-
-        // Insert the updated display compound
-        nbt.insert("display", Value::Compound(new_display));
+    pub const fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
         self
     }
 
@@ -122,73 +228,214 @@ impl ItemBuilder {
 
     /// Sets a custom name for the item
     pub fn name(mut self, name: impl Into<String>) -> Self {
-        let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
+        let name = name.into();
+        self.name = Some(text_json(&Text::new(&name)));
+        self
+    }
 
-        // Create or get existing display compound
-        let display = match nbt.remove("display") {
-            Some(Value::Compound(display)) => display,
-            _ => nbt::Compound::new(),
-        };
+    /// Adds a line of text below the name of the item.
+    ///
+    /// # Example
+    /// ```
+    /// use hyperion::ItemKind;
+    /// use hyperion_item::builder::ItemBuilder;
+    /// use hyperion_text::Text;
+    ///
+    /// let item = ItemBuilder::new(ItemKind::IronPickaxe)
+    ///     .add_lore(Text::new("Mines twice as fast"))
+    ///     .build();
+    /// ```
+    pub fn add_lore<'a>(mut self, line: impl Into<Text<'a>>) -> Self {
+        self.lore.push(text_json(&line.into()));
+        self
+    }
+
+    pub const fn count(mut self, count: i8) -> Self {
+        self.count = count;
+        self
+    }
 
-        // Create a new display compound with the name
-        let mut new_display = display;
+    pub const fn handler(mut self, handler: Entity) -> Self {
+        self.handler = Some(handler);
+        self
+    }
 
-        let name = name.into();
+    /// Makes the item glint as if it was enchanted.
+    pub const fn glowing(mut self) -> Self {
+        self.glowing = true;
+        self
+    }
 
-        // '{"text":"Your Custom Name"}'
-        let name = format!(r#"{{"text":"{name}"}}"#);
+    /// Adds an enchantment such as `minecraft:sharpness`. An enchantment which was already added
+    /// is replaced.
+    pub fn enchantment(mut self, id: impl Into<String>, level: i16) -> Self {
+        let id = namespaced(id.into());
 
-        new_display.insert("Name", Value::String(name));
+        self.enchantments.retain(|(existing, _)| *existing != id);
+        self.enchantments.push((id, level));
+        self
+    }
 
-        // Insert the updated display compound
-        nbt.insert("display", Value::Compound(new_display));
+    /// Stops the item from losing durability.
+    pub const fn unbreakable(mut self) -> Self {
+        self.unbreakable = true;
         self
     }
 
-    pub const fn count(mut self, count: i8) -> Self {
-        self.count = count;
+    /// Sets how much of the item's durability has been used up.
+    pub const fn damage(mut self, damage: i32) -> Self {
+        self.damage = Some(damage);
         self
     }
 
-    pub fn handler(mut self, handler: Entity) -> Self {
-        let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
-        let id = handler.0;
+    /// Sets how much durability the item has left, out of the maximum of its kind.
+    pub fn durability(self, durability: u16) -> Self {
+        let max = self.kind.max_durability();
+        let damage = i32::from(max) - i32::from(durability.min(max));
+        self.damage(damage)
+    }
 
-        // we are explicitly casting to i64 because although sign might be lost, when we read it back,
-        // we will revert it back to a u64.
-        let id: i64 = bytemuck::cast(id);
-        nbt.insert("Handler", Value::Long(id));
+    /// Sets the model a resource pack shows for the item.
+    pub const fn custom_model_data(mut self, data: i32) -> Self {
+        self.custom_model_data = Some(data);
         self
     }
 
-    pub fn glowing(mut self) -> Self {
-        let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
-        nbt.insert(
-            "Enchantments",
-            Value::List(nbt::list::List::Compound(vec![nbt::Compound::new()])),
-        );
+    /// Hides parts of the item's tooltip. Flags can be combined with `|`.
+    pub const fn hide_flags(mut self, flags: HideFlags) -> Self {
+        self.hide_flags = Some(flags);
         self
     }
 
-    pub fn add_attribute(mut self, attribute: impl Attribute) -> Self {
-        let nbt = self.nbt.get_or_insert_with(nbt::Compound::new);
-        let mut modifiers = match nbt.remove("AttributeModifiers") {
-            Some(Value::List(nbt::list::List::Compound(modifiers))) => modifiers,
-            _ => Vec::new(),
-        };
+    /// Gives a player head the texture of `skin`.
+    pub fn head_skin(mut self, skin: &PlayerSkin) -> Self {
+        self.skull = Some(Skull::new(skin));
+        self
+    }
 
-        modifiers.push(attribute.create_modifier());
+    /// Adds an effect of a potion, splash potion, lingering potion or tipped arrow.
+    pub fn add_potion_effect(mut self, effect: PotionEffect) -> Self {
+        self.potion_effects.push(effect);
+        self
+    }
 
-        nbt.insert(
-            "AttributeModifiers",
-            Value::List(nbt::list::List::Compound(modifiers)),
-        );
+    pub fn add_attribute(mut self, attribute: impl Attribute) -> Self {
+        self.attributes.push(attribute.create_modifier());
         self
     }
 
     #[must_use]
     pub fn build(self) -> ItemStack {
-        ItemStack::new(self.kind, self.count, self.nbt)
+        let mut nbt = self.nbt.unwrap_or_default();
+
+        let mut display = match nbt.remove("display") {
+            Some(Value::Compound(display)) => display,
+            _ => nbt::Compound::new(),
+        };
+
+        if let Some(name) = self.name {
+            display.insert("Name", Value::String(name));
+        }
+
+        if !self.lore.is_empty() {
+            display.insert("Lore", Value::List(nbt::List::String(self.lore)));
+        }
+
+        if let Some(color) = self.color {
+            display.insert("color", Value::Int(bytemuck::cast(color.to_rgb())));
+        }
+
+        if !display.is_empty() {
+            nbt.insert("display", Value::Compound(display));
+        }
+
+        let enchantments: Vec<nbt::Compound<String>> = self
+            .enchantments
+            .into_iter()
+            .map(|(id, level)| {
+                let mut enchantment = nbt::Compound::new();
+                enchantment.insert("id", Value::String(id));
+                enchantment.insert("lvl", Value::Short(level));
+                enchantment
+            })
+            .collect();
+
+        if !enchantments.is_empty() {
+            nbt.insert(
+                "Enchantments",
+                Value::List(nbt::List::Compound(enchantments)),
+            );
+        } else if self.glowing {
+            // an enchantment the client does not know makes the item glint without a tooltip
+            nbt.insert(
+                "Enchantments",
+                Value::List(nbt::List::Compound(vec![nbt::Compound::new()])),
+            );
+        }
+
+        if self.unbreakable {
+            nbt.insert("Unbreakable", Value::Byte(1));
+        }
+
+        if let Some(damage) = self.damage {
+            nbt.insert("Damage", Value::Int(damage));
+        }
+
+        if let Some(data) = self.custom_model_data {
+            nbt.insert("CustomModelData", Value::Int(data));
+        }
+
+        if let Some(flags) = self.hide_flags {
+            nbt.insert("HideFlags", Value::Int(flags.0));
+        }
+
+        if let Some(skull) = self.skull {
+            nbt.insert("SkullOwner", Value::Compound(skull.to_nbt()));
+        }
+
+        if !self.potion_effects.is_empty() {
+            let effects = self
+                .potion_effects
+                .into_iter()
+                .map(PotionEffect::to_nbt)
+                .collect();
+
+            nbt.insert(
+                "CustomPotionEffects",
+                Value::List(nbt::List::Compound(effects)),
+            );
+        }
+
+        if !self.attributes.is_empty() {
+            nbt.insert(
+                "AttributeModifiers",
+                Value::List(nbt::List::Compound(self.attributes)),
+            );
+        }
+
+        if let Some(handler) = self.handler {
+            // we are explicitly casting to i64 because although sign might be lost, when we read
+            // it back, we will revert it back to a u64.
+            let id: i64 = bytemuck::cast(handler.0);
+            nbt.insert("Handler", Value::Long(id));
+        }
+
+        let nbt = (!nbt.is_empty()).then_some(nbt);
+
+        ItemStack::new(self.kind, self.count, nbt)
+    }
+}
+
+/// Text components are stored in NBT as JSON.
+fn text_json(text: &Text<'_>) -> String {
+    serde_json::to_string(text).unwrap()
+}
+
+fn namespaced(id: String) -> String {
+    if id.contains(':') {
+        id
+    } else {
+        format!("minecraft:{id}")
     }
 }
 
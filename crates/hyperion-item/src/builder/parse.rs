@@ -0,0 +1,277 @@
+use flecs_ecs::core::Entity;
+use hyperion::simulation::skin::PlayerSkin;
+use valence_protocol::{ItemStack, nbt, nbt::Value};
+
+use crate::builder::{Color, HideFlags, ItemBuilder, PotionEffect, Skull};
+
+/// Removes `key` from `nbt` if `parse` understands it. Tags which do not have the expected shape
+/// are left in `nbt` so that they are written back unchanged.
+fn take<T>(
+    nbt: &mut nbt::Compound<String>,
+    key: &str,
+    parse: impl FnOnce(&Value) -> Option<T>,
+) -> Option<T> {
+    let parsed = parse(nbt.get(key)?)?;
+    nbt.remove(key);
+    Some(parsed)
+}
+
+fn compounds(value: &Value) -> Option<&[nbt::Compound<String>]> {
+    match value {
+        Value::List(nbt::List::Compound(compounds)) => Some(compounds),
+        _ => None,
+    }
+}
+
+fn enchantment(compound: &nbt::Compound<String>) -> Option<(String, i16)> {
+    let Some(Value::String(id)) = compound.get("id") else {
+        return None;
+    };
+
+    let level = match compound.get("lvl")? {
+        Value::Short(level) => *level,
+        Value::Int(level) => i16::try_from(*level).ok()?,
+        _ => return None,
+    };
+
+    Some((id.clone(), level))
+}
+
+fn skull(value: &Value) -> Option<Skull> {
+    let Value::Compound(owner) = value else {
+        return None;
+    };
+
+    let Some(Value::IntArray(id)) = owner.get("Id") else {
+        return None;
+    };
+
+    let Some(Value::Compound(properties)) = owner.get("Properties") else {
+        return None;
+    };
+
+    let [texture] = compounds(properties.get("textures")?)? else {
+        return None;
+    };
+
+    let Some(Value::String(textures)) = texture.get("Value") else {
+        return None;
+    };
+
+    let signature = match texture.get("Signature") {
+        Some(Value::String(signature)) => signature.clone(),
+        None => String::new(),
+        Some(_) => return None,
+    };
+
+    // anything else, such as the name of the owner, cannot be written back
+    if owner.len() != 2
+        || properties.len() != 1
+        || texture.len() != 1 + usize::from(!signature.is_empty())
+    {
+        return None;
+    }
+
+    Some(Skull {
+        id: id.clone(),
+        skin: PlayerSkin::new(textures.clone(), signature),
+    })
+}
+
+impl ItemBuilder {
+    /// Creates a builder holding everything about `stack`, so that building it again gives the
+    /// same item. Tags without a builder method are kept as they are.
+    pub fn from_stack(stack: &ItemStack) -> Self {
+        let mut builder = Self::new(stack.item).count(stack.count);
+
+        let Some(mut nbt) = stack.nbt.clone() else {
+            return builder;
+        };
+
+        if let Some(Value::Compound(mut display)) = nbt.remove("display") {
+            builder.name = take(&mut display, "Name", |value| match value {
+                Value::String(name) => Some(name.clone()),
+                _ => None,
+            });
+
+            builder.lore = take(&mut display, "Lore", |value| match value {
+                Value::List(nbt::List::String(lore)) => Some(lore.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+            builder.color = take(&mut display, "color", |value| match value {
+                Value::Int(color) => Some(Color::from_rgb(bytemuck::cast(*color))),
+                _ => None,
+            });
+
+            if !display.is_empty() {
+                nbt.insert("display", Value::Compound(display));
+            }
+        }
+
+        let enchantments = take(&mut nbt, "Enchantments", |value| {
+            let compounds = compounds(value).filter(|compounds| !compounds.is_empty())?;
+
+            // the empty compound is the placeholder added by `glowing`
+            let glowing = compounds.iter().any(nbt::Compound::is_empty);
+
+            let enchantments: Option<Vec<_>> = compounds
+                .iter()
+                .filter(|compound| !compound.is_empty())
+                .map(enchantment)
+                .collect();
+
+            let enchantments = enchantments?;
+
+            // both cannot be written back at once
+            if glowing && !enchantments.is_empty() {
+                return None;
+            }
+
+            Some((glowing, enchantments))
+        });
+
+        if let Some((glowing, enchantments)) = enchantments {
+            builder.glowing = glowing;
+            builder.enchantments = enchantments;
+        }
+
+        builder.unbreakable = take(&mut nbt, "Unbreakable", |value| match value {
+            Value::Byte(1) => Some(true),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+        builder.damage = take(&mut nbt, "Damage", |value| match value {
+            Value::Int(damage) => Some(*damage),
+            _ => None,
+        });
+
+        builder.custom_model_data = take(&mut nbt, "CustomModelData", |value| match value {
+            Value::Int(data) => Some(*data),
+            _ => None,
+        });
+
+        builder.hide_flags = take(&mut nbt, "HideFlags", |value| match value {
+            Value::Int(flags) => Some(HideFlags(*flags)),
+            _ => None,
+        });
+
+        builder.skull = take(&mut nbt, "SkullOwner", skull);
+
+        builder.potion_effects = take(&mut nbt, "CustomPotionEffects", |value| {
+            compounds(value)?
+                .iter()
+                .map(PotionEffect::from_nbt)
+                .collect()
+        })
+        .unwrap_or_default();
+
+        builder.attributes = take(&mut nbt, "AttributeModifiers", |value| {
+            compounds(value).map(<[_]>::to_vec)
+        })
+        .unwrap_or_default();
+
+        builder.handler = take(&mut nbt, "Handler", |value| match value {
+            Value::Long(id) => Some(Entity(bytemuck::cast(*id))),
+            _ => None,
+        });
+
+        builder.nbt = Some(nbt);
+
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_protocol::ItemKind;
+
+    use super::*;
+    use crate::builder::{AttackDamage, StatusEffect};
+
+    fn roundtrip(stack: &ItemStack) {
+        let rebuilt = ItemBuilder::from_stack(stack).build();
+        assert_eq!(&rebuilt, stack);
+    }
+
+    #[test]
+    fn test_roundtrip_builder() {
+        let skin = PlayerSkin::new("dGV4dHVyZXM=".to_string(), "c2lnbmF0dXJl".to_string());
+
+        roundtrip(
+            &ItemBuilder::new(ItemKind::DiamondSword)
+                .name("Excalibur")
+                .add_lore("Forged in \"fire\"")
+                .add_lore("Second line")
+                .enchantment("sharpness", 5)
+                .enchantment("minecraft:unbreaking", 3)
+                .unbreakable()
+                .durability(1000)
+                .custom_model_data(7)
+                .hide_flags(HideFlags::ENCHANTMENTS | HideFlags::UNBREAKABLE)
+                .add_attribute(AttackDamage(9.0))
+                .handler(Entity(12345))
+                .build(),
+        );
+
+        roundtrip(
+            &ItemBuilder::new(ItemKind::LeatherBoots)
+                .color(Color(10, 20, 30))
+                .glowing()
+                .count(3)
+                .build(),
+        );
+
+        roundtrip(
+            &ItemBuilder::new(ItemKind::PlayerHead)
+                .head_skin(&skin)
+                .build(),
+        );
+
+        roundtrip(
+            &ItemBuilder::new(ItemKind::Potion)
+                .add_potion_effect(PotionEffect::new(StatusEffect::Speed, 1, 600))
+                .build(),
+        );
+
+        roundtrip(&ItemBuilder::new(ItemKind::Stone).build());
+    }
+
+    #[test]
+    fn test_unknown_tags_are_kept() {
+        let mut nbt = nbt::Compound::new();
+        nbt.insert("CustomTag", Value::String("kept".to_string()));
+        // the wrong type for the builder, so it is kept as it is
+        nbt.insert("Damage", Value::String("broken".to_string()));
+
+        let mut display = nbt::Compound::new();
+        display.insert("Name", Value::String(r#"{"text":"Kit"}"#.to_string()));
+        display.insert("Other", Value::Byte(1));
+        nbt.insert("display", Value::Compound(display));
+
+        let stack = ItemStack::new(ItemKind::Chest, 1, Some(nbt));
+
+        roundtrip(&stack);
+
+        let builder = ItemBuilder::from_stack(&stack);
+        assert_eq!(builder.name.as_deref(), Some(r#"{"text":"Kit"}"#));
+        assert!(builder.damage.is_none());
+    }
+
+    #[test]
+    fn test_parse_enchantments() {
+        let stack = ItemBuilder::new(ItemKind::Bow)
+            .enchantment("power", 2)
+            .enchantment("power", 4)
+            .build();
+
+        let builder = ItemBuilder::from_stack(&stack);
+        assert_eq!(builder.enchantments, vec![(
+            "minecraft:power".to_string(),
+            4
+        )]);
+        assert!(!builder.glowing);
+    }
+}
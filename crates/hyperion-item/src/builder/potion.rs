@@ -0,0 +1,150 @@
+use valence_protocol::{nbt, nbt::Value};
+
+/// A status effect, numbered as in the `Id` of a custom potion effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StatusEffect {
+    Speed = 1,
+    Slowness,
+    Haste,
+    MiningFatigue,
+    Strength,
+    InstantHealth,
+    InstantDamage,
+    JumpBoost,
+    Nausea,
+    Regeneration,
+    Resistance,
+    FireResistance,
+    WaterBreathing,
+    Invisibility,
+    Blindness,
+    NightVision,
+    Hunger,
+    Weakness,
+    Poison,
+    Wither,
+    HealthBoost,
+    Absorption,
+    Saturation,
+    Glowing,
+    Levitation,
+    Luck,
+    Unluck,
+    SlowFalling,
+    ConduitPower,
+    DolphinsGrace,
+    BadOmen,
+    HeroOfTheVillage,
+    Darkness,
+}
+
+impl StatusEffect {
+    const ALL: [Self; 33] = [
+        Self::Speed,
+        Self::Slowness,
+        Self::Haste,
+        Self::MiningFatigue,
+        Self::Strength,
+        Self::InstantHealth,
+        Self::InstantDamage,
+        Self::JumpBoost,
+        Self::Nausea,
+        Self::Regeneration,
+        Self::Resistance,
+        Self::FireResistance,
+        Self::WaterBreathing,
+        Self::Invisibility,
+        Self::Blindness,
+        Self::NightVision,
+        Self::Hunger,
+        Self::Weakness,
+        Self::Poison,
+        Self::Wither,
+        Self::HealthBoost,
+        Self::Absorption,
+        Self::Saturation,
+        Self::Glowing,
+        Self::Levitation,
+        Self::Luck,
+        Self::Unluck,
+        Self::SlowFalling,
+        Self::ConduitPower,
+        Self::DolphinsGrace,
+        Self::BadOmen,
+        Self::HeroOfTheVillage,
+        Self::Darkness,
+    ];
+
+    #[must_use]
+    pub const fn to_raw(self) -> i32 {
+        self as i32
+    }
+
+    #[must_use]
+    pub fn from_raw(id: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|effect| effect.to_raw() == id)
+    }
+}
+
+/// An effect given by drinking a potion or hit by a tipped arrow.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PotionEffect {
+    pub effect: StatusEffect,
+    /// The level of the effect minus one.
+    pub amplifier: u8,
+    /// The duration in ticks.
+    pub duration: i32,
+    pub ambient: bool,
+    pub show_particles: bool,
+    pub show_icon: bool,
+}
+
+impl PotionEffect {
+    #[must_use]
+    pub const fn new(effect: StatusEffect, amplifier: u8, duration: i32) -> Self {
+        Self {
+            effect,
+            amplifier,
+            duration,
+            ambient: false,
+            show_particles: true,
+            show_icon: true,
+        }
+    }
+
+    pub(crate) fn to_nbt(self) -> nbt::Compound<String> {
+        let mut compound = nbt::Compound::new();
+        compound.insert("Id", Value::Int(self.effect.to_raw()));
+        compound.insert("Amplifier", Value::Byte(bytemuck::cast(self.amplifier)));
+        compound.insert("Duration", Value::Int(self.duration));
+        compound.insert("Ambient", Value::Byte(i8::from(self.ambient)));
+        compound.insert("ShowParticles", Value::Byte(i8::from(self.show_particles)));
+        compound.insert("ShowIcon", Value::Byte(i8::from(self.show_icon)));
+        compound
+    }
+
+    pub(crate) fn from_nbt(compound: &nbt::Compound<String>) -> Option<Self> {
+        let byte = |key: &str, default: i8| match compound.get(key) {
+            Some(Value::Byte(value)) => Some(*value),
+            None => Some(default),
+            Some(_) => None,
+        };
+
+        let Some(Value::Int(id)) = compound.get("Id") else {
+            return None;
+        };
+
+        let Some(Value::Int(duration)) = compound.get("Duration") else {
+            return None;
+        };
+
+        Some(Self {
+            effect: StatusEffect::from_raw(*id)?,
+            amplifier: bytemuck::cast(byte("Amplifier", 0)?),
+            duration: *duration,
+            ambient: byte("Ambient", 0)? != 0,
+            show_particles: byte("ShowParticles", 1)? != 0,
+            show_icon: byte("ShowIcon", 1)? != 0,
+        })
+    }
+}
@@ -1,59 +1,373 @@
-use derive_more::{Constructor, Deref, DerefMut};
 use flecs_ecs::{
-    core::{EntityViewGet, World, WorldGet},
-    macros::Component,
+    core::{EntityView, EntityViewGet, World, WorldGet},
+    macros::{Component, system},
     prelude::Module,
 };
-use hyperion::storage::{EventFn, GlobalEventHandlers, InteractEvent};
-use valence_protocol::nbt;
+use hyperion::{
+    simulation::handlers::PacketSwitchQuery,
+    storage::{
+        AttackEntityEvent, DigBlockEvent, DropItemEvent, EventFn, GlobalEventHandlers,
+        InteractBlockEvent, InteractEvent, SelectSlotEvent,
+    },
+};
+use hyperion_inventory::{OFFHAND_SLOT, PlayerInventory};
+use valence_protocol::{Hand, ItemKind, ItemStack, nbt};
 
 pub mod builder;
 
+/// A callback of an item which does not run in response to a packet, given the player holding
+/// the item.
+pub type HeldFn<T> = Box<dyn Fn(EntityView<'_>, &T) + 'static + Send + Sync>;
+
+/// The ticks it takes to eat food or drink a potion.
+pub const CONSUME_TICKS: u16 = 32;
+
+/// The player held the item for another tick.
+pub struct HeldTickEvent {
+    /// The inventory slot of the item.
+    pub slot: u16,
+}
+
+/// The player finished eating or drinking the item.
+pub struct ConsumeEvent {
+    pub hand: Hand,
+    /// The inventory slot of the item.
+    pub slot: u16,
+}
+
 #[derive(Component)]
 pub struct ItemModule;
 
-#[derive(Component, Constructor, Deref, DerefMut)]
+/// The callbacks of an item, which is linked to the item by the `Handler` tag holding the id of
+/// the entity with this component. See [`builder::ItemBuilder::handler`].
+#[derive(Component, Default)]
 pub struct Handler {
-    on_click: EventFn<InteractEvent>,
+    on_click: Option<EventFn<InteractEvent>>,
+    on_interact_block: Option<EventFn<InteractBlockEvent>>,
+    on_dig_block: Option<EventFn<DigBlockEvent>>,
+    on_attack_entity: Option<EventFn<AttackEntityEvent>>,
+    on_drop: Option<EventFn<DropItemEvent>>,
+    on_select: Option<EventFn<SelectSlotEvent>>,
+    on_consume: Option<HeldFn<ConsumeEvent>>,
+    on_held_tick: Option<HeldFn<HeldTickEvent>>,
+}
+
+impl Handler {
+    /// Creates a handler which only handles right-clicking with the item.
+    pub fn new(
+        on_click: impl Fn(&mut PacketSwitchQuery<'_>, &InteractEvent) + 'static + Send + Sync,
+    ) -> Self {
+        Self::default().on_click(on_click)
+    }
+
+    /// Called when the item is right-clicked in the air.
+    #[must_use]
+    pub fn on_click(
+        mut self,
+        handler: impl Fn(&mut PacketSwitchQuery<'_>, &InteractEvent) + 'static + Send + Sync,
+    ) -> Self {
+        self.on_click = Some(Box::new(handler));
+        self
+    }
+
+    /// Called when a block is right-clicked with the item, before the block is used or placed
+    /// against.
+    #[must_use]
+    pub fn on_interact_block(
+        mut self,
+        handler: impl Fn(&mut PacketSwitchQuery<'_>, &InteractBlockEvent) + 'static + Send + Sync,
+    ) -> Self {
+        self.on_interact_block = Some(Box::new(handler));
+        self
+    }
+
+    /// Called when a block is left-clicked with the item.
+    #[must_use]
+    pub fn on_dig_block(
+        mut self,
+        handler: impl Fn(&mut PacketSwitchQuery<'_>, &DigBlockEvent) + 'static + Send + Sync,
+    ) -> Self {
+        self.on_dig_block = Some(Box::new(handler));
+        self
+    }
+
+    /// Called when an entity is left-clicked with the item, before the attack is handled.
+    #[must_use]
+    pub fn on_attack_entity(
+        mut self,
+        handler: impl Fn(&mut PacketSwitchQuery<'_>, &AttackEntityEvent) + 'static + Send + Sync,
+    ) -> Self {
+        self.on_attack_entity = Some(Box::new(handler));
+        self
+    }
+
+    /// Called when the item is dropped.
+    #[must_use]
+    pub fn on_drop(
+        mut self,
+        handler: impl Fn(&mut PacketSwitchQuery<'_>, &DropItemEvent) + 'static + Send + Sync,
+    ) -> Self {
+        self.on_drop = Some(Box::new(handler));
+        self
+    }
+
+    /// Called when the hotbar slot with the item is selected.
+    #[must_use]
+    pub fn on_select(
+        mut self,
+        handler: impl Fn(&mut PacketSwitchQuery<'_>, &SelectSlotEvent) + 'static + Send + Sync,
+    ) -> Self {
+        self.on_select = Some(Box::new(handler));
+        self
+    }
+
+    /// Called once the item has been used for [`CONSUME_TICKS`] without being released, moved or
+    /// switched away from. Only potions, milk, honey bottles and food which may be eaten on a full
+    /// hunger bar, such as golden apples, are consumed.
+    #[must_use]
+    pub fn on_consume(
+        mut self,
+        handler: impl Fn(EntityView<'_>, &ConsumeEvent) + 'static + Send + Sync,
+    ) -> Self {
+        self.on_consume = Some(Box::new(handler));
+        self
+    }
+
+    /// Called every tick the item is in the main hand.
+    #[must_use]
+    pub fn on_held_tick(
+        mut self,
+        handler: impl Fn(EntityView<'_>, &HeldTickEvent) + 'static + Send + Sync,
+    ) -> Self {
+        self.on_held_tick = Some(Box::new(handler));
+        self
+    }
+}
+
+/// An item with a [`Handler::on_consume`] being eaten or drunk.
+#[derive(Component)]
+struct Consuming {
+    handler: u64,
+    hand: Hand,
+    slot: u16,
+    ticks_left: u16,
+}
+
+/// Whether the client starts eating or drinking `kind` when it is used. Players are always told
+/// their hunger bar is full, so food is only eaten if it may be eaten regardless of hunger.
+const fn is_consumable(kind: ItemKind) -> bool {
+    matches!(
+        kind,
+        ItemKind::Potion
+            | ItemKind::MilkBucket
+            | ItemKind::HoneyBottle
+            | ItemKind::GoldenApple
+            | ItemKind::EnchantedGoldenApple
+            | ItemKind::ChorusFruit
+            | ItemKind::SuspiciousStew
+    )
+}
+
+/// The id of the entity with the [`Handler`] of `stack`.
+fn handler_id(stack: &ItemStack) -> Option<u64> {
+    if stack.is_empty() {
+        return None;
+    }
+
+    let nbt::Value::Long(id) = stack.nbt.as_ref()?.get("Handler")? else {
+        return None;
+    };
+
+    Some(bytemuck::cast(*id))
+}
+
+const fn hand_slot(inventory: &PlayerInventory, hand: Hand) -> u16 {
+    match hand {
+        Hand::Main => inventory.get_cursor_index(),
+        Hand::Off => OFFHAND_SLOT,
+    }
+}
+
+/// Calls the callback `hook` picks from the handler of the item in `slot`.
+fn dispatch<T>(
+    query: &mut PacketSwitchQuery<'_>,
+    slot: u16,
+    event: &T,
+    hook: impl FnOnce(&Handler) -> Option<&EventFn<T>>,
+) {
+    let Some(id) = query.inventory.get(slot).ok().and_then(handler_id) else {
+        return;
+    };
+
+    let world = query.world;
+
+    world.entity_from_id(id).try_get::<&Handler>(|handler| {
+        if let Some(on_event) = hook(handler) {
+            on_event(query, event);
+        }
+    });
 }
 
 impl Module for ItemModule {
     fn module(world: &World) {
         world.import::<hyperion_inventory::InventoryModule>();
         world.component::<Handler>();
+        world.component::<Consuming>();
 
         world.get::<&mut GlobalEventHandlers>(|handlers| {
             handlers.interact.register(|query, event| {
-                let world = query.world;
-                let inventory = &mut *query.inventory;
-
-                let stack = inventory.get_cursor();
-
-                if stack.is_empty() {
-                    return;
-                }
+                let slot = hand_slot(query.inventory, event.hand);
 
-                let Some(nbt) = stack.nbt.as_ref() else {
+                let Ok(stack) = query.inventory.get(slot) else {
                     return;
                 };
 
-                let Some(handler) = nbt.get("Handler") else {
+                let Some(id) = handler_id(stack) else {
                     return;
                 };
 
-                let nbt::Value::Long(id) = handler else {
-                    return;
-                };
+                let consumable = is_consumable(stack.item);
+                let world = query.world;
+
+                world.entity_from_id(id).try_get::<&Handler>(|handler| {
+                    if consumable && handler.on_consume.is_some() {
+                        query.view.set(Consuming {
+                            handler: id,
+                            hand: event.hand,
+                            slot,
+                            ticks_left: CONSUME_TICKS,
+                        });
+                    }
+
+                    if let Some(on_click) = &handler.on_click {
+                        on_click(query, event);
+                    }
+                });
+            });
 
-                let id: u64 = bytemuck::cast(*id);
+            handlers.interact_block.register(|query, event| {
+                let slot = hand_slot(query.inventory, event.hand);
+                dispatch(query, slot, event, |handler| {
+                    handler.on_interact_block.as_ref()
+                });
+            });
 
-                let handler = world.entity_from_id(id);
+            handlers.dig_block.register(|query, event| {
+                let slot = query.inventory.get_cursor_index();
+                dispatch(query, slot, event, |handler| handler.on_dig_block.as_ref());
+            });
 
-                handler.try_get::<&Handler>(|handler| {
-                    let on_interact = &handler.on_click;
-                    on_interact(query, event);
+            handlers.attack_entity.register(|query, event| {
+                let slot = query.inventory.get_cursor_index();
+                dispatch(query, slot, event, |handler| {
+                    handler.on_attack_entity.as_ref()
                 });
             });
+
+            handlers.drop_item.register(|query, event| {
+                dispatch(query, event.slot, event, |handler| handler.on_drop.as_ref());
+            });
+
+            handlers.select_slot.register(|query, event| {
+                query.view.remove::<Consuming>();
+
+                let slot = event.slot + PlayerInventory::HOTBAR_START_SLOT;
+                dispatch(query, slot, event, |handler| handler.on_select.as_ref());
+            });
+
+            handlers.release_use_item.register(|query, _| {
+                query.view.remove::<Consuming>();
+            });
+        });
+
+        system!("item_held_tick", world, &PlayerInventory).each_iter(|it, row, inventory| {
+            let Some(id) = handler_id(inventory.get_cursor()) else {
+                return;
+            };
+
+            let event = HeldTickEvent {
+                slot: inventory.get_cursor_index(),
+            };
+
+            let player = it.entity(row);
+
+            it.world()
+                .entity_from_id(id)
+                .try_get::<&Handler>(|handler| {
+                    if let Some(on_held_tick) = &handler.on_held_tick {
+                        on_held_tick(player, &event);
+                    }
+                });
         });
+
+        system!("consume_items", world, &mut Consuming, &PlayerInventory).each_iter(
+            |it, row, (consuming, inventory)| {
+                let player = it.entity(row);
+
+                // the item may have been moved, dropped or replaced without switching slots
+                let held = inventory
+                    .get(consuming.slot)
+                    .is_ok_and(|stack| handler_id(stack) == Some(consuming.handler));
+
+                if !held {
+                    player.remove::<Consuming>();
+                    return;
+                }
+
+                consuming.ticks_left = consuming.ticks_left.saturating_sub(1);
+
+                if consuming.ticks_left > 0 {
+                    return;
+                }
+
+                let event = ConsumeEvent {
+                    hand: consuming.hand,
+                    slot: consuming.slot,
+                };
+
+                let handler = consuming.handler;
+
+                player.remove::<Consuming>();
+
+                it.world()
+                    .entity_from_id(handler)
+                    .try_get::<&Handler>(|handler| {
+                        if let Some(on_consume) = &handler.on_consume {
+                            on_consume(player, &event);
+                        }
+                    });
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flecs_ecs::core::Entity;
+    use valence_protocol::ItemKind;
+
+    use super::*;
+    use crate::builder::ItemBuilder;
+
+    #[test]
+    fn test_handler_id() {
+        let stack = ItemBuilder::new(ItemKind::Stick)
+            .handler(Entity(42))
+            .build();
+        assert_eq!(handler_id(&stack), Some(42));
+
+        let plain = ItemBuilder::new(ItemKind::Stick).build();
+        assert_eq!(handler_id(&plain), None);
+        assert_eq!(handler_id(&ItemStack::EMPTY), None);
+    }
+
+    #[test]
+    fn test_is_consumable() {
+        assert!(is_consumable(ItemKind::Potion));
+        assert!(is_consumable(ItemKind::GoldenApple));
+
+        // eaten only when hungry, which players never are
+        assert!(!is_consumable(ItemKind::Bread));
+        assert!(!is_consumable(ItemKind::Stick));
     }
 }
@@ -0,0 +1,223 @@
+//! Groups of players sharing permissions, and the permissions of each player.
+
+use std::collections::{BTreeMap, HashMap};
+
+use flecs_ecs::macros::Component;
+use serde::{Deserialize, Serialize};
+
+use crate::node;
+
+/// The group every player is in, after the groups they were added to.
+pub const DEFAULT_GROUP: &str = "default";
+
+/// A group of players sharing permissions. Members also have the permissions of its parents,
+/// unless the group itself grants or denies them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Group {
+    /// The groups this group inherits from, in the order they are checked.
+    #[serde(default)]
+    pub parents: Vec<String>,
+    /// The nodes the group grants (`true`) or denies (`false`).
+    #[serde(default)]
+    pub nodes: BTreeMap<String, bool>,
+}
+
+/// Whether the group `name` or its ancestors grant or deny `node`, or `None` if none of them
+/// decide. Groups already in `visited` are skipped, so cycles of parents end.
+fn decide_group<'a>(
+    groups: &'a HashMap<String, Group>,
+    name: &'a str,
+    node: &str,
+    visited: &mut Vec<&'a str>,
+) -> Option<bool> {
+    if visited.contains(&name) {
+        return None;
+    }
+
+    visited.push(name);
+
+    let group = groups.get(name)?;
+
+    node::decide(&group.nodes, node).or_else(|| {
+        group
+            .parents
+            .iter()
+            .find_map(|parent| decide_group(groups, parent, node, visited))
+    })
+}
+
+/// Whether the group `name` inherits from `ancestor`, directly or through its parents.
+#[must_use]
+pub fn inherits(groups: &HashMap<String, Group>, name: &str, ancestor: &str) -> bool {
+    let mut stack = vec![name];
+    let mut visited = Vec::new();
+
+    while let Some(name) = stack.pop() {
+        if visited.contains(&name) {
+            continue;
+        }
+
+        visited.push(name);
+
+        let Some(group) = groups.get(name) else {
+            continue;
+        };
+
+        if group.parents.iter().any(|parent| parent == ancestor) {
+            return true;
+        }
+
+        stack.extend(group.parents.iter().map(String::as_str));
+    }
+
+    false
+}
+
+/// The permissions of a player: the groups they are in, and nodes granted or denied to them
+/// alone, which take precedence over those of their groups.
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize
+)]
+pub struct Permissions {
+    /// The groups the player was added to, in the order they are checked. Every player is also
+    /// in [`DEFAULT_GROUP`].
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// The nodes granted (`true`) or denied (`false`) to the player.
+    #[serde(default)]
+    pub nodes: BTreeMap<String, bool>,
+}
+
+impl Permissions {
+    /// Every node, such as for the console.
+    #[must_use]
+    pub fn all() -> Self {
+        Self {
+            groups: Vec::new(),
+            nodes: BTreeMap::from([("*".to_owned(), true)]),
+        }
+    }
+
+    /// Whether the player has `node`. The player's own nodes are checked first, then each of
+    /// their groups and its ancestors in order, and the first to grant or deny it decides.
+    #[must_use]
+    pub fn has(&self, groups: &HashMap<String, Group>, node: &str) -> bool {
+        node::decide(&self.nodes, node)
+            .or_else(|| {
+                let mut visited = Vec::new();
+
+                self.groups
+                    .iter()
+                    .map(String::as_str)
+                    .chain([DEFAULT_GROUP])
+                    .find_map(|group| decide_group(groups, group, node, &mut visited))
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(parents: &[&str], nodes: &[(&str, bool)]) -> Group {
+        Group {
+            parents: parents.iter().map(ToString::to_string).collect(),
+            nodes: nodes
+                .iter()
+                .map(|(node, granted)| ((*node).to_owned(), *granted))
+                .collect(),
+        }
+    }
+
+    fn groups() -> HashMap<String, Group> {
+        HashMap::from([
+            (
+                DEFAULT_GROUP.to_owned(),
+                group(&[], &[("tag.command.class", true)]),
+            ),
+            (
+                "moderator".to_owned(),
+                group(&[DEFAULT_GROUP], &[
+                    ("tag.command.*", true),
+                    ("tag.command.xp", false),
+                ]),
+            ),
+            ("admin".to_owned(), group(&["moderator"], &[("*", true)])),
+        ])
+    }
+
+    #[test]
+    fn test_default_group() {
+        let groups = groups();
+        let player = Permissions::default();
+
+        assert!(player.has(&groups, "tag.command.class"));
+        assert!(!player.has(&groups, "tag.command.vanish"));
+    }
+
+    #[test]
+    fn test_inheritance() {
+        let groups = groups();
+
+        let moderator = Permissions {
+            groups: vec!["moderator".to_owned()],
+            ..Permissions::default()
+        };
+
+        assert!(moderator.has(&groups, "tag.command.vanish"));
+        assert!(moderator.has(&groups, "tag.command.class"));
+        assert!(!moderator.has(&groups, "tag.command.xp"));
+
+        let admin = Permissions {
+            groups: vec!["admin".to_owned()],
+            ..Permissions::default()
+        };
+
+        assert!(admin.has(&groups, "tag.command.xp"));
+        assert!(admin.has(&groups, "hyperion.command.perms"));
+    }
+
+    #[test]
+    fn test_player_overrides() {
+        let groups = groups();
+
+        let player = Permissions {
+            groups: vec!["moderator".to_owned()],
+            nodes: BTreeMap::from([
+                ("tag.command.xp".to_owned(), true),
+                ("tag.command.fly".to_owned(), false),
+            ]),
+        };
+
+        assert!(player.has(&groups, "tag.command.xp"));
+        assert!(!player.has(&groups, "tag.command.fly"));
+        assert!(player.has(&groups, "tag.command.vanish"));
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut groups = groups();
+        groups.insert("a".to_owned(), group(&["b"], &[]));
+        groups.insert("b".to_owned(), group(&["a"], &[]));
+
+        let player = Permissions {
+            groups: vec!["a".to_owned()],
+            ..Permissions::default()
+        };
+
+        assert!(player.has(&groups, "tag.command.class"));
+        assert!(!player.has(&groups, "tag.command.fly"));
+
+        assert!(inherits(&groups, "a", "b"));
+        assert!(inherits(&groups, "admin", DEFAULT_GROUP));
+        assert!(!inherits(&groups, "moderator", "admin"));
+    }
+}
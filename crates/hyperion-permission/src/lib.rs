@@ -1,81 +1,122 @@
-use clap::ValueEnum;
+//! Permission nodes, such as `tag.command.fly`, which players are granted by the groups they are
+//! in or by themselves.
+//!
+//! Groups are defined by operators at runtime and inherit the nodes of their parents. Both groups
+//! and the [`Permissions`] of each player are kept in [`LocalDb`]. Nodes may be wildcards, such
+//! as `tag.*`, and the most specific node which matches decides, so a group may grant `tag.*`
+//! but deny `tag.command.xp`.
+
 use flecs_ecs::{
-    core::{EntityViewGet, QueryBuilderImpl, SystemAPI, TermBuilderImpl, World, WorldGet},
+    core::{
+        Builder, EntityView, EntityViewGet, QueryAPI, QueryBuilderImpl, SystemAPI, TermBuilderImpl,
+        World, WorldGet, WorldProvider,
+    },
     macros::{Component, observer},
     prelude::{Module, flecs},
 };
 use hyperion::{
     net::{Compose, ConnectionId},
-    simulation::{Player, Uuid, command::get_command_packet},
+    simulation::{Player, Uuid, command::get_command_packet, console::get_console_entity},
     storage::LocalDb,
 };
-use num_derive::{FromPrimitive, ToPrimitive};
+
+mod group;
+pub mod node;
+mod storage;
+
+pub use group::{DEFAULT_GROUP, Group, Permissions};
+pub use storage::PermissionStorage;
 
 #[derive(Component)]
 pub struct PermissionModule;
 
-mod storage;
-
-#[derive(
-    Default,
-    Component,
-    FromPrimitive,
-    ToPrimitive,
-    Copy,
-    Clone,
-    Debug,
-    PartialEq,
-    ValueEnum,
-    Eq
-)]
-#[repr(C)]
-pub enum Group {
-    Banned,
-    #[default]
-    Normal,
-    Moderator,
-    Admin,
+/// Whether `entity` has the permission `node`. Entities without [`Permissions`] have none.
+#[must_use]
+pub fn has_permission(entity: EntityView<'_>, node: &str) -> bool {
+    entity.world().get::<&PermissionStorage>(|storage| {
+        entity
+            .try_get::<&Permissions>(|permissions| storage.has(permissions, node))
+            .unwrap_or(false)
+    })
 }
 
-// todo:
+/// Sends every player the commands they may run again, such as after a group changed.
+pub fn refresh_commands(world: &World) {
+    world
+        .query::<()>()
+        .with::<Permissions>()
+        .with::<Player>()
+        .build()
+        .each_entity(|entity, ()| {
+            entity.modified::<Permissions>();
+        });
+}
 
 impl Module for PermissionModule {
     fn module(world: &World) {
-        world.component::<Group>();
-        world.component::<storage::PermissionStorage>();
+        world.component::<Permissions>();
+        world.component::<PermissionStorage>();
 
         world.get::<&LocalDb>(|db| {
-            let storage = storage::PermissionStorage::new(db).unwrap();
+            let mut storage = PermissionStorage::new(db).unwrap();
+
+            storage.seed_group(DEFAULT_GROUP, Group::default()).unwrap();
+            storage
+                .seed_group("moderator", Group {
+                    parents: vec![DEFAULT_GROUP.to_owned()],
+                    ..Group::default()
+                })
+                .unwrap();
+            storage
+                .seed_group("admin", Group {
+                    parents: vec!["moderator".to_owned()],
+                    nodes: [("*".to_owned(), true)].into(),
+                })
+                .unwrap();
+
             world.set(storage);
         });
 
-        observer!(world, flecs::OnSet, &Uuid, &storage::PermissionStorage($))
+        observer!(world, flecs::OnSet, &Uuid, &PermissionStorage($))
             .with::<Player>()
-            .each_entity(|entity, (uuid, permissions)| {
-                let group = permissions.get(**uuid);
-                entity.set(group);
+            .each_entity(|entity, (uuid, storage)| match storage.get(**uuid) {
+                Ok(permissions) => {
+                    entity.set(permissions);
+                }
+                Err(e) => {
+                    tracing::error!("failed to load permissions of {}: {e}", **uuid);
+                    entity.set(Permissions::default());
+                }
             });
 
-        observer!(world, flecs::OnRemove, &Uuid, &Group, &storage::PermissionStorage($))
+        observer!(world, flecs::OnRemove, &Uuid, &Permissions, &PermissionStorage($))
             .with::<Player>()
-            .each(|(uuid, group, permissions)| {
-                permissions.set(**uuid, *group).unwrap();
+            .each(|(uuid, permissions, storage)| {
+                if let Err(e) = storage.set(**uuid, permissions) {
+                    tracing::error!("failed to save permissions of {}: {e}", **uuid);
+                }
             });
 
-        observer!(world, flecs::OnSet, &Group).each_iter(|it, row, _group| {
+        observer!(world, flecs::OnSet, &Permissions).each_iter(|it, row, _permissions| {
             let system = it.system();
             let world = it.world();
             let entity = it.entity(row);
 
-            let root_command = hyperion::simulation::command::get_root_command_entity();
+            // the console has no client to send the commands it may run to
+            entity.try_get::<&ConnectionId>(|stream| {
+                let root_command = hyperion::simulation::command::get_root_command_entity();
 
-            let cmd_pkt = get_command_packet(&world, root_command, Some(*entity));
+                let cmd_pkt = get_command_packet(&world, root_command, Some(*entity));
 
-            entity.get::<&ConnectionId>(|stream| {
                 world.get::<&Compose>(|compose| {
                     compose.unicast(&cmd_pkt, *stream, system).unwrap();
                 });
             });
         });
+
+        // the console may run every command
+        get_console_entity()
+            .entity_view(world)
+            .set(Permissions::all());
     }
 }
@@ -0,0 +1,102 @@
+//! Permission nodes, such as `tag.command.fly`, and the wildcards which grant or deny many of
+//! them at once, such as `tag.*` or `*`.
+
+use std::collections::BTreeMap;
+
+/// Whether `node` is a permission node or a wildcard: lowercase segments of letters, digits, `_`
+/// and `-` separated by dots, where the last segment may be `*`.
+#[must_use]
+pub fn is_valid(node: &str) -> bool {
+    let mut segments = node.split('.').peekable();
+
+    while let Some(segment) = segments.next() {
+        let last = segments.peek().is_none();
+
+        let valid = (last && segment == "*")
+            || (!segment.is_empty()
+                && segment
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"_-".contains(&b)));
+
+        if !valid {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// How specifically `pattern` matches `node`, or `None` if it does not match it. `node` itself
+/// is the most specific, followed by wildcards with more segments before the `*`, so `tag.*`
+/// matches `tag.command.fly` more specifically than `*`.
+///
+/// Wildcards only match the nodes under them, so `tag.*` does not match `tag`.
+fn specificity(pattern: &str, node: &str) -> Option<usize> {
+    if pattern == node {
+        return Some(usize::MAX);
+    }
+
+    if pattern == "*" {
+        return Some(0);
+    }
+
+    let prefix = pattern.strip_suffix(".*")?;
+
+    node.strip_prefix(prefix)?
+        .starts_with('.')
+        .then(|| prefix.split('.').count())
+}
+
+/// Whether `nodes` grant or deny `node`, from the most specific of them which matches it, or
+/// `None` if none of them match it.
+#[must_use]
+pub fn decide(nodes: &BTreeMap<String, bool>, node: &str) -> Option<bool> {
+    nodes
+        .iter()
+        .filter_map(|(pattern, granted)| Some((specificity(pattern, node)?, *granted)))
+        .max_by_key(|(specificity, _)| *specificity)
+        .map(|(_, granted)| granted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid() {
+        assert!(is_valid("tag.command.fly"));
+        assert!(is_valid("tag.*"));
+        assert!(is_valid("*"));
+        assert!(is_valid("hyperion.command.perms"));
+
+        assert!(!is_valid(""));
+        assert!(!is_valid("tag..fly"));
+        assert!(!is_valid("tag.*.fly"));
+        assert!(!is_valid("Tag.fly"));
+        assert!(!is_valid("tag fly"));
+    }
+
+    #[test]
+    fn test_decide_most_specific() {
+        let nodes = BTreeMap::from([
+            ("*".to_owned(), true),
+            ("tag.*".to_owned(), false),
+            ("tag.command.fly".to_owned(), true),
+        ]);
+
+        assert_eq!(decide(&nodes, "tag.command.fly"), Some(true));
+        assert_eq!(decide(&nodes, "tag.command.xp"), Some(false));
+        assert_eq!(decide(&nodes, "tag"), Some(true));
+        assert_eq!(decide(&nodes, "hyperion.command.perms"), Some(true));
+    }
+
+    #[test]
+    fn test_decide_unmatched() {
+        let nodes = BTreeMap::from([("tag.command.*".to_owned(), true)]);
+
+        assert_eq!(decide(&nodes, "tag.command.fly"), Some(true));
+        assert_eq!(decide(&nodes, "tag.commands"), None);
+        assert_eq!(decide(&nodes, "tag.command"), None);
+        assert_eq!(decide(&BTreeMap::new(), "tag.command.fly"), None);
+    }
+}
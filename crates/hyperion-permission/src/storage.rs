@@ -1,52 +1,185 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Context;
 use flecs_ecs::macros::Component;
 use heed::{Database, Env, byteorder::NativeEndian, types};
 use hyperion::storage::LocalDb;
-use num_traits::{FromPrimitive, ToPrimitive};
 
-use crate::Group;
+use crate::{
+    DEFAULT_GROUP,
+    group::{self, Group, Permissions},
+};
 
+/// The permissions of a player with the group `legacy` from before permission nodes. Banned
+/// players were refused every command, so they are denied every node.
+fn from_legacy(legacy: Option<u8>) -> Permissions {
+    match legacy {
+        Some(0) => Permissions {
+            groups: Vec::new(),
+            nodes: BTreeMap::from([("*".to_owned(), false)]),
+        },
+        Some(2) => Permissions {
+            groups: vec!["moderator".to_owned()],
+            ..Permissions::default()
+        },
+        Some(3) => Permissions {
+            groups: vec!["admin".to_owned()],
+            ..Permissions::default()
+        },
+        _ => Permissions::default(),
+    }
+}
+
+/// A singleton with the groups of the server, which keeps them and the permissions of players in
+/// [`LocalDb`].
 #[derive(Component)]
 pub struct PermissionStorage {
     env: Env,
-    perms: Database<types::U128<NativeEndian>, types::U8>,
+    players: Database<types::U128<NativeEndian>, types::SerdeJson<Permissions>>,
+    group_db: Database<types::Str, types::SerdeJson<Group>>,
+    /// The groups of players from before permission nodes, as `0` for banned, `1` for normal,
+    /// `2` for moderator and `3` for admin.
+    legacy: Database<types::U128<NativeEndian>, types::U8>,
+    groups: HashMap<String, Group>,
+    /// Whether no groups were stored when the server started.
+    first_start: bool,
 }
 
 impl PermissionStorage {
     pub fn new(db: &LocalDb) -> anyhow::Result<Self> {
-        // We open the default unnamed database
-        let perms = {
-            let mut wtxn = db.write_txn()?;
-            let db = db.create_database(&mut wtxn, Some("uuid-to-perms"))?;
-            wtxn.commit()?;
-            db
-        };
+        let mut wtxn = db.write_txn()?;
+        let players = db.create_database(&mut wtxn, Some("uuid-to-permissions"))?;
+        let group_db = db.create_database(&mut wtxn, Some("permission-groups"))?;
+        let legacy = db.create_database(&mut wtxn, Some("uuid-to-perms"))?;
+        wtxn.commit()?;
+
+        let rtxn = db.read_txn()?;
+        let groups = group_db
+            .iter(&rtxn)?
+            .map(|entry| entry.map(|(name, group)| (name.to_owned(), group)))
+            .collect::<Result<HashMap<_, _>, _>>()
+            .context("failed to load permission groups")?;
+        drop(rtxn);
 
         Ok(Self {
             env: (**db).clone(),
-            perms,
+            players,
+            group_db,
+            legacy,
+            first_start: groups.is_empty(),
+            groups,
         })
     }
 
-    pub fn get(&self, uuid: uuid::Uuid) -> Group {
+    /// The permissions of the player with `uuid`.
+    pub fn get(&self, uuid: uuid::Uuid) -> anyhow::Result<Permissions> {
         let uuid = uuid.as_u128();
-        let rtxn = self.env.read_txn().unwrap();
-        let Some(perms) = self.perms.get(&rtxn, &uuid).unwrap() else {
-            return Group::default();
-        };
+        let rtxn = self.env.read_txn()?;
 
-        let Some(group) = Group::from_u8(perms) else {
-            tracing::error!("invalid group {perms:?}");
-            return Group::default();
-        };
+        if let Some(permissions) = self.players.get(&rtxn, &uuid)? {
+            return Ok(permissions);
+        }
 
-        group
+        Ok(from_legacy(self.legacy.get(&rtxn, &uuid)?))
     }
 
-    pub fn set(&self, uuid: uuid::Uuid, group: Group) -> anyhow::Result<()> {
+    /// Stores the permissions of the player with `uuid`.
+    pub fn set(&self, uuid: uuid::Uuid, permissions: &Permissions) -> anyhow::Result<()> {
         let uuid = uuid.as_u128();
         let mut wtxn = self.env.write_txn()?;
-        self.perms.put(&mut wtxn, &uuid, &group.to_u8().unwrap())?;
+        self.players.put(&mut wtxn, &uuid, permissions)?;
+        wtxn.commit()?;
+        Ok(())
+    }
+
+    /// The groups by name.
+    #[must_use]
+    pub const fn groups(&self) -> &HashMap<String, Group> {
+        &self.groups
+    }
+
+    #[must_use]
+    pub fn group(&self, name: &str) -> Option<&Group> {
+        self.groups.get(name)
+    }
+
+    /// Whether `permissions` grant `node`.
+    #[must_use]
+    pub fn has(&self, permissions: &Permissions, node: &str) -> bool {
+        permissions.has(&self.groups, node)
+    }
+
+    /// Whether the group `name` inherits from `ancestor`, directly or through its parents.
+    #[must_use]
+    pub fn inherits(&self, name: &str, ancestor: &str) -> bool {
+        group::inherits(&self.groups, name, ancestor)
+    }
+
+    /// Adds or replaces the group `name`.
+    pub fn set_group(&mut self, name: &str, group: Group) -> anyhow::Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        self.group_db.put(&mut wtxn, name, &group)?;
         wtxn.commit()?;
+
+        self.groups.insert(name.to_owned(), group);
         Ok(())
     }
+
+    /// Removes the group `name`, returning it if it existed. [`DEFAULT_GROUP`] may be emptied but
+    /// not removed, since every player is in it.
+    pub fn remove_group(&mut self, name: &str) -> anyhow::Result<Option<Group>> {
+        if name == DEFAULT_GROUP {
+            let group = self.groups.get(name).cloned();
+            self.set_group(name, Group::default())?;
+            return Ok(group);
+        }
+
+        let mut wtxn = self.env.write_txn()?;
+        self.group_db.delete(&mut wtxn, name)?;
+        wtxn.commit()?;
+
+        Ok(self.groups.remove(name))
+    }
+
+    /// Adds the parents and nodes of `group` to the group `name` the first time the server
+    /// starts, so that plugins may set up their groups while changes operators make to them
+    /// later are kept.
+    pub fn seed_group(&mut self, name: &str, group: Group) -> anyhow::Result<()> {
+        if !self.first_start {
+            return Ok(());
+        }
+
+        let mut seeded = self.groups.get(name).cloned().unwrap_or_default();
+
+        for parent in group.parents {
+            if !seeded.parents.contains(&parent) {
+                seeded.parents.push(parent);
+            }
+        }
+
+        seeded.nodes.extend(group.nodes);
+
+        self.set_group(name, seeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_legacy() {
+        let groups = HashMap::from([(DEFAULT_GROUP.to_owned(), Group {
+            parents: Vec::new(),
+            nodes: BTreeMap::from([("tag.command.class".to_owned(), true)]),
+        })]);
+
+        // banned players keep none of the commands of the default group
+        assert!(!from_legacy(Some(0)).has(&groups, "tag.command.class"));
+        assert!(from_legacy(Some(1)).has(&groups, "tag.command.class"));
+        assert!(from_legacy(None).has(&groups, "tag.command.class"));
+
+        assert_eq!(from_legacy(Some(2)).groups, ["moderator"]);
+        assert_eq!(from_legacy(Some(3)).groups, ["admin"]);
+    }
 }
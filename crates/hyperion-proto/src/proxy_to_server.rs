@@ -1,4 +1,9 @@
-use rkyv::{Archive, Deserialize, Serialize, with::InlineAsBox};
+use std::net::SocketAddr;
+
+use rkyv::{
+    Archive, Deserialize, Serialize,
+    with::{InlineAsBox, Map},
+};
 
 #[derive(Archive, Deserialize, Serialize, Clone, PartialEq, Debug)]
 pub struct PlayerPackets<'a> {
@@ -9,8 +14,22 @@ pub struct PlayerPackets<'a> {
 }
 
 #[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
-pub struct PlayerConnect {
+pub struct PlayerConnect<'a> {
     pub stream: u64,
+
+    /// The address of the client. If the proxy is behind a load balancer speaking the PROXY
+    /// protocol, this is the address from the PROXY header rather than the load balancer's.
+    pub addr: Option<SocketAddr>,
+
+    /// The server address the client put in its handshake, if it could be read.
+    #[rkyv(with = Map<InlineAsBox>)]
+    pub virtual_host: Option<&'a str>,
+
+    /// Whether the player was already in the world on a previous server connection.
+    ///
+    /// The proxy follows this with the client's original handshake and login start packets.
+    /// The client is still in the play state, so the server must not send it any login packets.
+    pub resume: bool,
 }
 
 #[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
@@ -31,7 +50,7 @@ pub enum PlayerDisconnectReason<'a> {
 
 #[derive(Archive, Deserialize, Serialize, Clone, PartialEq, Debug)]
 pub enum ProxyToServerMessage<'a> {
-    PlayerConnect(PlayerConnect),
+    PlayerConnect(PlayerConnect<'a>),
     PlayerDisconnect(PlayerDisconnect<'a>),
     PlayerPackets(PlayerPackets<'a>),
 }
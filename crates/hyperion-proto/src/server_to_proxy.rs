@@ -24,11 +24,38 @@ pub struct BroadcastGlobal<'a> {
     pub data: &'a [u8],
 }
 
+/// How important a local broadcast is to a player who cannot keep up with their traffic.
+#[derive(
+    Archive,
+    Deserialize,
+    Serialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+    Default
+)]
+#[rkyv(derive(Debug))]
+pub enum Priority {
+    /// Always delivered, though from a smaller radius for a player who is far behind.
+    #[default]
+    Normal,
+    /// Absolute entity rotation and velocity, which the next update replaces. Only delivered from
+    /// nearby for a player who is falling behind. Relative moves are [`Priority::Normal`], since a
+    /// dropped one is never corrected.
+    Movement,
+    /// Particles, sounds and animations. The first traffic dropped for a player who is falling
+    /// behind.
+    Cosmetic,
+}
+
 #[derive(Archive, Deserialize, Serialize, Clone, PartialEq)]
 pub struct BroadcastLocal<'a> {
     pub center: ChunkPosition,
     pub exclude: u64,
     pub order: u32,
+    pub priority: Priority,
 
     #[rkyv(with = InlineAsBox)]
     pub data: &'a [u8],
@@ -0,0 +1,159 @@
+//! A minimal HTTP endpoint for load balancers and monitoring.
+//!
+//! - `GET /metrics` renders [`crate::metrics::METRICS`] in the Prometheus text format.
+//! - `GET /healthz` (liveness) responds `200` while the proxy process is running.
+//! - `GET /readyz` (readiness) responds `200` only while the proxy is connected to the game
+//!   server, so load balancers stop routing new players to a proxy which cannot serve them.
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use anyhow::{Context, ensure};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::Semaphore,
+};
+use tracing::{debug, info, warn};
+
+use crate::metrics::METRICS;
+
+/// Requests are tiny; anything larger than this is not for us.
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+
+/// How long a client has to send its request before the connection is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many connections are served at once. Further connections wait to be accepted.
+const MAX_CONNECTIONS: usize = 64;
+
+/// How long to wait before accepting again after accepting a connection failed, such as when
+/// running out of file descriptors.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Serves the admin endpoint on `addr` until the listener fails.
+pub async fn serve(addr: SocketAddr) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind admin endpoint to {addr}"))?;
+
+    info!("📈 Serving metrics and health checks on http://{addr}");
+
+    let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+
+    loop {
+        let permit = Arc::clone(&connections).acquire_owned().await?;
+
+        let (socket, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("failed to accept admin connection: {e}");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(socket).await {
+                debug!("admin request from {peer} failed: {e:?}");
+            }
+
+            drop(permit);
+        });
+    }
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn text(status: &'static str, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: body.into(),
+        }
+    }
+}
+
+fn route(method: &str, path: &str) -> Response {
+    // ignore any query string
+    let path = path.split('?').next().unwrap_or_default();
+
+    match (method, path) {
+        ("GET", "/metrics") => match METRICS.render() {
+            Ok(body) => Response {
+                status: "200 OK",
+                content_type: "text/plain; version=0.0.4; charset=utf-8",
+                body,
+            },
+            Err(e) => Response::text("500 Internal Server Error", format!("{e}\n")),
+        },
+        ("GET", "/healthz") => Response::text("200 OK", "ok\n"),
+        ("GET", "/readyz") => {
+            if METRICS.is_ready() {
+                Response::text("200 OK", "ready\n")
+            } else {
+                Response::text("503 Service Unavailable", "not connected to game server\n")
+            }
+        }
+        ("GET", _) => Response::text("404 Not Found", "not found\n"),
+        _ => Response::text("405 Method Not Allowed", "method not allowed\n"),
+    }
+}
+
+/// Reads the request line and headers, returning `None` if the client closed the connection
+/// first.
+async fn read_request(socket: &mut TcpStream) -> anyhow::Result<Option<Vec<u8>>> {
+    let mut buffer = Vec::with_capacity(1024);
+
+    while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+        ensure!(buffer.len() < MAX_REQUEST_LEN, "request is too large");
+
+        if socket.read_buf(&mut buffer).await? == 0 {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(buffer))
+}
+
+async fn handle_connection(mut socket: TcpStream) -> anyhow::Result<()> {
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut socket))
+        .await
+        .context("timed out reading request")??;
+
+    let Some(buffer) = request else {
+        return Ok(());
+    };
+
+    let request_line = buffer
+        .split(|&byte| byte == b'\r')
+        .next()
+        .unwrap_or_default();
+    let request_line = std::str::from_utf8(request_line)?;
+
+    let mut parts = request_line.split(' ');
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+
+    let Response {
+        status,
+        content_type,
+        body,
+    } = route(method, path);
+
+    let header = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: \
+         close\r\n\r\n",
+        body.len()
+    );
+
+    socket.write_all(header.as_bytes()).await?;
+    socket.write_all(body.as_bytes()).await?;
+    socket.shutdown().await?;
+
+    Ok(())
+}
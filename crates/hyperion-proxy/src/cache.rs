@@ -2,7 +2,7 @@ use std::{ops::Range, sync::Arc};
 
 use bvh::{Bvh, Data, Point};
 use glam::I16Vec2;
-use hyperion_proto::{ArchivedServerToProxyMessage, BroadcastGlobal};
+use hyperion_proto::{ArchivedServerToProxyMessage, BroadcastGlobal, Priority};
 use more_asserts::debug_assert_le;
 use rustc_hash::FxBuildHasher;
 
@@ -183,13 +183,24 @@ impl Data for LocalBroadcastData {
     }
 }
 
+/// Every [`Priority`], indexed by its discriminant.
+const PRIORITIES: [Priority; 3] = [Priority::Normal, Priority::Movement, Priority::Cosmetic];
+
+/// Local broadcasts of a single [`Priority`] waiting for the next flush.
+///
+/// Each priority gets its own BVH so the egress can query them with different radii.
+#[derive(Default)]
+struct LocalBroadcasts {
+    raw_data: Vec<u8>,
+    buffer: Vec<LocalBroadcastData>,
+}
+
 /// Buffers egress operations for optimized processing.
 pub struct BufferedEgress {
     /// Buffer for required broadcast data.
     global_broadcast_buffer: Vec<u8>,
 
-    raw_local_broadcast_data: Vec<u8>,
-    local_broadcast_buffer: Vec<LocalBroadcastData>,
+    local_broadcasts: [LocalBroadcasts; PRIORITIES.len()],
 
     /// Manages player-specific exclusions.
     exclusion_manager: ExclusionsManager,
@@ -206,8 +217,7 @@ impl BufferedEgress {
     pub fn new(egress: Egress) -> Self {
         Self {
             global_broadcast_buffer: Vec::new(),
-            raw_local_broadcast_data: vec![],
-            local_broadcast_buffer: Vec::default(),
+            local_broadcasts: Default::default(),
             exclusion_manager: ExclusionsManager::default(),
             egress,
             current_broadcast_order: None,
@@ -253,15 +263,17 @@ impl BufferedEgress {
                 let Ok(center_x) = rkyv::deserialize::<i16, !>(&packet.center.x);
                 let Ok(center_z) = rkyv::deserialize::<i16, !>(&packet.center.z);
                 let Ok(player_id_to_exclude) = rkyv::deserialize::<u64, !>(&packet.exclude);
+                let Ok(priority) = rkyv::deserialize::<Priority, !>(&packet.priority);
 
                 let position = I16Vec2::new(center_x, center_z);
 
-                let before_len = self.raw_local_broadcast_data.len();
-                self.raw_local_broadcast_data
-                    .extend_from_slice(&packet.data);
-                let after_len = self.raw_local_broadcast_data.len();
+                let local = &mut self.local_broadcasts[priority as usize];
 
-                self.local_broadcast_buffer.push(LocalBroadcastData {
+                let before_len = local.raw_data.len();
+                local.raw_data.extend_from_slice(&packet.data);
+                let after_len = local.raw_data.len();
+
+                local.buffer.push(LocalBroadcastData {
                     // todo: checked
                     position,
                     range_start: before_len,
@@ -283,47 +295,66 @@ impl BufferedEgress {
                 self.egress.handle_flush();
                 self.local_flush_counter = 0;
 
-                if self.local_broadcast_buffer.is_empty() {
-                    return;
-                }
+                self.flush_local_broadcasts();
+            }
+        }
+    }
 
-                let bvh = Bvh::build(
-                    &mut self.local_broadcast_buffer,
-                    &self.raw_local_broadcast_data,
-                );
+    /// Builds a BVH of the buffered local broadcasts of each priority and sends them to players
+    /// in range. They are sent in one task, in the order of [`PRIORITIES`], so that players receive
+    /// them in that order.
+    fn flush_local_broadcasts(&mut self) {
+        let mut flushed = Vec::new();
 
-                let mut exclusions = ExclusionsManager::default();
-                let mut idx_on = 0;
+        for priority in PRIORITIES {
+            let local = &mut self.local_broadcasts[priority as usize];
+
+            if local.buffer.is_empty() {
+                continue;
+            }
 
-                for packet in &self.local_broadcast_buffer {
-                    // todo: is there a more idiomatic way to do this?
-                    let packet_len = packet.len();
-                    let range = idx_on..idx_on + packet_len;
+            let bvh = Bvh::build(&mut local.buffer, &local.raw_data);
 
-                    if packet.player_id_to_exclude != 0 {
-                        exclusions.append_exclusion(packet.player_id_to_exclude, range);
-                    }
+            let mut exclusions = ExclusionsManager::default();
+            let mut idx_on = 0;
 
-                    idx_on += packet_len;
+            for packet in &local.buffer {
+                // todo: is there a more idiomatic way to do this?
+                let packet_len = packet.len();
+                let range = idx_on..idx_on + packet_len;
+
+                if packet.player_id_to_exclude != 0 {
+                    exclusions.append_exclusion(packet.player_id_to_exclude, range);
                 }
 
-                self.local_broadcast_buffer.clear();
-                self.raw_local_broadcast_data.clear();
+                idx_on += packet_len;
+            }
 
-                let egress = self.egress;
-                tokio::spawn(async move {
-                    let bvh = bvh.into_bytes();
+            local.buffer.clear();
+            local.raw_data.clear();
 
-                    let instruction = BroadcastLocalInstruction {
-                        order: 0,
-                        bvh: Arc::new(bvh),
-                        exclusions: Arc::new(exclusions),
-                    };
+            flushed.push((priority, bvh, exclusions));
+        }
 
-                    egress.handle_broadcast_local(instruction);
-                });
-            }
+        if flushed.is_empty() {
+            return;
         }
+
+        let egress = self.egress;
+        tokio::spawn(async move {
+            for (priority, bvh, exclusions) in flushed {
+                let bvh = bvh.into_bytes();
+
+                let instruction = BroadcastLocalInstruction {
+                    order: 0,
+                    priority,
+                    bvh: Arc::new(bvh),
+                    exclusions: Arc::new(exclusions),
+                };
+
+                egress.handle_broadcast_local(instruction);
+            }
+        });
     }
 
     /// Flushes the current broadcast buffer.
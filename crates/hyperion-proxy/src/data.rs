@@ -1,10 +1,19 @@
-use std::sync::{Arc, atomic, atomic::AtomicBool};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, OnceLock, atomic, atomic::AtomicBool},
+};
 
 use anyhow::bail;
 use bytes::Bytes;
 use slotmap::{KeyData, new_key_type};
 
-use crate::cache::ExclusionsManager;
+use crate::{MAX_PLAYER_PENDING_MESSAGES, cache::ExclusionsManager};
+
+/// A player with this many pending messages is [`Backpressure::Congested`].
+const CONGESTED_QUEUE_LEN: usize = MAX_PLAYER_PENDING_MESSAGES / 2;
+
+/// A player with this many pending messages is [`Backpressure::ReducedRadius`].
+const REDUCED_RADIUS_QUEUE_LEN: usize = MAX_PLAYER_PENDING_MESSAGES * 3 / 4;
 
 new_key_type! {
     pub struct PlayerId;
@@ -40,6 +49,12 @@ impl OrderedBytes {
         data: Bytes::from_static(b""),
         exclusions: None,
     };
+    pub const SERVER_LOST: Self = Self {
+        order: u32::MAX - 2,
+        offset: 0,
+        data: Bytes::from_static(b""),
+        exclusions: None,
+    };
     pub const SHUTDOWN: Self = Self {
         order: u32::MAX - 1,
         offset: 0,
@@ -55,6 +70,10 @@ impl OrderedBytes {
         self.order == u32::MAX - 1
     }
 
+    pub const fn is_server_lost(&self) -> bool {
+        self.order == u32::MAX - 2
+    }
+
     pub const fn no_order(data: Bytes) -> Self {
         Self {
             order: 0,
@@ -97,6 +116,44 @@ impl Ord for OrderedBytes {
     }
 }
 
+/// How far a player has fallen behind on writing their pending messages.
+///
+/// Low priority local broadcasts are dropped for players who are behind so that they can catch
+/// up, rather than being disconnected once their queue is full. See
+/// [`hyperion_proto::Priority`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Backpressure {
+    /// The player is keeping up and receives everything.
+    None,
+    /// Cosmetic traffic is dropped and entity movement is only sent from nearby.
+    Congested,
+    /// On top of that, the player only receives local broadcasts from a reduced radius.
+    ReducedRadius,
+}
+
+impl Backpressure {
+    #[must_use]
+    pub const fn from_queue_len(len: usize) -> Self {
+        if len >= REDUCED_RADIUS_QUEUE_LEN {
+            Self::ReducedRadius
+        } else if len >= CONGESTED_QUEUE_LEN {
+            Self::Congested
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// What is needed to announce a player to a restarted server.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub addr: Option<SocketAddr>,
+    pub virtual_host: Option<String>,
+
+    /// The client's handshake and login start packets, exactly as they were framed.
+    pub login: Bytes,
+}
+
 #[derive(Debug)]
 pub struct PlayerHandle {
     writer: kanal::AsyncSender<OrderedBytes>,
@@ -108,6 +165,13 @@ pub struct PlayerHandle {
     /// they will get packets that it deems are invalid because the broadcasts are using the play
     /// state and play IDs.
     can_receive_broadcasts: AtomicBool,
+
+    /// Set once the client has sent its handshake and login start.
+    session: OnceLock<Session>,
+
+    /// Whether the player was in the play state when the server connection was lost, and
+    /// should be announced again once a server is available.
+    resumable: AtomicBool,
 }
 
 impl PlayerHandle {
@@ -116,6 +180,8 @@ impl PlayerHandle {
         Self {
             writer,
             can_receive_broadcasts: AtomicBool::new(false),
+            session: OnceLock::new(),
+            resumable: AtomicBool::new(false),
         }
     }
 
@@ -124,6 +190,43 @@ impl PlayerHandle {
         self.writer.close();
     }
 
+    /// Disconnects the player because the server went away before they could be resumed.
+    pub fn server_lost(&self) {
+        let _ = self.writer.try_send(OrderedBytes::SERVER_LOST);
+        self.writer.close();
+    }
+
+    pub fn set_session(&self, session: Session) {
+        self.session.get_or_init(|| session);
+    }
+
+    pub fn session(&self) -> Option<&Session> {
+        self.session.get()
+    }
+
+    /// Called when the server connection is lost. Players who made it into the play state are
+    /// kept for the next server; everyone else is disconnected.
+    pub fn pause(&self) {
+        let in_play = self
+            .can_receive_broadcasts
+            .swap(false, atomic::Ordering::Relaxed);
+
+        if in_play && self.session().is_some() {
+            self.resumable.store(true, atomic::Ordering::Relaxed);
+        } else {
+            self.server_lost();
+        }
+    }
+
+    /// Returns the session to announce to a newly connected server if this player was paused.
+    pub fn take_resumable(&self) -> Option<&Session> {
+        if self.resumable.swap(false, atomic::Ordering::Relaxed) {
+            self.session()
+        } else {
+            None
+        }
+    }
+
     pub fn enable_receive_broadcasts(&self) {
         self.can_receive_broadcasts
             .store(true, atomic::Ordering::Relaxed);
@@ -133,6 +236,15 @@ impl PlayerHandle {
         self.can_receive_broadcasts.load(atomic::Ordering::Relaxed)
     }
 
+    /// The number of messages waiting to be written to the player.
+    pub fn queue_len(&self) -> usize {
+        self.writer.len()
+    }
+
+    pub fn backpressure(&self) -> Backpressure {
+        Backpressure::from_queue_len(self.queue_len())
+    }
+
     pub fn send(&self, ordered_bytes: OrderedBytes) -> anyhow::Result<()> {
         match self.writer.try_send(ordered_bytes) {
             Ok(true) => Ok(()),
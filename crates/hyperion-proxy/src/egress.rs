@@ -1,20 +1,46 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use bvh::{Aabb, Bvh};
 use bytes::Bytes;
 use glam::I16Vec2;
 use hyperion_proto::{
     ArchivedSetReceiveBroadcasts, ArchivedUnicast, ArchivedUpdatePlayerChunkPositions,
-    ChunkPosition,
+    ChunkPosition, Priority,
 };
 use rustc_hash::FxBuildHasher;
 use tracing::{Instrument, debug, error, info_span, instrument, warn};
 
 use crate::{
     cache::ExclusionsManager,
-    data::{OrderedBytes, PlayerHandle},
+    data::{Backpressure, OrderedBytes, PlayerHandle},
+    metrics::METRICS,
 };
 
+/// The radius, in chunks, of local broadcasts for a player who is keeping up.
+const RADIUS: i16 = 16;
+
+/// The radius of local broadcasts for a player who is far behind.
+const REDUCED_RADIUS: i16 = 8;
+
+/// Entity rotation and velocity from further away than this is dropped for a player who is falling
+/// behind.
+const NEAR_RADIUS: i16 = 4;
+
+/// The radius of local broadcasts of `priority` a player receives, or [`None`] if they are
+/// dropped entirely.
+///
+/// Cosmetic traffic and far-away movement go first, then the broadcast radius shrinks. Global
+/// broadcasts and unicasts are never dropped; a player who still cannot keep up is disconnected
+/// once their queue is full.
+const fn local_radius(backpressure: Backpressure, priority: Priority) -> Option<i16> {
+    match (backpressure, priority) {
+        (Backpressure::None, _) | (Backpressure::Congested, Priority::Normal) => Some(RADIUS),
+        (Backpressure::ReducedRadius, Priority::Normal) => Some(REDUCED_RADIUS),
+        (_, Priority::Movement) => Some(NEAR_RADIUS),
+        (_, Priority::Cosmetic) => None,
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct Egress {
     // todo: can we do some type of EntityId and SlotMap
@@ -26,6 +52,7 @@ pub struct Egress {
 
 pub struct BroadcastLocalInstruction {
     pub order: u32,
+    pub priority: Priority,
     pub bvh: Arc<Bvh<Bytes>>,
     pub exclusions: Arc<ExclusionsManager>,
 }
@@ -77,6 +104,7 @@ impl Egress {
 
         tokio::spawn(
             async move {
+                let start = Instant::now();
                 let exclusions = Arc::new(exclusions);
 
                 // imo it makes sense to read once... it is a fast loop
@@ -96,6 +124,8 @@ impl Egress {
                         }
                     }
                 }
+
+                METRICS.broadcast_global.observe(start.elapsed());
             }
             .instrument(info_span!("broadcast_global_task")),
         );
@@ -123,6 +153,7 @@ impl Egress {
     #[instrument(skip_all)]
     pub fn handle_broadcast_local(self, instruction: BroadcastLocalInstruction) {
         let order = instruction.order;
+        let priority = instruction.priority;
         let bvh = instruction.bvh;
         let exclusions = instruction.exclusions;
 
@@ -131,8 +162,7 @@ impl Egress {
         // #[allow(clippy::significant_drop_tightening)]
         tokio::spawn(
             async move {
-                const RADIUS: i16 = 16;
-
+                let start = Instant::now();
                 let players = self.player_registry.pin();
 
                 for (id, &position) in &positions {
@@ -146,9 +176,20 @@ impl Egress {
                         continue;
                     }
 
+                    let backpressure = player.backpressure();
+                    let radius = local_radius(backpressure, priority);
+
+                    if radius != Some(RADIUS) {
+                        METRICS.record_backpressure(backpressure);
+                    }
+
+                    let Some(radius) = radius else {
+                        continue;
+                    };
+
                     let position = I16Vec2::new(position.x, position.z);
-                    let min = position - I16Vec2::splat(RADIUS);
-                    let max = position + I16Vec2::splat(RADIUS);
+                    let min = position - I16Vec2::splat(radius);
+                    let max = position + I16Vec2::splat(radius);
 
                     let aabb = Aabb::new(min, max);
 
@@ -177,6 +218,8 @@ impl Egress {
                         }
                     }
                 }
+
+                METRICS.broadcast_local.observe(start.elapsed());
             }
             .instrument(info_span!("broadcast_local_task")),
         );
@@ -231,3 +274,36 @@ impl Egress {
         player.enable_receive_broadcasts();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_radius_shrinks_with_backpressure() {
+        let levels = [
+            Backpressure::None,
+            Backpressure::Congested,
+            Backpressure::ReducedRadius,
+        ];
+
+        for priority in [Priority::Normal, Priority::Movement, Priority::Cosmetic] {
+            let radii =
+                levels.map(|backpressure| local_radius(backpressure, priority).unwrap_or(0));
+            assert!(radii.is_sorted_by(|a, b| a >= b), "{priority:?}: {radii:?}");
+        }
+
+        assert_eq!(
+            local_radius(Backpressure::None, Priority::Cosmetic),
+            Some(RADIUS)
+        );
+        assert_eq!(
+            local_radius(Backpressure::Congested, Priority::Cosmetic),
+            None
+        );
+        assert_eq!(
+            local_radius(Backpressure::ReducedRadius, Priority::Normal),
+            Some(REDUCED_RADIUS)
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Splitting the client byte stream on packet frame boundaries.
+//!
+//! Every Minecraft packet is prefixed with its length as a `VarInt`, whether or not compression
+//! is enabled. Forwarding whole frames only means a stream can be handed to a different server
+//! process without the server seeing half a packet.
+
+/// The maximum length of a packet frame allowed by the vanilla client and server.
+const MAX_FRAME_LEN: usize = (1 << 21) - 1;
+
+pub(crate) enum VarInt {
+    Value(i32),
+    Incomplete,
+    TooLong,
+}
+
+pub(crate) fn read_var_int(buf: &mut &[u8]) -> VarInt {
+    let mut value = 0_i32;
+
+    for i in 0..5 {
+        let Some((&byte, rest)) = buf.split_first() else {
+            return VarInt::Incomplete;
+        };
+        *buf = rest;
+
+        value |= i32::from(byte & 0x7F) << (i * 7);
+
+        if byte & 0x80 == 0 {
+            return VarInt::Value(value);
+        }
+    }
+
+    VarInt::TooLong
+}
+
+/// The state of reading a frame from the start of a buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// More bytes are needed.
+    Incomplete,
+    /// A whole frame is buffered. Contains its length including the length prefix.
+    Complete(usize),
+    /// The buffer does not start with a valid frame.
+    Invalid,
+}
+
+/// Reads the frame at the start of `buf`.
+#[must_use]
+pub fn peek(buf: &[u8]) -> Frame {
+    let mut cursor = buf;
+
+    let len = match read_var_int(&mut cursor) {
+        VarInt::Value(len) => len,
+        VarInt::Incomplete => return Frame::Incomplete,
+        VarInt::TooLong => return Frame::Invalid,
+    };
+
+    let Ok(len) = usize::try_from(len) else {
+        return Frame::Invalid;
+    };
+
+    if len > MAX_FRAME_LEN {
+        return Frame::Invalid;
+    }
+
+    let prefix_len = buf.len() - cursor.len();
+
+    if cursor.len() < len {
+        return Frame::Incomplete;
+    }
+
+    Frame::Complete(prefix_len + len)
+}
+
+/// Returns the length of the longest prefix of `buf` which consists of whole frames, or [`None`]
+/// if `buf` contains an invalid frame.
+#[must_use]
+pub fn complete_len(buf: &[u8]) -> Option<usize> {
+    let mut len = 0;
+
+    loop {
+        match peek(&buf[len..]) {
+            Frame::Complete(frame_len) => len += frame_len,
+            Frame::Incomplete => return Some(len),
+            Frame::Invalid => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete_len() {
+        let buf = [0x02, 0x00, 0x01, 0x01, 0x05, 0x03, 0x00];
+
+        assert_eq!(peek(&buf), Frame::Complete(3));
+        assert_eq!(complete_len(&buf), Some(5));
+        assert_eq!(complete_len(&buf[..4]), Some(3));
+        assert_eq!(complete_len(&[]), Some(0));
+    }
+
+    #[test]
+    fn test_multi_byte_length() {
+        let mut buf = vec![0x80, 0x01];
+        buf.resize(2 + 128, 0);
+
+        assert_eq!(peek(&buf[..1]), Frame::Incomplete);
+        assert_eq!(peek(&buf[..100]), Frame::Incomplete);
+        assert_eq!(peek(&buf), Frame::Complete(130));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(peek(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]), Frame::Invalid);
+        assert_eq!(peek(&[0xFF, 0xFF, 0xFF, 0x01]), Frame::Invalid);
+        assert_eq!(
+            complete_len(&[0x01, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]),
+            None
+        );
+    }
+}
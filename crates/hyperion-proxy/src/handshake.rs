@@ -0,0 +1,147 @@
+//! Peeking at the Minecraft handshake to learn which host the client connected to.
+//!
+//! The proxy otherwise forwards client bytes opaquely. The handshake is always the first packet
+//! and is never compressed, so it can be read without tracking any connection state.
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::frame::{VarInt, read_var_int};
+
+/// The handshake is small; if we have buffered this much without finding it, give up.
+const MAX_HANDSHAKE_LEN: usize = 1024;
+
+/// The packet ID of the handshake packet.
+const HANDSHAKE_ID: i32 = 0x00;
+
+/// The first byte of a pre-netty server list ping, which has no handshake.
+const LEGACY_PING: u8 = 0xFE;
+
+/// The state of peeking a handshake from a buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Peek<'a> {
+    /// More bytes are needed.
+    Incomplete,
+    /// The handshake was found. Contains the server address the client connected to.
+    Host(&'a str),
+    /// The stream does not start with a handshake we understand.
+    Invalid,
+}
+
+/// Peeks the server address out of a handshake at the start of `buf`.
+#[must_use]
+pub fn peek(buf: &[u8]) -> Peek<'_> {
+    if buf.first() == Some(&LEGACY_PING) {
+        return Peek::Invalid;
+    }
+
+    let mut cursor = buf;
+
+    let packet_len = match read_var_int(&mut cursor) {
+        VarInt::Value(len) => len,
+        VarInt::Incomplete => return Peek::Incomplete,
+        VarInt::TooLong => return Peek::Invalid,
+    };
+
+    let Ok(packet_len) = usize::try_from(packet_len) else {
+        return Peek::Invalid;
+    };
+
+    if packet_len > MAX_HANDSHAKE_LEN {
+        return Peek::Invalid;
+    }
+
+    let Some(mut packet) = cursor.get(..packet_len) else {
+        return Peek::Incomplete;
+    };
+
+    // id, protocol version, server address length
+    let (VarInt::Value(HANDSHAKE_ID), VarInt::Value(_protocol), VarInt::Value(host_len)) = (
+        read_var_int(&mut packet),
+        read_var_int(&mut packet),
+        read_var_int(&mut packet),
+    ) else {
+        return Peek::Invalid;
+    };
+
+    let Some(host) = usize::try_from(host_len)
+        .ok()
+        .and_then(|host_len| packet.get(..host_len))
+    else {
+        return Peek::Invalid;
+    };
+
+    let Ok(host) = std::str::from_utf8(host) else {
+        return Peek::Invalid;
+    };
+
+    // Forge and some load balancers append data after a NUL byte
+    let host = host.split('\0').next().unwrap_or_default();
+
+    Peek::Host(host)
+}
+
+/// Reads from `reader` until the handshake in `buffer` can be peeked.
+///
+/// Nothing is consumed from `buffer`; the bytes still need to be forwarded to the server.
+/// Returns [`None`] if the stream does not start with a handshake.
+pub async fn read_virtual_host(
+    reader: &mut (impl AsyncRead + Unpin),
+    buffer: &mut Vec<u8>,
+) -> std::io::Result<Option<String>> {
+    loop {
+        match peek(buffer) {
+            Peek::Host(host) => return Ok(Some(host.to_owned())),
+            Peek::Invalid => return Ok(None),
+            Peek::Incomplete if buffer.len() >= MAX_HANDSHAKE_LEN => return Ok(None),
+            Peek::Incomplete => {}
+        }
+
+        buffer.reserve(MAX_HANDSHAKE_LEN);
+
+        if reader.read_buf(buffer).await? == 0 {
+            return Ok(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(host: &str) -> Vec<u8> {
+        // id, protocol version 763
+        let mut packet = vec![0x00, 0xFB, 0x05];
+        packet.push(u8::try_from(host.len()).unwrap());
+        packet.extend_from_slice(host.as_bytes());
+        packet.extend_from_slice(&25_565_u16.to_be_bytes());
+        packet.push(0x02);
+
+        let mut framed = vec![u8::try_from(packet.len()).unwrap()];
+        framed.extend_from_slice(&packet);
+        framed
+    }
+
+    #[test]
+    fn test_peek_host() {
+        let buf = handshake("play.example.com");
+        assert_eq!(peek(&buf), Peek::Host("play.example.com"));
+    }
+
+    #[test]
+    fn test_peek_incomplete() {
+        let buf = handshake("play.example.com");
+        assert_eq!(peek(&buf[..0]), Peek::Incomplete);
+        assert_eq!(peek(&buf[..8]), Peek::Incomplete);
+    }
+
+    #[test]
+    fn test_peek_forge_suffix() {
+        let buf = handshake("play.example.com\0FML3\0");
+        assert_eq!(peek(&buf), Peek::Host("play.example.com"));
+    }
+
+    #[test]
+    fn test_peek_legacy_ping() {
+        assert_eq!(peek(&[0xFE, 0x01]), Peek::Invalid);
+    }
+}
@@ -16,7 +16,7 @@
     clippy::future_not_send
 )]
 
-use std::fmt::Debug;
+use std::{fmt::Debug, net::SocketAddr, sync::atomic::Ordering, time::Duration};
 
 use anyhow::Context;
 use colored::Colorize;
@@ -30,8 +30,12 @@ use tokio_util::net::Listener;
 use tracing::{Instrument, debug, error, info, info_span, instrument, trace, warn};
 
 use crate::{
-    cache::BufferedEgress, data::PlayerHandle, egress::Egress, player::initiate_player_connection,
-    server_sender::launch_server_writer,
+    cache::BufferedEgress,
+    data::PlayerHandle,
+    egress::Egress,
+    metrics::METRICS,
+    player::{initiate_player_connection, resume},
+    server_sender::{ServerLink, ServerSender, launch_server_writer},
 };
 
 /// 4 KiB
@@ -42,10 +46,19 @@ const DEFAULT_BUFFER_SIZE: usize = 4 * 1024;
 /// memory exhaustion from slow or unresponsive clients.
 const MAX_PLAYER_PENDING_MESSAGES: usize = 1_024;
 
+/// How long to wait before accepting again after accepting a client failed, such as when running
+/// out of file descriptors.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+pub mod admin;
 pub mod cache;
 pub mod data;
 pub mod egress;
+pub mod frame;
+pub mod handshake;
+pub mod metrics;
 pub mod player;
+pub mod proxy_protocol;
 pub mod server_sender;
 pub mod util;
 
@@ -60,18 +73,18 @@ pub async fn connect(addr: impl ToSocketAddrs + Debug + Clone) -> TcpStream {
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum ShutdownType {
-    Reconnect,
-    Full,
-}
-
+/// Runs the proxy until it receives `SIGTERM` or `SIGQUIT`.
+///
+/// Clients are accepted for the whole lifetime of the proxy. If the server connection is lost,
+/// players who are already in the world are kept connected and resumed on the next server; their
+/// traffic is paused in the meantime.
 #[tracing::instrument(level = "trace", skip_all)]
 pub async fn run_proxy(
     mut listener: impl HyperionListener,
     server_addr: impl ToSocketAddrs + Debug + Clone,
+    proxy_protocol: bool,
 ) -> anyhow::Result<()> {
-    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(None);
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
 
     #[cfg(unix)]
     let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
@@ -82,125 +95,173 @@ pub async fn run_proxy(
         .context("failed to register SIGQUIT handler")?;
 
     #[cfg(unix)]
-    tokio::spawn({
-        let shutdown_tx = shutdown_tx.clone();
-        async move {
-            tokio::select! {
-                _ = sigterm.recv() => {
-                    warn!("SIGTERM received, shutting down");
-                    shutdown_tx.send(Some(ShutdownType::Full)).unwrap();
-                }
-                _ = sigquit.recv() => {
-                    warn!("SIGQUIT received, shutting down");
-                    shutdown_tx.send(Some(ShutdownType::Full)).unwrap();
-                }
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = sigterm.recv() => {
+                warn!("SIGTERM received, shutting down");
+            }
+            _ = sigquit.recv() => {
+                warn!("SIGQUIT received, shutting down");
             }
-        }
-    });
-
-    loop {
-        let mut shutdown_rx2 = shutdown_rx.clone();
-
-        if *shutdown_rx2.borrow() == Some(ShutdownType::Full) {
-            break Ok(());
         }
 
-        tokio::select! {
-            _ = shutdown_rx2.wait_for(|value| *value == Some(ShutdownType::Full)) => {
-                warn!("Received shutdown signal, exiting proxy loop");
-                break Ok(());
-            }
-            () = async {
+        shutdown_tx.send(true).unwrap();
+    });
 
-                // clear shutdown channel
-                shutdown_tx.send(None).unwrap();
+    let player_registry = papaya::HashMap::default();
+    let player_registry: &'static papaya::HashMap<u64, PlayerHandle, FxBuildHasher> =
+        Box::leak(Box::new(player_registry));
 
-                let binding_help = "~ Make sure the event server is running".dimmed();
-                info!("⏳ Binding to server... {binding_help}");
+    METRICS.set_player_registry(player_registry);
 
-                let server_socket = connect(server_addr.clone()).await;
-                server_socket.set_nodelay(true).unwrap();
+    let player_positions = papaya::HashMap::default();
+    let player_positions: &'static papaya::HashMap<u64, ChunkPosition, FxBuildHasher> =
+        Box::leak(Box::new(player_positions));
 
-                if let Err(e) = connect_to_server_and_run_proxy(&mut listener, server_socket, shutdown_rx.clone(), shutdown_tx.clone()).await {
-                    error!("Error connecting to server: {e:?}");
-                }
+    let (link_tx, server_link) = tokio::sync::watch::channel(None);
 
+    let mut shutdown = shutdown_rx.clone();
 
-            } => {}
+    tokio::select! {
+        _ = shutdown.wait_for(|&shutdown| shutdown) => {
+            warn!("Received shutdown signal, exiting proxy loop");
         }
+        () = maintain_server_connection(server_addr, link_tx, player_registry, player_positions) => {}
+        () = accept_connections(
+            &mut listener,
+            proxy_protocol,
+            shutdown_rx.clone(),
+            server_link,
+            player_registry,
+            player_positions,
+        ) => {}
     }
+
+    Ok(())
 }
 
+/// Connects to the server, and reconnects whenever the connection is lost.
+///
+/// `link` holds the sender for the current connection. It is only published once every paused
+/// player has been announced, so their packets cannot overtake their replayed login.
 #[tracing::instrument(level = "trace", skip_all)]
-async fn connect_to_server_and_run_proxy(
-    listener: &mut impl HyperionListener,
-    server_socket: TcpStream,
-    shutdown_rx: tokio::sync::watch::Receiver<Option<ShutdownType>>,
-    shutdown_tx: tokio::sync::watch::Sender<Option<ShutdownType>>,
-) -> anyhow::Result<()> {
-    info!("🔗 Connected to server, accepting connections");
-    let (server_read, server_write) = server_socket.into_split();
-    let server_sender = launch_server_writer(server_write);
+async fn maintain_server_connection(
+    server_addr: impl ToSocketAddrs + Debug + Clone,
+    link: tokio::sync::watch::Sender<Option<ServerSender>>,
+    player_registry: &'static papaya::HashMap<u64, PlayerHandle, FxBuildHasher>,
+    player_positions: &'static papaya::HashMap<u64, ChunkPosition, FxBuildHasher>,
+) {
+    loop {
+        let binding_help = "~ Make sure the event server is running".dimmed();
+        info!("⏳ Binding to server... {binding_help}");
 
-    let player_registry = papaya::HashMap::default();
-    let player_registry: &'static papaya::HashMap<u64, PlayerHandle, FxBuildHasher> =
-        Box::leak(Box::new(player_registry));
+        let server_socket = connect(server_addr.clone()).await;
+        server_socket.set_nodelay(true).unwrap();
 
-    let player_positions = papaya::HashMap::default();
-    let player_positions: &'static papaya::HashMap<u64, ChunkPosition, FxBuildHasher> =
-        Box::leak(Box::new(player_positions));
+        info!("🔗 Connected to server");
+        let (server_read, server_write) = server_socket.into_split();
+        let server_sender = launch_server_writer(server_write);
 
-    let egress = Egress::new(player_registry, player_positions);
+        resume_players(&server_sender, player_registry).await;
 
-    let egress = BufferedEgress::new(egress);
+        link.send_replace(Some(server_sender.clone()));
+        METRICS.server_connected.store(true, Ordering::Relaxed);
 
-    let mut handler = IngressHandler::new(BufReader::new(server_read), egress);
+        let egress = Egress::new(player_registry, player_positions);
 
-    tokio::spawn({
-        let mut shutdown_rx = shutdown_rx.clone();
+        let egress = BufferedEgress::new(egress);
 
-        async move {
+        let mut handler = IngressHandler::new(BufReader::new(server_read), egress);
+
+        let server_reader = tokio::spawn(
+            async move {
                 loop {
-                    tokio::select! {
-                    _ = shutdown_rx.wait_for(Option::is_some) => return,
-                    result = handler.handle_next() => {
-                        match result {
-                            Ok(()) => {},
-                            Err(e) => {
-                                error!(
-                                    "Error reading next packet: {e:?}. Are you connected to a valid \
-                                     hyperion server? If you are connected to a vanilla server, \
-                                     hyperion-proxy will not work."
-                                );
-                                break;
-                            }
-                        }
+                    if let Err(e) = handler.handle_next().await {
+                        error!(
+                            "Error reading next packet: {e:?}. Are you connected to a valid \
+                             hyperion server? If you are connected to a vanilla server, \
+                             hyperion-proxy will not work."
+                        );
+                        return;
                     }
                 }
-                }
+            }
+            .instrument(info_span!("server_reader_loop")),
+        );
+
+        if let Err(e) = server_reader.await {
+            error!("server reader panicked: {e:?}");
+        }
+
+        METRICS.server_connected.store(false, Ordering::Relaxed);
+
+        // Unpublish the sender before closing it so players wait for the next one
+        link.send_replace(None);
+        server_sender.close();
+
+        debug!("Pausing all players");
 
-                debug!("Sending shutdown to all players");
+        for (_, player) in &player_registry.pin() {
+            player.pause();
+        }
+    }
+}
+
+/// Announces every paused player to a newly connected server.
+async fn resume_players(
+    server_sender: &ServerSender,
+    player_registry: &'static papaya::HashMap<u64, PlayerHandle, FxBuildHasher>,
+) {
+    let paused: Vec<_> = player_registry
+        .pin()
+        .iter()
+        .filter_map(|(&player_id, player)| Some((player_id, player.take_resumable()?.clone())))
+        .collect();
+
+    if paused.is_empty() {
+        return;
+    }
 
-                shutdown_tx.send(Some(ShutdownType::Reconnect)).unwrap();
+    info!("Resuming {} players", paused.len());
+
+    for (player_id, session) in paused {
+        if let Err(e) = resume(server_sender, player_id, &session).await {
+            warn!("failed to resume player {player_id}: {e:?}");
+
+            if let Some(player) = player_registry.pin().get(&player_id) {
+                player.server_lost();
             }
-                .instrument(info_span!("server_reader_loop"))
-    });
+        }
+    }
+}
+
+/// Accepts clients until the proxy shuts down.
+#[tracing::instrument(level = "trace", skip_all)]
+async fn accept_connections(
+    listener: &mut impl HyperionListener,
+    proxy_protocol: bool,
+    shutdown_rx: tokio::sync::watch::Receiver<bool>,
+    server_link: ServerLink,
+    player_registry: &'static papaya::HashMap<u64, PlayerHandle, FxBuildHasher>,
+    player_positions: &'static papaya::HashMap<u64, ChunkPosition, FxBuildHasher>,
+) {
+    info!("Accepting connections");
 
     // 0 is reserved for "None" value
     let mut player_id_on = 1;
 
     loop {
-        let mut shutdown_rx = shutdown_rx.clone();
-        let socket = tokio::select! {
-            _ = shutdown_rx.wait_for(Option::is_some) => {
-                return Ok(())
-            }
-            Ok((socket, addr)) = listener.accept() => {
-                info!("New client connection from {addr:?}");
-                socket
+        let (socket, addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("failed to accept client connection: {e}");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
             }
         };
 
+        info!("New client connection from {addr:?}");
+
         let registry = player_registry.pin();
 
         // todo: re-add bounding but issues if have MASSIVE number of packets
@@ -210,12 +271,17 @@ async fn connect_to_server_and_run_proxy(
         // todo: some SlotMap like thing
         debug!("got player with id {player_id_on:?}");
 
+        METRICS.connections_accepted.fetch_add(1, Ordering::Relaxed);
+        METRICS.players_connected.fetch_add(1, Ordering::Relaxed);
+
         initiate_player_connection(
             socket,
+            addr.socket_addr(),
+            proxy_protocol,
             shutdown_rx.clone(),
             player_id_on,
             rx,
-            server_sender.clone(),
+            server_link.clone(),
             player_registry,
             player_positions,
         );
@@ -253,6 +319,10 @@ impl IngressHandler {
         let len = self.read_len().await?;
         let len = usize::try_from(len).context("Failed to convert len to usize")?;
 
+        METRICS
+            .server_bytes_received
+            .fetch_add(len as u64 + 8, Ordering::Relaxed);
+
         debug_assert!(len <= 1_000_000);
 
         trace!("Received packet of length {len}");
@@ -290,6 +360,25 @@ impl IngressHandler {
     }
 }
 
-trait HyperionListener: Listener<Io: Send, Addr: Debug> + 'static {}
+/// The address of a peer accepted by a [`Listener`].
+pub trait PeerAddr {
+    /// The socket address of the peer, if the listener has one.
+    fn socket_addr(&self) -> Option<SocketAddr>;
+}
+
+impl PeerAddr for SocketAddr {
+    fn socket_addr(&self) -> Option<SocketAddr> {
+        Some(*self)
+    }
+}
+
+#[cfg(unix)]
+impl PeerAddr for tokio::net::unix::SocketAddr {
+    fn socket_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+}
+
+trait HyperionListener: Listener<Io: Send, Addr: Debug + PeerAddr> + 'static {}
 
-impl<L: Listener<Io: Send, Addr: Debug> + 'static> HyperionListener for L {}
+impl<L: Listener<Io: Send, Addr: Debug + PeerAddr> + 'static> HyperionListener for L {}
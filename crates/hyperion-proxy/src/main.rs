@@ -1,7 +1,7 @@
 use std::{fmt::Debug, net::SocketAddr, path::PathBuf};
 
 use clap::Parser;
-use hyperion_proxy::run_proxy;
+use hyperion_proxy::{admin, run_proxy};
 use tokio::net::TcpListener;
 #[cfg(unix)]
 use tokio::net::UnixListener;
@@ -23,6 +23,16 @@ struct Params {
     /// The address of the target Minecraft game server to proxy from/to
     #[clap(short, long, default_value = "127.0.0.1:35565")]
     server: String,
+
+    /// Expect every client connection to start with a `HAProxy` PROXY protocol (v1 or v2) header.
+    /// Enable this when the proxy is behind a load balancer so the real client address is used.
+    #[clap(long)]
+    proxy_protocol: bool,
+
+    /// The address to serve Prometheus metrics (`/metrics`) and health checks (`/healthz`,
+    /// `/readyz`) on, e.g. "0.0.0.0:9100". Disabled if not set.
+    #[clap(long)]
+    admin_addr: Option<SocketAddr>,
 }
 
 #[derive(Debug)]
@@ -96,19 +106,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let server_help = "~ The event server internal address".dimmed();
     info!("👾 Internal server address: tcp://{server_addr} {server_help}");
 
+    if let Some(admin_addr) = params.admin_addr {
+        tokio::spawn(async move {
+            if let Err(e) = admin::serve(admin_addr).await {
+                error!("Admin endpoint failed: {e:?}");
+            }
+        });
+    }
+
+    let proxy_protocol = params.proxy_protocol;
+
+    if proxy_protocol {
+        info!("🧾 Expecting PROXY protocol headers on incoming connections");
+    }
+
     let handle = tokio::spawn(async move {
         match &proxy_addr {
             ProxyAddress::Tcp(addr) => {
                 let listener = TcpListener::bind(addr).await.unwrap();
                 let socket = NoDelayTcpListener { listener };
-                run_proxy(socket, server_addr).await.unwrap();
+                run_proxy(socket, server_addr, proxy_protocol)
+                    .await
+                    .unwrap();
             }
             #[cfg(unix)]
             ProxyAddress::Unix(path) => {
                 // remove file if already exists
                 let _unused = tokio::fs::remove_file(path).await;
                 let listener = UnixListener::bind(path).unwrap();
-                run_proxy(listener, server_addr).await.unwrap();
+                run_proxy(listener, server_addr, proxy_protocol)
+                    .await
+                    .unwrap();
             }
         }
     });
@@ -0,0 +1,392 @@
+//! Counters and gauges describing the proxy, rendered in the Prometheus text format.
+//!
+//! Everything is stored in atomics in the [`METRICS`] static so any task can record to it without
+//! coordination. The admin endpoint in [`crate::admin`] renders it on request.
+
+use std::{
+    fmt::Write,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use rustc_hash::FxBuildHasher;
+
+use crate::{
+    MAX_PLAYER_PENDING_MESSAGES,
+    data::{Backpressure, PlayerHandle},
+};
+
+/// The metrics of this proxy process.
+pub static METRICS: Metrics = Metrics::new();
+
+/// Upper bounds of the broadcast fan-out histogram buckets, in seconds.
+const FANOUT_BUCKETS: [f64; 10] = [
+    0.000_1, 0.000_25, 0.000_5, 0.001, 0.002_5, 0.005, 0.01, 0.025, 0.05, 0.1,
+];
+
+/// Upper bounds of the player queue depth histogram buckets, in messages.
+const QUEUE_DEPTH_BUCKETS: [usize; 7] = [0, 8, 32, 128, 256, 512, MAX_PLAYER_PENDING_MESSAGES];
+
+/// Why a player connection was closed by the proxy.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// The client closed the connection or a read or write to it failed.
+    LostConnection,
+    /// The player's queue of pending messages filled up.
+    CouldNotKeepUp,
+    /// The connection to the game server was lost.
+    ServerShutdown,
+    /// The connection did not start with a valid PROXY protocol header.
+    InvalidProxyHeader,
+}
+
+impl DisconnectReason {
+    const ALL: [Self; 4] = [
+        Self::LostConnection,
+        Self::CouldNotKeepUp,
+        Self::ServerShutdown,
+        Self::InvalidProxyHeader,
+    ];
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::LostConnection => "lost_connection",
+            Self::CouldNotKeepUp => "could_not_keep_up",
+            Self::ServerShutdown => "server_shutdown",
+            Self::InvalidProxyHeader => "invalid_proxy_header",
+        }
+    }
+}
+
+/// A histogram of durations with fixed buckets.
+pub struct Histogram {
+    buckets: [AtomicU64; FANOUT_BUCKETS.len()],
+    count: AtomicU64,
+    sum_nanos: AtomicU64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        Self {
+            buckets: [const { AtomicU64::new(0) }; FANOUT_BUCKETS.len()],
+            count: AtomicU64::new(0),
+            sum_nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Records a single observation.
+    pub fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+
+        if let Some(bucket) = FANOUT_BUCKETS.iter().position(|&bound| seconds <= bound) {
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) -> std::fmt::Result {
+        let mut cumulative = 0;
+
+        for (bound, bucket) in FANOUT_BUCKETS.iter().zip(&self.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {cumulative}")?;
+        }
+
+        let count = self.count.load(Ordering::Relaxed);
+        let sum = Duration::from_nanos(self.sum_nanos.load(Ordering::Relaxed)).as_secs_f64();
+
+        writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {count}")?;
+        writeln!(out, "{name}_sum{{{labels}}} {sum}")?;
+        writeln!(out, "{name}_count{{{labels}}} {count}")
+    }
+}
+
+/// The metrics of a proxy. See [`METRICS`].
+pub struct Metrics {
+    /// The number of players with an open connection to the proxy.
+    pub players_connected: AtomicU64,
+    /// The number of client connections accepted since startup.
+    pub connections_accepted: AtomicU64,
+    /// Bytes read from clients.
+    pub client_bytes_received: AtomicU64,
+    /// Bytes written to clients.
+    pub client_bytes_sent: AtomicU64,
+    /// Bytes read from the game server.
+    pub server_bytes_received: AtomicU64,
+    /// Bytes written to the game server.
+    pub server_bytes_sent: AtomicU64,
+    /// Whether the proxy currently has a connection to the game server.
+    pub server_connected: AtomicBool,
+    /// How long it takes to hand a global broadcast to every player.
+    pub broadcast_global: Histogram,
+    /// How long it takes to hand a local broadcast to every player in range.
+    pub broadcast_local: Histogram,
+    disconnects: [AtomicU64; DisconnectReason::ALL.len()],
+    /// Local broadcasts limited for a player who was behind, indexed by
+    /// [`Backpressure::Congested`] and [`Backpressure::ReducedRadius`].
+    backpressure: [AtomicU64; 2],
+    player_registry: Mutex<Option<&'static papaya::HashMap<u64, PlayerHandle, FxBuildHasher>>>,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            players_connected: AtomicU64::new(0),
+            connections_accepted: AtomicU64::new(0),
+            client_bytes_received: AtomicU64::new(0),
+            client_bytes_sent: AtomicU64::new(0),
+            server_bytes_received: AtomicU64::new(0),
+            server_bytes_sent: AtomicU64::new(0),
+            server_connected: AtomicBool::new(false),
+            broadcast_global: Histogram::new(),
+            broadcast_local: Histogram::new(),
+            disconnects: [const { AtomicU64::new(0) }; DisconnectReason::ALL.len()],
+            backpressure: [const { AtomicU64::new(0) }; 2],
+            player_registry: Mutex::new(None),
+        }
+    }
+
+    /// Records that a player connection was closed.
+    pub fn record_disconnect(&self, reason: DisconnectReason) {
+        self.disconnects[reason as usize].fetch_add(1, Ordering::Relaxed);
+        self.players_connected.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Records that a local broadcast was dropped or sent with a reduced radius to a player who
+    /// is behind.
+    pub fn record_backpressure(&self, backpressure: Backpressure) {
+        let idx = match backpressure {
+            Backpressure::None => return,
+            Backpressure::Congested => 0,
+            Backpressure::ReducedRadius => 1,
+        };
+
+        self.backpressure[idx].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Sets the registry of the current server connection, used to sample player queue depths.
+    pub fn set_player_registry(
+        &self,
+        registry: &'static papaya::HashMap<u64, PlayerHandle, FxBuildHasher>,
+    ) {
+        *self.player_registry.lock().unwrap() = Some(registry);
+    }
+
+    /// Whether the proxy is ready to accept players.
+    pub fn is_ready(&self) -> bool {
+        self.server_connected.load(Ordering::Relaxed)
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> Result<String, std::fmt::Error> {
+        let mut out = String::new();
+
+        let counters = [
+            (
+                "hyperion_proxy_connections_accepted_total",
+                "Client connections accepted.",
+                &self.connections_accepted,
+            ),
+            (
+                "hyperion_proxy_client_bytes_received_total",
+                "Bytes read from clients.",
+                &self.client_bytes_received,
+            ),
+            (
+                "hyperion_proxy_client_bytes_sent_total",
+                "Bytes written to clients.",
+                &self.client_bytes_sent,
+            ),
+            (
+                "hyperion_proxy_server_bytes_received_total",
+                "Bytes read from the game server.",
+                &self.server_bytes_received,
+            ),
+            (
+                "hyperion_proxy_server_bytes_sent_total",
+                "Bytes written to the game server.",
+                &self.server_bytes_sent,
+            ),
+        ];
+
+        for (name, help, value) in counters {
+            writeln!(out, "# HELP {name} {help}")?;
+            writeln!(out, "# TYPE {name} counter")?;
+            writeln!(out, "{name} {}", value.load(Ordering::Relaxed))?;
+        }
+
+        writeln!(
+            out,
+            "# HELP hyperion_proxy_players_connected Players with an open connection."
+        )?;
+        writeln!(out, "# TYPE hyperion_proxy_players_connected gauge")?;
+        writeln!(
+            out,
+            "hyperion_proxy_players_connected {}",
+            self.players_connected.load(Ordering::Relaxed)
+        )?;
+
+        writeln!(
+            out,
+            "# HELP hyperion_proxy_server_connected Whether the game server is connected."
+        )?;
+        writeln!(out, "# TYPE hyperion_proxy_server_connected gauge")?;
+        writeln!(
+            out,
+            "hyperion_proxy_server_connected {}",
+            u8::from(self.server_connected.load(Ordering::Relaxed))
+        )?;
+
+        writeln!(
+            out,
+            "# HELP hyperion_proxy_disconnects_total Player connections closed, by reason."
+        )?;
+        writeln!(out, "# TYPE hyperion_proxy_disconnects_total counter")?;
+        for reason in DisconnectReason::ALL {
+            writeln!(
+                out,
+                "hyperion_proxy_disconnects_total{{reason=\"{}\"}} {}",
+                reason.label(),
+                self.disconnects[reason as usize].load(Ordering::Relaxed)
+            )?;
+        }
+
+        writeln!(
+            out,
+            "# HELP hyperion_proxy_backpressure_limited_total Local broadcasts dropped or sent \
+             with a reduced radius to a player who was behind, by how far behind."
+        )?;
+        writeln!(
+            out,
+            "# TYPE hyperion_proxy_backpressure_limited_total counter"
+        )?;
+        for (level, count) in ["congested", "reduced_radius"]
+            .iter()
+            .zip(&self.backpressure)
+        {
+            writeln!(
+                out,
+                "hyperion_proxy_backpressure_limited_total{{level=\"{level}\"}} {}",
+                count.load(Ordering::Relaxed)
+            )?;
+        }
+
+        writeln!(
+            out,
+            "# HELP hyperion_proxy_broadcast_fanout_seconds Time to hand a broadcast to every \
+             receiving player."
+        )?;
+        writeln!(
+            out,
+            "# TYPE hyperion_proxy_broadcast_fanout_seconds histogram"
+        )?;
+        self.broadcast_global.render(
+            &mut out,
+            "hyperion_proxy_broadcast_fanout_seconds",
+            "kind=\"global\"",
+        )?;
+        self.broadcast_local.render(
+            &mut out,
+            "hyperion_proxy_broadcast_fanout_seconds",
+            "kind=\"local\"",
+        )?;
+
+        self.render_queue_depths(&mut out)?;
+
+        Ok(out)
+    }
+
+    /// Samples the number of pending messages of every player.
+    fn render_queue_depths(&self, out: &mut String) -> std::fmt::Result {
+        let registry = *self.player_registry.lock().unwrap();
+
+        let mut buckets = [0_u64; QUEUE_DEPTH_BUCKETS.len()];
+        let mut count = 0_u64;
+        let mut sum = 0_u64;
+        let mut max = 0;
+
+        if let Some(registry) = registry {
+            for player in registry.pin().values() {
+                let depth = player.queue_len();
+
+                if let Some(bucket) = QUEUE_DEPTH_BUCKETS.iter().position(|&bound| depth <= bound) {
+                    buckets[bucket] += 1;
+                }
+
+                count += 1;
+                sum += depth as u64;
+                max = max.max(depth);
+            }
+        }
+
+        writeln!(
+            out,
+            "# HELP hyperion_proxy_player_queue_capacity Maximum pending messages per player."
+        )?;
+        writeln!(out, "# TYPE hyperion_proxy_player_queue_capacity gauge")?;
+        writeln!(
+            out,
+            "hyperion_proxy_player_queue_capacity {MAX_PLAYER_PENDING_MESSAGES}"
+        )?;
+
+        writeln!(
+            out,
+            "# HELP hyperion_proxy_player_queue_depth_max Largest pending message queue of any \
+             player."
+        )?;
+        writeln!(out, "# TYPE hyperion_proxy_player_queue_depth_max gauge")?;
+        writeln!(out, "hyperion_proxy_player_queue_depth_max {max}")?;
+
+        writeln!(
+            out,
+            "# HELP hyperion_proxy_player_queue_depth Pending messages per player."
+        )?;
+        writeln!(out, "# TYPE hyperion_proxy_player_queue_depth histogram")?;
+
+        let mut cumulative = 0;
+        for (bound, bucket) in QUEUE_DEPTH_BUCKETS.iter().zip(buckets) {
+            cumulative += bucket;
+            writeln!(
+                out,
+                "hyperion_proxy_player_queue_depth_bucket{{le=\"{bound}\"}} {cumulative}"
+            )?;
+        }
+        writeln!(
+            out,
+            "hyperion_proxy_player_queue_depth_bucket{{le=\"+Inf\"}} {count}"
+        )?;
+        writeln!(out, "hyperion_proxy_player_queue_depth_sum {sum}")?;
+        writeln!(out, "hyperion_proxy_player_queue_depth_count {count}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_is_cumulative() {
+        let histogram = Histogram::new();
+        histogram.observe(Duration::from_micros(50));
+        histogram.observe(Duration::from_millis(3));
+        histogram.observe(Duration::from_secs(1));
+
+        let mut out = String::new();
+        histogram
+            .render(&mut out, "fanout", "kind=\"global\"")
+            .unwrap();
+
+        assert!(out.contains("fanout_bucket{kind=\"global\",le=\"0.0001\"} 1\n"));
+        assert!(out.contains("fanout_bucket{kind=\"global\",le=\"0.005\"} 2\n"));
+        assert!(out.contains("fanout_bucket{kind=\"global\",le=\"0.1\"} 2\n"));
+        assert!(out.contains("fanout_bucket{kind=\"global\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("fanout_count{kind=\"global\"} 3\n"));
+    }
+}
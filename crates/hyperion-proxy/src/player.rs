@@ -1,7 +1,16 @@
 //! Player connection handling and packet processing.
 
-use std::io::IoSlice;
+use std::{
+    io::IoSlice,
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
+use bytes::Bytes;
 use hyperion_proto::{
     ChunkPosition, PlayerConnect, PlayerDisconnect, PlayerDisconnectReason, PlayerPackets,
     ProxyToServerMessage,
@@ -15,16 +24,25 @@ use tokio::{
 use tracing::{info, info_span, instrument, warn};
 
 use crate::{
-    ShutdownType,
     cache::ExclusionsManager,
-    data::{OrderedBytes, PlayerHandle},
-    server_sender::ServerSender,
+    data::{OrderedBytes, PlayerHandle, Session},
+    frame::{self, Frame},
+    handshake::read_virtual_host,
+    metrics::{DisconnectReason, METRICS},
+    proxy_protocol,
+    server_sender::{ServerLink, ServerSender, connected},
     util::AsyncWriteVectoredExt,
 };
 
 /// Default buffer size for reading player packets, set to 8 KiB.
 const DEFAULT_READ_BUFFER_SIZE: usize = 8 * 1024;
 
+/// How long a client has to send its PROXY header and handshake before it is dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The handshake and login start packets, which are replayed when a player is resumed.
+const LOGIN_FRAMES: usize = 2;
+
 /// Initiates a player connection handler, managing both incoming and outgoing packet streams.
 ///
 /// This function sets up two asynchronous tasks:
@@ -32,13 +50,22 @@ const DEFAULT_READ_BUFFER_SIZE: usize = 8 * 1024;
 /// 2. A writer task that sends outgoing packets to the player.
 ///
 /// It also handles player disconnection and shutdown scenarios.
+///
+/// If `proxy_protocol` is set, the connection must start with a PROXY protocol header and the
+/// client address is taken from it instead of `peer_addr`.
+///
+/// While there is no server connection the reader stops reading from the client. Only whole
+/// packet frames are forwarded, so a resumed player's stream continues cleanly on the next server.
 #[instrument(skip_all, fields(player_id = player_id))]
+#[expect(clippy::too_many_arguments, reason = "todo; refactor")]
 pub fn initiate_player_connection(
     socket: impl tokio::io::AsyncRead + AsyncWrite + Send + 'static,
-    mut shutdown_signal: tokio::sync::watch::Receiver<Option<ShutdownType>>,
+    peer_addr: Option<SocketAddr>,
+    proxy_protocol: bool,
+    mut shutdown_signal: tokio::sync::watch::Receiver<bool>,
     player_id: u64,
     incoming_packet_receiver: kanal::AsyncReceiver<OrderedBytes>,
-    server_sender: ServerSender,
+    server_link: ServerLink,
     player_registry: &'static papaya::HashMap<u64, PlayerHandle, FxBuildHasher>,
     player_positions: &'static papaya::HashMap<u64, ChunkPosition, FxBuildHasher>,
 ) -> JoinHandle<()> {
@@ -51,47 +78,130 @@ pub fn initiate_player_connection(
     let mut socket_reader = Box::pin(socket_reader);
     let socket_writer = Box::pin(socket_writer);
 
+    // Whether the server was told about the player, which it must then be told they left
+    let announced = Arc::new(AtomicBool::new(false));
+
     // Task for handling incoming packets (player -> proxy)
     let mut packet_reader_task = tokio::spawn({
-        let server_sender = server_sender.clone();
+        let mut server_link = server_link.clone();
+        let announced = Arc::clone(&announced);
         async move {
             let mut read_buffer = Vec::new();
             let player_stream_id = player_id;
 
+            let addr = if proxy_protocol {
+                let header = proxy_protocol::read_header(&mut socket_reader, &mut read_buffer);
+
+                match tokio::time::timeout(HANDSHAKE_TIMEOUT, header)
+                    .await
+                    .unwrap_or_else(|elapsed| Err(elapsed.into()))
+                {
+                    Ok(header) => header.source.or(peer_addr),
+                    Err(e) => {
+                        warn!("Error reading PROXY header from {peer_addr:?}: {e:?}");
+                        return DisconnectReason::InvalidProxyHeader;
+                    }
+                }
+            } else {
+                peer_addr
+            };
+
+            let handshake = read_virtual_host(&mut socket_reader, &mut read_buffer);
+
+            let virtual_host = match tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake)
+                .await
+                .unwrap_or_else(|elapsed| Err(elapsed.into()))
+            {
+                Ok(virtual_host) => virtual_host,
+                Err(e) => {
+                    warn!("Error reading handshake from player: {e:?}");
+                    return DisconnectReason::LostConnection;
+                }
+            };
+
+            let Some(mut server_sender) = connected(&mut server_link).await else {
+                return DisconnectReason::ServerShutdown;
+            };
+
             let connect = rkyv::to_bytes::<rkyv::rancor::Error>(
                 &ProxyToServerMessage::PlayerConnect(PlayerConnect {
                     stream: player_stream_id,
+                    addr,
+                    virtual_host: virtual_host.as_deref(),
+                    resume: false,
                 }),
             )
             .unwrap();
 
             if let Err(e) = server_sender.send(connect).await {
                 warn!("failed to send player connect to server: {e}");
-                return;
+                return DisconnectReason::ServerShutdown;
             }
 
+            announced.store(true, Ordering::Relaxed);
+
+            let mut login = Vec::new();
+            let mut login_frames = 0;
+
             let mut arena = Arena::new();
 
             loop {
-                // Ensure the buffer has enough capacity
-                read_buffer.reserve(DEFAULT_READ_BUFFER_SIZE);
+                let Some(len) = frame::complete_len(&read_buffer) else {
+                    warn!("Invalid packet frame from player");
+                    return DisconnectReason::LostConnection;
+                };
 
-                let bytes_read = match socket_reader.read_buf(&mut read_buffer).await {
-                    Ok(n) => n,
-                    Err(e) => {
-                        warn!("Error reading from player: {e:?}");
-                        return;
+                if len == 0 {
+                    // Ensure the buffer has enough capacity
+                    read_buffer.reserve(DEFAULT_READ_BUFFER_SIZE);
+
+                    let bytes_read = match socket_reader.read_buf(&mut read_buffer).await {
+                        Ok(n) => n,
+                        Err(e) => {
+                            warn!("Error reading from player: {e:?}");
+                            return DisconnectReason::LostConnection;
+                        }
+                    };
+
+                    if bytes_read == 0 {
+                        warn!("End of stream reached for player");
+                        return DisconnectReason::LostConnection;
                     }
-                };
 
-                if bytes_read == 0 {
-                    warn!("End of stream reached for player");
-                    return;
+                    METRICS
+                        .client_bytes_received
+                        .fetch_add(bytes_read as u64, Ordering::Relaxed);
+
+                    continue;
+                }
+
+                let frames = &read_buffer[..len];
+
+                if login_frames < LOGIN_FRAMES {
+                    let mut rest = frames;
+
+                    while login_frames < LOGIN_FRAMES
+                        && let Frame::Complete(frame_len) = frame::peek(rest)
+                    {
+                        login.extend_from_slice(&rest[..frame_len]);
+                        rest = &rest[frame_len..];
+                        login_frames += 1;
+                    }
+
+                    if login_frames == LOGIN_FRAMES
+                        && let Some(handle) = player_registry.pin().get(&player_id)
+                    {
+                        handle.set_session(Session {
+                            addr,
+                            virtual_host: virtual_host.clone(),
+                            login: Bytes::from(std::mem::take(&mut login)),
+                        });
+                    }
                 }
 
                 let player_packets = ProxyToServerMessage::PlayerPackets(PlayerPackets {
                     stream: player_id,
-                    data: &read_buffer,
+                    data: frames,
                 });
 
                 let aligned_vec = rkyv::api::high::to_bytes_with_alloc::<_, rkyv::rancor::Error>(
@@ -100,11 +210,15 @@ pub fn initiate_player_connection(
                 )
                 .unwrap();
 
-                read_buffer.clear();
+                read_buffer.drain(..len);
 
-                if let Err(e) = server_sender.send(aligned_vec).await {
-                    warn!("Error forwarding player packets to server: {e:?}");
-                    return;
+                if server_sender.send(aligned_vec).await.is_err() {
+                    // The server is gone and these packets with it. If the player is resumed,
+                    // the next server replays their login and carries on from the next frame.
+                    let Some(sender) = connected(&mut server_link).await else {
+                        return DisconnectReason::ServerShutdown;
+                    };
+                    server_sender = sender;
                 }
             }
         }
@@ -114,16 +228,22 @@ pub fn initiate_player_connection(
     let mut packet_writer_task = tokio::spawn(async move {
         let mut packet_writer = PlayerPacketWriter::new(socket_writer, player_id);
 
+        // The channel is only shut down or closed when the player's queue is full or the
+        // player could not be kept across a server reconnect
         while let Ok(outgoing_packet) = incoming_packet_receiver.recv().await {
             if outgoing_packet.is_shutdown() {
-                return;
+                return DisconnectReason::CouldNotKeepUp;
+            }
+
+            if outgoing_packet.is_server_lost() {
+                return DisconnectReason::ServerShutdown;
             }
 
             if outgoing_packet.is_flush() {
                 let time_start = std::time::Instant::now();
                 if let Err(e) = packet_writer.flush_pending_packets().await {
                     warn!("Error flushing packets to player: {e:?}");
-                    return;
+                    return DisconnectReason::LostConnection;
                 }
                 let duration = time_start.elapsed();
                 if duration > std::time::Duration::from_millis(50) {
@@ -133,60 +253,119 @@ pub fn initiate_player_connection(
                 packet_writer.enqueue_packet(outgoing_packet);
             }
         }
+
+        DisconnectReason::CouldNotKeepUp
     });
 
     tokio::task::spawn(async move {
         let shutdown_received = async move {
-            shutdown_signal.wait_for(Option::is_some).await.unwrap();
+            shutdown_signal
+                .wait_for(|&shutdown| shutdown)
+                .await
+                .unwrap();
         };
 
-        tokio::select! {
+        let reason = tokio::select! {
             () = shutdown_received => {
                 info!("Shutting down player connection due to server shutdown");
                 packet_reader_task.abort();
                 packet_writer_task.abort();
+
+                DisconnectReason::ServerShutdown
             },
-            _ = &mut packet_writer_task => {
+            result = &mut packet_writer_task => {
                 info!("Player disconnected because writer task finished: {player_id:?}");
                 packet_reader_task.abort();
 
-                let disconnect = rkyv::to_bytes::<rkyv::rancor::Error>(
-                    &ProxyToServerMessage::PlayerDisconnect(PlayerDisconnect {
-                        stream: player_id,
-                        reason: PlayerDisconnectReason::LostConnection,
-                    }),
-                ).unwrap();
+                let reason = result.unwrap_or(DisconnectReason::LostConnection);
 
-                if let Err(e) = server_sender.send(disconnect).await {
-                    warn!("failed to send player disconnect to server: {e}");
+                if announced.load(Ordering::Relaxed) {
+                    send_disconnect(&server_link, player_id, reason).await;
                 }
+
+                reason
             },
-            _ = &mut packet_reader_task => {
+            result = &mut packet_reader_task => {
                 info!("Player disconnected because reader task finished: {player_id:?}");
                 packet_writer_task.abort();
 
+                let reason = result.unwrap_or(DisconnectReason::LostConnection);
 
-                let disconnect = rkyv::to_bytes::<rkyv::rancor::Error>(
-                    &ProxyToServerMessage::PlayerDisconnect(PlayerDisconnect {
-                        stream: player_id,
-                        reason: PlayerDisconnectReason::LostConnection,
-                    })).unwrap();
-
-                if let Err(e) = server_sender.send(disconnect).await {
-                    warn!("failed to send player disconnect to server: {e}");
+                if announced.load(Ordering::Relaxed) {
+                    send_disconnect(&server_link, player_id, reason).await;
                 }
 
-                let map_ref = player_registry.pin();
-                map_ref.remove(&player_id);
+                reason
+            }
+        };
 
-                let map_ref = player_positions.pin();
-                map_ref.remove(&player_id);
+        // Removing the player also stops them from being resumed on the next server
+        let map_ref = player_registry.pin();
+        map_ref.remove(&player_id);
 
-            }
-        }
+        let map_ref = player_positions.pin();
+        map_ref.remove(&player_id);
+
+        METRICS.record_disconnect(reason);
     })
 }
 
+/// Tells the server that a player has disconnected.
+///
+/// Nothing is sent while there is no server connection; the next server never hears of them.
+async fn send_disconnect(server_link: &ServerLink, player_id: u64, reason: DisconnectReason) {
+    let Some(server_sender) = server_link.borrow().clone() else {
+        return;
+    };
+
+    let reason = match reason {
+        DisconnectReason::CouldNotKeepUp => PlayerDisconnectReason::CouldNotKeepUp,
+        DisconnectReason::LostConnection
+        | DisconnectReason::ServerShutdown
+        | DisconnectReason::InvalidProxyHeader => PlayerDisconnectReason::LostConnection,
+    };
+
+    let disconnect = rkyv::to_bytes::<rkyv::rancor::Error>(
+        &ProxyToServerMessage::PlayerDisconnect(PlayerDisconnect {
+            stream: player_id,
+            reason,
+        }),
+    )
+    .unwrap();
+
+    if let Err(e) = server_sender.send(disconnect).await {
+        warn!("failed to send player disconnect to server: {e}");
+    }
+}
+
+/// Announces a paused player to a newly connected server by replaying their login.
+pub(crate) async fn resume(
+    server_sender: &ServerSender,
+    player_id: u64,
+    session: &Session,
+) -> anyhow::Result<()> {
+    let connect = rkyv::to_bytes::<rkyv::rancor::Error>(&ProxyToServerMessage::PlayerConnect(
+        PlayerConnect {
+            stream: player_id,
+            addr: session.addr,
+            virtual_host: session.virtual_host.as_deref(),
+            resume: true,
+        },
+    ))?;
+
+    let login = rkyv::to_bytes::<rkyv::rancor::Error>(&ProxyToServerMessage::PlayerPackets(
+        PlayerPackets {
+            stream: player_id,
+            data: &session.login,
+        },
+    ))?;
+
+    server_sender.send(connect).await?;
+    server_sender.send(login).await?;
+
+    Ok(())
+}
+
 /// Manages the writing of packets to a player's connection.
 struct PlayerPacketWriter<W> {
     writer: W,
@@ -233,7 +412,14 @@ impl<W: AsyncWrite + Unpin> PlayerPacketWriter<W> {
             }
         }
 
+        let bytes: usize = self.io_vecs.iter().map(|iovec| iovec.len()).sum();
+
         self.writer.write_vectored_all(&mut self.io_vecs).await?;
+
+        METRICS
+            .client_bytes_sent
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+
         self.pending_packets.clear();
         self.io_vecs.clear();
 
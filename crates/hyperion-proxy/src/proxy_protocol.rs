@@ -0,0 +1,239 @@
+//! Parsing of `HAProxy` PROXY protocol headers (v1 and v2).
+//!
+//! When the proxy sits behind a load balancer, the TCP peer address is the load balancer's.
+//! The load balancer prepends a PROXY header to every connection which carries the address
+//! of the real client.
+//!
+//! See <https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt>
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use anyhow::{bail, ensure};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// The signature every v2 header starts with.
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The prefix every v1 header starts with.
+const V1_PREFIX: &[u8] = b"PROXY ";
+
+/// The maximum length of a v1 header including the trailing CRLF.
+const V1_MAX_LEN: usize = 107;
+
+/// The length of the fixed part of a v2 header.
+const V2_HEADER_LEN: usize = 16;
+
+/// The result of parsing a PROXY header.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ProxyHeader {
+    /// The number of bytes the header occupies at the start of the stream.
+    pub len: usize,
+
+    /// The address of the client. This is [`None`] for health checks from the load balancer
+    /// itself (`LOCAL` in v2, `UNKNOWN` in v1) and for address families we do not support.
+    pub source: Option<SocketAddr>,
+}
+
+/// The state of parsing a PROXY header from a buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Parse {
+    /// More bytes are needed.
+    Incomplete,
+    /// A full header has been parsed.
+    Complete(ProxyHeader),
+}
+
+/// Parses a PROXY header from the start of `buf`.
+pub fn parse(buf: &[u8]) -> anyhow::Result<Parse> {
+    let prefix_len = buf.len().min(V2_SIGNATURE.len());
+
+    if buf[..prefix_len] == V2_SIGNATURE[..prefix_len] {
+        if buf.len() < V2_HEADER_LEN {
+            return Ok(Parse::Incomplete);
+        }
+        return parse_v2(buf);
+    }
+
+    let prefix_len = buf.len().min(V1_PREFIX.len());
+
+    if buf[..prefix_len] == V1_PREFIX[..prefix_len] {
+        return parse_v1(buf);
+    }
+
+    bail!("connection did not start with a PROXY protocol header")
+}
+
+fn parse_v1(buf: &[u8]) -> anyhow::Result<Parse> {
+    let Some(end) = buf.windows(2).position(|window| window == b"\r\n") else {
+        ensure!(buf.len() < V1_MAX_LEN, "PROXY v1 header is too long");
+        return Ok(Parse::Incomplete);
+    };
+
+    let len = end + 2;
+    ensure!(len <= V1_MAX_LEN, "PROXY v1 header is too long");
+
+    let line = std::str::from_utf8(&buf[V1_PREFIX.len()..end])?;
+    let mut parts = line.split(' ');
+
+    let source = match parts.next() {
+        Some("TCP4" | "TCP6") => {
+            let (Some(src), Some(_dst), Some(src_port), Some(_dst_port)) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                bail!("malformed PROXY v1 header: {line:?}");
+            };
+
+            let ip: IpAddr = src.parse()?;
+            let port: u16 = src_port.parse()?;
+
+            Some(SocketAddr::new(ip, port))
+        }
+        Some("UNKNOWN") => None,
+        _ => bail!("unsupported PROXY v1 protocol: {line:?}"),
+    };
+
+    Ok(Parse::Complete(ProxyHeader { len, source }))
+}
+
+fn parse_v2(buf: &[u8]) -> anyhow::Result<Parse> {
+    let version_command = buf[12];
+    let family_protocol = buf[13];
+    let address_len = usize::from(u16::from_be_bytes([buf[14], buf[15]]));
+
+    ensure!(
+        version_command >> 4 == 2,
+        "unsupported PROXY protocol version {}",
+        version_command >> 4
+    );
+
+    let len = V2_HEADER_LEN + address_len;
+
+    if buf.len() < len {
+        return Ok(Parse::Incomplete);
+    }
+
+    let addresses = &buf[V2_HEADER_LEN..len];
+
+    let source = match version_command & 0x0F {
+        // LOCAL: the connection was made by the load balancer itself
+        0x0 => None,
+        // PROXY
+        0x1 => match family_protocol >> 4 {
+            // AF_INET
+            0x1 => {
+                ensure!(addresses.len() >= 12, "PROXY v2 IPv4 block is too short");
+                let ip: [u8; 4] = addresses[..4].try_into()?;
+                let port = u16::from_be_bytes([addresses[8], addresses[9]]);
+                Some(SocketAddr::new(Ipv4Addr::from(ip).into(), port))
+            }
+            // AF_INET6
+            0x2 => {
+                ensure!(addresses.len() >= 36, "PROXY v2 IPv6 block is too short");
+                let ip: [u8; 16] = addresses[..16].try_into()?;
+                let port = u16::from_be_bytes([addresses[32], addresses[33]]);
+                Some(SocketAddr::new(Ipv6Addr::from(ip).into(), port))
+            }
+            // AF_UNSPEC, AF_UNIX
+            _ => None,
+        },
+        command => bail!("unsupported PROXY v2 command {command:#x}"),
+    };
+
+    Ok(Parse::Complete(ProxyHeader { len, source }))
+}
+
+/// Reads a PROXY header from `reader`.
+///
+/// Bytes read past the end of the header are left in `buffer` so they can be forwarded.
+pub async fn read_header(
+    reader: &mut (impl AsyncRead + Unpin),
+    buffer: &mut Vec<u8>,
+) -> anyhow::Result<ProxyHeader> {
+    loop {
+        if let Parse::Complete(header) = parse(buffer)? {
+            buffer.drain(..header.len);
+            return Ok(header);
+        }
+
+        buffer.reserve(V1_MAX_LEN);
+
+        let bytes_read = reader.read_buf(buffer).await?;
+        ensure!(bytes_read != 0, "end of stream before PROXY header");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v1_tcp4() {
+        let header = b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 25565\r\n\x10\x00";
+
+        assert_eq!(
+            parse(header).unwrap(),
+            Parse::Complete(ProxyHeader {
+                len: header.len() - 2,
+                source: Some("192.0.2.1:56324".parse().unwrap()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_v1_unknown() {
+        let header = b"PROXY UNKNOWN\r\n";
+
+        assert_eq!(
+            parse(header).unwrap(),
+            Parse::Complete(ProxyHeader {
+                len: header.len(),
+                source: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_v1_incomplete() {
+        assert_eq!(parse(b"PRO").unwrap(), Parse::Incomplete);
+        assert_eq!(parse(b"PROXY TCP4 192.0.2.1").unwrap(), Parse::Incomplete);
+    }
+
+    #[test]
+    fn test_v2_tcp4() {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0x21, 0x11, 0x00, 0x0C]);
+        header.extend_from_slice(&[203, 0, 113, 7]);
+        header.extend_from_slice(&[10, 0, 0, 1]);
+        header.extend_from_slice(&40_000_u16.to_be_bytes());
+        header.extend_from_slice(&25_565_u16.to_be_bytes());
+
+        assert_eq!(parse(&header[..20]).unwrap(), Parse::Incomplete);
+
+        assert_eq!(
+            parse(&header).unwrap(),
+            Parse::Complete(ProxyHeader {
+                len: 28,
+                source: Some("203.0.113.7:40000".parse().unwrap()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_v2_local() {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0x20, 0x00, 0x00, 0x00]);
+
+        assert_eq!(
+            parse(&header).unwrap(),
+            Parse::Complete(ProxyHeader {
+                len: 16,
+                source: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_header() {
+        assert!(parse(b"\x10\x00\xfa\x05").is_err());
+    }
+}
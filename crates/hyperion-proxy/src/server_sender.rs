@@ -1,12 +1,29 @@
-use std::io::IoSlice;
+use std::{io::IoSlice, sync::atomic::Ordering};
 
 use rkyv::util::AlignedVec;
 use tracing::{Instrument, trace_span, warn};
 
-use crate::util::AsyncWriteVectoredExt;
+use crate::{metrics::METRICS, util::AsyncWriteVectoredExt};
 
 pub type ServerSender = kanal::AsyncSender<AlignedVec>;
 
+/// The sender for the current server connection, or [`None`] while reconnecting.
+///
+/// Players hold on to this instead of a [`ServerSender`] so they survive the server going away.
+pub type ServerLink = tokio::sync::watch::Receiver<Option<ServerSender>>;
+
+/// Waits until there is a server connection and returns its sender.
+///
+/// A sender which has already been closed is skipped; the link is about to change.
+///
+/// Returns [`None`] if the proxy is shutting down.
+pub async fn connected(link: &mut ServerLink) -> Option<ServerSender> {
+    link.wait_for(|sender| sender.as_ref().is_some_and(|sender| !sender.is_closed()))
+        .await
+        .ok()
+        .and_then(|sender| sender.clone())
+}
+
 // todo: probably makes sense for caller to encode bytes
 #[must_use]
 pub fn launch_server_writer(mut write: tokio::net::tcp::OwnedWriteHalf) -> ServerSender {
@@ -44,11 +61,17 @@ pub fn launch_server_writer(mut write: tokio::net::tcp::OwnedWriteHalf) -> Serve
                     io_slices.push(msg);
                 }
 
+                let bytes: usize = io_slices.iter().map(|slice| slice.len()).sum();
+
                 if let Err(e) = write.write_vectored_all(&mut io_slices).await {
                     warn!("failed to write to server: {e}");
                     return;
                 }
 
+                METRICS
+                    .server_bytes_sent
+                    .fetch_add(bytes as u64, Ordering::Relaxed);
+
                 lengths.clear();
                 messages.clear();
                 io_slices.clear();
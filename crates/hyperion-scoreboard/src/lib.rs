@@ -0,0 +1,295 @@
+//! Scoreboards: a sidebar for each player, objectives every player is sent and teams, which color
+//! the name tags of their members and set whether they collide and may hurt each other.
+//!
+//! Players are sent the whole scoreboard once they have joined, and only what changes after that.
+//! Players are on the team their [`TeamName`] names, so that teams follow whichever components
+//! the game mode sets it from.
+
+use std::{collections::HashMap, sync::Arc};
+
+use flecs_ecs::{
+    core::{
+        Builder, EntityView, EntityViewGet, Query, QueryAPI, QueryBuilderImpl, SystemAPI,
+        TermBuilderImpl, World, WorldGet, flecs,
+    },
+    macros::{Component, observer, system},
+    prelude::Module,
+};
+use hyperion::{
+    net::{Compose, ConnectionId, DataBundle},
+    simulation::{Name, Player, TeamName, skin::PlayerSkin},
+    util::SendableQuery,
+    valence_protocol::packets::play::{self, team_s2c::Mode},
+};
+
+pub mod objective;
+pub mod sidebar;
+pub mod team;
+
+pub use objective::{Objective, Objectives};
+pub use sidebar::Sidebar;
+pub use team::{TeamChange, TeamStyle, Teams};
+
+/// Marks players who were sent the scoreboard, who are only sent what changes after.
+#[derive(Component)]
+struct Synced;
+
+/// Marks players whose [`TeamName`] changed since the other players were told.
+#[derive(Component)]
+struct TeamChanged;
+
+/// The queries the scoreboard is synced with, which are built once as they run every tick.
+struct Queries {
+    /// The players who have joined.
+    members: SendableQuery<(&'static TeamName, &'static Name)>,
+    /// The players who have joined and whose team changed.
+    moved: SendableQuery<(&'static TeamName, &'static Name)>,
+    /// The players who were sent the scoreboard.
+    synced: SendableQuery<&'static ConnectionId>,
+    /// The players who have joined but were not sent the scoreboard.
+    joined: SendableQuery<&'static ConnectionId>,
+}
+
+impl Queries {
+    fn new(world: &World) -> Self {
+        Self {
+            members: SendableQuery(
+                world
+                    .query::<(&TeamName, &Name)>()
+                    .with::<PlayerSkin>()
+                    .set_cached()
+                    .build(),
+            ),
+            moved: SendableQuery(
+                world
+                    .query::<(&TeamName, &Name)>()
+                    .with::<TeamChanged>()
+                    .with::<PlayerSkin>()
+                    .set_cached()
+                    .build(),
+            ),
+            synced: SendableQuery(
+                world
+                    .query::<&ConnectionId>()
+                    .with::<Synced>()
+                    .set_cached()
+                    .build(),
+            ),
+            joined: SendableQuery(
+                world
+                    .query::<&ConnectionId>()
+                    .with::<PlayerSkin>()
+                    .without::<Synced>()
+                    .set_cached()
+                    .build(),
+            ),
+        }
+    }
+}
+
+/// The names of the players who have joined, by the name of their team.
+fn members(query: &Query<(&'static TeamName, &'static Name)>) -> HashMap<Arc<str>, Vec<Arc<str>>> {
+    let mut members: HashMap<Arc<str>, Vec<Arc<str>>> = HashMap::new();
+
+    query.each(|(team, name)| {
+        members
+            .entry(Arc::clone(team))
+            .or_default()
+            .push(Arc::clone(name));
+    });
+
+    members
+}
+
+/// Writes the packets which create `team` with its members.
+fn write_team(
+    name: &str,
+    style: &TeamStyle,
+    members: &HashMap<Arc<str>, Vec<Arc<str>>>,
+    bundle: &mut DataBundle<'_, '_>,
+) -> anyhow::Result<()> {
+    let entities = members
+        .get(name)
+        .map(|members| members.iter().map(|member| &**member).collect())
+        .unwrap_or_default();
+
+    bundle.add_packet(&style.create_packet(name, entities))
+}
+
+/// Tells the players who were sent the scoreboard what changed, and sends it to the players who
+/// joined since.
+fn sync(
+    queries: &Queries,
+    compose: &Compose,
+    system: EntityView<'_>,
+    teams: &mut Teams,
+    objectives: &mut Objectives,
+) -> anyhow::Result<()> {
+    let mut bundle = DataBundle::new(compose, system);
+
+    let team_changes = teams.take_changes();
+
+    if !team_changes.is_empty() {
+        let members = members(&queries.members.0);
+
+        for change in &team_changes {
+            match change {
+                TeamChange::Create(name) => {
+                    if let Some(style) = teams.get(name) {
+                        write_team(name, style, &members, &mut bundle)?;
+                    }
+                }
+                TeamChange::Update(name) => {
+                    if let Some(style) = teams.get(name) {
+                        bundle.add_packet(&style.update_packet(name))?;
+                    }
+                }
+                TeamChange::Remove(name) => {
+                    bundle.add_packet(&play::TeamS2c {
+                        team_name: name,
+                        mode: Mode::RemoveTeam,
+                    })?;
+                }
+            }
+        }
+    }
+
+    // players are only moved once they have joined, as joining puts them on the team which hides
+    // name tags
+    let mut moved = Vec::new();
+
+    queries.moved.0.each_entity(|entity, (team, name)| {
+        entity.remove::<TeamChanged>();
+
+        if teams.get(team).is_some() {
+            moved.push((Arc::clone(team), Arc::clone(name)));
+        }
+    });
+
+    for (team, name) in &moved {
+        bundle.add_packet(&play::TeamS2c {
+            team_name: team,
+            mode: Mode::AddEntities {
+                entities: vec![name],
+            },
+        })?;
+    }
+
+    let objective_changes = objectives.take_changes();
+    objectives.write_changes(&objective_changes, &mut bundle)?;
+
+    let mut streams = Vec::new();
+
+    queries.synced.0.each(|stream| streams.push(*stream));
+
+    for stream in streams {
+        bundle.unicast(stream)?;
+    }
+
+    let mut joined = Vec::new();
+
+    queries.joined.0.each_entity(|entity, stream| {
+        entity.add::<Synced>();
+        joined.push(*stream);
+    });
+
+    if joined.is_empty() {
+        return Ok(());
+    }
+
+    let members = members(&queries.members.0);
+    let mut bundle = DataBundle::new(compose, system);
+
+    for (name, style) in teams.sent() {
+        write_team(name, style, &members, &mut bundle)?;
+    }
+
+    objectives.write_all(&mut bundle)?;
+
+    for stream in joined {
+        bundle.unicast(stream)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Component)]
+pub struct ScoreboardModule;
+
+impl Module for ScoreboardModule {
+    fn module(world: &World) {
+        world.component::<Teams>();
+        world.component::<Objectives>();
+        world.component::<Sidebar>();
+        world.component::<Synced>();
+        world.component::<TeamChanged>();
+
+        world.set(Teams::default());
+        world.set(Objectives::default());
+
+        observer!(world, flecs::OnSet, &TeamName)
+            .with::<Player>()
+            .each_entity(|entity, _| {
+                entity.add::<TeamChanged>();
+            });
+
+        // players keep their sidebar on screen until they are told it is gone
+        observer!(world, flecs::OnRemove, &Sidebar).each_iter(|it, row, _sidebar| {
+            let system = it.system();
+            let world = it.world();
+            let entity = it.entity(row);
+
+            entity.try_get::<&ConnectionId>(|stream| {
+                let pkt = sidebar::remove_packet();
+
+                world.get::<&Compose>(|compose| {
+                    if let Err(e) = compose.unicast(&pkt, *stream, system) {
+                        tracing::error!("failed to remove sidebar: {e}");
+                    }
+                });
+            });
+        });
+
+        let queries = Queries::new(world);
+
+        system!(
+            "sync_scoreboard",
+            world,
+            &Compose($),
+            &mut Teams($),
+            &mut Objectives($),
+        )
+        .each_iter(move |it, _, (compose, teams, objectives)| {
+            let system = it.system();
+
+            if let Err(e) = sync(&queries, compose, system, teams, objectives) {
+                tracing::error!("failed to sync scoreboard: {e}");
+            }
+        });
+
+        system!(
+            "sync_sidebars",
+            world,
+            &Compose($),
+            &mut Sidebar,
+            &ConnectionId,
+        )
+        .with::<PlayerSkin>()
+        .each_iter(|it, _, (compose, sidebar, stream)| {
+            if !sidebar.is_changed() {
+                return;
+            }
+
+            let system = it.system();
+            let mut bundle = DataBundle::new(compose, system);
+
+            let result = sidebar
+                .write_changes(&mut bundle)
+                .and_then(|()| bundle.unicast(*stream));
+
+            if let Err(e) = result {
+                tracing::error!("failed to send sidebar: {e}");
+            }
+        });
+    }
+}
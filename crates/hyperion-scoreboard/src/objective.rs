@@ -0,0 +1,353 @@
+//! Objectives every player is sent, such as the kills of each player shown below their name or in
+//! the player list.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use flecs_ecs::macros::Component;
+pub use hyperion::valence_protocol::packets::play::{
+    scoreboard_display_s2c::ScoreboardPosition,
+    scoreboard_objective_update_s2c::ObjectiveRenderType,
+};
+use hyperion::{
+    net::DataBundle,
+    valence_protocol::{
+        VarInt,
+        packets::play::{
+            self, scoreboard_objective_update_s2c::ObjectiveMode,
+            scoreboard_player_update_s2c::ScoreboardPlayerUpdateAction,
+        },
+        text::IntoText,
+    },
+};
+
+/// An objective, which keeps a score for each of its entries, such as the names of players.
+#[derive(Clone, Debug)]
+pub struct Objective {
+    /// The name of the objective where clients show it, such as `Kills`.
+    pub display_name: String,
+    pub render: ObjectiveRenderType,
+    /// Where clients show the objective, or `None` if they do not.
+    pub position: Option<ScoreboardPosition>,
+    scores: BTreeMap<String, i32>,
+}
+
+impl Objective {
+    #[must_use]
+    pub fn new(
+        display_name: impl Into<String>,
+        render: ObjectiveRenderType,
+        position: Option<ScoreboardPosition>,
+    ) -> Self {
+        Self {
+            display_name: display_name.into(),
+            render,
+            position,
+            scores: BTreeMap::new(),
+        }
+    }
+
+    fn write_info(
+        &self,
+        name: &str,
+        bundle: &mut DataBundle<'_, '_>,
+        create: bool,
+    ) -> anyhow::Result<()> {
+        let objective_display_name = self.display_name.as_str().into_cow_text();
+
+        let mode = if create {
+            ObjectiveMode::Create {
+                objective_display_name,
+                render_type: self.render,
+            }
+        } else {
+            ObjectiveMode::Update {
+                objective_display_name,
+                render_type: self.render,
+            }
+        };
+
+        bundle.add_packet(&play::ScoreboardObjectiveUpdateS2c {
+            objective_name: name,
+            mode,
+        })?;
+
+        if let Some(position) = self.position {
+            bundle.add_packet(&play::ScoreboardDisplayS2c {
+                position,
+                score_name: name,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn write_score(
+        &self,
+        name: &str,
+        entry: &str,
+        bundle: &mut DataBundle<'_, '_>,
+    ) -> anyhow::Result<()> {
+        let action = match self.scores.get(entry) {
+            Some(&score) => ScoreboardPlayerUpdateAction::Update {
+                objective_name: name,
+                objective_score: VarInt(score),
+            },
+            None => ScoreboardPlayerUpdateAction::Remove {
+                objective_name: name,
+            },
+        };
+
+        bundle.add_packet(&play::ScoreboardPlayerUpdateS2c {
+            entity_name: entry,
+            action,
+        })
+    }
+
+    /// Writes the packets which create the objective named `name` with all its scores.
+    fn write_create(&self, name: &str, bundle: &mut DataBundle<'_, '_>) -> anyhow::Result<()> {
+        self.write_info(name, bundle, true)?;
+
+        for entry in self.scores.keys() {
+            self.write_score(name, entry, bundle)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A change to objectives which clients have not been told about yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Pending {
+    Define(Arc<str>),
+    Remove(Arc<str>),
+    Score { objective: Arc<str>, entry: String },
+}
+
+/// What clients are told about an objective.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObjectiveChange {
+    Create(Arc<str>),
+    Update(Arc<str>),
+    Remove(Arc<str>),
+    /// The score of `entry` was set or reset.
+    Score {
+        objective: Arc<str>,
+        entry: String,
+    },
+}
+
+/// A singleton with the objectives every player is sent. Scores are only sent when they change.
+#[derive(Component, Debug, Default)]
+pub struct Objectives {
+    objectives: BTreeMap<Arc<str>, Objective>,
+    pending: Vec<Pending>,
+    /// The objectives clients know about.
+    sent: BTreeSet<Arc<str>>,
+}
+
+impl Objectives {
+    /// Defines the objective `name`, or changes how it is shown if it is already defined, keeping
+    /// its scores.
+    pub fn define(&mut self, name: impl Into<Arc<str>>, mut objective: Objective) {
+        let name = name.into();
+
+        if let Some(old) = self.objectives.get_mut(&name) {
+            objective.scores = std::mem::take(&mut old.scores);
+        }
+
+        self.objectives.insert(name.clone(), objective);
+        self.pending.push(Pending::Define(name));
+    }
+
+    /// Removes the objective `name` with its scores, returning it if it was defined.
+    pub fn remove(&mut self, name: &str) -> Option<Objective> {
+        let (name, objective) = self.objectives.remove_entry(name)?;
+        self.pending.push(Pending::Remove(name));
+        Some(objective)
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Objective> {
+        self.objectives.get(name)
+    }
+
+    /// The score of `entry` in the objective `objective`.
+    #[must_use]
+    pub fn score(&self, objective: &str, entry: &str) -> Option<i32> {
+        self.objectives.get(objective)?.scores.get(entry).copied()
+    }
+
+    /// Sets the score of `entry` in the objective `objective`, if it is defined.
+    pub fn set_score(&mut self, objective: &str, entry: &str, score: i32) {
+        let Some((name, found)) = self.objectives.get_key_value(objective) else {
+            return;
+        };
+
+        if found.scores.get(entry) == Some(&score) {
+            return;
+        }
+
+        let name = name.clone();
+
+        if let Some(found) = self.objectives.get_mut(objective) {
+            found.scores.insert(entry.to_owned(), score);
+        }
+
+        self.pending.push(Pending::Score {
+            objective: name,
+            entry: entry.to_owned(),
+        });
+    }
+
+    /// Removes the score of `entry` in the objective `objective`.
+    pub fn reset_score(&mut self, objective: &str, entry: &str) {
+        let Some(found) = self.objectives.get_mut(objective) else {
+            return;
+        };
+
+        if found.scores.remove(entry).is_none() {
+            return;
+        }
+
+        if let Some((name, _)) = self.objectives.get_key_value(objective) {
+            self.pending.push(Pending::Score {
+                objective: name.clone(),
+                entry: entry.to_owned(),
+            });
+        }
+    }
+
+    /// What clients must be told since they were last, counting it as told.
+    pub fn take_changes(&mut self) -> Vec<ObjectiveChange> {
+        let mut changes = Vec::new();
+
+        for pending in std::mem::take(&mut self.pending) {
+            match pending {
+                Pending::Define(name) => {
+                    if !self.objectives.contains_key(&name) {
+                        continue;
+                    }
+
+                    if self.sent.insert(name.clone()) {
+                        changes.push(ObjectiveChange::Create(name));
+                    } else {
+                        changes.push(ObjectiveChange::Update(name));
+                    }
+                }
+                Pending::Remove(name) => {
+                    if self.sent.remove(&name) {
+                        changes.push(ObjectiveChange::Remove(name));
+                    }
+                }
+                // objectives clients do not know about yet are sent with all their scores
+                Pending::Score { objective, entry } if self.sent.contains(&objective) => {
+                    changes.push(ObjectiveChange::Score { objective, entry });
+                }
+                Pending::Score { .. } => {}
+            }
+        }
+
+        changes
+    }
+
+    /// Writes the packets which tell clients about `changes`.
+    pub fn write_changes(
+        &self,
+        changes: &[ObjectiveChange],
+        bundle: &mut DataBundle<'_, '_>,
+    ) -> anyhow::Result<()> {
+        for change in changes {
+            match change {
+                ObjectiveChange::Create(name) => {
+                    if let Some(objective) = self.objectives.get(name) {
+                        objective.write_create(name, bundle)?;
+                    }
+                }
+                ObjectiveChange::Update(name) => {
+                    if let Some(objective) = self.objectives.get(name) {
+                        objective.write_info(name, bundle, false)?;
+                    }
+                }
+                ObjectiveChange::Remove(name) => {
+                    bundle.add_packet(&play::ScoreboardObjectiveUpdateS2c {
+                        objective_name: name,
+                        mode: ObjectiveMode::Remove,
+                    })?;
+                }
+                ObjectiveChange::Score { objective, entry } => {
+                    if let Some(found) = self.objectives.get(objective) {
+                        found.write_score(objective, entry, bundle)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the packets which create every objective clients know about, for players who join.
+    pub fn write_all(&self, bundle: &mut DataBundle<'_, '_>) -> anyhow::Result<()> {
+        for name in &self.sent {
+            if let Some(objective) = self.objectives.get(name) {
+                objective.write_create(name, bundle)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kills() -> Objective {
+        Objective::new("Kills", ObjectiveRenderType::Integer, None)
+    }
+
+    #[test]
+    fn test_changes() {
+        let mut objectives = Objectives::default();
+
+        objectives.define("kills", kills());
+        objectives.set_score("kills", "Notch", 1);
+        // undefined objectives have no scores
+        objectives.set_score("deaths", "Notch", 1);
+
+        assert_eq!(objectives.take_changes(), [ObjectiveChange::Create(
+            "kills".into()
+        )]);
+        assert_eq!(objectives.score("kills", "Notch"), Some(1));
+        assert_eq!(objectives.score("deaths", "Notch"), None);
+
+        // scores which do not change are not sent again
+        objectives.set_score("kills", "Notch", 1);
+        objectives.set_score("kills", "jeb_", 2);
+        objectives.reset_score("kills", "Notch");
+
+        assert_eq!(objectives.take_changes(), [
+            ObjectiveChange::Score {
+                objective: "kills".into(),
+                entry: "jeb_".to_owned(),
+            },
+            ObjectiveChange::Score {
+                objective: "kills".into(),
+                entry: "Notch".to_owned(),
+            },
+        ]);
+
+        // redefining keeps the scores
+        objectives.define("kills", kills());
+        assert_eq!(objectives.score("kills", "jeb_"), Some(2));
+
+        objectives.remove("kills");
+
+        // the objective was removed before clients were told it changed
+        assert_eq!(objectives.take_changes(), [ObjectiveChange::Remove(
+            "kills".into()
+        )]);
+    }
+}
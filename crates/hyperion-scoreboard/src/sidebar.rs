@@ -0,0 +1,318 @@
+//! The sidebar of each player, which shows them a title and lines of text on the right of their
+//! screen. Only the lines which change are sent again.
+
+use flecs_ecs::macros::Component;
+use hyperion::{
+    net::DataBundle,
+    valence_protocol::{
+        VarInt,
+        packets::play::{
+            self,
+            scoreboard_display_s2c::ScoreboardPosition,
+            scoreboard_objective_update_s2c::{ObjectiveMode, ObjectiveRenderType},
+            scoreboard_player_update_s2c::ScoreboardPlayerUpdateAction,
+        },
+        text::IntoText,
+    },
+};
+
+/// The name of the objective each player is shown their sidebar with. Every player has their own,
+/// so they may all share the name.
+const OBJECTIVE: &str = "hyperion:sidebar";
+
+/// The most lines clients show.
+pub const MAX_LINES: usize = 15;
+
+/// The most characters a line may have, leaving room for the code which keeps it unique within
+/// the 40 characters clients accept.
+pub const MAX_LINE_LENGTH: usize = 38;
+
+/// The text a player is shown on the right of their screen.
+#[derive(Component, Debug, Default)]
+pub struct Sidebar {
+    title: String,
+    lines: Vec<String>,
+    /// Whether the title or lines changed since the player was last sent them.
+    changed: bool,
+    /// The title and entries the player was last sent, or `None` if they were not sent one.
+    shown: Option<(String, Vec<String>)>,
+}
+
+impl Sidebar {
+    #[must_use]
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            changed: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        if title != self.title {
+            title.clone_into(&mut self.title);
+            self.changed = true;
+        }
+    }
+
+    /// Replaces the lines, from top to bottom. Lines past [`MAX_LINES`] are left out and
+    /// characters past [`MAX_LINE_LENGTH`] are cut, never splitting a `§` from its code.
+    pub fn set_lines<I>(&mut self, lines: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let lines: Vec<String> = lines.into_iter().take(MAX_LINES).map(Into::into).collect();
+
+        if lines != self.lines {
+            self.lines = lines;
+            self.changed = true;
+        }
+    }
+
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    #[must_use]
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Whether the player must be sent the sidebar again.
+    #[must_use]
+    pub const fn is_changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Writes the packets which bring the sidebar the player was last sent up to date, counting it
+    /// as sent.
+    pub fn write_changes(&mut self, bundle: &mut DataBundle<'_, '_>) -> anyhow::Result<()> {
+        let entries = entries_of(&self.lines);
+        let objective_display_name = self.title.as_str().into_cow_text();
+
+        let shown_entries = match &self.shown {
+            None => {
+                bundle.add_packet(&play::ScoreboardObjectiveUpdateS2c {
+                    objective_name: OBJECTIVE,
+                    mode: ObjectiveMode::Create {
+                        objective_display_name,
+                        render_type: ObjectiveRenderType::Integer,
+                    },
+                })?;
+
+                bundle.add_packet(&play::ScoreboardDisplayS2c {
+                    position: ScoreboardPosition::Sidebar,
+                    score_name: OBJECTIVE,
+                })?;
+
+                &[][..]
+            }
+            Some((title, shown_entries)) => {
+                if *title != self.title {
+                    bundle.add_packet(&play::ScoreboardObjectiveUpdateS2c {
+                        objective_name: OBJECTIVE,
+                        mode: ObjectiveMode::Update {
+                            objective_display_name,
+                            render_type: ObjectiveRenderType::Integer,
+                        },
+                    })?;
+                }
+
+                shown_entries.as_slice()
+            }
+        };
+
+        let (removed, updated) = diff(shown_entries, &entries);
+
+        for entry in removed {
+            bundle.add_packet(&play::ScoreboardPlayerUpdateS2c {
+                entity_name: entry,
+                action: ScoreboardPlayerUpdateAction::Remove {
+                    objective_name: OBJECTIVE,
+                },
+            })?;
+        }
+
+        for (entry, score) in updated {
+            bundle.add_packet(&play::ScoreboardPlayerUpdateS2c {
+                entity_name: entry,
+                action: ScoreboardPlayerUpdateAction::Update {
+                    objective_name: OBJECTIVE,
+                    objective_score: VarInt(score),
+                },
+            })?;
+        }
+
+        self.shown = Some((self.title.clone(), entries));
+        self.changed = false;
+
+        Ok(())
+    }
+}
+
+/// The packet which removes the sidebar from the screen of a player.
+pub(crate) const fn remove_packet() -> play::ScoreboardObjectiveUpdateS2c<'static> {
+    play::ScoreboardObjectiveUpdateS2c {
+        objective_name: OBJECTIVE,
+        mode: ObjectiveMode::Remove,
+    }
+}
+
+/// The names of the scores each line is shown with. Clients show the scores of an objective by
+/// name, so each line ends with an invisible color code of its own to keep lines with the same
+/// text apart.
+fn entries_of(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| format!("{}§{index:x}", truncate(line)))
+        .collect()
+}
+
+/// Cuts `line` to at most [`MAX_LINE_LENGTH`] characters. A `§` is cut together with the code
+/// after it, as a code left without its `§` would be shown and a `§` left without its code would
+/// take the code of the entry instead.
+fn truncate(line: &str) -> &str {
+    let mut chars = line.char_indices().peekable();
+    let mut len = 0;
+
+    while let Some((index, c)) = chars.next() {
+        let width = match c {
+            '§' if chars.peek().is_none() => return &line[..index],
+            '§' => 2,
+            _ => 1,
+        };
+
+        if len + width > MAX_LINE_LENGTH {
+            return &line[..index];
+        }
+
+        if width == 2 {
+            chars.next();
+        }
+
+        len += width;
+    }
+
+    line
+}
+
+/// The score which puts the line at `index` of `len` lines in its place, as clients sort scores
+/// from highest to lowest.
+fn score_of(index: usize, len: usize) -> i32 {
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        reason = "there are at most 15 lines"
+    )]
+    let score = (len - index) as i32;
+    score
+}
+
+/// The entries of `shown` which are gone from `entries`, and the entries with the score each must
+/// be set to.
+fn diff<'a>(shown: &'a [String], entries: &'a [String]) -> (Vec<&'a str>, Vec<(&'a str, i32)>) {
+    let removed = shown
+        .iter()
+        .filter(|entry| !entries.contains(entry))
+        .map(String::as_str)
+        .collect();
+
+    let updated = entries
+        .iter()
+        .enumerate()
+        .filter(|&(index, entry)| {
+            let score = score_of(index, entries.len());
+
+            shown
+                .iter()
+                .position(|shown_entry| shown_entry == entry)
+                .is_none_or(|shown_index| score_of(shown_index, shown.len()) != score)
+        })
+        .map(|(index, entry)| (entry.as_str(), score_of(index, entries.len())))
+        .collect();
+
+    (removed, updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_changes() {
+        let mut sidebar = Sidebar::default();
+        assert!(!sidebar.is_changed());
+
+        sidebar.set_lines(["Kills: 0"]);
+        assert!(sidebar.is_changed());
+
+        sidebar.changed = false;
+        sidebar.set_lines(["Kills: 0"]);
+        sidebar.set_title("");
+        assert!(!sidebar.is_changed());
+    }
+
+    #[test]
+    fn test_entries_are_unique() {
+        let entries = entries_of(&lines(&["", "Kills: 1", ""]));
+        assert_eq!(entries, ["§0", "Kills: 1§1", "§2"]);
+
+        let long = "x".repeat(50);
+        let entries = entries_of(&[long]);
+        assert_eq!(entries[0].chars().count(), MAX_LINE_LENGTH + 2);
+    }
+
+    #[test]
+    fn test_truncate_keeps_codes_whole() {
+        let fits = format!("{}§a", "x".repeat(MAX_LINE_LENGTH - 2));
+        assert_eq!(truncate(&fits), fits);
+
+        let long = "x".repeat(MAX_LINE_LENGTH - 1);
+        assert_eq!(truncate(&format!("{long}§ax")), long);
+
+        let code = "§a".repeat(MAX_LINE_LENGTH);
+        assert_eq!(truncate(&code).chars().count(), MAX_LINE_LENGTH);
+
+        assert_eq!(truncate("Kills§"), "Kills");
+    }
+
+    #[test]
+    fn test_diff_sends_changed_lines() {
+        let shown = entries_of(&lines(&["Kills: 1", "Level: 2"]));
+        let entries = entries_of(&lines(&["Kills: 2", "Level: 2"]));
+
+        let (removed, updated) = diff(&shown, &entries);
+
+        assert_eq!(removed, ["Kills: 1§0"]);
+        assert_eq!(updated, [("Kills: 2§0", 2)]);
+    }
+
+    #[test]
+    fn test_diff_reorders_lines() {
+        let shown = entries_of(&lines(&["Kills: 1"]));
+        let entries = entries_of(&lines(&["Kills: 1", "Level: 2"]));
+
+        let (removed, updated) = diff(&shown, &entries);
+
+        // with another line below it, the first line must score higher
+        assert!(removed.is_empty());
+        assert_eq!(updated, [("Kills: 1§0", 2), ("Level: 2§1", 1)]);
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let shown = entries_of(&lines(&["Kills: 1"]));
+
+        let (removed, updated) = diff(&shown, &shown);
+
+        assert!(removed.is_empty());
+        assert!(updated.is_empty());
+    }
+}
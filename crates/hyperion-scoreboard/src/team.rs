@@ -0,0 +1,207 @@
+//! Scoreboard teams, which color the name tags of their members and set whether they collide and
+//! may hurt each other. Players are on the team their [`TeamName`](hyperion::simulation::TeamName)
+//! names, once that team is defined in [`Teams`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use flecs_ecs::macros::Component;
+pub use hyperion::valence_protocol::packets::play::team_s2c::{
+    CollisionRule, NameTagVisibility, TeamColor,
+};
+use hyperion::valence_protocol::{
+    packets::play::{
+        self,
+        team_s2c::{Mode, TeamFlags},
+    },
+    text::IntoText,
+};
+
+/// How a team looks and behaves.
+#[derive(Clone, Debug)]
+pub struct TeamStyle {
+    /// The name of the team where clients show it, such as `Red`.
+    pub display_name: String,
+    /// The color of the names of members.
+    pub color: TeamColor,
+    /// Shown before the names of members, such as `[Admin] `.
+    pub prefix: String,
+    /// Shown after the names of members.
+    pub suffix: String,
+    /// Whose name tags members see.
+    pub name_tags: NameTagVisibility,
+    /// Whom members push.
+    pub collision: CollisionRule,
+    /// Whether members may hurt each other.
+    pub friendly_fire: bool,
+    /// Whether members see invisible members as translucent.
+    pub see_invisible_teammates: bool,
+}
+
+impl Default for TeamStyle {
+    fn default() -> Self {
+        Self {
+            display_name: String::new(),
+            color: TeamColor::White,
+            prefix: String::new(),
+            suffix: String::new(),
+            name_tags: NameTagVisibility::Always,
+            collision: CollisionRule::Always,
+            friendly_fire: true,
+            see_invisible_teammates: false,
+        }
+    }
+}
+
+impl TeamStyle {
+    fn flags(&self) -> TeamFlags {
+        TeamFlags::new()
+            .with_friendly_fire(self.friendly_fire)
+            .with_see_invisible_teammates(self.see_invisible_teammates)
+    }
+
+    /// The packet which creates the team named `name` with `members` on clients.
+    #[must_use]
+    pub fn create_packet<'a>(&'a self, name: &'a str, members: Vec<&'a str>) -> play::TeamS2c<'a> {
+        play::TeamS2c {
+            team_name: name,
+            mode: Mode::CreateTeam {
+                team_display_name: self.display_name.as_str().into_cow_text(),
+                friendly_flags: self.flags(),
+                name_tag_visibility: self.name_tags,
+                collision_rule: self.collision,
+                team_color: self.color,
+                team_prefix: self.prefix.as_str().into_cow_text(),
+                team_suffix: self.suffix.as_str().into_cow_text(),
+                entities: members,
+            },
+        }
+    }
+
+    /// The packet which changes the style of the team named `name` on clients.
+    #[must_use]
+    pub fn update_packet<'a>(&'a self, name: &'a str) -> play::TeamS2c<'a> {
+        play::TeamS2c {
+            team_name: name,
+            mode: Mode::UpdateTeamInfo {
+                team_display_name: self.display_name.as_str().into_cow_text(),
+                friendly_flags: self.flags(),
+                name_tag_visibility: self.name_tags,
+                collision_rule: self.collision,
+                team_color: self.color,
+                team_prefix: self.prefix.as_str().into_cow_text(),
+                team_suffix: self.suffix.as_str().into_cow_text(),
+            },
+        }
+    }
+}
+
+/// What clients are told about a team.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TeamChange {
+    Create(Arc<str>),
+    Update(Arc<str>),
+    Remove(Arc<str>),
+}
+
+/// A singleton with the teams players may be on, which every player is sent.
+#[derive(Component, Debug, Default)]
+pub struct Teams {
+    teams: BTreeMap<Arc<str>, TeamStyle>,
+    /// The teams defined, changed or removed since clients were last told.
+    changed: BTreeSet<Arc<str>>,
+    /// The teams clients know about.
+    sent: BTreeSet<Arc<str>>,
+}
+
+impl Teams {
+    /// Defines the team `name`, or changes its style if it is already defined.
+    pub fn define(&mut self, name: impl Into<Arc<str>>, style: TeamStyle) {
+        let name = name.into();
+        self.teams.insert(name.clone(), style);
+        self.changed.insert(name);
+    }
+
+    /// Removes the team `name`, returning its style if it was defined. Its members are left
+    /// without a team until it is defined again.
+    pub fn remove(&mut self, name: &str) -> Option<TeamStyle> {
+        let (name, style) = self.teams.remove_entry(name)?;
+        self.changed.insert(name);
+        Some(style)
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&TeamStyle> {
+        self.teams.get(name)
+    }
+
+    /// The teams clients know about, which players who join are sent.
+    pub fn sent(&self) -> impl Iterator<Item = (&str, &TeamStyle)> {
+        self.sent
+            .iter()
+            .filter_map(|name| Some((&**name, self.teams.get(name)?)))
+    }
+
+    /// What clients must be told since they were last, counting it as told.
+    pub fn take_changes(&mut self) -> Vec<TeamChange> {
+        let mut changes = Vec::new();
+
+        for name in std::mem::take(&mut self.changed) {
+            let defined = self.teams.contains_key(&name);
+            let sent = self.sent.contains(&name);
+
+            match (defined, sent) {
+                (true, false) => {
+                    self.sent.insert(name.clone());
+                    changes.push(TeamChange::Create(name));
+                }
+                (true, true) => changes.push(TeamChange::Update(name)),
+                (false, true) => {
+                    self.sent.remove(&name);
+                    changes.push(TeamChange::Remove(name));
+                }
+                // defined and removed before clients were told
+                (false, false) => {}
+            }
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes() {
+        let mut teams = Teams::default();
+
+        teams.define("red", TeamStyle::default());
+        teams.define("blue", TeamStyle::default());
+        teams.define("green", TeamStyle::default());
+        teams.remove("green");
+
+        assert_eq!(teams.take_changes(), [
+            TeamChange::Create("blue".into()),
+            TeamChange::Create("red".into()),
+        ]);
+        assert_eq!(teams.take_changes(), []);
+
+        teams.define("red", TeamStyle {
+            friendly_fire: false,
+            ..TeamStyle::default()
+        });
+        teams.remove("blue");
+
+        assert_eq!(teams.take_changes(), [
+            TeamChange::Remove("blue".into()),
+            TeamChange::Update("red".into()),
+        ]);
+
+        let sent: Vec<_> = teams.sent().map(|(name, _)| name).collect();
+        assert_eq!(sent, ["red"]);
+    }
+}
@@ -92,6 +92,7 @@ impl Color {
     pub const YELLOW: Self = Self::Named(NamedColor::Yellow);
 
     /// Constructs a new RGB color
+    #[must_use]
     pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
         Self::Rgb(RgbColor::new(r, g, b))
     }
@@ -99,11 +100,13 @@ impl Color {
 
 impl RgbColor {
     /// Constructs a new color from red, green, and blue components.
+    #[must_use]
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b }
     }
 
     /// Converts the RGB color to the closest [`NamedColor`] equivalent (lossy).
+    #[must_use]
     pub fn to_named_lossy(self) -> NamedColor {
         // calculates the squared distance between 2 colors
         const fn squared_distance(c1: RgbColor, c2: RgbColor) -> i32 {
@@ -138,11 +141,13 @@ impl RgbColor {
 
 impl NamedColor {
     /// Returns the corresponding hex digit of the color.
+    #[must_use]
     pub const fn hex_digit(self) -> char {
         b"0123456789abcdef"[self as usize] as char
     }
 
     /// Returns the identifier of the color.
+    #[must_use]
     pub const fn name(self) -> &'static str {
         [
             "black",
@@ -32,6 +32,16 @@ impl<'a> From<&'a str> for Text<'a> {
     }
 }
 
+impl From<String> for Text<'_> {
+    fn from(s: String) -> Self {
+        let mut text = Text::new("");
+        text.content = TextContent::Text {
+            text: Cow::Owned(s),
+        };
+        text
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
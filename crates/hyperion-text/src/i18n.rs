@@ -0,0 +1,259 @@
+//! Server messages in the language of each player.
+//!
+//! Every message is a [`Key`] declared with [`translation_keys!`](crate::translation_keys),
+//! which holds the text shown when no translation is loaded, so a misspelled key is a compile
+//! error rather than a missing message. Translations are loaded into [`Translations`] from one
+//! JSON file per locale, such as `de_de.json`, mapping the name of each key to its text:
+//!
+//! ```json
+//! { "command.no_permission": "<red>Du hast keine Berechtigung für diesen Befehl!" }
+//! ```
+//!
+//! Texts are written in the [MiniMessage-like format](Text::from_mini_message), and `{0}`,
+//! `{1}`, ... are replaced by the arguments of the message.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::Text;
+
+/// The locale of the texts in [`Key::default`], which is used for players whose language has no
+/// translation of a message.
+pub const DEFAULT_LOCALE: &str = "en_us";
+
+/// A message the server sends.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    /// The name of the message in translation files, such as `command.no_permission`.
+    pub name: &'static str,
+    /// The text of the message in [`DEFAULT_LOCALE`].
+    pub default: &'static str,
+}
+
+/// Declares [`Key`] constants, each with its name and its text in [`DEFAULT_LOCALE`].
+///
+/// ```
+/// hyperion_text::translation_keys! {
+///     /// Sent when a player joins.
+///     pub WELCOME = "welcome" => "<gold>Welcome, {0}!";
+/// }
+///
+/// assert_eq!(WELCOME.name, "welcome");
+/// ```
+#[macro_export]
+macro_rules! translation_keys {
+    ($($(#[$meta:meta])* $vis:vis $ident:ident = $name:literal => $default:literal;)*) => {
+        $(
+            $(#[$meta])*
+            $vis const $ident: $crate::Key = $crate::Key {
+                name: $name,
+                default: $default,
+            };
+        )*
+    };
+}
+
+/// An error loading translation files.
+#[derive(Debug, Error)]
+pub enum TranslationError {
+    #[error("failed to read {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("{} is not a JSON object of translations", path.display())]
+    Json {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// The translations of the server's messages into every loaded locale.
+#[derive(Clone, Debug)]
+pub struct Translations {
+    /// The locale used when the locale of a player has no translation of a message.
+    default_locale: String,
+    /// The texts of each locale by the name of their key.
+    locales: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for Translations {
+    fn default() -> Self {
+        Self {
+            default_locale: DEFAULT_LOCALE.to_owned(),
+            locales: HashMap::new(),
+        }
+    }
+}
+
+impl Translations {
+    /// Sets the locale used when the locale of a player has no translation of a message. Keys
+    /// without a translation in either locale use [`Key::default`].
+    pub fn set_default_locale(&mut self, locale: &str) {
+        self.default_locale = locale.to_ascii_lowercase();
+    }
+
+    /// Sets the text of the key named `key` in `locale`.
+    pub fn insert(&mut self, locale: &str, key: impl Into<String>, text: impl Into<String>) {
+        self.locales
+            .entry(locale.to_ascii_lowercase())
+            .or_default()
+            .insert(key.into(), text.into());
+    }
+
+    /// Loads the translations of `locale` from a JSON object, returning how many there were.
+    pub fn load(&mut self, locale: &str, json: &str) -> serde_json::Result<usize> {
+        let texts: HashMap<String, String> = serde_json::from_str(json)?;
+        let count = texts.len();
+
+        for (key, text) in texts {
+            self.insert(locale, key, text);
+        }
+
+        Ok(count)
+    }
+
+    /// Loads every `<locale>.json` file in `dir`, returning how many translations there were.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize, TranslationError> {
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| TranslationError::Io { path, source }
+        };
+
+        let mut count = 0;
+
+        for entry in fs::read_dir(dir).map_err(io_error(dir))? {
+            let path = entry.map_err(io_error(dir))?.path();
+
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let json = fs::read_to_string(&path).map_err(io_error(&path))?;
+
+            count += self
+                .load(locale, &json)
+                .map_err(|source| TranslationError::Json {
+                    path: path.clone(),
+                    source,
+                })?;
+        }
+
+        Ok(count)
+    }
+
+    /// The untranslated text of `key` in `locale`, falling back to the default locale.
+    #[must_use]
+    pub fn text(&self, locale: &str, key: Key) -> &str {
+        let locale = locale.to_ascii_lowercase();
+
+        [locale.as_str(), self.default_locale.as_str()]
+            .into_iter()
+            .find_map(|locale| self.locales.get(locale)?.get(key.name))
+            .map_or(key.default, String::as_str)
+    }
+
+    /// The message `key` in `locale`, with `{0}`, `{1}`, ... replaced by `args`. The arguments
+    /// are not parsed for tags, so names and other input from players are shown as they are.
+    #[must_use]
+    pub fn translate(&self, locale: &str, key: Key, args: &[&str]) -> Text<'static> {
+        let text = substitute(self.text(locale, key), args);
+        Text::from_mini_message(&text)
+    }
+}
+
+/// Replaces `{n}` in `text` with argument `n`, escaped so that it cannot open a tag.
+fn substitute(text: &str, args: &[&str]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        rest = &rest[open..];
+
+        let arg = rest[1..]
+            .split_once('}')
+            .and_then(|(index, after)| Some((args.get(index.parse::<usize>().ok()?)?, after)));
+
+        let Some((arg, after)) = arg else {
+            out.push('{');
+            rest = &rest[1..];
+            continue;
+        };
+
+        for c in arg.chars() {
+            if matches!(c, '<' | '\\') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+
+        rest = after;
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    crate::translation_keys! {
+        GREETING = "test.greeting" => "<gold>Hello, {0}!";
+        UNTRANSLATED = "test.untranslated" => "Only in English";
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        let mut translations = Translations::default();
+        translations.insert("de_de", GREETING.name, "<gold>Hallo, {0}!");
+        translations.insert("fr_fr", GREETING.name, "<gold>Bonjour, {0} !");
+        translations.set_default_locale("fr_fr");
+
+        assert_eq!(translations.text("de_DE", GREETING), "<gold>Hallo, {0}!");
+        assert_eq!(translations.text("ja_jp", GREETING), "<gold>Bonjour, {0} !");
+        assert_eq!(translations.text("de_de", UNTRANSLATED), "Only in English");
+    }
+
+    #[test]
+    fn test_translate_with_args() {
+        let translations = Translations::default();
+        let text = translations.translate("en_us", GREETING, &["<red>Steve"]);
+
+        assert_eq!(text.color, Some(Color::GOLD));
+        assert_eq!(text.to_legacy(), "§6Hello, <red>Steve!");
+    }
+
+    #[test]
+    fn test_substitute() {
+        assert_eq!(
+            substitute("{1} {0} {2} {x} {", &["a", "b"]),
+            "b a {2} {x} {"
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let mut translations = Translations::default();
+        let count = translations
+            .load("es_es", r#"{ "test.greeting": "¡Hola, {0}!" }"#)
+            .unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(translations.text("es_es", GREETING), "¡Hola, {0}!");
+        assert!(translations.load("es_es", "[]").is_err());
+    }
+}
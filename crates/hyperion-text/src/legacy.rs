@@ -0,0 +1,325 @@
+//! Legacy formatting codes such as `§c` for red and `§l` for bold, which are still used where
+//! the client does not accept JSON text, such as in team prefixes of old clients and in
+//! configuration written by hand.
+
+use crate::{
+    Text, TextContent,
+    color::{Color, NamedColor, RgbColor},
+    style::{Style, join},
+};
+
+/// The marker the client uses for formatting codes.
+pub const SECTION_SIGN: char = '§';
+
+const NAMED_COLORS: [NamedColor; 16] = [
+    NamedColor::Black,
+    NamedColor::DarkBlue,
+    NamedColor::DarkGreen,
+    NamedColor::DarkAqua,
+    NamedColor::DarkRed,
+    NamedColor::DarkPurple,
+    NamedColor::Gold,
+    NamedColor::Gray,
+    NamedColor::DarkGray,
+    NamedColor::Blue,
+    NamedColor::Green,
+    NamedColor::Aqua,
+    NamedColor::Red,
+    NamedColor::LightPurple,
+    NamedColor::Yellow,
+    NamedColor::White,
+];
+
+/// The style after the formatting code `code`, if it is one.
+fn apply_code(style: &Style, code: char) -> Option<Style> {
+    if let Some(digit) = code.to_digit(16) {
+        // a color also turns off every decoration
+        return Some(Style {
+            color: Some(Color::Named(NAMED_COLORS[usize::try_from(digit).ok()?])),
+            ..Style::default()
+        });
+    }
+
+    let mut style = style.clone();
+
+    let decoration = match code.to_ascii_lowercase() {
+        'k' => &mut style.obfuscated,
+        'l' => &mut style.bold,
+        'm' => &mut style.strikethrough,
+        'n' => &mut style.underlined,
+        'o' => &mut style.italic,
+        'r' => return Some(Style::default()),
+        _ => return None,
+    };
+
+    *decoration = Some(true);
+
+    Some(style)
+}
+
+/// The color of the hex format `§x§r§r§g§g§b§b` at the start of `chars`, which come after `§x`.
+fn hex_color(chars: &[char], marker: char) -> Option<Color> {
+    let [m0, r0, m1, r1, m2, g0, m3, g1, m4, b0, m5, b1, ..] = *chars else {
+        return None;
+    };
+
+    if [m0, m1, m2, m3, m4, m5].iter().any(|&m| m != marker) {
+        return None;
+    }
+
+    let hex: String = ['#', r0, r1, g0, g1, b0, b1].into_iter().collect();
+
+    RgbColor::try_from(hex.as_str()).ok().map(Color::Rgb)
+}
+
+/// The formatting a legacy string can express.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+struct Legacy {
+    color: Option<NamedColor>,
+    obfuscated: bool,
+    bold: bool,
+    strikethrough: bool,
+    underlined: bool,
+    italic: bool,
+}
+
+impl Legacy {
+    /// The formatting of `text` when its parent is formatted as `self`.
+    fn child(self, text: &Text<'_>) -> Self {
+        let color = match text.color {
+            None => self.color,
+            Some(Color::Reset) => None,
+            Some(Color::Named(named)) => Some(named),
+            Some(Color::Rgb(rgb)) => Some(rgb.to_named_lossy()),
+        };
+
+        Self {
+            color,
+            obfuscated: text.obfuscated.unwrap_or(self.obfuscated),
+            bold: text.bold.unwrap_or(self.bold),
+            strikethrough: text.strikethrough.unwrap_or(self.strikethrough),
+            underlined: text.underlined.unwrap_or(self.underlined),
+            italic: text.italic.unwrap_or(self.italic),
+        }
+    }
+
+    const fn decorations(self) -> [(bool, char); 5] {
+        [
+            (self.obfuscated, 'k'),
+            (self.bold, 'l'),
+            (self.strikethrough, 'm'),
+            (self.underlined, 'n'),
+            (self.italic, 'o'),
+        ]
+    }
+
+    /// Writes the codes which change the formatting from `self` to `to`.
+    fn write_change(self, to: Self, out: &mut String) {
+        if self == to {
+            return;
+        }
+
+        let removes_decoration = self
+            .decorations()
+            .into_iter()
+            .zip(to.decorations())
+            .any(|((was, _), (is, _))| was && !is);
+
+        // decorations can only be turned off by a color code or a reset
+        let from = if removes_decoration || self.color != to.color {
+            out.push(SECTION_SIGN);
+            out.push(to.color.map_or('r', NamedColor::hex_digit));
+
+            Self {
+                color: to.color,
+                ..Self::default()
+            }
+        } else {
+            self
+        };
+
+        for ((was, _), (is, code)) in from.decorations().into_iter().zip(to.decorations()) {
+            if is && !was {
+                out.push(SECTION_SIGN);
+                out.push(code);
+            }
+        }
+    }
+}
+
+impl Text<'_> {
+    /// Parses a string with legacy formatting codes which start with `marker`, usually
+    /// [`SECTION_SIGN`] or `&` for text written by hand. The hex colors of the form
+    /// `§x§r§r§g§g§b§b` are understood as well.
+    ///
+    /// ```
+    /// use hyperion_text::Text;
+    ///
+    /// let text = Text::from_legacy("&cYou do not have &lpermission", '&');
+    /// assert_eq!(text.to_legacy(), "§cYou do not have §lpermission");
+    /// ```
+    #[must_use]
+    pub fn from_legacy(s: &str, marker: char) -> Text<'static> {
+        let chars: Vec<char> = s.chars().collect();
+
+        let mut runs = Vec::new();
+        let mut style = Style::default();
+        let mut current = String::new();
+
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            i += 1;
+
+            let Some(&code) = chars.get(i).filter(|_| c == marker) else {
+                current.push(c);
+                continue;
+            };
+
+            let next = if code.eq_ignore_ascii_case(&'x') {
+                hex_color(&chars[i + 1..], marker).map(|color| {
+                    i += 12;
+                    Style {
+                        color: Some(color),
+                        ..Style::default()
+                    }
+                })
+            } else {
+                apply_code(&style, code)
+            };
+
+            let Some(next) = next else {
+                current.push(c);
+                continue;
+            };
+
+            i += 1;
+
+            if next != style {
+                runs.push((style, std::mem::take(&mut current)));
+                style = next;
+            }
+        }
+
+        runs.push((style, current));
+
+        join(runs)
+    }
+
+    /// Writes the text with legacy formatting codes, for the places which do not show JSON text.
+    /// RGB colors become the closest named color, and everything other than the text and its
+    /// formatting, such as click events, is left out.
+    #[must_use]
+    pub fn to_legacy(&self) -> String {
+        let mut out = String::new();
+        let mut written = Legacy::default();
+        self.write_legacy(Legacy::default(), &mut written, &mut out);
+        out
+    }
+
+    /// Writes only the text, for places without formatting such as logs. Legacy formatting codes
+    /// within the text are removed as well.
+    #[must_use]
+    pub fn to_plain(&self) -> String {
+        let legacy = self.to_legacy();
+        let mut out = String::with_capacity(legacy.len());
+        let mut chars = legacy.chars();
+
+        while let Some(c) = chars.next() {
+            if c == SECTION_SIGN {
+                chars.next();
+            } else {
+                out.push(c);
+            }
+        }
+
+        out
+    }
+
+    fn write_legacy(&self, parent: Legacy, written: &mut Legacy, out: &mut String) {
+        let style = parent.child(self);
+
+        let content = match &self.content {
+            TextContent::Text { text } => Some(&**text),
+            TextContent::Translate { translate, .. } => Some(&**translate),
+            TextContent::Keybind { keybind } => Some(&**keybind),
+            TextContent::ScoreboardValue { score } => score.value.as_deref(),
+            _ => None,
+        };
+
+        if let Some(content) = content.filter(|content| !content.is_empty()) {
+            written.write_change(style, out);
+            *written = style;
+            out.push_str(content);
+        }
+
+        for child in &self.extra {
+            child.write_legacy(style, written, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_legacy() {
+        let text = Text::from_legacy("§cRed §lbold§r plain", SECTION_SIGN);
+
+        assert_eq!(text.extra.len(), 3);
+        assert_eq!(text.extra[0].color, Some(Color::RED));
+        assert_eq!(text.extra[1].bold, Some(true));
+        assert_eq!(text.extra[1].color, Some(Color::RED));
+        assert_eq!(text.extra[2].color, None);
+        assert_eq!(text.extra[2].bold, None);
+    }
+
+    #[test]
+    fn test_unknown_codes_are_kept() {
+        let text = Text::from_legacy("Tom & Jerry &z", '&');
+        assert_eq!(text, Text::new("Tom & Jerry &z"));
+    }
+
+    #[test]
+    fn test_to_plain() {
+        let text = Text::from_legacy("§cRed §lbold", SECTION_SIGN);
+        assert_eq!(text.to_plain(), "Red bold");
+
+        // codes left in the text itself
+        assert_eq!(Text::new("§cAvailable: §r[a]").to_plain(), "Available: [a]");
+    }
+
+    #[test]
+    fn test_hex_colors() {
+        let text = Text::from_legacy("§x§f§f§0§0§0§0hex", SECTION_SIGN);
+        assert_eq!(text.color, Some(Color::rgb(255, 0, 0)));
+        // the closest named color
+        assert_eq!(text.to_legacy(), "§4hex");
+    }
+
+    #[test]
+    fn test_legacy_roundtrip() {
+        for legacy in [
+            "§6Gold §lbold §nunderlined§r plain",
+            "§aGreen§c red",
+            "§lbold §oitalic§7 gray",
+            "no codes",
+        ] {
+            let text = Text::from_legacy(legacy, SECTION_SIGN);
+            assert_eq!(text.to_legacy(), legacy);
+        }
+    }
+
+    #[test]
+    fn test_to_legacy_inherits() {
+        let mut parent = Text::new("a");
+        parent.color = Some(Color::GOLD);
+
+        let mut child = Text::new("b");
+        child.bold = Some(true);
+        parent.extra.push(child);
+
+        assert_eq!(parent.to_legacy(), "§6a§lb");
+    }
+}
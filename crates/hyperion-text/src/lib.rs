@@ -1,20 +1,26 @@
+#![feature(let_chains)]
+
 use std::{borrow::Cow, io::Write};
 
 use serde::{Deserialize, Serialize};
 use valence_protocol::{Bounded, Encode, anyhow, anyhow::Context};
 
-use crate::{
-    color::Color,
-    event::{ClickEvent, HoverEvent},
-    font::Font,
-    scoreboard::ScoreboardValueContent,
-};
-
 mod color;
 mod event;
 mod font;
 mod helper;
+mod i18n;
+mod legacy;
+mod mini;
 mod scoreboard;
+mod style;
+
+pub use color::{Color, ColorError, NamedColor, RgbColor};
+pub use event::{ClickEvent, HoverEvent};
+pub use font::Font;
+pub use i18n::{DEFAULT_LOCALE, Key, TranslationError, Translations};
+pub use legacy::SECTION_SIGN;
+pub use scoreboard::ScoreboardValueContent;
 
 /// Text data and formatting.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -0,0 +1,483 @@
+//! A format in the style of MiniMessage, in which text is formatted with tags such as `<red>`,
+//! `<bold>`, `<hover:show_text:'Click me'>`, `<click:run_command:/spawn>` and
+//! `<gradient:red:blue>`, so that messages can be written in configuration files.
+//!
+//! A tag lasts until it is closed with `</name>`, or until `<reset>` closes every tag. Arguments
+//! are separated by `:` and may be quoted with `'` or `"` when they contain `:` or `>`. A tag
+//! which is not understood is shown as it is written, so a typo is visible in the message rather
+//! than breaking it, and `\<` writes a `<` which does not start a tag.
+
+use crate::{
+    Text,
+    color::{Color, RgbColor},
+    event::{ClickEvent, HoverEvent},
+    style::{Style, join},
+};
+
+/// Colors spread over the characters of a run of text.
+enum Gradient {
+    Colors(Vec<RgbColor>),
+    Rainbow,
+}
+
+impl Gradient {
+    /// The color of character `index` out of `len`.
+    fn color(&self, index: usize, len: usize) -> RgbColor {
+        match self {
+            Self::Colors(colors) => {
+                let steps = len.saturating_sub(1).max(1);
+                let position = index * (colors.len() - 1);
+                let segment = (position / steps).min(colors.len() - 2);
+                let progress = position - segment * steps;
+
+                mix(colors[segment], colors[segment + 1], progress, steps)
+            }
+            Self::Rainbow => hue(index * 360 / len.max(1)),
+        }
+    }
+}
+
+/// The color `progress / steps` of the way from `from` to `to`.
+fn mix(from: RgbColor, to: RgbColor, progress: usize, steps: usize) -> RgbColor {
+    let channel = |from: u8, to: u8| {
+        let value = (usize::from(from) * (steps - progress) + usize::from(to) * progress) / steps;
+        u8::try_from(value).unwrap_or(u8::MAX)
+    };
+
+    RgbColor::new(
+        channel(from.r, to.r),
+        channel(from.g, to.g),
+        channel(from.b, to.b),
+    )
+}
+
+/// The fully saturated color with the hue `degrees`.
+fn hue(degrees: usize) -> RgbColor {
+    let degrees = degrees % 360;
+    let rising = u8::try_from(255 * (degrees % 60) / 60).unwrap_or(u8::MAX);
+    let falling = u8::MAX - rising;
+
+    match degrees / 60 {
+        0 => RgbColor::new(255, rising, 0),
+        1 => RgbColor::new(falling, 255, 0),
+        2 => RgbColor::new(0, 255, rising),
+        3 => RgbColor::new(0, falling, 255),
+        4 => RgbColor::new(rising, 0, 255),
+        _ => RgbColor::new(255, 0, falling),
+    }
+}
+
+fn rgb(color: Color) -> Option<RgbColor> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(rgb) => Some(rgb),
+        Color::Named(named) => Some(named.into()),
+    }
+}
+
+/// The name a tag is closed with, so that `</b>` closes `<bold>`.
+fn canonical(name: &str) -> &str {
+    match name {
+        "b" => "bold",
+        "i" | "em" => "italic",
+        "u" => "underlined",
+        "st" => "strikethrough",
+        "obf" => "obfuscated",
+        "colour" | "c" => "color",
+        "insertion" => "insert",
+        name => name,
+    }
+}
+
+/// The end of the tag at the start of `s`, which comes after its `<`: the contents of the tag
+/// and what follows it.
+fn split_tag(s: &str) -> Option<(&str, &str)> {
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            _ if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(open), c) if c == open => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '>') => return Some((&s[..i], &s[i + 1..])),
+            (None, '<') => return None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Splits the contents of a tag into its name and arguments, removing quotes.
+fn split_args(tag: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut chars = tag.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => current.extend(chars.next()),
+            (Some(open), c) if c == open => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ':') => args.push(std::mem::take(&mut current)),
+            (_, c) => current.push(c),
+        }
+    }
+
+    args.push(current);
+    args
+}
+
+fn click_event(args: &[String]) -> Option<ClickEvent<'static>> {
+    let [action, value @ ..] = args else {
+        return None;
+    };
+
+    if value.is_empty() {
+        return None;
+    }
+
+    // a url such as `https://example.com` is split at its colon if it is not quoted
+    let value = value.join(":");
+
+    let event = match action.as_str() {
+        "open_url" => ClickEvent::OpenUrl(value.into()),
+        "run_command" => ClickEvent::RunCommand(value.into()),
+        "suggest_command" => ClickEvent::SuggestCommand(value.into()),
+        "copy_to_clipboard" => ClickEvent::CopyToClipboard(value.into()),
+        "change_page" => ClickEvent::ChangePage(value.parse().ok()?),
+        _ => return None,
+    };
+
+    Some(event)
+}
+
+fn hover_event(args: &[String]) -> Option<HoverEvent<'static>> {
+    let [action, value @ ..] = args else {
+        return None;
+    };
+
+    if action != "show_text" || value.is_empty() {
+        return None;
+    }
+
+    Some(HoverEvent::ShowText(Text::from_mini_message(
+        &value.join(":"),
+    )))
+}
+
+/// A tag which has not been closed yet.
+struct Frame {
+    /// The canonical name of the tag.
+    tag: String,
+    /// The style of the text inside the tag.
+    style: Style,
+    /// The gradient of the tag and the first run it colors.
+    gradient: Option<(Gradient, usize)>,
+}
+
+#[derive(Default)]
+struct Parser {
+    runs: Vec<(Style, String)>,
+    frames: Vec<Frame>,
+    /// The text since the last tag.
+    text: String,
+}
+
+impl Parser {
+    fn style(&self) -> Style {
+        self.frames
+            .last()
+            .map(|frame| frame.style.clone())
+            .unwrap_or_default()
+    }
+
+    fn flush(&mut self) {
+        let text = std::mem::take(&mut self.text);
+        self.runs.push((self.style(), text));
+    }
+
+    /// Handles the contents of a tag, returning whether it was understood.
+    fn tag(&mut self, tag: &str) -> bool {
+        let mut args = split_args(tag);
+        let name = args.remove(0).to_ascii_lowercase();
+
+        if let Some(name) = name.strip_prefix('/') {
+            return args.is_empty() && self.close(canonical(name));
+        }
+
+        match name.as_str() {
+            "reset" if args.is_empty() => {
+                self.flush();
+                self.close_from(0);
+                true
+            }
+            "newline" | "br" if args.is_empty() => {
+                self.text.push('\n');
+                true
+            }
+            _ => self.open(&name, &args),
+        }
+    }
+
+    fn open(&mut self, name: &str, args: &[String]) -> bool {
+        let (negated, name) = match name.strip_prefix('!') {
+            Some(name) => (true, canonical(name)),
+            None => (false, canonical(name)),
+        };
+
+        let mut style = self.style();
+        let mut gradient = None;
+
+        match name {
+            "bold" | "italic" | "underlined" | "strikethrough" | "obfuscated" => {
+                let value = match args {
+                    [] => !negated,
+                    [value] if !negated => match value.as_str() {
+                        "true" => true,
+                        "false" => false,
+                        _ => return false,
+                    },
+                    _ => return false,
+                };
+
+                let decoration = match name {
+                    "bold" => &mut style.bold,
+                    "italic" => &mut style.italic,
+                    "underlined" => &mut style.underlined,
+                    "strikethrough" => &mut style.strikethrough,
+                    _ => &mut style.obfuscated,
+                };
+
+                *decoration = Some(value);
+            }
+            _ if negated => return false,
+            "color" => {
+                let [color] = args else {
+                    return false;
+                };
+
+                let Ok(color) = Color::try_from(color.as_str()) else {
+                    return false;
+                };
+
+                style.color = Some(color);
+            }
+            "click" => {
+                let Some(event) = click_event(args) else {
+                    return false;
+                };
+
+                style.click_event = Some(event);
+            }
+            "hover" => {
+                let Some(event) = hover_event(args) else {
+                    return false;
+                };
+
+                style.hover_event = Some(event);
+            }
+            "insert" => {
+                if args.is_empty() {
+                    return false;
+                }
+
+                style.insertion = Some(args.join(":"));
+            }
+            "gradient" => {
+                let colors: Option<Vec<RgbColor>> = if args.is_empty() {
+                    Some(vec![RgbColor::new(255, 255, 255), RgbColor::new(0, 0, 0)])
+                } else {
+                    args.iter()
+                        .map(|color| Color::try_from(color.as_str()).ok().and_then(rgb))
+                        .collect()
+                };
+
+                let Some(colors) = colors.filter(|colors| colors.len() >= 2) else {
+                    return false;
+                };
+
+                gradient = Some(Gradient::Colors(colors));
+            }
+            "rainbow" if args.is_empty() => gradient = Some(Gradient::Rainbow),
+            _ => {
+                // a color by its name or hex code, such as `<red>` or `<#ff5555>`
+                let Ok(color) = Color::try_from(name) else {
+                    return false;
+                };
+
+                if !args.is_empty() {
+                    return false;
+                }
+
+                style.color = Some(color);
+            }
+        }
+
+        self.flush();
+
+        let start = self.runs.len();
+
+        self.frames.push(Frame {
+            tag: name.to_owned(),
+            style,
+            gradient: gradient.map(|gradient| (gradient, start)),
+        });
+
+        true
+    }
+
+    fn close(&mut self, name: &str) -> bool {
+        let Some(index) = self.frames.iter().rposition(|frame| frame.tag == name) else {
+            return false;
+        };
+
+        self.flush();
+        self.close_from(index);
+        true
+    }
+
+    /// Closes the tag at `index` and the tags opened after it.
+    fn close_from(&mut self, index: usize) {
+        let closed = self.frames.split_off(index);
+
+        // inner gradients are applied first so that outer ones win
+        for frame in closed.into_iter().rev() {
+            if let Some((gradient, start)) = frame.gradient {
+                self.apply_gradient(&gradient, start);
+            }
+        }
+    }
+
+    fn apply_gradient(&mut self, gradient: &Gradient, start: usize) {
+        let runs = self.runs.split_off(start);
+        let len = runs.iter().map(|(_, text)| text.chars().count()).sum();
+
+        let chars = runs
+            .iter()
+            .flat_map(|(style, text)| text.chars().map(move |c| (style, c)));
+
+        for (index, (style, c)) in chars.enumerate() {
+            let mut style = style.clone();
+            style.color = Some(Color::Rgb(gradient.color(index, len)));
+            self.runs.push((style, c.to_string()));
+        }
+    }
+
+    fn finish(mut self) -> Text<'static> {
+        self.flush();
+        self.close_from(0);
+        join(self.runs)
+    }
+}
+
+impl Text<'_> {
+    /// Parses text formatted with MiniMessage-like tags.
+    ///
+    /// ```
+    /// use hyperion_text::Text;
+    ///
+    /// let text = Text::from_mini_message(
+    ///     "<red>You do not have <bold>permission</bold> to do that. <click:run_command:/help><u>Get \
+    ///      help</u></click>",
+    /// );
+    /// assert_eq!(
+    ///     text.to_legacy(),
+    ///     "§cYou do not have §lpermission§c to do that. §nGet help"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn from_mini_message(s: &str) -> Text<'static> {
+        let mut parser = Parser::default();
+        let mut rest = s;
+
+        while let Some(c) = rest.chars().next() {
+            let after = &rest[c.len_utf8()..];
+
+            if c == '\\'
+                && let Some(escaped @ ('<' | '\\')) = after.chars().next()
+            {
+                parser.text.push(escaped);
+                rest = &after[1..];
+                continue;
+            }
+
+            if c == '<'
+                && let Some((tag, after_tag)) = split_tag(after)
+                && parser.tag(tag)
+            {
+                rest = after_tag;
+                continue;
+            }
+
+            parser.text.push(c);
+            rest = after;
+        }
+
+        parser.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::NamedColor;
+
+    #[test]
+    fn test_colors_and_decorations() {
+        let text = Text::from_mini_message("<red>red <b>bold</b></red> <#00ff00>green");
+
+        assert_eq!(text.extra.len(), 4);
+        assert_eq!(text.extra[0].color, Some(Color::RED));
+        assert_eq!(text.extra[1].bold, Some(true));
+        assert_eq!(text.extra[1].color, Some(Color::RED));
+        assert_eq!(text.extra[2].color, None);
+        assert_eq!(text.extra[3].color, Some(Color::rgb(0, 255, 0)));
+    }
+
+    #[test]
+    fn test_unknown_tags_are_text() {
+        let text = Text::from_mini_message("1 < 2 and <nope> \\<red> </red>");
+        assert_eq!(text, Text::new("1 < 2 and <nope> <red> </red>"));
+    }
+
+    #[test]
+    fn test_events() {
+        let text = Text::from_mini_message(
+            "<click:open_url:https://example.com><hover:show_text:'<red>Open: it'>link",
+        );
+
+        assert_eq!(
+            text.click_event.as_deref(),
+            Some(&ClickEvent::OpenUrl("https://example.com".into()))
+        );
+
+        let Some(HoverEvent::ShowText(hover)) = text.hover_event.as_deref() else {
+            panic!("expected a hover text");
+        };
+        assert_eq!(hover.color, Some(Color::RED));
+        assert_eq!(hover.to_legacy(), "§cOpen: it");
+    }
+
+    #[test]
+    fn test_gradient() {
+        let text = Text::from_mini_message("<gradient:red:blue>abc</gradient>d");
+
+        assert_eq!(text.extra.len(), 4);
+        assert_eq!(text.extra[0].color, Some(NamedColor::Red.into()));
+        assert_eq!(text.extra[2].color, Some(NamedColor::Blue.into()));
+        assert_eq!(text.extra[3].color, None);
+
+        let middle = mix(NamedColor::Red.into(), NamedColor::Blue.into(), 1, 2);
+        assert_eq!(text.extra[1].color, Some(middle.into()));
+    }
+
+    #[test]
+    fn test_negated_decoration() {
+        let text = Text::from_mini_message("<bold>a<!bold>b");
+        assert_eq!(text.extra[1].bold, Some(false));
+    }
+}
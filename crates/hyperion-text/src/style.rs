@@ -0,0 +1,70 @@
+//! The formatting of a run of text, shared by the parsers of formatted strings.
+
+use std::borrow::Cow;
+
+use crate::{
+    Text, TextContent,
+    color::Color,
+    event::{ClickEvent, HoverEvent},
+};
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+    pub insertion: Option<String>,
+    pub click_event: Option<ClickEvent<'static>>,
+    pub hover_event: Option<HoverEvent<'static>>,
+}
+
+impl Style {
+    fn text(&self, text: String) -> Text<'static> {
+        Text {
+            content: TextContent::Text {
+                text: Cow::Owned(text),
+            },
+            color: self.color,
+            font: None,
+            bold: self.bold,
+            italic: self.italic,
+            underlined: self.underlined,
+            strikethrough: self.strikethrough,
+            obfuscated: self.obfuscated,
+            insertion: self.insertion.clone().map(Cow::Owned),
+            click_event: self.click_event.clone().map(Box::new),
+            hover_event: self.hover_event.clone().map(Box::new),
+            extra: Vec::new(),
+        }
+    }
+}
+
+/// Builds the text made of `runs`, merging neighbouring runs with the same style.
+pub fn join(runs: Vec<(Style, String)>) -> Text<'static> {
+    let mut merged: Vec<(Style, String)> = Vec::new();
+
+    for (style, text) in runs {
+        if text.is_empty() {
+            continue;
+        }
+
+        match merged.last_mut() {
+            Some((last, last_text)) if *last == style => last_text.push_str(&text),
+            _ => merged.push((style, text)),
+        }
+    }
+
+    if let [(style, text)] = merged.as_slice() {
+        return style.text(text.clone());
+    }
+
+    let mut root = Text::from(String::new());
+    root.extra = merged
+        .into_iter()
+        .map(|(style, text)| style.text(text))
+        .collect();
+    root
+}
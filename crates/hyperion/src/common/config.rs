@@ -1,6 +1,12 @@
 //! Configuration for the server.
 
-use std::{fmt::Debug, fs::File, io::Read, path::Path};
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::Read,
+    net::{IpAddr, Ipv4Addr},
+    path::Path,
+};
 
 use flecs_ecs::macros::Component;
 use serde::{Deserialize, Serialize};
@@ -15,6 +21,24 @@ pub struct Config {
     pub simulation_distance: i32,
     pub server_desc: String,
     pub spawn: Spawn,
+    /// Record egress traffic by packet type into
+    /// [`EgressStats`](crate::net::stats::EgressStats).
+    #[serde(default)]
+    pub egress_stats: bool,
+    /// How often, in seconds, the state of online players is saved. It is always saved when they
+    /// leave.
+    #[serde(default = "default_player_data_save_interval")]
+    pub player_data_save_interval: u32,
+    /// Lets commands be run remotely over the RCON protocol when set.
+    #[serde(default)]
+    pub rcon: Option<Rcon>,
+    /// How chat is moderated.
+    #[serde(default)]
+    pub chat: Chat,
+}
+
+const fn default_player_data_save_interval() -> u32 {
+    300
 }
 
 #[derive(Serialize, Deserialize, Debug, Component)]
@@ -26,6 +50,54 @@ pub struct Spawn {
     pub z: i32,
 }
 
+/// The listener for RCON clients, which run commands as the console.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Rcon {
+    /// The address to listen on. Only local clients may connect unless it is changed.
+    #[serde(default = "default_rcon_address")]
+    pub address: IpAddr,
+    pub port: u16,
+    /// The password clients log in with.
+    pub password: String,
+}
+
+const fn default_rcon_address() -> IpAddr {
+    IpAddr::V4(Ipv4Addr::LOCALHOST)
+}
+
+/// How chat is moderated. Players with the `hyperion.chat.bypass` permission skip these limits.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Chat {
+    /// How many seconds players wait between messages, or `0` to turn slow mode off.
+    pub slow_mode: u32,
+    /// Words replaced with asterisks in messages, matched ignoring case.
+    pub blocked_words: Vec<String>,
+    /// The largest share of capital letters in a message of at least eight letters before it is
+    /// lowercased.
+    pub max_caps: f32,
+    /// How many times in a row a player may send the same message.
+    pub max_repeats: u32,
+    /// How many messages a player may send within ten seconds.
+    pub max_burst: u32,
+    /// Sends messages as player chat, which clients attribute to their sender, instead of as
+    /// system messages.
+    pub player_chat: bool,
+}
+
+impl Default for Chat {
+    fn default() -> Self {
+        Self {
+            slow_mode: 0,
+            blocked_words: Vec::new(),
+            max_caps: 0.7,
+            max_repeats: 2,
+            max_burst: 5,
+            player_chat: false,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum Radius {
     Chebyshev,
@@ -41,6 +113,10 @@ impl Default for Config {
             simulation_distance: 10,
             server_desc: "Hyperion Test Server".to_owned(),
             spawn: Spawn::default(),
+            egress_stats: false,
+            player_data_save_interval: default_player_data_save_interval(),
+            rcon: None,
+            chat: Chat::default(),
         }
     }
 }
@@ -5,7 +5,10 @@ use rkyv::util::AlignedVec;
 use tracing::{error, info_span};
 use valence_protocol::{VarInt, packets::play};
 
-use crate::{net::Compose, simulation::EgressComm};
+use crate::{
+    net::{Compose, stats::EgressStats},
+    simulation::EgressComm,
+};
 
 pub mod metadata;
 pub mod player_join;
@@ -101,9 +104,10 @@ impl Module for EgressModule {
             world,
             &mut Compose($),
             &mut EgressComm($),
+            &mut EgressStats($),
         )
         .kind_id(pipeline)
-        .each(move |(compose, egress)| {
+        .each(move |(compose, egress, stats)| {
             let span = info_span!("egress");
             let _enter = span.enter();
 
@@ -148,6 +152,11 @@ impl Module for EgressModule {
             }
 
             let io = compose.io_buf_mut();
+
+            if io.records_stats() {
+                stats.push_tick(io.take_stats());
+            }
+
             for bytes in io.reset_and_split() {
                 if bytes.is_empty() {
                     continue;
@@ -1,13 +1,18 @@
-use std::{borrow::Cow, collections::BTreeSet, ops::Index};
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+    ops::Index,
+};
 
 use anyhow::Context;
 use flecs_ecs::prelude::*;
-use hyperion_crafting::{Action, CraftingRegistry, RecipeBookState};
+use hyperion_crafting::{Action, CraftingRegistry, ItemTags, RecipeBookState};
+use hyperion_inventory::PlayerInventory;
 use hyperion_utils::EntityExt;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use tracing::{info, instrument};
 use valence_protocol::{
-    ByteAngle, GameMode, Ident, PacketEncoder, RawBytes, VarInt, Velocity,
+    ByteAngle, GameMode, Ident, ItemKind, ItemStack, PacketEncoder, RawBytes, VarInt, Velocity,
     game_mode::OptGameMode,
     ident,
     packets::play::{
@@ -31,9 +36,9 @@ use crate::{
     ingress::PendingRemove,
     net::{Compose, ConnectionId, DataBundle},
     simulation::{
-        Comms, Name, Position, Uuid, Yaw,
+        Comms, Name, Position, Uuid, Xp, Yaw,
         command::{Command, ROOT_COMMAND, get_command_packet},
-        metadata::{MetadataChanges, entity::EntityFlags},
+        metadata::{MetadataChanges, entity::EntityFlags, living_entity::Health},
         skin::PlayerSkin,
         util::registry_codec_raw,
     },
@@ -54,6 +59,9 @@ pub fn player_join_world(
     position: &Position,
     yaw: &Yaw,
     pitch: &Pitch,
+    inventory: &PlayerInventory,
+    health: &Health,
+    xp: Xp,
     world: &WorldRef<'_>,
     skin: &PlayerSkin,
     system: EntityView<'_>,
@@ -113,6 +121,14 @@ pub fn player_join_world(
         .add_packet(&pkt)
         .context("failed to send player spawn packet")?;
 
+    // the server runs in offline mode, so it cannot verify the signatures of chat messages.
+    // telling clients secure chat is not enforced keeps them from warning about it.
+    bundle.add_packet(&play::ServerMetadataS2c {
+        motd: config.server_desc.as_str().into_cow_text(),
+        icon: None,
+        enforce_secure_chat: false,
+    })?;
+
     let center_chunk = position.to_chunk();
 
     let pkt = play::ChunkRenderDistanceCenterS2c {
@@ -171,6 +187,27 @@ pub fn player_join_world(
         teleport_id: 1.into(),
     })?;
 
+    bundle.add_packet(&play::InventoryS2c {
+        window_id: 0,
+        state_id: VarInt::default(),
+        slots: Cow::Borrowed(inventory.slots()),
+        carried_item: Cow::Borrowed(&ItemStack::EMPTY),
+    })?;
+
+    bundle.add_packet(&play::HealthUpdateS2c {
+        health: **health,
+        food: VarInt(20),
+        food_saturation: 5.0,
+    })?;
+
+    let visual = xp.get_visual();
+
+    bundle.add_packet(&play::ExperienceBarUpdateS2c {
+        bar: visual.prop,
+        level: VarInt(i32::from(visual.level)),
+        total_xp: VarInt::default(),
+    })?;
+
     let mut entries = Vec::new();
     let mut all_player_names = Vec::new();
 
@@ -369,6 +406,30 @@ pub fn player_join_world(
     Ok(())
 }
 
+/// The item tags sent to clients, such as `minecraft:planks`, used to resolve the tags in data
+/// pack recipes.
+pub fn item_tags() -> anyhow::Result<ItemTags> {
+    let bytes = include_bytes!("data/tags.json");
+
+    let mut groups: HashMap<String, HashMap<String, Vec<u16>>> = serde_json::from_slice(bytes)?;
+
+    let items = groups
+        .remove("minecraft:item")
+        .context("tags.json has no item tags")?;
+
+    items
+        .into_iter()
+        .map(|(tag, ids)| {
+            let kinds = ids
+                .into_iter()
+                .map(|id| ItemKind::from_raw(id).with_context(|| format!("unknown item id {id}")))
+                .collect::<anyhow::Result<_>>()?;
+
+            Ok((tag, kinds))
+        })
+        .collect()
+}
+
 fn send_sync_tags(encoder: &mut PacketEncoder) -> anyhow::Result<()> {
     let bytes = include_bytes!("data/tags.json");
 
@@ -563,8 +624,18 @@ impl Module for PlayerJoinModule {
 
                     let entity = world.entity_from_id(entity);
 
-                    entity.get::<(&Uuid, &Name, &Position, &Yaw, &Pitch, &ConnectionId)>(
-                        |(uuid, name, position, yaw, pitch, &stream_id)| {
+                    entity.get::<(
+                        &Uuid,
+                        &Name,
+                        &Position,
+                        &Yaw,
+                        &Pitch,
+                        &ConnectionId,
+                        &PlayerInventory,
+                        &Health,
+                        &Xp,
+                    )>(
+                        |(uuid, name, position, yaw, pitch, &stream_id, inventory, health, xp)| {
                             let query = &query;
                             let query = &query.0;
 
@@ -578,6 +649,9 @@ impl Module for PlayerJoinModule {
                                 position,
                                 yaw,
                                 pitch,
+                                inventory,
+                                health,
+                                *xp,
                                 world,
                                 &skin,
                                 system,
@@ -6,7 +6,7 @@ use glam::I16Vec2;
 use itertools::Itertools;
 use tracing::error;
 use valence_protocol::{
-    ChunkPos, VarInt,
+    ChunkPos, Packet, VarInt,
     packets::play::{self},
 };
 
@@ -204,7 +204,7 @@ impl Module for SyncChunksModule {
 
                         match chunks.get_cached_or_load(elem) {
                             GetChunk::Loaded(chunk) => {
-                                bundle.add_raw(&chunk.base_packet_bytes);
+                                bundle.add_raw_packet(play::ChunkDataS2c::ID, &chunk.base_packet_bytes);
 
                                 for packet in chunk.original_delta_packets() {
                                     if let Err(e) = bundle.add_packet(packet) {
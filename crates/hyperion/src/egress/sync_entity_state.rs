@@ -14,7 +14,7 @@ use valence_server::BlockState;
 
 use crate::{
     Prev,
-    net::{Compose, ConnectionId, DataBundle},
+    net::{Compose, ConnectionId, DataBundle, Priority},
     simulation::{
         Pitch, Position, Velocity, Xp, Yaw,
         animation::ActiveAnimation,
@@ -174,6 +174,7 @@ impl Module for EntityStateSyncModule {
                     compose
                         .broadcast_local(&pkt, chunk_pos, system)
                         .exclude(io)
+                        .priority(Priority::Cosmetic)
                         .send()
                         .unwrap();
                 }
@@ -325,7 +326,11 @@ impl Module for EntityStateSyncModule {
 
                 let look_changed = (**yaw - **prev_yaw).abs() >= 0.01 || (**pitch - **prev_pitch).abs() >= 0.01;
 
-                let mut bundle = DataBundle::new(compose, system);
+                // relative moves build on each other, so dropping one would leave the entity in
+                // the wrong place for good; only rotation and velocity, which are absolute, may
+                // be dropped for players who fall behind
+                let mut moves = DataBundle::new(compose, system);
+                let mut bundle = DataBundle::new(compose, system).with_priority(Priority::Movement);
 
                 world.get::<&mut Blocks>(|blocks| {
                     let grounded = is_grounded(position, blocks);
@@ -340,7 +345,7 @@ impl Module for EntityStateSyncModule {
                             on_ground: grounded,
                         };
 
-                        bundle.add_packet(&packet).unwrap();
+                        moves.add_packet(&packet).unwrap();
                     } else {
                         if changed_position && !needs_teleport {
                             let packet = play::MoveRelativeS2c {
@@ -350,7 +355,7 @@ impl Module for EntityStateSyncModule {
                                 on_ground: grounded,
                             };
 
-                            bundle.add_packet(&packet).unwrap();
+                            moves.add_packet(&packet).unwrap();
                         }
 
                         if look_changed {
@@ -380,7 +385,7 @@ impl Module for EntityStateSyncModule {
                             on_ground: grounded,
                         };
 
-                        bundle.add_packet(&packet).unwrap();
+                        moves.add_packet(&packet).unwrap();
                     }
                 });
 
@@ -394,6 +399,7 @@ impl Module for EntityStateSyncModule {
                     bundle.add_packet(&packet).unwrap();
                 }
 
+                moves.broadcast_local(chunk_pos).unwrap();
                 bundle.broadcast_local(chunk_pos).unwrap();
             },
         );
@@ -3,6 +3,7 @@ use std::{borrow::Cow, sync::Arc};
 use anyhow::Context;
 use colored::Colorize;
 use flecs_ecs::prelude::*;
+use hyperion_inventory::PlayerInventory;
 use hyperion_utils::EntityExt;
 use serde_json::json;
 use sha2::Digest;
@@ -19,8 +20,8 @@ use crate::{
     Prev, Shutdown,
     egress::sync_chunks::ChunkSendQueue,
     net::{
-        Compose, ConnectionId, MINECRAFT_VERSION, PROTOCOL_VERSION, PacketDecoder,
-        decoder::BorrowedPacketFrame, proxy::ReceiveState,
+        ClientAddress, Compose, ConnectionId, MINECRAFT_VERSION, PROTOCOL_VERSION, PacketDecoder,
+        Resumed, VirtualHost, decoder::BorrowedPacketFrame, proxy::ReceiveState,
     },
     runtime::AsyncRuntime,
     simulation::{
@@ -30,10 +31,14 @@ use crate::{
         animation::ActiveAnimation,
         blocks::Blocks,
         handlers::PacketSwitchQuery,
-        metadata::{MetadataPrefabs, entity::Pose},
+        locale::Locale,
+        metadata::{MetadataPrefabs, entity::Pose, living_entity::Health},
         skin::PlayerSkin,
     },
-    storage::{Events, GlobalEventHandlers, PlayerJoinServer, SkinHandler},
+    storage::{
+        BanStorage, Events, GlobalEventHandlers, PlayerDataHandler, PlayerJoinServer, SkinHandler,
+        ban_message, unix_now,
+    },
     util::{SendableRef, TracingExt, mojang::MojangClient},
 };
 
@@ -51,6 +56,12 @@ impl PendingRemove {
     }
 }
 
+/// A login the server refused, such as of a banned player, with the screen they are disconnected
+/// with.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+struct LoginRefused(String);
+
 fn process_handshake(
     login_state: &mut PacketState,
     packet: &BorrowedPacketFrame<'_>,
@@ -90,6 +101,7 @@ fn process_login(
     entity: &EntityView<'_>,
     system: EntityView<'_>,
     ign_map: &IgnMap,
+    inventory: &mut PlayerInventory,
 ) -> anyhow::Result<()> {
     debug_assert!(
         *login_state == PacketState::Login,
@@ -112,19 +124,43 @@ fn process_login(
 
     let global = compose.global();
 
-    let pkt = LoginCompressionS2c {
-        threshold: VarInt(global.shared.compression_threshold.0),
-    };
+    // a resumed client finished logging in with a previous server; it already has compression
+    // enabled and expects play packets only
+    let resumed = entity.has::<Resumed>();
+
+    let uuid = profile_id.unwrap_or_else(|| offline_uuid(username));
+
+    // checked before compression is enabled, so the disconnect screen of a fresh login can be
+    // sent uncompressed
+    let ip = entity.try_get::<&ClientAddress>(|address| address.ip());
+
+    let ban = world
+        .get::<&BanStorage>(|bans| bans.find_login(uuid, ip))
+        .context("failed to check bans")?;
+
+    if let Some((target, ban)) = ban {
+        info!("Refusing login of {username}: {target} is banned");
+        return Err(LoginRefused(ban_message(&ban, unix_now())).into());
+    }
+
+    if !resumed {
+        let pkt = LoginCompressionS2c {
+            threshold: VarInt(global.shared.compression_threshold.0),
+        };
 
-    compose.unicast_no_compression(&pkt, stream_id, system)?;
+        compose.unicast_no_compression(&pkt, stream_id, system)?;
+    }
 
     decoder.set_compression(global.shared.compression_threshold);
 
     let username = Arc::from(username);
-
-    let uuid = profile_id.unwrap_or_else(|| offline_uuid(&username));
     let uuid_s = format!("{uuid:?}").dimmed();
-    info!("Starting login: {username} {uuid_s}");
+
+    if resumed {
+        info!("Resuming login: {username} {uuid_s}");
+    } else {
+        info!("Starting login: {username} {uuid_s}");
+    }
 
     let skins = comms.skins_tx.clone();
     let id = entity.id();
@@ -145,29 +181,59 @@ fn process_login(
         skins.send((id, skin)).unwrap();
     });
 
-    let pkt = login::LoginSuccessS2c {
-        uuid,
-        username: Bounded(&username),
-        properties: Cow::default(),
-    };
+    if !resumed {
+        let pkt = login::LoginSuccessS2c {
+            uuid,
+            username: Bounded(&username),
+            properties: Cow::default(),
+        };
 
-    compose
-        .unicast(&pkt, stream_id, system)
-        .context("failed to send login success packet")?;
+        compose
+            .unicast(&pkt, stream_id, system)
+            .context("failed to send login success packet")?;
+    }
 
     *login_state = PacketState::Play;
 
     ign_map.insert(username.clone(), entity.id(), world);
 
+    let data = world
+        .get::<&PlayerDataHandler>(|handler| handler.find(uuid))
+        .unwrap_or_else(|e| {
+            error!("failed to load player data of {username}, starting fresh: {e}");
+            None
+        });
+
+    let xp = data
+        .as_ref()
+        .map_or_else(Xp::default, |data| Xp::from(data.xp));
+
     world.get::<&MetadataPrefabs>(|prefabs| {
+        entity.is_a_id(prefabs.player_base);
+
+        // restored before the uuid is set so its observers see where the player left off
+        if let Some(data) = &data {
+            *inventory = data.inventory();
+        }
+
+        // a player who left while dead respawns with full health rather than dead where they died
+        if let Some(data) = data.as_ref().filter(|data| !data.is_dead()) {
+            let [x, y, z] = data.position;
+
+            entity
+                .set(Position::new(x, y, z))
+                .set(Yaw::new(data.yaw))
+                .set(Pitch::new(data.pitch))
+                .set(Health::new(data.health));
+        }
+
         entity
-            .is_a_id(prefabs.player_base)
             .set(Name::from(username))
             .add::<AiTargetable>()
             .set(ImmuneStatus::default())
             .set(Uuid::from(uuid))
-            .add::<Xp>()
-            .set_pair::<Prev, _>(Xp::default())
+            .set(xp)
+            .set_pair::<Prev, _>(xp)
             .add::<ChunkSendQueue>()
             .add::<Velocity>()
             .set(ChunkPosition::null())
@@ -313,18 +379,44 @@ impl Module for IngressModule {
             let mut recv = receive.0.lock();
 
             for connect in recv.player_connect.drain(..) {
-                info!("player_connect");
+                let addr = connect
+                    .addr
+                    .map_or_else(|| "unknown".to_owned(), |addr| addr.to_string());
+                let host = connect.virtual_host.as_deref().unwrap_or("unknown");
+                info!("player_connect from {addr} via {host}");
+
+                // The proxy reconnected to this same process and is resuming a stream we still
+                // have. Remove the stale player without sending a disconnect to the client.
+                if let Some(&stale) = lookup.get(&connect.stream)
+                    && world.is_alive(stale)
+                {
+                    world.entity_from_id(stale).set(PendingRemove::new(""));
+                }
+
                 let view = world
                     .entity()
-                    .set(ConnectionId::new(connect))
-                    .set(hyperion_inventory::PlayerInventory::default())
+                    .set(ConnectionId::new(connect.stream))
+                    .set(PlayerInventory::default())
                     .set(ConfirmBlockSequences::default())
+                    .set(Locale::default())
                     .set(PacketState::Handshake)
                     .set(ActiveAnimation::NONE)
                     .set(PacketDecoder::default())
                     .add::<Player>();
 
-                lookup.insert(connect, view.id());
+                if let Some(addr) = connect.addr {
+                    view.set(ClientAddress(addr));
+                }
+
+                if let Some(virtual_host) = connect.virtual_host {
+                    view.set(VirtualHost(virtual_host));
+                }
+
+                if connect.resume {
+                    view.add::<Resumed>();
+                }
+
+                lookup.insert(connect.stream, view.id());
             }
 
             for disconnect in recv.player_disconnect.drain(..) {
@@ -471,7 +563,7 @@ impl Module for IngressModule {
             &mut Yaw,
             &mut Pitch,
             &mut ConfirmBlockSequences,
-            &mut hyperion_inventory::PlayerInventory,
+            &mut PlayerInventory,
             &mut ActiveAnimation,
             &hyperion_crafting::CraftingRegistry($),
             &IgnMap($),
@@ -558,24 +650,42 @@ impl Module for IngressModule {
                                 &entity,
                                 system,
                                 ign_map,
+                                inventory,
                             ) {
-                                error!("failed to process login packet");
-                                let msg = format!(
-                                    "§c§lFailed to process login packet:§r\n\n§4{e}§r\n\n§eAre \
-                                     you on the right version of Minecraft?§r\n§b(Required: \
-                                     1.20.1)§r"
-                                );
-
-                                // hopefully we were in no compression mode
-                                // todo we want to handle sending different based on whether
-                                // we sent compression packet or not
-                                if let Err(e) = compose.unicast_no_compression(
-                                    &login::LoginDisconnectS2c {
-                                        reason: msg.into_cow_text(),
-                                    },
-                                    io_ref,
-                                    system,
-                                ) {
+                                let msg = if let Some(refused) = e.downcast_ref::<LoginRefused>() {
+                                    refused.0.clone()
+                                } else {
+                                    error!("failed to process login packet");
+                                    format!(
+                                        "§c§lFailed to process login \
+                                         packet:§r\n\n§4{e}§r\n\n§eAre you on the right version \
+                                         of Minecraft?§r\n§b(Required: 1.20.1)§r"
+                                    )
+                                };
+
+                                // a resumed client is already in play with compression enabled
+                                let sent = if entity.has::<Resumed>() {
+                                    compose.unicast(
+                                        &play::DisconnectS2c {
+                                            reason: msg.into_cow_text(),
+                                        },
+                                        io_ref,
+                                        system,
+                                    )
+                                } else {
+                                    // hopefully we were in no compression mode
+                                    // todo we want to handle sending different based on whether
+                                    // we sent compression packet or not
+                                    compose.unicast_no_compression(
+                                        &login::LoginDisconnectS2c {
+                                            reason: msg.into_cow_text(),
+                                        },
+                                        io_ref,
+                                        system,
+                                    )
+                                };
+
+                                if let Err(e) = sent {
                                     error!("failed to send login disconnect packet: {e}");
                                 }
 
@@ -33,6 +33,7 @@ use std::{
     fmt::Debug,
     io::Write,
     net::SocketAddr,
+    path::Path,
     sync::{Arc, atomic::AtomicBool},
 };
 
@@ -47,8 +48,11 @@ use ingress::IngressModule;
 #[cfg(unix)]
 use libc::{RLIMIT_NOFILE, getrlimit, setrlimit};
 use libdeflater::CompressionLvl;
-use simulation::{Comms, SimModule, StreamLookup, blocks::Blocks};
-use storage::{Events, GlobalEventHandlers, LocalDb, SkinHandler, ThreadLocal};
+use simulation::{Comms, SimModule, StreamLookup, blocks::Blocks, locale::Localization};
+use storage::{
+    BanStorage, Events, GlobalEventHandlers, LocalDb, MuteStorage, PlayerDataHandler, SkinHandler,
+    ThreadLocal,
+};
 use tracing::{info, info_span, warn};
 use util::mojang::MojangClient;
 pub use uuid;
@@ -64,7 +68,9 @@ pub use valence_protocol::{
 pub use valence_server as server;
 
 use crate::{
-    net::{Compose, Compressors, IoBuf, MAX_PACKET_SIZE, proxy::init_proxy_comms},
+    net::{
+        Compose, Compressors, IoBuf, MAX_PACKET_SIZE, proxy::init_proxy_comms, stats::EgressStats,
+    },
     runtime::AsyncRuntime,
     simulation::{Pitch, Yaw},
 };
@@ -77,7 +83,7 @@ pub use valence_ident;
 
 use crate::{
     ingress::PendingRemove,
-    net::{ConnectionId, PacketDecoder, proxy::ReceiveState},
+    net::{ClientAddress, ConnectionId, PacketDecoder, Resumed, VirtualHost, proxy::ReceiveState},
     runtime::Tasks,
     simulation::{EgressComm, EntitySize, IgnMap, PacketState, Player},
     util::mojang::ApiProvider,
@@ -94,10 +100,20 @@ pub mod storage;
 pub struct Prev;
 
 pub trait PacketBundle {
+    /// The ID of the packet, used for [`net::stats`]. Bundles without one are counted as raw
+    /// bytes.
+    fn packet_id(&self) -> Option<i32> {
+        None
+    }
+
     fn encode_including_ids(self, w: impl Write) -> anyhow::Result<()>;
 }
 
 impl<T: Packet + Encode> PacketBundle for &T {
+    fn packet_id(&self) -> Option<i32> {
+        Some(T::ID)
+    }
+
     fn encode_including_ids(self, w: impl Write) -> anyhow::Result<()> {
         self.encode_with_id(w)
     }
@@ -256,12 +272,18 @@ impl HyperionCore {
         world.component::<PacketState>();
 
         world.component::<ConnectionId>();
+        world.component::<ClientAddress>();
+        world.component::<VirtualHost>();
+        world.component::<Resumed>();
         world.component::<ReceiveState>();
         world.component::<Compose>();
         world.component::<CraftingRegistry>();
 
         world.component::<LocalDb>();
         world.component::<SkinHandler>();
+        world.component::<PlayerDataHandler>();
+        world.component::<BanStorage>();
+        world.component::<MuteStorage>();
         world.component::<MojangClient>();
         world.component::<Events>();
         world.component::<Comms>();
@@ -288,9 +310,11 @@ impl HyperionCore {
         world.component::<IgnMap>();
 
         world.component::<config::Config>();
+        world.component::<EgressStats>();
 
         info!("starting hyperion");
         let config = config::Config::load("run/config.toml")?;
+        let record_egress_stats = config.egress_stats;
         world.set(config);
 
         let (task_tx, task_rx) = kanal::bounded(32);
@@ -329,10 +353,16 @@ impl HyperionCore {
         info!("initializing database");
         let db = LocalDb::new()?;
         let skins = SkinHandler::new(&db)?;
+        let player_data = PlayerDataHandler::new(&db)?;
+        let bans = BanStorage::new(&db, "bans")?;
+        let mutes = MuteStorage::new(&db, "mutes")?;
         info!("database initialized");
 
         world.set(db);
         world.set(skins);
+        world.set(player_data);
+        world.set(bans);
+        world.set(mutes);
 
         world.set(MojangClient::new(&runtime, ApiProvider::MAT_DOES_DEV));
 
@@ -351,14 +381,38 @@ impl HyperionCore {
 
         let global = Global::new(shared.clone());
 
+        let mut io_buf = IoBuf::default();
+        io_buf.set_record_stats(record_egress_stats);
+
         world.set(Compose::new(
             Compressors::new(shared.compression_level),
             Scratches::default(),
             global,
-            IoBuf::default(),
+            io_buf,
         ));
 
-        world.set(CraftingRegistry::default());
+        world.set(EgressStats::default());
+
+        let mut crafting_registry = CraftingRegistry::default();
+
+        let data_packs = Path::new("run/datapacks");
+        if data_packs.is_dir() {
+            let tags = egress::player_join::item_tags()?;
+            let count = crafting_registry.load_data_packs(data_packs, &tags)?;
+            info!("loaded {count} recipes from data packs");
+        }
+
+        world.set(crafting_registry);
+
+        let mut translations = hyperion_text::Translations::default();
+
+        let lang = Path::new("run/lang");
+        if lang.is_dir() {
+            let count = translations.load_dir(lang)?;
+            info!("loaded {count} translations");
+        }
+
+        world.set(Localization::new(translations));
 
         world.set(Comms::default());
 
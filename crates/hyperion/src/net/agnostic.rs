@@ -1,7 +1,7 @@
 //! Agnostic networking primitives. Translates to correct protocol version.
 
 mod chat;
-pub use chat::{Chat, chat};
+pub use chat::{Chat, chat, chat_text, translated, unicast_chat};
 
 mod sound;
 pub use sound::{Sound, SoundBuilder, sound};
@@ -1,24 +1,66 @@
 use std::io::Write;
 
-use valence_protocol::packets::play;
-use valence_text::IntoText;
+use flecs_ecs::prelude::*;
+use hyperion_text::{Key, Text};
+use valence_protocol::Packet;
 
-use crate::PacketBundle;
+use crate::{
+    PacketBundle,
+    net::{Compose, ConnectionId, packets::GameMessageS2c},
+    simulation::{
+        console::{Console, ConsoleOutput},
+        locale::Localization,
+    },
+};
 
 pub struct Chat {
-    raw: play::GameMessageS2c<'static>,
+    raw: GameMessageS2c<'static>,
 }
 
 pub fn chat(chat: impl Into<String>) -> Chat {
-    let chat = chat.into();
+    chat_text(Text::from(chat.into()))
+}
+
+/// A chat message of formatted text.
+#[must_use]
+pub const fn chat_text(chat: Text<'static>) -> Chat {
     Chat {
-        raw: play::GameMessageS2c {
-            chat: chat.into_cow_text(),
+        raw: GameMessageS2c {
+            chat,
             overlay: false,
         },
     }
 }
 
+/// The message `key` in the language of `player`, with `{0}`, `{1}`, ... replaced by `args`.
+#[must_use]
+pub fn translated(player: EntityView<'_>, key: Key, args: &[&str]) -> Chat {
+    let text = player
+        .world()
+        .get::<&Localization>(|localization| localization.translate(player, key, args));
+
+    chat_text(text)
+}
+
+/// Sends `chat` to `receiver`, which may be a player or the [`Console`], where it is logged and
+/// kept in its [`ConsoleOutput`].
+pub fn unicast_chat(receiver: EntityView<'_>, chat: &Chat, system: EntityView<'_>) {
+    let world = receiver.world();
+
+    if receiver.has::<Console>() {
+        let text = chat.raw.chat.to_plain();
+        tracing::info!("{text}");
+        receiver.get::<&mut ConsoleOutput>(|output| output.push(text));
+        return;
+    }
+
+    receiver.try_get::<&ConnectionId>(|stream| {
+        world.get::<&Compose>(|compose| {
+            compose.unicast(chat, *stream, system).unwrap();
+        });
+    });
+}
+
 #[macro_export]
 macro_rules! chat {
     ($($arg:tt)*) => {
@@ -27,6 +69,10 @@ macro_rules! chat {
 }
 
 impl PacketBundle for &Chat {
+    fn packet_id(&self) -> Option<i32> {
+        Some(GameMessageS2c::ID)
+    }
+
     fn encode_including_ids(self, mut w: impl Write) -> anyhow::Result<()> {
         self.raw.encode_including_ids(&mut w)
     }
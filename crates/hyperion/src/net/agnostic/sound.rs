@@ -2,6 +2,7 @@ use std::io::Write;
 
 use glam::Vec3;
 use valence_protocol::{
+    Packet,
     packets::play,
     sound::{SoundCategory, SoundId},
 };
@@ -56,6 +57,10 @@ impl SoundBuilder {
 }
 
 impl PacketBundle for &Sound {
+    fn packet_id(&self) -> Option<i32> {
+        Some(play::PlaySoundS2c::ID)
+    }
+
     fn encode_including_ids(self, mut w: impl Write) -> anyhow::Result<()> {
         self.raw.encode_including_ids(&mut w)
     }
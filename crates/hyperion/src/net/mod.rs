@@ -3,18 +3,21 @@
 use std::{
     cell::{Cell, RefCell},
     fmt::Debug,
+    net::SocketAddr,
+    sync::Arc,
 };
 
 use bumpalo::Bump;
 use byteorder::WriteBytesExt;
 use bytes::{Bytes, BytesMut};
 pub use decoder::PacketDecoder;
-use derive_more::Deref;
+use derive_more::{Deref, Display};
 use flecs_ecs::{
     core::{EntityView, World, WorldProvider},
     macros::Component,
 };
 use glam::I16Vec2;
+pub use hyperion_proto::Priority;
 use hyperion_proto::{ChunkPosition, ServerToProxyMessage};
 use libdeflater::CompressionLvl;
 use rkyv::util::AlignedVec;
@@ -22,7 +25,10 @@ use system_order::SystemOrder;
 
 use crate::{
     Global, PacketBundle, Scratch, Scratches,
-    net::encoder::{PacketEncoder, append_packet_without_compression},
+    net::{
+        encoder::{PacketEncoder, append_packet_without_compression},
+        stats::{BroadcastKind, PacketStats},
+    },
     storage::ThreadLocal,
 };
 
@@ -31,6 +37,7 @@ pub mod decoder;
 pub mod encoder;
 pub mod packets;
 pub mod proxy;
+pub mod stats;
 
 /// The Minecraft protocol version this library currently targets.
 pub const PROTOCOL_VERSION: i32 = 763;
@@ -121,6 +128,25 @@ impl ConnectionId {
     }
 }
 
+/// The address of the client behind a connection.
+///
+/// This is the address the proxy saw the client connect from, or the address forwarded by a load
+/// balancer through the PROXY protocol. It is suitable for logging, IP bans and per-IP limits.
+/// Connections made through a Unix socket do not have this component.
+#[derive(Component, Copy, Clone, Debug, Deref, Display, PartialEq, Eq, Hash)]
+pub struct ClientAddress(pub SocketAddr);
+
+/// The server address the client put in its handshake, i.e. the host name it connected to.
+#[derive(Component, Clone, Debug, Deref, Display, PartialEq, Eq, Hash)]
+pub struct VirtualHost(pub Arc<str>);
+
+/// Marks a connection the proxy carried over from a previous server process.
+///
+/// The client is still in the play state. Its handshake and login start are replayed by the
+/// proxy, but it must not be sent any login packets; it goes straight to joining the world.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Resumed;
+
 /// A singleton that can be used to compose and encode packets.
 #[derive(Component)]
 pub struct Compose {
@@ -136,6 +162,9 @@ pub struct DataBundle<'a, 'b> {
     compose: &'a Compose,
     system: EntityView<'b>,
     data: BytesMut,
+    priority: Priority,
+    /// The ID and encoded length of each packet in `data`, if recording [`stats`].
+    packets: Vec<(Option<i32>, usize)>,
 }
 
 impl<'a, 'b> DataBundle<'a, 'b> {
@@ -144,29 +173,61 @@ impl<'a, 'b> DataBundle<'a, 'b> {
             compose,
             system,
             data: BytesMut::new(),
+            priority: Priority::Normal,
+            packets: Vec::new(),
         }
     }
 
+    /// Sets the [`Priority`] of local broadcasts of this bundle.
+    pub const fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     pub fn add_packet(&mut self, pkt: impl PacketBundle) -> anyhow::Result<()> {
         let world = self.system.world();
+        let packet_id = pkt.packet_id();
         let data = self
             .compose
             .io_buf
             .encode_packet(pkt, self.compose, &world)?;
+        self.track(packet_id, data.len());
         // todo: test to see if this ever actually unsplits
         self.data.unsplit(data);
         Ok(())
     }
 
+    /// Adds pre-encoded bytes, which may contain any number of packets.
     pub fn add_raw(&mut self, raw: &[u8]) {
+        self.track(None, raw.len());
         self.data.extend_from_slice(raw);
     }
 
+    /// Adds a single pre-encoded packet with the ID `packet_id`.
+    pub fn add_raw_packet(&mut self, packet_id: i32, raw: &[u8]) {
+        self.track(Some(packet_id), raw.len());
+        self.data.extend_from_slice(raw);
+    }
+
+    fn track(&mut self, packet_id: Option<i32>, len: usize) {
+        if self.compose.io_buf.records_stats() {
+            self.packets.push((packet_id, len));
+        }
+    }
+
+    fn record(&self, kind: BroadcastKind) {
+        let world = self.system.world();
+        for &(packet_id, len) in &self.packets {
+            self.compose.io_buf.record(kind, packet_id, len, &world);
+        }
+    }
+
     pub fn unicast(&self, stream: ConnectionId) -> anyhow::Result<()> {
         if self.data.is_empty() {
             return Ok(());
         }
 
+        self.record(BroadcastKind::Unicast);
         self.compose
             .io_buf
             .unicast_raw(&self.data, stream, self.system);
@@ -179,9 +240,10 @@ impl<'a, 'b> DataBundle<'a, 'b> {
             return Ok(());
         }
 
+        self.record(BroadcastKind::Local);
         self.compose
             .io_buf
-            .broadcast_local_raw(&self.data, center, 0, self.system);
+            .broadcast_local_raw(&self.data, center, 0, self.priority, self.system);
         Ok(())
     }
 }
@@ -255,6 +317,7 @@ impl Compose {
             packet,
             compose: self,
             exclude: 0,
+            priority: Priority::Normal,
             center: ChunkPosition {
                 x: center.x,
                 z: center.y,
@@ -333,6 +396,8 @@ pub struct IoBuf {
     // broadcast_buffer: ThreadLocal<RefCell<BytesMut>>,
     temp_buffer: ThreadLocal<RefCell<BytesMut>>,
     idx: ThreadLocal<Cell<u16>>,
+    record_stats: bool,
+    stats: ThreadLocal<RefCell<PacketStats>>,
 }
 
 impl IoBuf {
@@ -346,6 +411,38 @@ impl IoBuf {
     pub fn order_id(&self, system_order: SystemOrder, world: &World) -> u32 {
         u32::from(system_order.value()) << 16 | u32::from(self.fetch_add_idx(world))
     }
+
+    /// Enables or disables recording of [`stats`] for packets sent through [`Compose`].
+    pub fn set_record_stats(&mut self, record_stats: bool) {
+        self.record_stats = record_stats;
+    }
+
+    #[must_use]
+    pub const fn records_stats(&self) -> bool {
+        self.record_stats
+    }
+
+    fn record(&self, kind: BroadcastKind, packet_id: Option<i32>, bytes: usize, world: &World) {
+        if !self.record_stats {
+            return;
+        }
+
+        self.stats
+            .get(world)
+            .borrow_mut()
+            .record(kind, packet_id, bytes);
+    }
+
+    /// Merges and resets the statistics recorded on each thread since the last call.
+    pub fn take_stats(&mut self) -> PacketStats {
+        let mut result = PacketStats::default();
+        for stats in &mut self.stats {
+            let stats = stats.get_mut();
+            result.merge(stats);
+            stats.clear();
+        }
+        result
+    }
 }
 
 /// A broadcast builder
@@ -389,12 +486,16 @@ impl<P> Broadcast<'_, '_, P> {
         P: PacketBundle,
     {
         let world = self.system.world();
+        let packet_id = self.packet.packet_id();
 
         let bytes = self
             .compose
             .io_buf
             .encode_packet(self.packet, self.compose, &world)?;
 
+        self.compose
+            .io_buf
+            .record(BroadcastKind::Global, packet_id, bytes.len(), &world);
         self.compose
             .io_buf
             .broadcast_raw(&bytes, self.exclude, self.system);
@@ -422,6 +523,7 @@ pub struct BroadcastLocal<'a, 'b, P> {
     compose: &'a Compose,
     center: ChunkPosition,
     exclude: u64,
+    priority: Priority,
     system: EntityView<'b>,
 }
 
@@ -432,6 +534,7 @@ impl<P> BroadcastLocal<'_, '_, P> {
         P: PacketBundle,
     {
         let world = self.system.world();
+        let packet_id = self.packet.packet_id();
 
         let bytes = self
             .compose
@@ -440,7 +543,14 @@ impl<P> BroadcastLocal<'_, '_, P> {
 
         self.compose
             .io_buf
-            .broadcast_local_raw(&bytes, self.center, self.exclude, self.system);
+            .record(BroadcastKind::Local, packet_id, bytes.len(), &world);
+        self.compose.io_buf.broadcast_local_raw(
+            &bytes,
+            self.center,
+            self.exclude,
+            self.priority,
+            self.system,
+        );
 
         Ok(())
     }
@@ -454,6 +564,20 @@ impl<P> BroadcastLocal<'_, '_, P> {
             compose: self.compose,
             center: self.center,
             exclude,
+            priority: self.priority,
+            system: self.system,
+        }
+    }
+
+    /// Sets the [`Priority`] of the packet. Anything other than [`Priority::Normal`] may be
+    /// dropped by the proxy for players who cannot keep up.
+    pub fn priority(self, priority: Priority) -> Self {
+        BroadcastLocal {
+            packet: self.packet,
+            compose: self.compose,
+            center: self.center,
+            exclude: self.exclude,
+            priority,
             system: self.system,
         }
     }
@@ -524,6 +648,7 @@ impl IoBuf {
         P: PacketBundle,
     {
         let world = system.world();
+        let packet_id = packet.packet_id();
 
         let bytes = if compress {
             self.encode_packet(packet, compose, &world)?
@@ -531,6 +656,7 @@ impl IoBuf {
             self.encode_packet_no_compression(packet, &world)?
         };
 
+        self.record(BroadcastKind::Unicast, packet_id, bytes.len(), &world);
         self.unicast_raw(&bytes, id, system);
         Ok(())
     }
@@ -540,6 +666,7 @@ impl IoBuf {
         data: &[u8],
         center: impl Into<ChunkPosition>,
         exclude: u64,
+        priority: Priority,
         system: EntityView<'_>,
     ) {
         let center = center.into();
@@ -556,6 +683,7 @@ impl IoBuf {
             center,
             exclude,
             order,
+            priority,
         };
 
         let to_send = ServerToProxyMessage::BroadcastLocal(to_send);
@@ -79,3 +79,11 @@ pub enum BossBarAction<'a> {
     UpdateStyle(BossBarColor, BossBarDivision),
     UpdateFlags(BossBarFlags),
 }
+
+/// A system message in [`hyperion_text::Text`], so that translated and formatted messages can be
+/// sent.
+#[derive(Clone, Debug, Encode, Packet)]
+pub struct GameMessageS2c<'a> {
+    pub chat: hyperion_text::Text<'a>,
+    pub overlay: bool,
+}
@@ -11,11 +11,24 @@ use tracing::{error, info, warn};
 
 use crate::{runtime::AsyncRuntime, simulation::EgressComm};
 
+/// A player connection which the proxy has recently announced.
+#[derive(Debug)]
+pub struct PlayerConnection {
+    /// The stream id of the connection.
+    pub stream: u64,
+    /// The address of the client, if the proxy knows it.
+    pub addr: Option<SocketAddr>,
+    /// The server address the client used in its handshake, if the proxy could read it.
+    pub virtual_host: Option<Arc<str>>,
+    /// Whether the player was already in the world on a previous server process.
+    pub resume: bool,
+}
+
 /// This is used
 #[derive(Default)]
 pub struct ReceiveStateInner {
     /// All players who have recently connected to the server.
-    pub player_connect: Vec<u64>,
+    pub player_connect: Vec<PlayerConnection>,
     /// All players who have recently disconnected from the server.
     pub player_disconnect: Vec<u64>,
     /// A map of stream ids to the corresponding [`BytesMut`] buffers. This represents data from the client to the server.
@@ -119,8 +132,20 @@ async fn inner(
                         match result {
                             ArchivedProxyToServerMessage::PlayerConnect(message) => {
                                 let Ok(stream) = rkyv::deserialize::<u64, !>(&message.stream);
-
-                                shared.lock().player_connect.push(stream);
+                                let Ok(addr) =
+                                    rkyv::deserialize::<Option<SocketAddr>, !>(&message.addr);
+                                let virtual_host = message
+                                    .virtual_host
+                                    .as_ref()
+                                    .map(|host| Arc::from(host.as_ref()));
+                                let resume = message.resume;
+
+                                shared.lock().player_connect.push(PlayerConnection {
+                                    stream,
+                                    addr,
+                                    virtual_host,
+                                    resume,
+                                });
                             }
                             ArchivedProxyToServerMessage::PlayerDisconnect(message) => {
                                 let Ok(stream) = rkyv::deserialize::<u64, !>(&message.stream);
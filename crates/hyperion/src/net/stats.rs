@@ -0,0 +1,292 @@
+//! Accounting of egress traffic by packet type.
+//!
+//! The proxy only sees opaque bytes, so this is the one place where we can tell which packets
+//! dominate bandwidth. Recording is off by default; enable it with `egress_stats` in the
+//! [`Config`](crate::config::Config). Admins write the statistics to [`STATS_DIR`] with
+//! `/egress`.
+
+use std::{
+    fmt::{self, Display},
+    io::Write,
+    path::PathBuf,
+};
+
+use flecs_ecs::macros::Component;
+use rustc_hash::FxHashMap;
+use tracing::{error, info};
+
+use crate::runtime::AsyncRuntime;
+
+/// The directory [`EgressStats::dump`] writes to.
+pub const STATS_DIR: &str = "run/stats";
+
+/// A file name given to [`EgressStats::dump`] which is not a plain file name, so it could point
+/// outside of [`STATS_DIR`].
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("`{0}` is not a plain file name")]
+pub struct InvalidFileName(pub String);
+
+/// How a packet is sent to players.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BroadcastKind {
+    /// Sent to a single player.
+    Unicast,
+    /// Sent to players near a chunk.
+    Local,
+    /// Sent to every player.
+    Global,
+}
+
+impl Display for BroadcastKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Unicast => "unicast",
+            Self::Local => "local",
+            Self::Global => "global",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Identifies a row of [`PacketStats`].
+///
+/// The packet ID is [`None`] for pre-encoded bytes added with
+/// [`DataBundle::add_raw`](crate::net::DataBundle::add_raw), which may contain several packets,
+/// and for [`PacketBundle`](crate::PacketBundle)s which do not give one.
+pub type PacketKey = (BroadcastKind, Option<i32>);
+
+/// The number of packets sent and the bytes they took up, after compression.
+///
+/// Bytes are counted once per send. The fan-out of broadcasts to individual players happens in
+/// the proxy and is not included.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PacketStat {
+    pub count: u64,
+    pub bytes: u64,
+}
+
+impl PacketStat {
+    fn add(&mut self, other: Self) {
+        self.count += other.count;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Egress traffic keyed by [`PacketKey`].
+#[derive(Clone, Debug, Default)]
+pub struct PacketStats {
+    entries: FxHashMap<PacketKey, PacketStat>,
+}
+
+impl PacketStats {
+    /// Records a single packet of `bytes` bytes.
+    pub fn record(&mut self, kind: BroadcastKind, packet_id: Option<i32>, bytes: usize) {
+        let entry = self.entries.entry((kind, packet_id)).or_default();
+        entry.count += 1;
+        entry.bytes += bytes as u64;
+    }
+
+    /// Adds all of `other` to `self`.
+    pub fn merge(&mut self, other: &Self) {
+        for (&key, &stat) in &other.entries {
+            self.entries.entry(key).or_default().add(stat);
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, kind: BroadcastKind, packet_id: Option<i32>) -> PacketStat {
+        self.entries
+            .get(&(kind, packet_id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The total over every packet type.
+    #[must_use]
+    pub fn total(&self) -> PacketStat {
+        let mut total = PacketStat::default();
+        for &stat in self.entries.values() {
+            total.add(stat);
+        }
+        total
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (PacketKey, PacketStat)> + '_ {
+        self.entries.iter().map(|(&key, &stat)| (key, stat))
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// A singleton with the egress traffic of the last tick and since the server started.
+///
+/// Updated by the egress system at the end of every tick while recording is enabled.
+#[derive(Component, Debug, Default)]
+pub struct EgressStats {
+    /// The number of ticks which have been recorded.
+    pub ticks: u64,
+    pub last_tick: PacketStats,
+    pub total: PacketStats,
+}
+
+impl EgressStats {
+    pub(crate) fn push_tick(&mut self, tick: PacketStats) {
+        self.ticks += 1;
+        self.total.merge(&tick);
+        self.last_tick = tick;
+    }
+
+    /// Writes the statistics as CSV, sorted by total bytes with the largest first.
+    pub fn write_csv(&self, mut w: impl Write) -> std::io::Result<()> {
+        let mut rows: Vec<_> = self.total.iter().collect();
+        rows.sort_unstable_by(|(a_key, a), (b_key, b)| {
+            b.bytes.cmp(&a.bytes).then_with(|| a_key.cmp(b_key))
+        });
+
+        let ticks = self.ticks.max(1);
+
+        writeln!(
+            w,
+            "kind,packet_id,last_tick_count,last_tick_bytes,total_count,total_bytes,\
+             avg_bytes_per_tick"
+        )?;
+
+        for ((kind, packet_id), total) in rows {
+            let last_tick = self.last_tick.get(kind, packet_id);
+
+            let packet_id = packet_id.map_or_else(|| "raw".to_owned(), |id| format!("0x{id:02X}"));
+
+            writeln!(
+                w,
+                "{kind},{packet_id},{},{},{},{},{}",
+                last_tick.count,
+                last_tick.bytes,
+                total.count,
+                total.bytes,
+                total.bytes / ticks
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the statistics as CSV to the file `name` in [`STATS_DIR`], returning its path.
+    ///
+    /// The CSV is rendered right away and written on `runtime`, so the tick is not held up by
+    /// the disk. `name` must be a plain file name; anything with a path separator or `..` is
+    /// refused.
+    pub fn dump(&self, name: &str, runtime: &AsyncRuntime) -> Result<PathBuf, InvalidFileName> {
+        let path = stats_path(name)?;
+
+        let mut csv = Vec::new();
+        self.write_csv(&mut csv)
+            .expect("writing to a Vec cannot fail");
+
+        let dump_path = path.clone();
+        runtime.spawn_blocking(move || {
+            let result =
+                std::fs::create_dir_all(STATS_DIR).and_then(|()| std::fs::write(&dump_path, csv));
+
+            match result {
+                Ok(()) => info!("wrote egress stats to {}", dump_path.display()),
+                Err(e) => error!(
+                    "failed to write egress stats to {}: {e}",
+                    dump_path.display()
+                ),
+            }
+        });
+
+        Ok(path)
+    }
+}
+
+/// The path of the file `name` in [`STATS_DIR`], if `name` is a plain file name.
+fn stats_path(name: &str) -> Result<PathBuf, InvalidFileName> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(InvalidFileName(name.to_owned()));
+    }
+
+    Ok(PathBuf::from(STATS_DIR).join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_tick() {
+        let mut stats = EgressStats::default();
+
+        let mut tick = PacketStats::default();
+        tick.record(BroadcastKind::Local, Some(0x2B), 10);
+        tick.record(BroadcastKind::Local, Some(0x2B), 20);
+        tick.record(BroadcastKind::Unicast, None, 1000);
+        stats.push_tick(tick);
+
+        let mut tick = PacketStats::default();
+        tick.record(BroadcastKind::Local, Some(0x2B), 5);
+        stats.push_tick(tick);
+
+        assert_eq!(stats.ticks, 2);
+        assert_eq!(
+            stats.last_tick.get(BroadcastKind::Local, Some(0x2B)),
+            PacketStat { count: 1, bytes: 5 }
+        );
+        assert_eq!(
+            stats.total.get(BroadcastKind::Local, Some(0x2B)),
+            PacketStat {
+                count: 3,
+                bytes: 35
+            }
+        );
+        assert_eq!(stats.total.total(), PacketStat {
+            count: 4,
+            bytes: 1035
+        });
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut stats = EgressStats::default();
+
+        let mut tick = PacketStats::default();
+        tick.record(BroadcastKind::Global, Some(0x10), 8);
+        tick.record(BroadcastKind::Unicast, None, 100);
+        stats.push_tick(tick);
+
+        let mut csv = Vec::new();
+        stats.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "unicast,raw,1,100,1,100,100");
+        assert_eq!(lines[2], "global,0x10,1,8,1,8,8");
+    }
+
+    #[test]
+    fn test_stats_path() {
+        assert_eq!(
+            stats_path("egress.csv"),
+            Ok(PathBuf::from(STATS_DIR).join("egress.csv"))
+        );
+
+        for name in [
+            "",
+            "../egress.csv",
+            "..",
+            "a/b.csv",
+            "a\\b.csv",
+            "/etc/passwd",
+        ] {
+            assert_eq!(stats_path(name), Err(InvalidFileName(name.to_owned())));
+        }
+    }
+}
@@ -22,6 +22,10 @@ pub struct DeltaDrainPacket<'a> {
 }
 
 impl PacketBundle for DeltaDrainPacket<'_> {
+    fn packet_id(&self) -> Option<i32> {
+        Some(ChunkDeltaUpdateS2c::ID)
+    }
+
     fn encode_including_ids(self, mut write: impl Write) -> anyhow::Result<()> {
         VarInt(ChunkDeltaUpdateS2c::ID).encode(&mut write)?;
 
@@ -65,6 +69,10 @@ pub struct DeltaPacket<'a> {
 }
 
 impl PacketBundle for DeltaPacket<'_> {
+    fn packet_id(&self) -> Option<i32> {
+        Some(ChunkDeltaUpdateS2c::ID)
+    }
+
     fn encode_including_ids(self, mut write: impl Write) -> anyhow::Result<()> {
         VarInt(ChunkDeltaUpdateS2c::ID).encode(&mut write)?;
 
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use flecs_ecs::{
     core::{Entity, EntityViewGet, IdOperations, World},
     macros::Component,
@@ -13,6 +15,10 @@ use valence_protocol::{
 pub struct Command {
     data: NodeData,
     has_permission: fn(world: &World, caller: Entity) -> bool,
+    /// Whether the command can be run when it ends at this node.
+    executable: bool,
+    /// The node whose children follow this node, such as for flags which can be repeated.
+    redirect: Option<Entity>,
 }
 
 pub(crate) static ROOT_COMMAND: once_cell::sync::OnceCell<Entity> =
@@ -26,6 +32,8 @@ impl Command {
     pub const ROOT: Self = Self {
         data: NodeData::Root,
         has_permission: |_: _, _: _| true,
+        executable: false,
+        redirect: None,
     };
 
     #[must_use]
@@ -37,6 +45,8 @@ impl Command {
         Self {
             data: NodeData::Literal { name },
             has_permission,
+            executable: true,
+            redirect: None,
         }
     }
 
@@ -50,8 +60,26 @@ impl Command {
                 suggestion: Some(Suggestion::AskServer),
             },
             has_permission: |_: _, _: _| true,
+            executable: true,
+            redirect: None,
         }
     }
+
+    /// Sets whether the command can be run when it ends at this node, which nodes are by
+    /// default.
+    #[must_use]
+    pub const fn executable(mut self, executable: bool) -> Self {
+        self.executable = executable;
+        self
+    }
+
+    /// Continues the command after this node with the children of `node`, which must come
+    /// before this node in the tree.
+    #[must_use]
+    pub const fn redirect(mut self, node: Entity) -> Self {
+        self.redirect = Some(node);
+        self
+    }
 }
 
 // we want a get command packet
@@ -71,6 +99,10 @@ pub fn get_command_packet(
 
     let mut commands = Vec::new();
 
+    // the index of each node, for redirects
+    let mut indices = HashMap::new();
+    indices.insert(root, 0);
+
     let mut stack = vec![StackElement {
         depth: 0,
         ptr: 0,
@@ -105,13 +137,20 @@ pub fn get_command_packet(
 
                 let ptr = commands.len();
 
+                let redirect_node = command
+                    .redirect
+                    .and_then(|redirect| indices.get(&redirect))
+                    .map(|&index| VarInt(i32::try_from(index).unwrap()));
+
                 commands.push(Node {
                     data: command.data.clone(),
-                    executable: true,
+                    executable: command.executable,
                     children: Vec::new(),
-                    redirect_node: None,
+                    redirect_node,
                 });
 
+                indices.insert(child.id(), ptr);
+
                 let node = &mut commands[parent_ptr];
                 node.children.push(i32::try_from(ptr).unwrap().into());
 
@@ -162,6 +201,8 @@ mod tests {
                     name: "test".to_string(),
                 },
                 has_permission: |_: _, _: _| true,
+                executable: true,
+                redirect: None,
             })
             .child_of_id(root);
 
@@ -190,6 +231,8 @@ mod tests {
                     name: "parent".to_string(),
                 },
                 has_permission: |_: _, _: _| true,
+                executable: true,
+                redirect: None,
             })
             .child_of_id(root);
 
@@ -200,6 +243,8 @@ mod tests {
                     name: "child".to_string(),
                 },
                 has_permission: |_: _, _: _| true,
+                executable: true,
+                redirect: None,
             })
             .child_of_id(parent);
 
@@ -233,6 +278,8 @@ mod tests {
                         name: format!("command_{i}"),
                     },
                     has_permission: |_: _, _: _| true,
+                    executable: true,
+                    redirect: None,
                 })
                 .child_of_id(parent);
             parent = child;
@@ -242,4 +289,29 @@ mod tests {
 
         assert_eq!(packet.commands.len(), MAX_DEPTH + 1);
     }
+
+    #[test]
+    fn test_executable_and_redirect() {
+        let world = World::new();
+        world.component::<Command>();
+
+        let root = world.entity();
+
+        let literal = world
+            .entity()
+            .set(Command::literal("team", |_: _, _: _| true).executable(false))
+            .child_of_id(root);
+
+        let _flag = world
+            .entity()
+            .set(Command::literal("--silent", |_: _, _: _| true).redirect(literal.id()))
+            .child_of_id(literal);
+
+        let packet = get_command_packet(&world, root.id(), None);
+
+        assert_eq!(packet.commands.len(), 3);
+        assert!(!packet.commands[1].executable);
+        assert!(packet.commands[2].executable);
+        assert_eq!(packet.commands[2].redirect_node, Some(VarInt(1)));
+    }
 }
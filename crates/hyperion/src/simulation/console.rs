@@ -0,0 +1,51 @@
+//! The server console, which runs commands typed into the server's terminal or sent over RCON.
+//!
+//! Commands from the console are run by a single entity with the [`Console`] component. Messages
+//! sent to it with [`unicast_chat`](crate::net::agnostic::unicast_chat) are logged and kept in
+//! its [`ConsoleOutput`] so that RCON can send them back to the client which ran the command.
+
+use std::sync::Arc;
+
+use derive_more::{Deref, DerefMut};
+use flecs_ecs::prelude::*;
+
+use super::{Name, locale::Locale};
+
+pub(crate) static CONSOLE: once_cell::sync::OnceCell<Entity> = once_cell::sync::OnceCell::new();
+
+/// The entity commands from the console are run by.
+#[must_use]
+pub fn get_console_entity() -> Entity {
+    *CONSOLE.get().unwrap()
+}
+
+/// Marks the entity which runs the commands of the console.
+#[derive(Component, Debug)]
+pub struct Console;
+
+/// The messages sent to the console since they were last taken.
+#[derive(Component, Debug, Default, Deref, DerefMut)]
+pub struct ConsoleOutput(Vec<String>);
+
+impl ConsoleOutput {
+    /// Takes the messages sent so far, one per line.
+    pub fn take(&mut self) -> String {
+        let output = self.0.join("\n");
+        self.0.clear();
+        output
+    }
+}
+
+pub(crate) fn init_console(world: &World) {
+    world.component::<Console>();
+    world.component::<ConsoleOutput>();
+
+    let console = world
+        .entity_named("console")
+        .add::<Console>()
+        .set(Name::from(Arc::<str>::from("Console")))
+        .set(Locale::default())
+        .set(ConsoleOutput::default());
+
+    CONSOLE.set(console.id()).unwrap();
+}
@@ -0,0 +1,461 @@
+//! Opening [`Container`]s and keeping their viewers in sync.
+//!
+//! Clicks on a container window are queued as [`event::WindowAction`]s by the packet handlers and
+//! applied here one at a time, as several players may click the same container in one tick.
+//!
+//! Containers marked [`Personal`], such as crafting tables, belong to a single viewer and are
+//! destroyed when they close them. Items left in them are given back to the viewer.
+//!
+//! Furnaces, blast furnaces and smokers have a container of their own which is kept in
+//! [`BlockContainers`] and ticked whether or not anyone views it.
+
+use std::collections::HashMap;
+
+use derive_more::{Deref, DerefMut};
+use flecs_ecs::prelude::*;
+use glam::IVec3;
+use hyperion_crafting::{CraftingRegistry, FurnaceKind};
+use hyperion_inventory::{
+    PlayerInventory,
+    container::{Container, OpenContainer, next_window_id},
+    furnace::Furnace,
+};
+use tracing::{debug, warn};
+use valence_generated::block::{BlockKind, PropName, PropValue};
+use valence_protocol::{
+    ItemStack, VarInt,
+    packets::play::{self, open_screen_s2c::WindowType},
+};
+use valence_text::IntoText;
+
+use crate::{
+    net::{Compose, ConnectionId},
+    simulation::{blocks::Blocks, event},
+    storage::EventQueue,
+};
+
+#[derive(Component)]
+pub struct ContainerModule;
+
+/// Marks a container that is only shown to the player who opened it and is destroyed when they
+/// close it.
+#[derive(Component, Debug, Default)]
+pub struct Personal;
+
+/// The containers of blocks, such as furnaces, by the position of the block.
+#[derive(Component, Debug, Default, Deref, DerefMut)]
+pub struct BlockContainers(HashMap<IVec3, Entity>);
+
+/// The position of the block a container belongs to.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ContainerBlock(pub IVec3);
+
+/// The kind of furnace `block` is, if it is one.
+#[must_use]
+pub const fn furnace_kind(block: BlockKind) -> Option<FurnaceKind> {
+    match block {
+        BlockKind::Furnace => Some(FurnaceKind::Furnace),
+        BlockKind::BlastFurnace => Some(FurnaceKind::BlastFurnace),
+        BlockKind::Smoker => Some(FurnaceKind::Smoker),
+        _ => None,
+    }
+}
+
+/// Gives the item on the cursor back to the player. Hyperion has no item entities yet, so what
+/// does not fit is lost.
+fn return_cursor(open: &mut OpenContainer, inventory: &mut PlayerInventory) {
+    let cursor = open.take_cursor();
+
+    if cursor.is_empty() {
+        return;
+    }
+
+    if let Some(remaining) = inventory.try_add_item(cursor).remaining {
+        debug!("discarding {remaining:?} which did not fit in the inventory");
+    }
+}
+
+/// Opens the window of `container` for `player`, replacing any container they have open.
+pub fn open(
+    player: EntityView<'_>,
+    container: Entity,
+    window_type: WindowType,
+    title: impl Into<String>,
+    system: EntityView<'_>,
+) {
+    let world = system.world();
+
+    let replaced = player
+        .try_get::<(&mut OpenContainer, &mut PlayerInventory)>(|(replaced, inventory)| {
+            return_cursor(replaced, inventory);
+        })
+        .is_some();
+
+    if replaced {
+        player.remove::<OpenContainer>();
+    }
+
+    let mut open = OpenContainer::new(next_window_id(), container);
+
+    let pkt = play::OpenScreenS2c {
+        window_id: VarInt(i32::from(open.window_id())),
+        window_type,
+        window_title: title.into().into_cow_text(),
+    };
+
+    world.get::<&Compose>(|compose| {
+        player.get::<(&ConnectionId, &PlayerInventory)>(|(stream, inventory)| {
+            compose.unicast(&pkt, *stream, system).unwrap();
+
+            container.entity_view(world).get::<&Container>(|container| {
+                let pkt = open.contents(container, inventory);
+                compose.unicast(&pkt, *stream, system).unwrap();
+            });
+        });
+    });
+
+    player.set(open);
+}
+
+/// Opens a [`Personal`] crafting table for `player`.
+pub fn open_crafting_table(player: EntityView<'_>, system: EntityView<'_>) {
+    let world = system.world();
+
+    let container = world
+        .entity()
+        .set(Container::crafting_table())
+        .add::<Personal>();
+
+    open(
+        player,
+        container.id(),
+        WindowType::Crafting,
+        "Crafting",
+        system,
+    );
+}
+
+/// Opens the furnace of `kind` at `position` for `player`, creating its container the first time
+/// it is opened.
+pub fn open_furnace(
+    player: EntityView<'_>,
+    position: IVec3,
+    kind: FurnaceKind,
+    system: EntityView<'_>,
+) {
+    let world = system.world();
+
+    let container = world.get::<&mut BlockContainers>(|containers| {
+        *containers.entry(position).or_insert_with(|| {
+            world
+                .entity()
+                .set(Container::furnace(kind))
+                .set(Furnace::new(kind))
+                .set(ContainerBlock(position))
+                .id()
+        })
+    });
+
+    let (window_type, title) = match kind {
+        FurnaceKind::Furnace => (WindowType::Furnace, "Furnace"),
+        FurnaceKind::BlastFurnace => (WindowType::BlastFurnace, "Blast Furnace"),
+        FurnaceKind::Smoker => (WindowType::Smoker, "Smoker"),
+    };
+
+    open(player, container, window_type, title, system);
+}
+
+/// Closes the container `player` has open, if any.
+pub fn close(player: EntityView<'_>, system: EntityView<'_>) {
+    let world = system.world();
+
+    let closed = player
+        .try_get::<(&ConnectionId, &mut OpenContainer, &mut PlayerInventory)>(
+            |(stream, open, inventory)| {
+                return_cursor(open, inventory);
+
+                let pkt = play::CloseScreenS2c {
+                    window_id: open.window_id(),
+                };
+
+                world.get::<&Compose>(|compose| {
+                    compose.unicast(&pkt, *stream, system).unwrap();
+                });
+            },
+        )
+        .is_some();
+
+    if closed {
+        player.remove::<OpenContainer>();
+    }
+}
+
+/// Closes the window of every player viewing `container` and gives them `items`, which it held,
+/// before it is destroyed. There are no item entities to drop the items as yet, so what does not
+/// fit in the inventories of viewers is lost.
+fn close_viewers(container: Entity, items: Vec<ItemStack>, system: EntityView<'_>) {
+    let world = system.world();
+
+    let mut viewers = Vec::new();
+
+    world
+        .query::<&OpenContainer>()
+        .build()
+        .each_entity(|viewer, open| {
+            if open.container() == container {
+                viewers.push(viewer.id());
+            }
+        });
+
+    for item in items {
+        let mut remaining = Some(item);
+
+        for viewer in &viewers {
+            let Some(item) = remaining.take() else {
+                break;
+            };
+
+            remaining = viewer
+                .entity_view(world)
+                .try_get::<&mut PlayerInventory>(|inventory| {
+                    inventory.try_add_item(item.clone()).remaining
+                })
+                .unwrap_or(Some(item));
+        }
+
+        if let Some(remaining) = remaining {
+            debug!("discarding {remaining:?} which did not fit in the inventory of any viewer");
+        }
+    }
+
+    for viewer in viewers {
+        close(viewer.entity_view(world), system);
+    }
+}
+
+fn handle_click(
+    player: EntityView<'_>,
+    packet: &play::ClickSlotC2s<'_>,
+    compose: &Compose,
+    registry: &CraftingRegistry,
+    system: EntityView<'_>,
+) {
+    let world = system.world();
+
+    player.try_get::<(&ConnectionId, &mut OpenContainer, &mut PlayerInventory)>(
+        |(stream, open, inventory)| {
+            if open.window_id() != packet.window_id {
+                return;
+            }
+
+            // the container may have been destroyed since the click was queued
+            if !world.is_alive(open.container()) {
+                warn!(
+                    "container {:?} of window {} was destroyed",
+                    open.container(),
+                    packet.window_id
+                );
+                return;
+            }
+
+            let container = open.container().entity_view(world);
+
+            let found = container.try_get::<&mut Container>(|container| {
+                let resync = match open.click(container, inventory, registry, packet) {
+                    Ok(result) => {
+                        for item in result.dropped {
+                            // there are no item entities to drop yet
+                            if let Some(remaining) = inventory.try_add_item(item).remaining {
+                                debug!("discarding dropped {remaining:?}");
+                            }
+                        }
+                        result.resync
+                    }
+                    Err(e) => {
+                        warn!("invalid container click: {e}");
+                        true
+                    }
+                };
+
+                if resync {
+                    let pkt = open.contents(container, inventory);
+                    compose.unicast(&pkt, *stream, system).unwrap();
+                }
+            });
+
+            if found.is_none() {
+                warn!(
+                    "container {:?} of window {} no longer exists",
+                    open.container(),
+                    packet.window_id
+                );
+            }
+        },
+    );
+}
+
+fn handle_close(player: EntityView<'_>, window_id: u8) {
+    let closed = player
+        .try_get::<(&mut OpenContainer, &mut PlayerInventory)>(|(open, inventory)| {
+            if open.window_id() != window_id {
+                return false;
+            }
+
+            return_cursor(open, inventory);
+            true
+        })
+        .unwrap_or(false);
+
+    if closed {
+        player.remove::<OpenContainer>();
+    }
+}
+
+impl Module for ContainerModule {
+    fn module(world: &World) {
+        world.component::<Personal>();
+        world.component::<BlockContainers>();
+        world.component::<ContainerBlock>();
+
+        world.set(BlockContainers::default());
+
+        // gives back what is left in a personal container once its viewer stops viewing it
+        observer!(world, flecs::OnRemove, &OpenContainer, &mut PlayerInventory).each_entity(
+            |player, (open, inventory)| {
+                let world = player.world();
+
+                if !world.is_alive(open.container()) {
+                    return;
+                }
+
+                let container = open.container().entity_view(world);
+
+                if !container.has::<Personal>() {
+                    return;
+                }
+
+                container.try_get::<&mut Container>(|container| {
+                    for item in container.drain() {
+                        if let Some(remaining) = inventory.try_add_item(item).remaining {
+                            debug!("discarding {remaining:?} which did not fit in the inventory");
+                        }
+                    }
+                });
+
+                container.destruct();
+            },
+        );
+
+        system!(
+            "handle_window_actions",
+            world,
+            &mut EventQueue<event::WindowAction>($),
+            &Compose($),
+            &CraftingRegistry($),
+        )
+        .each_iter(|it, _, (queue, compose, registry)| {
+            let system = it.system();
+            let world = it.world();
+
+            for action in queue.drain() {
+                match action {
+                    event::WindowAction::Click { by, packet } => {
+                        handle_click(by.entity_view(world), &packet, compose, registry, system);
+                    }
+                    event::WindowAction::Close { by, window_id } => {
+                        handle_close(by.entity_view(world), window_id);
+                    }
+                    event::WindowAction::OpenCraftingTable { by } => {
+                        open_crafting_table(by.entity_view(world), system);
+                    }
+                    event::WindowAction::OpenFurnace { by, position, kind } => {
+                        open_furnace(by.entity_view(world), position, kind, system);
+                    }
+                }
+            }
+        });
+
+        // furnaces keep cooking while no one views them, as long as their chunk is loaded
+        system!(
+            "tick_furnaces",
+            world,
+            &mut Blocks($),
+            &mut BlockContainers($),
+            &CraftingRegistry($),
+            &mut Container,
+            &mut Furnace,
+            &ContainerBlock,
+        )
+        .each_iter(
+            |it, row, (blocks, containers, registry, container, furnace, block)| {
+                let position = block.0;
+
+                let Some(state) = blocks.get_block(position) else {
+                    return;
+                };
+
+                if furnace_kind(state.to_kind()) != Some(furnace.kind()) {
+                    // the block was broken or replaced
+                    debug!("removing the container of the furnace at {position}");
+
+                    let entity = it.entity(row);
+                    close_viewers(entity.id(), container.drain(), it.system());
+
+                    containers.remove(&position);
+                    entity.destruct();
+                    return;
+                }
+
+                let was_lit = furnace.is_lit();
+
+                furnace.tick(container, registry);
+
+                if furnace.is_lit() != was_lit {
+                    let lit = if furnace.is_lit() {
+                        PropValue::True
+                    } else {
+                        PropValue::False
+                    };
+
+                    if let Err(e) = blocks.set_block(position, state.set(PropName::Lit, lit)) {
+                        warn!("failed to light the furnace at {position}: {e:?}");
+                    }
+                }
+            },
+        );
+
+        // sends slots changed by other viewers or by the server
+        system!(
+            "sync_open_containers",
+            world,
+            &Compose($),
+            &mut OpenContainer,
+            &ConnectionId,
+        )
+        .multi_threaded()
+        .kind::<flecs::pipeline::OnStore>()
+        .each_iter(|it, _, (compose, open, stream)| {
+            let system = it.system();
+            let world = it.world();
+
+            // viewers of a destroyed container are closed in the tick it is destroyed in
+            if !world.is_alive(open.container()) {
+                return;
+            }
+
+            let container = open.container().entity_view(world);
+
+            container.try_get::<&Container>(|container| {
+                for pkt in open.changes(container) {
+                    compose.unicast(&pkt, *stream, system).unwrap();
+                }
+            });
+
+            container.try_get::<&Furnace>(|furnace| {
+                for pkt in open.property_changes(&furnace.properties()) {
+                    compose.unicast(&pkt, *stream, system).unwrap();
+                }
+            });
+        });
+    }
+}
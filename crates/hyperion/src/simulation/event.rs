@@ -3,8 +3,9 @@
 use derive_more::Constructor;
 use flecs_ecs::{core::Entity, macros::Component};
 use glam::{IVec3, Vec3};
+use hyperion_crafting::FurnaceKind;
 use valence_generated::block::BlockState;
-use valence_protocol::Hand;
+use valence_protocol::{Hand, packets::play};
 use valence_server::{ItemKind, entity::item_frame::ItemStack};
 
 use crate::simulation::skin::PlayerSkin;
@@ -146,3 +147,30 @@ pub struct ClientStatusEvent {
     pub client: Entity,
     pub status: ClientStatusCommand,
 }
+
+/// A click, close or open of a window with a [`Container`](hyperion_inventory::container::Container).
+///
+/// Containers may be shared between players, so these are applied one at a time after packets
+/// have been handled.
+#[derive(Debug)]
+pub enum WindowAction {
+    Click {
+        by: Entity,
+        packet: play::ClickSlotC2s<'static>,
+    },
+    Close {
+        by: Entity,
+        window_id: u8,
+    },
+    /// Opens a crafting table of its own for the player.
+    OpenCraftingTable {
+        by: Entity,
+    },
+    /// Opens the furnace, blast furnace or smoker at `position`, which keeps cooking after the
+    /// player closes it.
+    OpenFurnace {
+        by: Entity,
+        position: IVec3,
+        kind: FurnaceKind,
+    },
+}
@@ -6,6 +6,7 @@ use anyhow::{Context, bail};
 use flecs_ecs::core::{Entity, EntityView, EntityViewGet, World};
 use geometry::aabb::Aabb;
 use glam::{IVec3, Vec3};
+use hyperion_inventory::{PlayerInventory, container::OpenContainer};
 use hyperion_utils::EntityExt;
 use tracing::{info, instrument, trace, warn};
 use valence_generated::{
@@ -28,13 +29,17 @@ use super::{
     block_bounds,
     blocks::Blocks,
     bow::BowCharging,
+    container,
     event::ClientStatusEvent,
+    locale::Locale,
 };
 use crate::{
     net::{Compose, ConnectionId, decoder::BorrowedPacketFrame},
     simulation::{Pitch, Yaw, aabb, event, event::PluginMessage, metadata::entity::Pose},
     storage::{
-        ClickSlotEvent, CommandCompletionRequest, Events, GlobalEventHandlers, InteractEvent,
+        AttackEntityEvent, ClickSlotEvent, CloseScreenEvent, CommandCompletionRequest,
+        DigBlockEvent, DropItemEvent, Events, GlobalEventHandlers, InteractBlockEvent,
+        InteractEvent, SelectSlotEvent,
     },
 };
 
@@ -231,7 +236,10 @@ fn hand_swing(mut data: &[u8], query: &mut PacketSwitchQuery<'_>) -> anyhow::Res
 }
 
 #[instrument(skip_all)]
-fn player_interact_entity(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
+fn player_interact_entity(
+    mut data: &[u8],
+    query: &mut PacketSwitchQuery<'_>,
+) -> anyhow::Result<()> {
     let packet = play::PlayerInteractEntityC2s::decode(&mut data)?;
 
     // attack
@@ -242,6 +250,9 @@ fn player_interact_entity(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> any
     let target = packet.entity_id.0;
     let target = Entity::from_minecraft_id(target);
 
+    let event = AttackEntityEvent { target };
+    query.handlers.attack_entity.trigger_all(query, &event);
+
     query.events.push(
         event::AttackEntity {
             origin: query.id,
@@ -276,13 +287,27 @@ pub struct PacketSwitchQuery<'a> {
 }
 
 // i.e., shooting a bow, digging a block, etc
-fn player_action(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
+fn player_action(mut data: &[u8], query: &mut PacketSwitchQuery<'_>) -> anyhow::Result<()> {
     let packet = play::PlayerActionC2s::decode(&mut data)?;
 
     let sequence = packet.sequence.0;
     let position = IVec3::new(packet.position.x, packet.position.y, packet.position.z);
 
     match packet.action {
+        PlayerAction::StartDestroyBlock => {
+            let Some(block) = query.blocks.get_block(position) else {
+                return Ok(());
+            };
+
+            let event = DigBlockEvent {
+                position,
+                block,
+                face: packet.direction,
+                sequence,
+            };
+
+            query.handlers.dig_block.trigger_all(query, &event);
+        }
         PlayerAction::StopDestroyBlock => {
             let event = event::DestroyBlock {
                 position,
@@ -298,8 +323,23 @@ fn player_action(mut data: &[u8], query: &PacketSwitchQuery<'_>) -> anyhow::Resu
                 item: query.inventory.get_cursor().item,
             };
 
+            query.handlers.release_use_item.trigger_all(query, &event);
             query.events.push(event, query.world);
         }
+        action @ (PlayerAction::DropItem | PlayerAction::DropAllItems) => {
+            let slot = query.inventory.get_cursor_index();
+
+            let event = DropItemEvent {
+                slot,
+                whole_stack: matches!(action, PlayerAction::DropAllItems),
+            };
+
+            query.handlers.drop_item.trigger_all(query, &event);
+
+            // dropped items are not spawned, so the client is told what is left in the slot
+            let stack = query.inventory.get(slot)?.clone();
+            query.inventory.set(slot, stack)?;
+        }
         action => bail!("unimplemented {action:?}"),
     }
 
@@ -400,6 +440,39 @@ pub fn player_interact_block(
         return Ok(());
     };
 
+    let event = InteractBlockEvent {
+        hand: packet.hand,
+        position: interacted_block_pos_vec,
+        block: interacted_block,
+        face: packet.face,
+        sequence: packet.sequence.0,
+    };
+
+    query.handlers.interact_block.trigger_all(query, &event);
+
+    // sneaking with an item in hand places the item instead, as in vanilla
+    let sneaking_with_item =
+        *query.pose == Pose::Sneaking && !query.inventory.get_cursor().is_empty();
+
+    if !sneaking_with_item {
+        let block_kind = interacted_block.to_kind();
+
+        let action = if block_kind == BlockKind::CraftingTable {
+            Some(event::WindowAction::OpenCraftingTable { by: query.id })
+        } else {
+            container::furnace_kind(block_kind).map(|kind| event::WindowAction::OpenFurnace {
+                by: query.id,
+                position: interacted_block_pos_vec,
+                kind,
+            })
+        };
+
+        if let Some(action) = action {
+            query.events.push(action, query.world);
+            return Ok(());
+        }
+    }
+
     if interacted_block.get(PropName::Open).is_some() {
         // Toggle the open state of a door
         // todo: place block instead of toggling door if the player is crouching and holding a
@@ -473,8 +546,17 @@ pub fn update_selected_slot(
 
     let play::UpdateSelectedSlotC2s { slot } = packet;
 
+    let event = SelectSlotEvent {
+        previous: query.inventory.get_cursor_index() - PlayerInventory::HOTBAR_START_SLOT,
+        slot,
+    };
+
     query.inventory.set_cursor(slot);
 
+    if event.previous != event.slot {
+        query.handlers.select_slot.trigger_all(query, &event);
+    }
+
     Ok(())
 }
 
@@ -521,10 +603,27 @@ pub fn custom_payload(
     Ok(())
 }
 
+/// Whether `window_id` is a window with a [`Container`](hyperion_inventory::container::Container)
+/// the player has open.
+fn is_open_container(player: EntityView<'_>, window_id: u8) -> bool {
+    player
+        .try_get::<&OpenContainer>(|open| open.window_id() == window_id)
+        .unwrap_or(false)
+}
+
 // keywords: inventory
 fn click_slot(mut data: &'static [u8], query: &mut PacketSwitchQuery<'_>) -> anyhow::Result<()> {
     let pkt = play::ClickSlotC2s::decode(&mut data)?;
 
+    if is_open_container(query.view, pkt.window_id) {
+        let event = event::WindowAction::Click {
+            by: query.id,
+            packet: pkt,
+        };
+        query.events.push(event, query.world);
+        return Ok(());
+    }
+
     let to_send_pkt = play::ScreenHandlerSlotUpdateS2c {
         window_id: -1,
         state_id: VarInt::default(),
@@ -580,6 +679,30 @@ fn click_slot(mut data: &'static [u8], query: &mut PacketSwitchQuery<'_>) -> any
     Ok(())
 }
 
+fn close_handled_screen(
+    mut data: &'static [u8],
+    query: &mut PacketSwitchQuery<'_>,
+) -> anyhow::Result<()> {
+    let pkt = play::CloseHandledScreenC2s::decode(&mut data)?;
+
+    let window_id = u8::try_from(pkt.window_id).context("window id is negative")?;
+
+    if is_open_container(query.view, window_id) {
+        let event = event::WindowAction::Close {
+            by: query.id,
+            window_id,
+        };
+        query.events.push(event, query.world);
+    }
+
+    let event = CloseScreenEvent { window_id };
+    query.handlers.close_screen.trigger_all(query, &event);
+
+    Ok(())
+}
+
+/// Queues a chat message. The server runs in offline mode, so there are no profile keys to
+/// verify the signature of the message with, and it is relayed unsigned.
 fn chat_message(mut data: &'static [u8], query: &PacketSwitchQuery<'_>) -> anyhow::Result<()> {
     // todo: we could technically remove allocations &[u8] exists until end of tick
     let pkt = play::ChatMessageC2s::decode(&mut data)?;
@@ -633,6 +756,18 @@ pub fn client_status(
     Ok(())
 }
 
+pub fn client_settings(
+    mut data: &'static [u8],
+    query: &mut PacketSwitchQuery<'_>,
+) -> anyhow::Result<()> {
+    let pkt = play::ClientSettingsC2s::decode(&mut data)?;
+
+    // messages are translated when they are sent, so only the locale needs to be kept
+    query.view.set(Locale::new(pkt.locale));
+
+    Ok(())
+}
+
 pub fn packet_switch(
     raw: BorrowedPacketFrame<'_>,
     query: &mut PacketSwitchQuery<'_>,
@@ -648,7 +783,9 @@ pub fn packet_switch(
         play::ChatMessageC2s::ID => chat_message(data, query)?,
         play::ClickSlotC2s::ID => click_slot(data, query)?,
         play::ClientCommandC2s::ID => client_command(data, query)?,
+        play::ClientSettingsC2s::ID => client_settings(data, query)?,
         play::ClientStatusC2s::ID => client_status(data, query)?,
+        play::CloseHandledScreenC2s::ID => close_handled_screen(data, query)?,
         play::CommandExecutionC2s::ID => chat_command(data, query)?,
         play::CreativeInventoryActionC2s::ID => creative_inventory_action(data, query)?,
         play::CustomPayloadC2s::ID => custom_payload(data, query)?,
@@ -662,6 +799,9 @@ pub fn packet_switch(
         play::PositionAndOnGroundC2s::ID => position_and_on_ground(query, data)?,
         play::RequestCommandCompletionsC2s::ID => request_command_completions(data, query)?,
         play::UpdateSelectedSlotC2s::ID => update_selected_slot(data, query)?,
+        // chat is relayed unsigned, so the keys messages are signed with and the messages
+        // clients have seen are not needed
+        play::PlayerSessionC2s::ID | play::MessageAcknowledgmentC2s::ID => {}
         _ => trace!("unknown packet id: 0x{:02X}", packet_id),
     }
 
@@ -0,0 +1,43 @@
+//! The language of each player and the translations of the server's messages.
+
+use derive_more::{Deref, DerefMut};
+use flecs_ecs::prelude::*;
+use hyperion_text::{DEFAULT_LOCALE, Key, Text, Translations};
+
+/// The locale the client of a player uses, such as `en_us` or `de_de`, from its client settings.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Deref)]
+pub struct Locale(String);
+
+impl Locale {
+    #[must_use]
+    pub fn new(locale: &str) -> Self {
+        Self(locale.to_ascii_lowercase())
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self(DEFAULT_LOCALE.to_owned())
+    }
+}
+
+/// A singleton with the translations of the server's messages.
+#[derive(Component, Debug, Default, Deref, DerefMut)]
+pub struct Localization {
+    translations: Translations,
+}
+
+impl Localization {
+    #[must_use]
+    pub const fn new(translations: Translations) -> Self {
+        Self { translations }
+    }
+
+    /// The message `key` in the language of `player`, with `{0}`, `{1}`, ... replaced by `args`.
+    #[must_use]
+    pub fn translate(&self, player: EntityView<'_>, key: Key, args: &[&str]) -> Text<'static> {
+        player
+            .try_get::<&Locale>(|locale| self.translations.translate(locale, key, args))
+            .unwrap_or_else(|| self.translations.translate(DEFAULT_LOCALE, key, args))
+    }
+}
@@ -7,7 +7,7 @@ use flecs_ecs::prelude::*;
 use geometry::aabb::Aabb;
 use glam::{I16Vec2, IVec3, Quat, Vec3};
 use hyperion_utils::EntityExt;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 use skin::PlayerSkin;
 use tracing::{debug, error};
@@ -30,10 +30,14 @@ pub mod animation;
 pub mod blocks;
 pub mod bow;
 pub mod command;
+pub mod console;
+pub mod container;
 pub mod entity_kind;
 pub mod event;
 pub mod handlers;
+pub mod locale;
 pub mod metadata;
+pub mod player_data;
 pub mod skin;
 pub mod util;
 
@@ -88,6 +92,11 @@ impl<K: Eq + Hash, V> DeferredMap<K, V> {
     pub fn remove(&self, key: K, world: &World) {
         self.to_remove.push(key, world);
     }
+
+    /// Iterates over the entries as of the last [`Self::update`].
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.map.iter()
+    }
 }
 
 impl<K: Eq + Hash, V> DeferredMap<K, V> {
@@ -111,6 +120,15 @@ pub struct Name(Arc<str>);
 #[derive(Component, Deref, DerefMut, From, Debug, Default)]
 pub struct IgnMap(DeferredMap<Arc<str>, Entity>);
 
+/// The name of the team an entity is on, which entity selectors match with `team=`.
+#[derive(Component, Clone, Deref, From, Display, Debug, PartialEq, Eq)]
+pub struct TeamName(Arc<str>);
+
+/// Labels given to an entity, which entity selectors match with `tag=`, like the tags of `/tag`
+/// in vanilla.
+#[derive(Component, Deref, DerefMut, Debug, Default)]
+pub struct EntityTags(FxHashSet<Arc<str>>);
+
 #[derive(Component, Debug, Default)]
 pub struct RaycastTravel;
 
@@ -609,11 +627,16 @@ impl Module for SimModule {
         world.component::<PlayerSkin>();
         world.component::<Command>();
 
+        world.component::<locale::Locale>();
+        world.component::<locale::Localization>();
+
         component!(world, IgnMap);
 
         world.component::<Position>().meta();
 
         world.component::<Name>();
+        world.component::<TeamName>();
+        world.component::<EntityTags>();
         component!(world, Name).opaque_func(meta_ser_stringify_type_display::<Name>);
 
         world.component::<AiTargetable>();
@@ -627,6 +650,11 @@ impl Module for SimModule {
         world.component::<animation::ActiveAnimation>();
 
         world.component::<hyperion_inventory::PlayerInventory>();
+        world.component::<hyperion_inventory::container::Container>();
+        world.component::<hyperion_inventory::container::OpenContainer>();
+
+        world.import::<container::ContainerModule>();
+        world.import::<player_data::PlayerDataModule>();
 
         world.component::<BowCharging>();
         component!(world, BowCharging).opaque_func(meta_ser_stringify_type_display::<BowCharging>);
@@ -716,6 +744,8 @@ impl Module for SimModule {
                     _ => {}
                 });
             });
+
+        console::init_console(world);
     }
 }
 
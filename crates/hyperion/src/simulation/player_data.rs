@@ -0,0 +1,180 @@
+//! Saving the [`PlayerData`] of players while they are online and when they leave.
+//!
+//! The players due to be saved in a tick are written in one transaction on the
+//! [`AsyncRuntime`], except on shutdown, when every player is saved once before the server stops.
+//! The data is restored on login; see [`crate::ingress`].
+
+use std::sync::atomic::Ordering;
+
+use flecs_ecs::prelude::*;
+use hyperion_inventory::PlayerInventory;
+use tokio::task::JoinHandle;
+use tracing::error;
+
+use crate::{
+    Shutdown,
+    config::Config,
+    runtime::AsyncRuntime,
+    simulation::{Pitch, Player, Position, Uuid, Xp, Yaw, metadata::living_entity::Health},
+    storage::{PlayerData, PlayerDataHandler},
+};
+
+/// The number of ticks per second the server runs at.
+const TICKS_PER_SECOND: u64 = 20;
+
+#[derive(Component)]
+pub struct PlayerDataModule;
+
+fn player_data(
+    inventory: &PlayerInventory,
+    xp: Xp,
+    health: &Health,
+    position: &Position,
+    yaw: &Yaw,
+    pitch: &Pitch,
+) -> PlayerData {
+    PlayerData {
+        inventory: inventory.slots().to_vec(),
+        xp: xp.amount,
+        health: **health,
+        position: position.to_array(),
+        yaw: **yaw,
+        pitch: **pitch,
+    }
+}
+
+/// The players whose data is saved at the end of this tick.
+#[derive(Component, Default)]
+struct PendingSaves {
+    data: Vec<(uuid::Uuid, PlayerData)>,
+    /// The last batch being written, which the next batch waits for so that batches are written
+    /// in order.
+    writing: Option<JoinHandle<()>>,
+    /// Whether every player was saved for shutdown, after which nothing is saved anymore.
+    shut_down: bool,
+}
+
+fn save_all(handler: &PlayerDataHandler, data: &[(uuid::Uuid, PlayerData)]) {
+    if let Err(e) = handler.insert_all(data) {
+        error!("failed to save player data of {} players: {e}", data.len());
+    }
+}
+
+impl Module for PlayerDataModule {
+    fn module(world: &World) {
+        world.component::<PendingSaves>();
+        world.set(PendingSaves::default());
+
+        observer!(
+            world,
+            flecs::OnRemove,
+            &Uuid,
+            [filter] &PlayerInventory,
+            [filter] &Xp,
+            [filter] &Health,
+            [filter] &Position,
+            [filter] &Yaw,
+            [filter] &Pitch,
+            &mut PendingSaves($),
+        )
+        .with::<Player>()
+        .each(
+            |(uuid, inventory, xp, health, position, yaw, pitch, pending)| {
+                if !pending.shut_down {
+                    let data = player_data(inventory, *xp, health, position, yaw, pitch);
+                    pending.data.push((uuid.0, data));
+                }
+            },
+        );
+
+        // players are spread over the interval by their id so saves do not all land on one tick
+        system!(
+            "save_player_data",
+            world,
+            &mut PendingSaves($),
+            &Config($),
+            &Shutdown($),
+            &Uuid,
+            &PlayerInventory,
+            &Xp,
+            &Health,
+            &Position,
+            &Yaw,
+            &Pitch,
+        )
+        .with::<Player>()
+        .kind::<flecs::pipeline::OnStore>()
+        .each_iter(
+            |it,
+             row,
+             (pending, config, shutdown, uuid, inventory, xp, health, position, yaw, pitch)| {
+                if pending.shut_down {
+                    return;
+                }
+
+                let shutting_down = shutdown.value.load(Ordering::Relaxed);
+                let interval = u64::from(config.player_data_save_interval) * TICKS_PER_SECOND;
+
+                let due = interval != 0 && {
+                    let frame = it.world().info().frame_count_total.unsigned_abs();
+                    let id = it.entity(row).id().0;
+                    frame.wrapping_add(id) % interval == 0
+                };
+
+                if shutting_down || due {
+                    let data = player_data(inventory, *xp, health, position, yaw, pitch);
+                    pending.data.push((uuid.0, data));
+                }
+            },
+        );
+
+        system!(
+            "flush_player_data",
+            world,
+            &mut PendingSaves($),
+            &PlayerDataHandler($),
+            &AsyncRuntime($),
+            &Shutdown($),
+        )
+        .kind::<flecs::pipeline::OnStore>()
+        .each(|(pending, handler, runtime, shutdown)| {
+            if pending.shut_down {
+                return;
+            }
+
+            if shutdown.value.load(Ordering::Relaxed) {
+                // the server stops after this tick, so the last saves are written before it does
+                if let Some(writing) = pending.writing.take()
+                    && let Err(e) = runtime.block_on(writing)
+                {
+                    error!("failed to wait for player data to be saved: {e}");
+                }
+
+                save_all(handler, &pending.data);
+                pending.data.clear();
+                pending.shut_down = true;
+                return;
+            }
+
+            if pending.data.is_empty() {
+                return;
+            }
+
+            let data = std::mem::take(&mut pending.data);
+            let handler = handler.clone();
+            let previous = pending.writing.take();
+
+            pending.writing = Some(runtime.spawn(async move {
+                if let Some(previous) = previous {
+                    previous.await.ok();
+                }
+
+                let result = tokio::task::spawn_blocking(move || save_all(&handler, &data)).await;
+
+                if let Err(e) = result {
+                    error!("failed to save player data: {e}");
+                }
+            }));
+        });
+    }
+}
@@ -0,0 +1,127 @@
+//! Bans of players and IP addresses, which refuse their logins until they are lifted or expire.
+
+use std::{fmt, net::IpAddr};
+
+use humantime::format_duration;
+use uuid::Uuid;
+
+use crate::storage::{Sanction, SanctionStorage, SanctionTarget};
+
+/// Who a ban applies to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BanTarget {
+    Player(Uuid),
+    Ip(IpAddr),
+}
+
+impl SanctionTarget for BanTarget {
+    fn key(self) -> String {
+        match self {
+            Self::Player(uuid) => format!("player:{uuid}"),
+            Self::Ip(ip) => format!("ip:{ip}"),
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        let (kind, value) = key.split_once(':')?;
+
+        match kind {
+            "player" => value.parse().ok().map(Self::Player),
+            "ip" => value.parse().ok().map(Self::Ip),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BanTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Player(uuid) => write!(f, "{uuid}"),
+            Self::Ip(ip) => write!(f, "{ip}"),
+        }
+    }
+}
+
+/// Why and until when a player or address is banned.
+pub type Ban = Sanction;
+
+/// Keeps the bans of players and IP addresses in [`LocalDb`](crate::storage::LocalDb).
+pub type BanStorage = SanctionStorage<BanTarget>;
+
+/// The screen the banned player is disconnected with.
+#[must_use]
+pub fn ban_message(ban: &Ban, now: u64) -> String {
+    let title = if ban.expires.is_some() {
+        "You are temporarily banned from this server"
+    } else {
+        "You are banned from this server"
+    };
+
+    let expires = ban.remaining(now).map_or_else(
+        || "Never".to_owned(),
+        |remaining| format!("in {}", format_duration(remaining)),
+    );
+
+    format!(
+        "§c§l{title}§r\n\n§7Reason: §f{}\n§7Banned by: §f{}\n§7Expires: §f{expires}",
+        ban.reason, ban.issuer,
+    )
+}
+
+impl SanctionStorage<BanTarget> {
+    /// The ban in effect for a player logging in as `uuid` from `ip`, checking the player before
+    /// their address.
+    pub fn find_login(
+        &self,
+        uuid: Uuid,
+        ip: Option<IpAddr>,
+    ) -> anyhow::Result<Option<(BanTarget, Ban)>> {
+        let targets = std::iter::once(BanTarget::Player(uuid)).chain(ip.map(BanTarget::Ip));
+
+        for target in targets {
+            if let Some(ban) = self.find(target)? {
+                return Ok(Some((target, ban)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_key_roundtrip() {
+        let targets = [
+            BanTarget::Player(Uuid::from_u128(0x1234)),
+            BanTarget::Ip("127.0.0.1".parse().unwrap()),
+            BanTarget::Ip("::1".parse().unwrap()),
+        ];
+
+        for target in targets {
+            assert_eq!(BanTarget::from_key(&target.key()), Some(target));
+        }
+
+        assert_eq!(BanTarget::from_key("team:red"), None);
+    }
+
+    #[test]
+    fn test_message() {
+        let ban = Ban {
+            name: None,
+            reason: "Griefing".to_owned(),
+            issuer: "Console".to_owned(),
+            created: 0,
+            expires: Some(60),
+        };
+
+        let message = ban_message(&ban, 0);
+
+        assert!(message.contains("temporarily banned"));
+        assert!(message.contains("Reason: §fGriefing"));
+        assert!(message.contains("Banned by: §fConsole"));
+        assert!(message.contains("Expires: §fin 1m"));
+    }
+}
@@ -25,7 +25,7 @@ impl LocalDb {
 
         let env = unsafe {
             EnvOpenOptions::new()
-                .map_size(10 * 1024 * 1024) // 10MB
+                .map_size(100 * 1024 * 1024) // 100MB
                 .max_dbs(8) // todo: why is this needed/configurable? ideally would be infinite...
                 .open(&path)?
         };
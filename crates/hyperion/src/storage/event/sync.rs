@@ -1,14 +1,19 @@
 use anyhow::Context;
 use flecs_ecs::{core::Entity, macros::Component};
+use glam::IVec3;
+use valence_generated::block::BlockState;
 use valence_protocol::{
-    Hand, ItemStack,
+    Direction, Hand, ItemStack,
     packets::{
         play,
         play::click_slot_c2s::{ClickMode, SlotChange},
     },
 };
 
-use crate::simulation::{event::ClientStatusEvent, handlers::PacketSwitchQuery};
+use crate::simulation::{
+    event::{ClientStatusEvent, ReleaseUseItem},
+    handlers::PacketSwitchQuery,
+};
 
 pub type EventFn<T> = Box<dyn Fn(&mut PacketSwitchQuery<'_>, &T) + 'static + Send + Sync>;
 
@@ -22,6 +27,45 @@ pub struct InteractEvent {
     pub sequence: i32,
 }
 
+/// The player right-clicked a block.
+pub struct InteractBlockEvent {
+    pub hand: Hand,
+    pub position: IVec3,
+    pub block: BlockState,
+    pub face: Direction,
+    pub sequence: i32,
+}
+
+/// The player left-clicked a block, starting to break it.
+pub struct DigBlockEvent {
+    pub position: IVec3,
+    pub block: BlockState,
+    pub face: Direction,
+    pub sequence: i32,
+}
+
+/// The player left-clicked an entity.
+pub struct AttackEntityEvent {
+    pub target: Entity,
+}
+
+/// The player dropped the item they are holding. Dropped items are not spawned in the world, so
+/// the slot is sent to the player again after the handlers ran.
+pub struct DropItemEvent {
+    /// The inventory slot the item was dropped from.
+    pub slot: u16,
+    /// Whether the whole stack was dropped rather than a single item.
+    pub whole_stack: bool,
+}
+
+/// The player selected another hotbar slot.
+pub struct SelectSlotEvent {
+    /// The hotbar slot selected before, from 0 to 8.
+    pub previous: u16,
+    /// The hotbar slot selected now, from 0 to 8.
+    pub slot: u16,
+}
+
 pub struct ClickSlotEvent {
     pub window_id: u8,
     pub state_id: i32,
@@ -63,10 +107,22 @@ impl TryFrom<play::ClickSlotC2s<'static>> for ClickSlotEvent {
     }
 }
 
+/// The client closed the screen with the given window ID.
+pub struct CloseScreenEvent {
+    pub window_id: u8,
+}
+
 #[derive(Component, Default)]
 pub struct GlobalEventHandlers {
     pub click: EventHandlers<ClickSlotEvent>,
+    pub close_screen: EventHandlers<CloseScreenEvent>,
     pub interact: EventHandlers<InteractEvent>,
+    pub interact_block: EventHandlers<InteractBlockEvent>,
+    pub dig_block: EventHandlers<DigBlockEvent>,
+    pub attack_entity: EventHandlers<AttackEntityEvent>,
+    pub drop_item: EventHandlers<DropItemEvent>,
+    pub select_slot: EventHandlers<SelectSlotEvent>,
+    pub release_use_item: EventHandlers<ReleaseUseItem>,
 
     // todo: this should be a lifetime for<'a>
     pub completion: EventHandlers<CommandCompletionRequest<'static>>,
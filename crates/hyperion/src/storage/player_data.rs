@@ -0,0 +1,200 @@
+//! Persisting player state across sessions.
+
+use anyhow::{bail, ensure};
+use byteorder::NativeEndian;
+use flecs_ecs::macros::Component;
+use heed::{Database, Env, types};
+use hyperion_inventory::PlayerInventory;
+use uuid::Uuid;
+use valence_protocol::{Decode, Encode, ItemStack};
+
+use crate::storage::LocalDb;
+
+/// The version of the [`PlayerData`] encoding written by [`PlayerData::to_bytes`]. Bump it when
+/// changing [`PlayerData`] and keep decoding the older versions in [`PlayerData::from_bytes`].
+const VERSION: u8 = 1;
+
+/// The state of a player that is kept after they leave.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct PlayerData {
+    /// Every slot of the [`PlayerInventory`], including armor and the offhand.
+    pub inventory: Vec<ItemStack>,
+    pub xp: u16,
+    pub health: f32,
+    pub position: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl PlayerData {
+    /// Encodes the data prefixed with its version.
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = vec![VERSION];
+        self.encode(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decodes data written by [`Self::to_bytes`] of this or an earlier version.
+    pub fn from_bytes(mut bytes: &[u8]) -> anyhow::Result<Self> {
+        let version = u8::decode(&mut bytes)?;
+
+        let data = match version {
+            1 => Self::decode(&mut bytes)?,
+            _ => bail!("unsupported player data version {version}"),
+        };
+
+        ensure!(
+            bytes.is_empty(),
+            "{} trailing bytes after player data",
+            bytes.len()
+        );
+
+        Ok(data)
+    }
+
+    /// Creates the inventory of the player. Slots that do not exist are dropped and missing slots
+    /// are empty.
+    #[must_use]
+    pub fn inventory(&self) -> PlayerInventory {
+        let slots = core::array::from_fn(|idx| {
+            self.inventory.get(idx).cloned().unwrap_or(ItemStack::EMPTY)
+        });
+
+        PlayerInventory::from_slots(slots)
+    }
+
+    /// Whether the player left while dead, in which case they respawn rather than being put back
+    /// where they died.
+    #[must_use]
+    pub fn is_dead(&self) -> bool {
+        // also catches a health of NaN, which would never let the player respawn
+        !(self.health > 0.0)
+    }
+}
+
+/// A handler for player data operations
+#[derive(Component, Debug, Clone)]
+pub struct PlayerDataHandler {
+    env: Env,
+    data: Database<types::U128<NativeEndian>, types::Bytes>,
+}
+
+impl PlayerDataHandler {
+    /// Creates a new [`PlayerDataHandler`] from a given [`LocalDb`].
+    pub fn new(db: &LocalDb) -> anyhow::Result<Self> {
+        let data = {
+            let mut wtxn = db.write_txn()?;
+            let db = db.create_database(&mut wtxn, Some("uuid-to-player-data"))?;
+            wtxn.commit()?;
+            db
+        };
+
+        Ok(Self {
+            env: (**db).clone(),
+            data,
+        })
+    }
+
+    /// Finds the [`PlayerData`] of a player by their UUID.
+    pub fn find(&self, uuid: Uuid) -> anyhow::Result<Option<PlayerData>> {
+        let uuid = uuid.as_u128();
+
+        let rtxn = self.env.read_txn()?;
+
+        let Some(bytes) = self.data.get(&rtxn, &uuid)? else {
+            return Ok(None);
+        };
+
+        PlayerData::from_bytes(bytes).map(Some)
+    }
+
+    /// Inserts the [`PlayerData`] of a player, replacing what was stored before.
+    pub fn insert(&self, uuid: Uuid, data: &PlayerData) -> anyhow::Result<()> {
+        let uuid = uuid.as_u128();
+        let bytes = data.to_bytes()?;
+
+        let mut wtxn = self.env.write_txn()?;
+        self.data.put(&mut wtxn, &uuid, &bytes)?;
+        wtxn.commit()?;
+
+        Ok(())
+    }
+
+    /// Inserts the [`PlayerData`] of several players in one transaction.
+    pub fn insert_all(&self, data: &[(Uuid, PlayerData)]) -> anyhow::Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+
+        for (uuid, data) in data {
+            self.data
+                .put(&mut wtxn, &uuid.as_u128(), &data.to_bytes()?)?;
+        }
+
+        wtxn.commit()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_protocol::ItemKind;
+
+    use super::*;
+
+    fn data() -> PlayerData {
+        let mut inventory = vec![ItemStack::EMPTY; 46];
+        inventory[usize::from(PlayerInventory::HELMET_SLOT)] =
+            ItemStack::new(ItemKind::IronHelmet, 1, None);
+        inventory[36] = ItemStack::new(ItemKind::Diamond, 12, None);
+
+        PlayerData {
+            inventory,
+            xp: 42,
+            health: 13.5,
+            position: [1.5, 64.0, -20.25],
+            yaw: 90.0,
+            pitch: -10.0,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let data = data();
+        let bytes = data.to_bytes().unwrap();
+
+        assert_eq!(bytes[0], VERSION);
+        assert_eq!(PlayerData::from_bytes(&bytes).unwrap(), data);
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        let mut bytes = data().to_bytes().unwrap();
+        bytes[0] = VERSION + 1;
+
+        assert!(PlayerData::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_inventory() {
+        let mut data = data();
+        data.inventory.truncate(40);
+
+        let inventory = data.inventory();
+
+        assert_eq!(inventory.get_helmet().item, ItemKind::IronHelmet);
+        assert_eq!(inventory.get(36).unwrap().count, 12);
+        assert!(inventory.get_offhand().is_empty());
+        assert!(inventory.updated_since_last_tick.is_empty());
+    }
+
+    #[test]
+    fn test_is_dead() {
+        let mut data = data();
+        assert!(!data.is_dead());
+
+        for health in [0.0, -1.0, f32::NAN] {
+            data.health = health;
+            assert!(data.is_dead());
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Sanctions, such as bans and mutes, which last until they are lifted or expire and are kept in
+//! [`LocalDb`] so that they last across restarts.
+
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use flecs_ecs::macros::Component;
+use heed::{Database, Env, types};
+use humantime::format_duration;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::storage::LocalDb;
+
+/// The seconds since the Unix epoch, which sanctions are timed in.
+#[must_use]
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Why and until when a player or address is sanctioned.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sanction {
+    /// The name of the sanctioned player when they were sanctioned, if known, for listing them.
+    pub name: Option<String>,
+    pub reason: String,
+    /// The name of who issued the sanction.
+    pub issuer: String,
+    /// When the sanction was issued, in seconds since the Unix epoch.
+    pub created: u64,
+    /// When the sanction ends, in seconds since the Unix epoch, or `None` if it is permanent.
+    pub expires: Option<u64>,
+}
+
+impl Sanction {
+    /// A sanction issued now, which lasts for `duration` or forever if it is `None`.
+    #[must_use]
+    pub fn new(
+        name: Option<String>,
+        reason: impl Into<String>,
+        issuer: impl Into<String>,
+        duration: Option<Duration>,
+    ) -> Self {
+        let created = unix_now();
+
+        Self {
+            name,
+            reason: reason.into(),
+            issuer: issuer.into(),
+            created,
+            expires: duration.map(|duration| created.saturating_add(duration.as_secs())),
+        }
+    }
+
+    #[must_use]
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// How long until the sanction ends, or `None` if it is permanent.
+    #[must_use]
+    pub fn remaining(&self, now: u64) -> Option<Duration> {
+        self.expires
+            .map(|expires| Duration::from_secs(expires.saturating_sub(now)))
+    }
+
+    /// How long until the sanction ends, such as `1day 2h`, or `never`.
+    #[must_use]
+    pub fn remaining_text(&self, now: u64) -> String {
+        self.remaining(now).map_or_else(
+            || "never".to_owned(),
+            |remaining| format_duration(remaining).to_string(),
+        )
+    }
+}
+
+/// Who a sanction applies to, which it is stored under in [`SanctionStorage`].
+pub trait SanctionTarget: Copy + Debug + Send + Sync + 'static {
+    fn key(self) -> String;
+
+    fn from_key(key: &str) -> Option<Self>;
+}
+
+/// Players, such as those who are muted.
+impl SanctionTarget for Uuid {
+    fn key(self) -> String {
+        self.to_string()
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        key.parse().ok()
+    }
+}
+
+/// Keeps the sanctions of one kind, by their [`SanctionTarget`], in a database of [`LocalDb`].
+#[derive(Component, Debug, Clone)]
+pub struct SanctionStorage<T>
+where
+    T: SanctionTarget,
+{
+    env: Env,
+    sanctions: Database<types::Str, types::SerdeJson<Sanction>>,
+    _target: PhantomData<fn() -> T>,
+}
+
+/// Keeps the mutes of players, which keep them from chatting.
+pub type MuteStorage = SanctionStorage<Uuid>;
+
+impl<T: SanctionTarget> SanctionStorage<T> {
+    /// Creates a new [`SanctionStorage`] from a given [`LocalDb`], keeping its sanctions in the
+    /// database called `name`.
+    pub fn new(db: &LocalDb, name: &str) -> anyhow::Result<Self> {
+        let sanctions = {
+            let mut wtxn = db.write_txn()?;
+            let db = db.create_database(&mut wtxn, Some(name))?;
+            wtxn.commit()?;
+            db
+        };
+
+        Ok(Self {
+            env: (**db).clone(),
+            sanctions,
+            _target: PhantomData,
+        })
+    }
+
+    /// Sanctions `target`, replacing the sanction it had before.
+    pub fn insert(&self, target: T, sanction: &Sanction) -> anyhow::Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        self.sanctions.put(&mut wtxn, &target.key(), sanction)?;
+        wtxn.commit()?;
+        Ok(())
+    }
+
+    /// Lifts the sanction of `target`, returning it if it had one in effect.
+    pub fn remove(&self, target: T) -> anyhow::Result<Option<Sanction>> {
+        let key = target.key();
+
+        let mut wtxn = self.env.write_txn()?;
+        let sanction = self.sanctions.get(&wtxn, &key)?;
+        self.sanctions.delete(&mut wtxn, &key)?;
+        wtxn.commit()?;
+
+        Ok(sanction.filter(|sanction| !sanction.is_expired(unix_now())))
+    }
+
+    /// The sanction in effect for `target`.
+    pub fn find(&self, target: T) -> anyhow::Result<Option<Sanction>> {
+        let rtxn = self.env.read_txn()?;
+        let sanction = self.sanctions.get(&rtxn, &target.key())?;
+
+        Ok(sanction.filter(|sanction| !sanction.is_expired(unix_now())))
+    }
+
+    /// The sanctions in effect, removing those which expired.
+    pub fn list(&self) -> anyhow::Result<Vec<(T, Sanction)>> {
+        let now = unix_now();
+
+        let mut wtxn = self.env.write_txn()?;
+        let mut sanctions = Vec::new();
+        let mut expired = Vec::new();
+
+        for entry in self.sanctions.iter(&wtxn)? {
+            let (key, sanction) = entry?;
+
+            match T::from_key(key) {
+                Some(target) if !sanction.is_expired(now) => sanctions.push((target, sanction)),
+                _ => expired.push(key.to_owned()),
+            }
+        }
+
+        for key in &expired {
+            self.sanctions.delete(&mut wtxn, key)?;
+        }
+
+        wtxn.commit()?;
+
+        Ok(sanctions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry() {
+        let sanction = Sanction {
+            name: Some("Notch".to_owned()),
+            reason: "Griefing".to_owned(),
+            issuer: "Console".to_owned(),
+            created: 100,
+            expires: Some(3_700),
+        };
+
+        assert!(!sanction.is_expired(100));
+        assert!(sanction.is_expired(3_700));
+        assert_eq!(sanction.remaining(100), Some(Duration::from_secs(3_600)));
+        assert_eq!(sanction.remaining_text(100), "1h");
+
+        let permanent = Sanction {
+            expires: None,
+            ..sanction
+        };
+
+        assert!(!permanent.is_expired(u64::MAX));
+        assert_eq!(permanent.remaining_text(100), "never");
+    }
+
+    #[test]
+    fn test_player_key_roundtrip() {
+        let uuid = Uuid::from_u128(0x1234);
+
+        assert_eq!(Uuid::from_key(&uuid.key()), Some(uuid));
+        assert_eq!(Uuid::from_key("player:1234"), None);
+    }
+}
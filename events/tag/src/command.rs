@@ -1,10 +1,20 @@
-use flecs_ecs::core::World;
+use flecs_ecs::core::{World, WorldGet};
 use hyperion_clap::{MinecraftCommand, hyperion_command::CommandRegistry};
+use hyperion_permission::{DEFAULT_GROUP, Group, PermissionStorage};
 
 use crate::command::{
-    bow::BowCommand, class::ClassCommand, fly::FlyCommand, gui::GuiCommand,
-    raycast::RaycastCommand, replace::ReplaceCommand, shoot::ShootCommand, spawn::SpawnCommand,
-    speed::SpeedCommand, vanish::VanishCommand, xp::XpCommand,
+    bow::BowCommand,
+    class::ClassCommand,
+    fly::FlyCommand,
+    gui::{ClassMenuCommand, GuiCommand, TeleportMenuCommand},
+    raycast::RaycastCommand,
+    replace::ReplaceCommand,
+    shoot::ShootCommand,
+    spawn::SpawnCommand,
+    speed::SpeedCommand,
+    tp::TpCommand,
+    vanish::VanishCommand,
+    xp::XpCommand,
 };
 
 mod bow;
@@ -16,12 +26,14 @@ mod replace;
 mod shoot;
 mod spawn;
 mod speed;
+mod tp;
 mod vanish;
 mod xp;
 
 pub fn register(registry: &mut CommandRegistry, world: &World) {
     BowCommand::register(registry, world);
     ClassCommand::register(registry, world);
+    ClassMenuCommand::register(registry, world);
     FlyCommand::register(registry, world);
     GuiCommand::register(registry, world);
     RaycastCommand::register(registry, world);
@@ -29,6 +41,51 @@ pub fn register(registry: &mut CommandRegistry, world: &World) {
     ShootCommand::register(registry, world);
     SpawnCommand::register(registry, world);
     SpeedCommand::register(registry, world);
+    TeleportMenuCommand::register(registry, world);
+    TpCommand::register(registry, world);
     VanishCommand::register(registry, world);
     XpCommand::register(registry, world);
 }
+
+/// Grants the commands of the event and of chat to the groups the server starts with. Admins have
+/// every node, and operators may change these groups with `/perms` once the server has started.
+pub fn seed_groups(world: &World) {
+    let group = |commands: &[&str], nodes: &[&str]| Group {
+        parents: Vec::new(),
+        nodes: commands
+            .iter()
+            .map(|command| format!("tag.command.{command}"))
+            .chain(nodes.iter().map(ToString::to_string))
+            .map(|node| (node, true))
+            .collect(),
+    };
+
+    world.get::<&mut PermissionStorage>(|storage| {
+        storage
+            .seed_group(
+                DEFAULT_GROUP,
+                group(
+                    &["bow", "class", "classmenu", "shoot", "spawn", "testgui"],
+                    &[
+                        "hyperion.command.channel",
+                        "hyperion.command.msg",
+                        "hyperion.command.reply",
+                    ],
+                ),
+            )
+            .unwrap();
+
+        storage
+            .seed_group(
+                "moderator",
+                group(&["fly", "speed", "tp", "tpmenu", "vanish"], &[
+                    hyperion_chat::BYPASS_PERMISSION,
+                    "hyperion.command.mute",
+                    "hyperion.command.slowmode",
+                    "hyperion.command.tempmute",
+                    "hyperion.command.unmute",
+                ]),
+            )
+            .unwrap();
+    });
+}
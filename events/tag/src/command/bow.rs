@@ -6,7 +6,7 @@ use hyperion_inventory::PlayerInventory;
 
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "bow")]
-#[command_permission(group = "Normal")]
+#[command_permission(node = "tag.command.bow")]
 pub struct BowCommand;
 
 impl MinecraftCommand for BowCommand {
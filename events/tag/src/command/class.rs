@@ -4,9 +4,11 @@ use hyperion::net::{Compose, ConnectionId, agnostic};
 use hyperion_clap::{CommandPermission, MinecraftCommand};
 use hyperion_rank_tree::{Class, Team};
 
+use crate::messages;
+
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "class")]
-#[command_permission(group = "Normal")]
+#[command_permission(node = "tag.command.class")]
 pub struct ClassCommand {
     class: Class,
     team: Team,
@@ -22,7 +24,7 @@ impl MinecraftCommand for ClassCommand {
             let caller = caller.entity_view(world);
             caller.get::<(&ConnectionId, &mut Team, &mut Class)>(|(stream, team, class)| {
                 if *team == team_param && *class == class_param {
-                    let chat_pkt = agnostic::chat("§cYou’re already using this class!");
+                    let chat_pkt = agnostic::translated(caller, messages::CLASS_ALREADY_USED, &[]);
 
                     compose.unicast(&chat_pkt, *stream, system).unwrap();
                     return;
@@ -38,8 +40,8 @@ impl MinecraftCommand for ClassCommand {
                     caller.modified::<Class>();
                 }
 
-                let msg = format!("Setting rank to {class:?}");
-                let chat = agnostic::chat(msg);
+                let class = format!("{class:?}");
+                let chat = agnostic::translated(caller, messages::CLASS_SET, &[&class]);
                 compose.unicast(&chat, *stream, system).unwrap();
             });
         });
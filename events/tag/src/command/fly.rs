@@ -12,6 +12,8 @@ use hyperion::{
 };
 use hyperion_clap::{CommandPermission, MinecraftCommand};
 
+use crate::messages;
+
 #[derive(Component)]
 #[meta]
 pub struct Flight {
@@ -20,7 +22,7 @@ pub struct Flight {
 
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "fly")]
-#[command_permission(group = "Moderator")]
+#[command_permission(node = "tag.command.fly")]
 pub struct FlyCommand;
 
 impl MinecraftCommand for FlyCommand {
@@ -34,12 +36,14 @@ impl MinecraftCommand for FlyCommand {
 
                     let allow_flight = flight.allow;
 
-                    let chat_packet = if allow_flight {
-                        agnostic::chat("§aFlying enabled")
+                    let message = if allow_flight {
+                        messages::FLY_ENABLED
                     } else {
-                        agnostic::chat("§cFlying disabled")
+                        messages::FLY_DISABLED
                     };
 
+                    let chat_packet = agnostic::translated(caller.entity_view(world), message, &[]);
+
                     let packet = fly_packet(allow_flight);
 
                     let mut bundle = DataBundle::new(compose, system);
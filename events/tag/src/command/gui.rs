@@ -1,26 +1,43 @@
-use clap::Parser;
-use flecs_ecs::core::{Entity, EntityView};
-use hyperion::valence_protocol::packets::play::click_slot_c2s::ClickMode;
+use clap::{Parser, ValueEnum};
+use flecs_ecs::core::{Entity, EntityView, EntityViewGet, WorldGet, WorldProvider};
+use hyperion::{
+    ItemKind,
+    net::{Compose, ConnectionId, agnostic},
+    simulation::{Name, Pitch, Position, Yaw},
+    valence_protocol::{
+        VarInt,
+        packets::play::{
+            self, click_slot_c2s::ClickMode, player_position_look_s2c::PlayerPositionLookFlags,
+        },
+    },
+};
 use hyperion_clap::{CommandPermission, MinecraftCommand};
-use hyperion_gui::{ContainerType, Gui, GuiItem};
+use hyperion_gui::{
+    ContainerType, Gui, GuiClick, GuiItem,
+    widget::{Confirmation, Paginated, PlayerSelector},
+};
 use hyperion_item::builder::ItemBuilder;
+use hyperion_rank_tree::Class;
+use hyperion_text::Key;
 use tracing::debug;
 
+use crate::messages;
+
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "testgui")]
-#[command_permission(group = "Normal")]
+#[command_permission(node = "tag.command.testgui")]
 pub struct GuiCommand;
 
 impl MinecraftCommand for GuiCommand {
     fn execute(self, system: EntityView<'_>, caller: Entity) {
-        let mut gui = Gui::new(27, "Test Chest GUI".to_string(), ContainerType::Chest);
+        let mut gui = Gui::new("Test Chest GUI", ContainerType::Generic9x3);
 
         let info_item = GuiItem::new(
-            ItemBuilder::new(hyperion::ItemKind::GoldIngot)
+            ItemBuilder::new(ItemKind::GoldIngot)
                 .name("Information")
                 .glowing()
                 .build(),
-            |_player, click_mode| match click_mode {
+            |click| match click.mode {
                 ClickMode::Click => debug!("Left Click"),
                 ClickMode::ShiftClick => debug!("Shift Click"),
                 ClickMode::Hotbar => debug!("Hotbar"),
@@ -36,3 +53,102 @@ impl MinecraftCommand for GuiCommand {
         gui.open(system, caller);
     }
 }
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "classmenu")]
+#[command_permission(node = "tag.command.classmenu")]
+pub struct ClassMenuCommand;
+
+const fn class_icon(class: Class) -> ItemKind {
+    match class {
+        Class::Stick => ItemKind::Stick,
+        Class::Archer => ItemKind::Bow,
+        Class::Sword => ItemKind::IronSword,
+        Class::Miner => ItemKind::IronPickaxe,
+        Class::Excavator => ItemKind::IronShovel,
+        Class::Mage => ItemKind::BlazeRod,
+        Class::Knight => ItemKind::Shield,
+        Class::Builder => ItemKind::Bricks,
+    }
+}
+
+fn unicast_chat(click: &GuiClick<'_>, key: Key, args: &[&str]) {
+    let world = click.system.world();
+    let player = click.player.entity_view(world);
+
+    player.get::<&ConnectionId>(|stream| {
+        world.get::<&Compose>(|compose| {
+            let chat = agnostic::translated(player, key, args);
+            compose.unicast(&chat, *stream, click.system).unwrap();
+        });
+    });
+}
+
+impl MinecraftCommand for ClassMenuCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let mut menu = Paginated::new("Select a class", ContainerType::Generic9x1);
+
+        for &class in Class::value_variants() {
+            let icon = ItemBuilder::new(class_icon(class))
+                .name(format!("{class:?}"))
+                .build();
+
+            let prompt = icon.clone();
+
+            menu.add_item(GuiItem::new(icon, move |click| {
+                let confirmation =
+                    Confirmation::new(format!("Switch to {class:?}?"), move |click| {
+                        let world = click.system.world();
+                        click.player.entity_view(world).set(class);
+                        unicast_chat(click, messages::CLASS_SET, &[&format!("{class:?}")]);
+                    })
+                    .prompt(prompt.clone());
+
+                click.set_gui(confirmation.build());
+            }));
+        }
+
+        menu.open(system, caller);
+    }
+}
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "tpmenu")]
+#[command_permission(node = "tag.command.tpmenu")]
+pub struct TeleportMenuCommand;
+
+impl MinecraftCommand for TeleportMenuCommand {
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        PlayerSelector::new("Teleport to", |click, target| {
+            let world = click.system.world();
+            let player = click.player.entity_view(world);
+
+            let Some((position, name)) = target
+                .entity_view(world)
+                .try_get::<(&Position, &Name)>(|(position, name)| (*position, name.to_string()))
+            else {
+                return;
+            };
+
+            player.get::<(&ConnectionId, &Yaw, &Pitch)>(|(stream, yaw, pitch)| {
+                let pkt = play::PlayerPositionLookS2c {
+                    position: position.as_dvec3(),
+                    yaw: **yaw,
+                    pitch: **pitch,
+                    flags: PlayerPositionLookFlags::default(),
+                    teleport_id: VarInt(fastrand::i32(..)),
+                };
+
+                world.get::<&Compose>(|compose| {
+                    compose.unicast(&pkt, *stream, click.system).unwrap();
+                });
+            });
+
+            player.set(position);
+            click.close();
+            unicast_chat(click, messages::TELEPORTED, &[&name]);
+        })
+        .exclude(caller)
+        .open(system, caller);
+    }
+}
@@ -11,7 +11,7 @@ use tracing::debug;
 
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "raycast")]
-#[command_permission(group = "Admin")]
+#[command_permission(node = "tag.command.raycast")]
 pub struct RaycastCommand;
 
 /// Converts Minecraft yaw and pitch angles to a direction vector
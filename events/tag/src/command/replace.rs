@@ -9,7 +9,7 @@ use crate::OreVeins;
 
 #[derive(clap::Parser, CommandPermission, Debug)]
 #[command(name = "replace")]
-#[command_permission(group = "Admin")]
+#[command_permission(node = "tag.command.replace")]
 pub struct ReplaceCommand;
 
 /// Picks a random ore based on weighted probabilities
@@ -9,7 +9,7 @@ use tracing::debug;
 
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "shoot")]
-#[command_permission(group = "Normal")]
+#[command_permission(node = "tag.command.shoot")]
 pub struct ShootCommand {
     #[arg(help = "Initial velocity of the arrow")]
     velocity: f32,
@@ -20,7 +20,7 @@ use crate::FollowClosestPlayer;
 
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "spawn")]
-#[command_permission(group = "Normal")]
+#[command_permission(node = "tag.command.spawn")]
 pub struct SpawnCommand;
 
 impl MinecraftCommand for SpawnCommand {
@@ -8,9 +8,11 @@ use hyperion::{
 };
 use hyperion_clap::{CommandPermission, MinecraftCommand};
 
+use crate::messages;
+
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "speed")]
-#[command_permission(group = "Moderator")]
+#[command_permission(node = "tag.command.speed")]
 pub struct SpeedCommand {
     amount: f32,
 }
@@ -18,8 +20,8 @@ pub struct SpeedCommand {
 impl MinecraftCommand for SpeedCommand {
     fn execute(self, system: EntityView<'_>, caller: Entity) {
         let world = system.world();
-        let msg = format!("Setting speed to {}", self.amount);
-        let chat = agnostic::chat(msg);
+        let amount = self.amount.to_string();
+        let chat = agnostic::translated(caller.entity_view(world), messages::SPEED_SET, &[&amount]);
 
         world.get::<&Compose>(|compose| {
             caller.entity_view(world).get::<&ConnectionId>(|stream| {
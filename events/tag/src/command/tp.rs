@@ -0,0 +1,105 @@
+use clap::Parser;
+use flecs_ecs::core::{Entity, EntityView, EntityViewGet, WorldGet, WorldProvider};
+use hyperion::{
+    net::{Compose, ConnectionId, agnostic},
+    simulation::{Name, Pitch, Position, Yaw, console::Console},
+    valence_protocol::{
+        VarInt,
+        packets::play::{self, player_position_look_s2c::PlayerPositionLookFlags},
+    },
+};
+use hyperion_clap::{CommandPermission, MinecraftCommand, selector::Selector};
+use hyperion_text::Key;
+
+use crate::messages;
+
+#[derive(Parser, CommandPermission, Debug)]
+#[command(name = "tp")]
+#[command_permission(node = "tag.command.tp")]
+pub struct TpCommand {
+    /// Who to teleport to, or who to teleport if a destination is given
+    target: Selector,
+    /// Where to teleport the targets to
+    destination: Option<Selector>,
+}
+
+/// Moves `entity` to `position`, which players are told about as they move themselves.
+fn teleport(entity: EntityView<'_>, position: Position, system: EntityView<'_>) {
+    let world = entity.world();
+
+    entity.try_get::<(&ConnectionId, &Yaw, &Pitch)>(|(stream, yaw, pitch)| {
+        let pkt = play::PlayerPositionLookS2c {
+            position: position.as_dvec3(),
+            yaw: **yaw,
+            pitch: **pitch,
+            flags: PlayerPositionLookFlags::default(),
+            teleport_id: VarInt(fastrand::i32(..)),
+        };
+
+        world.get::<&Compose>(|compose| {
+            compose.unicast(&pkt, *stream, system).unwrap();
+        });
+    });
+
+    entity.set(position);
+}
+
+impl MinecraftCommand for TpCommand {
+    const CONSOLE: bool = true;
+
+    fn execute(self, system: EntityView<'_>, caller: Entity) {
+        let world = system.world();
+        let caller = caller.entity_view(world);
+
+        let unicast = |key: Key, args: &[&str]| {
+            let chat = agnostic::translated(caller, key, args);
+            agnostic::unicast_chat(caller, &chat, system);
+        };
+
+        let (targets, destination) = match &self.destination {
+            Some(destination) => (self.target.resolve(&world, caller.id()), destination),
+            None if caller.has::<Console>() => {
+                unicast(hyperion_clap::messages::PLAYER_REQUIRED, &[]);
+                return;
+            }
+            None => (vec![caller], &self.target),
+        };
+
+        let destination_name = destination.to_string();
+
+        let Some((position, name)) = destination
+            .resolve(&world, caller.id())
+            .into_iter()
+            .find_map(|destination| {
+                let position = destination.try_get::<&Position>(|position| *position)?;
+                let name = destination
+                    .try_get::<&Name>(ToString::to_string)
+                    .unwrap_or_else(|| destination_name.clone());
+
+                Some((position, name))
+            })
+        else {
+            unicast(hyperion_clap::messages::PLAYER_NOT_FOUND, &[
+                &destination_name,
+            ]);
+            return;
+        };
+
+        if targets.is_empty() {
+            let target_name = self.target.to_string();
+            unicast(hyperion_clap::messages::PLAYER_NOT_FOUND, &[&target_name]);
+            return;
+        }
+
+        for target in &targets {
+            teleport(*target, position, system);
+        }
+
+        if self.destination.is_none() {
+            unicast(messages::TELEPORTED, &[&name]);
+        } else {
+            let count = targets.len().to_string();
+            unicast(messages::TELEPORTED_TARGETS, &[&count, &name]);
+        }
+    }
+}
@@ -3,14 +3,14 @@ use flecs_ecs::{
     core::{Entity, EntityView, EntityViewGet, WorldProvider},
     prelude::*,
 };
-use hyperion::net::{Compose, ConnectionId};
+use hyperion::net::{Compose, ConnectionId, agnostic};
 use hyperion_clap::{CommandPermission, MinecraftCommand};
 
-use crate::module::vanish::Vanished;
+use crate::{messages, module::vanish::Vanished};
 
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "vanish")]
-#[command_permission(group = "Admin")]
+#[command_permission(node = "tag.command.vanish")]
 pub struct VanishCommand;
 
 impl MinecraftCommand for VanishCommand {
@@ -25,19 +25,16 @@ impl MinecraftCommand for VanishCommand {
             )>(|(vanished, stream, name)| {
                 let is_vanished = vanished.is_some_and(Vanished::is_vanished);
                 let caller = caller.entity_view(world);
-                if is_vanished {
-                    caller.set(Vanished::new(false));
-                    let packet = hyperion::net::agnostic::chat(format!(
-                        "§7[Admin] §f{name} §7is now visible",
-                    ));
-                    compose.unicast(&packet, *stream, system).unwrap();
+                let message = if is_vanished {
+                    messages::NOW_VISIBLE
                 } else {
-                    caller.set(Vanished::new(true));
-                    let packet = hyperion::net::agnostic::chat(format!(
-                        "§7[Admin] §f{name} §7is now vanished",
-                    ));
-                    compose.unicast(&packet, *stream, system).unwrap();
-                }
+                    messages::NOW_VANISHED
+                };
+
+                caller.set(Vanished::new(!is_vanished));
+
+                let packet = agnostic::translated(caller, message, &[name.as_ref()]);
+                compose.unicast(&packet, *stream, system).unwrap();
             });
         });
     }
@@ -5,7 +5,7 @@ use hyperion_clap::{CommandPermission, MinecraftCommand};
 
 #[derive(Parser, CommandPermission, Debug)]
 #[command(name = "xp")]
-#[command_permission(group = "Admin")]
+#[command_permission(node = "tag.command.xp")]
 pub struct XpCommand {
     amount: u16,
 }
@@ -3,7 +3,7 @@
 #![feature(stmt_expr_attributes)]
 #![feature(exact_size_is_empty)]
 
-use std::{collections::HashSet, net::SocketAddr};
+use std::{collections::HashSet, net::SocketAddr, sync::Arc};
 
 use flecs_ecs::prelude::*;
 use hyperion::{GameServerEndpoint, HyperionCore, simulation::Player};
@@ -13,13 +13,18 @@ use module::{block::BlockModule, vanish::VanishModule};
 mod module;
 
 use derive_more::{Deref, DerefMut};
-use hyperion::{glam::IVec3, simulation::Position};
+use hyperion::{
+    glam::IVec3,
+    simulation::{Position, TeamName},
+};
 use hyperion_rank_tree::Team;
 use module::{attack::AttackModule, level::LevelModule, regeneration::RegenerationModule};
 use spatial::SpatialIndex;
 
 use crate::{
-    module::{bow::BowModule, chat::ChatModule, spawn::SpawnModule, stats::StatsModule},
+    module::{
+        bow::BowModule, scoreboard::ScoreboardModule, spawn::SpawnModule, stats::StatsModule,
+    },
     skin::SkinModule,
 };
 
@@ -27,6 +32,7 @@ use crate::{
 pub struct TagModule;
 
 mod command;
+mod messages;
 mod skin;
 
 #[derive(Component, Default, Deref, DerefMut)]
@@ -66,8 +72,12 @@ impl Module for TagModule {
             .component::<Player>()
             .add_trait::<(flecs::With, Team)>();
 
+        // entity selectors match teams by name, as in `@a[team=red]`
+        observer!(world, flecs::OnAdd, &Team).each_entity(set_team_name);
+        observer!(world, flecs::OnSet, &Team).each_entity(set_team_name);
+
         world.import::<SpawnModule>();
-        world.import::<ChatModule>();
+        world.import::<hyperion_chat::ChatModule>();
         world.import::<StatsModule>();
         world.import::<BlockModule>();
         world.import::<hyperion_respawn::RespawnModule>();
@@ -75,9 +85,11 @@ impl Module for TagModule {
         world.import::<LevelModule>();
         world.import::<BowModule>();
         world.import::<RegenerationModule>();
+        world.import::<ScoreboardModule>();
         world.import::<hyperion_permission::PermissionModule>();
         world.import::<hyperion_utils::HyperionUtilsModule>();
         world.import::<hyperion_clap::ClapCommandModule>();
+        world.import::<hyperion_gui::GuiModule>();
         world.import::<SkinModule>();
         world.import::<VanishModule>();
         world.import::<hyperion_genmap::GenMapModule>();
@@ -86,6 +98,8 @@ impl Module for TagModule {
             command::register(registry, world);
         });
 
+        command::seed_groups(world);
+
         world.set(hyperion_utils::AppId {
             qualifier: "com".to_string(),
             organization: "andrewgazelka".to_string(),
@@ -128,6 +142,11 @@ impl Module for TagModule {
     }
 }
 
+fn set_team_name(entity: EntityView<'_>, team: &Team) {
+    let name = format!("{team:?}").to_ascii_lowercase();
+    entity.set(TeamName::from(Arc::<str>::from(name)));
+}
+
 pub fn init_game(address: SocketAddr) -> anyhow::Result<()> {
     let world = World::new();
 
@@ -0,0 +1,21 @@
+//! The messages of the event, which can be translated in `run/lang`.
+
+hyperion_text::translation_keys! {
+    pub FLY_ENABLED = "tag.fly.enabled" => "<green>Flying enabled";
+    pub FLY_DISABLED = "tag.fly.disabled" => "<red>Flying disabled";
+    /// `{0}` is the new flying speed.
+    pub SPEED_SET = "tag.speed.set" => "Setting speed to {0}";
+    pub CLASS_ALREADY_USED = "tag.class.already_used" => "<red>You’re already using this class!";
+    /// `{0}` is the new class.
+    pub CLASS_SET = "tag.class.set" => "Setting rank to {0}";
+    /// `{0}` is the name of the player who teleported.
+    pub TELEPORTED = "tag.teleported" => "Teleported to {0}";
+    /// `{0}` is the number of entities teleported and `{1}` the name of their destination.
+    pub TELEPORTED_TARGETS = "tag.tp.teleported_targets" => "Teleported {0} entities to {1}";
+    /// `{0}` is the name of the admin.
+    pub NOW_VISIBLE = "tag.vanish.visible" => "<gray>[Admin] <white>{0} <gray>is now visible";
+    /// `{0}` is the name of the admin.
+    pub NOW_VANISHED = "tag.vanish.vanished" => "<gray>[Admin] <white>{0} <gray>is now vanished";
+    pub CANNOT_PLACE_BLOCK = "tag.block.cannot_place" => "<red>You can't place this block";
+    pub CANNOT_ATTACK_TEAMMATES = "tag.attack.teammate" => "<red>Cannot attack teammates";
+}
@@ -1,9 +1,9 @@
 pub mod attack;
 pub mod block;
 pub mod bow;
-pub mod chat;
 pub mod level;
 pub mod regeneration;
+pub mod scoreboard;
 pub mod spawn;
 pub mod stats;
 pub mod vanish;
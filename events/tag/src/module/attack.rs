@@ -42,6 +42,7 @@ use tracing::info_span;
 use valence_protocol::packets::play::player_position_look_s2c::PlayerPositionLookFlags;
 
 use super::spawn::{avoid_blocks, is_valid_spawn_block};
+use crate::messages;
 
 #[derive(Component)]
 pub struct AttackModule;
@@ -173,11 +174,7 @@ impl Module for AttackModule {
                                     }
 
                                     if target_team == origin_team {
-                                        let msg = "§cCannot attack teammates";
-                                        let pkt_msg = play::GameMessageS2c {
-                                            chat: msg.into_cow_text(),
-                                            overlay: false,
-                                        };
+                                        let pkt_msg = agnostic::translated(origin, messages::CANNOT_ATTACK_TEAMMATES, &[]);
 
                                         compose.unicast(&pkt_msg, *origin_connection, system).unwrap();
                                         return;
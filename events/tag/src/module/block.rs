@@ -9,7 +9,7 @@ use flecs_ecs::{
     prelude::Module,
 };
 use hyperion::{
-    BlockKind, chat,
+    BlockKind,
     net::{Compose, ConnectionId, agnostic},
     simulation::{
         Xp,
@@ -31,7 +31,7 @@ use hyperion_rank_tree::inventory;
 use hyperion_scheduled::Scheduled;
 use tracing::{error, info_span};
 
-use crate::{MainBlockCount, OreVeins};
+use crate::{MainBlockCount, OreVeins, messages};
 
 #[derive(Component)]
 pub struct BlockModule;
@@ -261,7 +261,7 @@ impl Module for BlockModule {
                             // so we send update to player
                             let _ = inventory.get_cursor_mut();
 
-                            let msg = chat!("§cYou can't place this block");
+                            let msg = agnostic::translated(from.entity_view(world), messages::CANNOT_PLACE_BLOCK, &[]);
 
                             compose.unicast(&msg, *stream, system).unwrap();
                         });
@@ -0,0 +1,69 @@
+use flecs_ecs::{
+    core::{QueryBuilderImpl, TermBuilderImpl, World, WorldGet, flecs},
+    macros::{Component, system},
+    prelude::Module,
+};
+use hyperion::simulation::{Player, Xp, skin::PlayerSkin};
+use hyperion_rank_tree::Team;
+use hyperion_scoreboard::{Sidebar, TeamStyle, Teams, team::TeamColor};
+
+use crate::module::attack::KillCount;
+
+const TITLE: &str = "§e§lTag";
+
+#[derive(Component)]
+pub struct ScoreboardModule;
+
+/// The color each team is shown in, matching the chat colors of teams.
+const fn team_color(team: Team) -> (TeamColor, &'static str) {
+    match team {
+        Team::Blue => (TeamColor::Blue, "§9"),
+        Team::Green => (TeamColor::BrightGreen, "§a"),
+        Team::Red => (TeamColor::Red, "§c"),
+        Team::Yellow => (TeamColor::Yellow, "§e"),
+    }
+}
+
+impl Module for ScoreboardModule {
+    fn module(world: &World) {
+        world.import::<hyperion_scoreboard::ScoreboardModule>();
+
+        // named like the `TeamName` each player is given
+        world.get::<&mut Teams>(|teams| {
+            for team in [Team::Blue, Team::Green, Team::Red, Team::Yellow] {
+                let (color, _) = team_color(team);
+
+                teams.define(format!("{team:?}").to_ascii_lowercase(), TeamStyle {
+                    display_name: format!("{team:?}"),
+                    color,
+                    friendly_fire: false,
+                    ..TeamStyle::default()
+                });
+            }
+        });
+
+        world
+            .component::<Player>()
+            .add_trait::<(flecs::With, Sidebar)>();
+
+        system!(
+            "update_sidebar",
+            world,
+            &KillCount,
+            &Xp,
+            &Team,
+            &mut Sidebar,
+        )
+        .with::<PlayerSkin>()
+        .each(|(kill_count, xp, team, sidebar)| {
+            sidebar.set_title(TITLE);
+            sidebar.set_lines([
+                String::new(),
+                format!("Team: {}{team:?}", team_color(*team).1),
+                format!("Kills: §a{}", kill_count.kill_count),
+                format!("Level: §a{}", xp.get_visual().level),
+                String::new(),
+            ]);
+        });
+    }
+}